@@ -14,6 +14,7 @@ cd "${SCRIPT_DIR}"/..
 # These paths are relative to the root directory
 ANDROID_LIB_DIR=java/android/src/main/jniLibs
 DESKTOP_LIB_DIR=java/java/src/main/resources
+DESKTOP_TESTING_LIB_DIR=java/java/build/testingJniLibs
 
 export RUSTFLAGS="-C link-args=-s"
 export CARGO_PROFILE_RELEASE_DEBUG=1 # enable line tables
@@ -27,6 +28,11 @@ if [ "$1" = 'desktop' ];
 then
     echo_then_run cargo build -p libsignal-jni --release
     copy_built_library target/release signal_jni $DESKTOP_LIB_DIR/
+elif [ "$1" = 'desktop-testing' ];
+then
+    # Only used by the Java tests; test-only entry points must never ship in the desktop library.
+    echo_then_run cargo build -p libsignal-jni --release --features libsignal-jni/testing
+    copy_built_library target/release signal_jni $DESKTOP_TESTING_LIB_DIR/
 elif [ "$1" = 'android' ];
 then
     echo_then_run cargo ndk --target armv7-linux-androideabi --platform 19 -- build -Z unstable-options -p libsignal-jni --release --out-dir=$ANDROID_LIB_DIR/armeabi-v7a
@@ -34,6 +40,6 @@ then
     echo_then_run cargo ndk --target i686-linux-android --platform 19 -- build -Z unstable-options -p libsignal-jni --release --out-dir=$ANDROID_LIB_DIR/x86
     echo_then_run cargo ndk --target x86_64-linux-android --platform 21 -- build -Z unstable-options -p libsignal-jni --release --out-dir=$ANDROID_LIB_DIR/x86_64
 else
-    echo "Unknown target (use 'desktop' or 'android')"
+    echo "Unknown target (use 'desktop', 'desktop-testing', or 'android')"
 fi
 
@@ -21,6 +21,7 @@ import java.io.FileOutputStream;
 import java.io.IOException;
 import java.io.InputStream;
 import java.io.OutputStream;
+import java.nio.ByteBuffer;
 import java.nio.file.Files;
 
 public final class Native {
@@ -69,36 +70,86 @@ public final class Native {
 
   private Native() {}
 
+  public static native byte[] Aes256Cbc_Decrypt(byte[] key, byte[] iv, byte[] ctext);
+  public static native byte[] Aes256Cbc_Encrypt(byte[] key, byte[] iv, byte[] ptext);
+
   public static native byte[] Aes256GcmSiv_Decrypt(long aesGcmSiv, byte[] ctext, byte[] nonce, byte[] associatedData);
   public static native void Aes256GcmSiv_Destroy(long handle);
   public static native byte[] Aes256GcmSiv_Encrypt(long aesGcmSiv, byte[] ptext, byte[] nonce, byte[] associatedData);
+  public static native void Aes256GcmSiv_EncryptInPlace(long aesGcmSiv, ByteBuffer buffer, byte[] tagOut, byte[] nonce, byte[] associatedData);
   public static native long Aes256GcmSiv_New(byte[] key);
+  public static native void Aes256GcmSiv_Rekey(long aesGcmSiv, byte[] key);
+  public static native int Aes256GcmSiv_TagLength();
+
+  public static native long Attachment_PaddedSize(long plaintextLength);
+
+  public static native void CiphertextMessage_Destroy(long handle);
+  public static native long CiphertextMessage_FromParts(int msgType, byte[] data);
+  public static native byte[] CiphertextMessage_Serialize(long obj);
+  public static native int CiphertextMessage_Type(long msg);
+  public static native boolean CiphertextMessage_TypeMatchesBody(int messageType, byte[] body);
+
+  public static native boolean ConstantTimeEqual(byte[] a, byte[] b);
+
+  public static native void CryptographicMac_Destroy(long handle);
+  public static native byte[] CryptographicMac_Finalize(long mac);
+  public static native long CryptographicMac_New(byte[] key);
+  public static native void CryptographicMac_Update(long mac, byte[] input);
+
+  public static native boolean DecryptPlan_ChangesIdentity(long obj);
+  public static native boolean DecryptPlan_CreatesSession(long obj);
+  public static native void DecryptPlan_Destroy(long handle);
+  public static native int DecryptPlan_GetConsumedPreKeyId(long obj);
 
   public static native byte[] ECPrivateKey_Agree(long privateKey, long publicKey);
+  public static native byte[] ECPrivateKey_AgreeValidated(long privateKey, long publicKey);
   public static native long ECPrivateKey_Deserialize(byte[] data);
   public static native void ECPrivateKey_Destroy(long handle);
   public static native long ECPrivateKey_Generate();
   public static native long ECPrivateKey_GetPublicKey(long k);
   public static native byte[] ECPrivateKey_Serialize(long handle);
   public static native byte[] ECPrivateKey_Sign(long key, byte[] message);
+  public static native byte[] ECPrivateKey_SignCanonical(long key, byte[] fields);
+  public static native byte[] ECPrivateKey_SignDeterministic(long key, byte[] message, byte[] random);
 
   public static native int ECPublicKey_Compare(long key1, long key2);
+  public static native boolean ECPublicKey_Equals(long key1, long key2);
   public static native long ECPublicKey_Deserialize(byte[] data, int offset);
   public static native void ECPublicKey_Destroy(long handle);
   public static native byte[] ECPublicKey_GetPublicKeyBytes(long handle);
+  public static native boolean ECPublicKey_IsValid(byte[] data);
+  public static native boolean ECPublicKey_IsWeak(long key);
   public static native byte[] ECPublicKey_Serialize(long handle);
   public static native boolean ECPublicKey_Verify(long key, byte[] message, byte[] signature);
 
+  public static native byte[] Fingerprint_GetQrCodePayload(long fingerprint);
+  public static native long Fingerprint_New(int iterations, int version, byte[] localIdentifier, long localKey, byte[] remoteIdentifier, long remoteKey);
+  public static native boolean Fingerprint_VersionsCompatible(int ours, int theirs);
+
+  public static native byte[] GroupCipher_DecryptBatch(long senderKeyName, byte[] messages, SenderKeyStore store);
   public static native byte[] GroupCipher_DecryptMessage(long senderKeyName, byte[] message, SenderKeyStore store);
   public static native byte[] GroupCipher_EncryptMessage(long senderKeyName, byte[] message, SenderKeyStore store);
+  public static native boolean GroupCipher_VerifyAgainstDistribution(long message, long distribution);
 
   public static native long GroupSessionBuilder_CreateSenderKeyDistributionMessage(long senderKeyName, SenderKeyStore store);
   public static native void GroupSessionBuilder_ProcessSenderKeyDistributionMessage(long senderKeyName, long senderKeyDistributionMessage, SenderKeyStore store);
 
+  public static native byte[] HKDF_DeriveMany(int version, byte[] ikm, byte[] salt, byte[] labels, byte[] lengths);
   public static native byte[] HKDF_DeriveSecrets(int outputLength, int version, byte[] ikm, byte[] label, byte[] salt);
 
   public static native long[] IdentityKeyPair_Deserialize(byte[] data);
   public static native byte[] IdentityKeyPair_Serialize(long publicKey, long privateKey);
+  public static native byte[] IdentityKeyPair_SignAlternateIdentity(long publicKey, long privateKey, long otherIdentity);
+
+  public static native byte[] IdentityKey_ComputeChangeHash(long identityKey);
+  public static native String IdentityKey_ToDisplayString(long identityKey);
+  public static native boolean IdentityKey_VerifyAlternateIdentitySignature(long identityKey, long otherIdentity, byte[] signature);
+
+  public static native void KeyPair_Destroy(long handle);
+  public static native long KeyPair_Generate();
+  public static native long KeyPair_GetPrivateKey(long keyPair);
+  public static native long KeyPair_GetPublicKey(long keyPair);
+  public static native byte[] KeyPair_SerializeAsIdentityKeyPair(long keyPair);
 
   public static native void Logger_Initialize(int maxLevel, Class loggerClass);
   public static native void Logger_SetMaxLevel(int maxLevel);
@@ -109,6 +160,7 @@ public final class Native {
   public static native long NumericFingerprintGenerator_New(int iterations, int version, byte[] localIdentifier, byte[] localKey, byte[] remoteIdentifier, byte[] remoteKey);
 
   public static native void PreKeyBundle_Destroy(long handle);
+  public static native long PreKeyBundle_FromLinkingPayload(byte[] payload);
   public static native int PreKeyBundle_GetDeviceId(long obj);
   public static native long PreKeyBundle_GetIdentityKey(long p);
   public static native int PreKeyBundle_GetPreKeyId(long obj);
@@ -118,9 +170,12 @@ public final class Native {
   public static native long PreKeyBundle_GetSignedPreKeyPublic(long obj);
   public static native byte[] PreKeyBundle_GetSignedPreKeySignature(long handle);
   public static native long PreKeyBundle_New(int registrationId, int deviceId, int prekeyId, long prekey, int signedPrekeyId, long signedPrekey, byte[] signedPrekeySignature, long identityKey);
+  public static native long PreKeyBundle_NewWithoutOneTimePreKey(int registrationId, int deviceId, int signedPrekeyId, long signedPrekey, byte[] signedPrekeySignature, long identityKey);
+  public static native byte[] PreKeyBundle_ToLinkingPayload(long obj);
 
   public static native long PreKeyRecord_Deserialize(byte[] data);
   public static native void PreKeyRecord_Destroy(long handle);
+  public static native byte[] PreKeyRecord_FindDuplicateKeys(byte[] serializedRecords);
   public static native int PreKeyRecord_GetId(long obj);
   public static native long PreKeyRecord_GetPrivateKey(long obj);
   public static native long PreKeyRecord_GetPublicKey(long obj);
@@ -138,16 +193,23 @@ public final class Native {
   public static native int PreKeySignalMessage_GetSignedPreKeyId(long obj);
   public static native int PreKeySignalMessage_GetVersion(long obj);
   public static native long PreKeySignalMessage_New(int messageVersion, int registrationId, int preKeyId, int signedPreKeyId, long baseKey, long identityKey, long signalMessage);
+  public static native boolean PreKeySignalMessage_UsedOneTimePreKey(long m);
 
   public static native void ProtocolAddress_Destroy(long handle);
   public static native int ProtocolAddress_DeviceId(long obj);
+  public static native long ProtocolAddress_FromString(String s);
   public static native String ProtocolAddress_Name(long obj);
   public static native long ProtocolAddress_New(String name, int deviceId);
+  public static native String ProtocolAddress_ToString(long addr);
+
+  public static native String ProfileCipher_DecryptName(byte[] ciphertext, byte[] profileKey);
+  public static native byte[] ProfileCipher_EncryptName(String name, byte[] profileKey);
 
   public static native boolean ScannableFingerprint_Compare(byte[] fprint1, byte[] fprint2);
 
   public static native long SealedSessionCipher_DecryptToUsmc(byte[] ctext, IdentityKeyStore identityStore);
   public static native byte[] SealedSessionCipher_Encrypt(long destination, long senderCert, byte[] ptext, SessionStore sessionStore, IdentityKeyStore identityStore);
+  public static native byte[] SealedSessionCipher_EncryptFromUsmc(long destination, long content, SessionStore sessionStore, IdentityKeyStore identityStore);
 
   public static native long SenderCertificate_Deserialize(byte[] data);
   public static native void SenderCertificate_Destroy(long handle);
@@ -160,9 +222,15 @@ public final class Native {
   public static native byte[] SenderCertificate_GetSerialized(long handle);
   public static native long SenderCertificate_GetServerCertificate(long cert);
   public static native byte[] SenderCertificate_GetSignature(long handle);
+  public static native boolean SenderCertificate_HasValidUuid(long cert);
+  public static native boolean SenderCertificate_IsForDevice(long cert, int deviceId);
   public static native long SenderCertificate_New(String senderUuid, String senderE164, int senderDeviceId, long senderKey, long expiration, long signerCert, long signerKey);
+  public static native boolean SenderCertificate_SignerKeyIdEquals(long cert, int keyId);
   public static native boolean SenderCertificate_Validate(long cert, long key, long time);
+  public static native boolean SenderCertificate_ValidateChain(long cert, long trustRoot, long time);
 
+  public static native int SenderKeyDistributionMessage_ChainKeyLength();
+  public static native byte[] SenderKeyDistributionMessage_DeriveGroupKey(long handle);
   public static native long SenderKeyDistributionMessage_Deserialize(byte[] data);
   public static native void SenderKeyDistributionMessage_Destroy(long handle);
   public static native byte[] SenderKeyDistributionMessage_GetChainKey(long handle);
@@ -182,14 +250,19 @@ public final class Native {
   public static native boolean SenderKeyMessage_VerifySignature(long skm, long pubkey);
 
   public static native void SenderKeyName_Destroy(long handle);
+  public static native long SenderKeyName_FromStorageKey(String key);
   public static native String SenderKeyName_GetGroupId(long obj);
   public static native int SenderKeyName_GetSenderDeviceId(long skn);
   public static native String SenderKeyName_GetSenderName(long obj);
   public static native long SenderKeyName_New(String groupId, String senderName, int senderDeviceId);
+  public static native String SenderKeyName_ToStorageKey(long skn);
 
+  public static native long SenderKeyRecord_CreateDistributionMessage(long record);
   public static native long SenderKeyRecord_Deserialize(byte[] data);
+  public static native byte[] SenderKeyRecord_DeriveMessageKeyAt(long record, int iteration);
   public static native void SenderKeyRecord_Destroy(long handle);
   public static native byte[] SenderKeyRecord_GetSerialized(long handle);
+  public static native int SenderKeyRecord_MessagesUntilRotation(long record);
   public static native long SenderKeyRecord_New();
 
   public static native long ServerCertificate_Deserialize(byte[] data);
@@ -200,30 +273,56 @@ public final class Native {
   public static native byte[] ServerCertificate_GetSerialized(long handle);
   public static native byte[] ServerCertificate_GetSignature(long handle);
   public static native long ServerCertificate_New(int keyId, long serverKey, long trustRoot);
+  public static native boolean ServerCertificate_Verify(long cert, long trustRoot);
 
   public static native void SessionBuilder_ProcessPreKeyBundle(long bundle, long protocolAddress, SessionStore sessionStore, IdentityKeyStore identityKeyStore);
 
   public static native byte[] SessionCipher_DecryptPreKeySignalMessage(long message, long protocolAddress, SessionStore sessionStore, IdentityKeyStore identityKeyStore, PreKeyStore prekeyStore, SignedPreKeyStore signedPrekeyStore);
   public static native byte[] SessionCipher_DecryptSignalMessage(long message, long protocolAddress, SessionStore sessionStore, IdentityKeyStore identityKeyStore);
   public static native CiphertextMessage SessionCipher_EncryptMessage(byte[] message, long protocolAddress, SessionStore sessionStore, IdentityKeyStore identityKeyStore);
+  public static native int SessionCipher_EstimateCiphertextSize(int plaintextLength, long sessionRecord);
+  public static native int SessionCipher_MaxPlaintextSize(int ciphertextBudget, long sessionRecord);
+  public static native long SessionCipher_PlanDecrypt(long message, long sessionRecord, long storedIdentity);
+  public static native boolean SessionCipher_ShouldResetSession(int errorCode, int messageType, byte[] message);
 
   public static native void SessionRecord_ArchiveCurrentState(long sessionRecord);
+  public static native boolean SessionRecord_CanEncrypt(long obj);
+  public static native byte[] SessionRecord_ComputeKeyConfirmation(long handle);
+  public static native byte[] SessionRecord_ComputeSessionId(long handle);
   public static native long SessionRecord_Deserialize(byte[] data);
+  public static native byte[] SessionRecord_DeriveMessageKeyAt(long sessionState, long ratchetKey, int index);
   public static native void SessionRecord_Destroy(long handle);
+  public static native byte[] SessionRecord_ExportAllChainKeys(long sessionRecord);
   public static native long SessionRecord_FromSingleSessionState(byte[] sessionState);
   public static native byte[] SessionRecord_GetAliceBaseKey(long handle);
+  public static native long SessionRecord_GetCreationTimestamp(long obj);
   public static native byte[] SessionRecord_GetLocalIdentityKeyPublic(long handle);
   public static native int SessionRecord_GetLocalRegistrationId(long obj);
   public static native byte[] SessionRecord_GetReceiverChainKeyValue(long sessionState, long key);
+  public static native byte[] SessionRecord_GetRegistrationIds(long s);
   public static native byte[] SessionRecord_GetRemoteIdentityKeyPublic(long handle);
   public static native int SessionRecord_GetRemoteRegistrationId(long obj);
+  public static native byte[] SessionRecord_GetRootKey(long handle);
   public static native byte[] SessionRecord_GetSenderChainKeyValue(long handle);
   public static native int SessionRecord_GetSessionVersion(long s);
   public static native boolean SessionRecord_HasSenderChain(long obj);
-  public static native long SessionRecord_InitializeAliceSession(long identityKeyPrivate, long identityKeyPublic, long basePrivate, long basePublic, long theirIdentityKey, long theirSignedPrekey, long theirRatchetKey);
-  public static native long SessionRecord_InitializeBobSession(long identityKeyPrivate, long identityKeyPublic, long signedPrekeyPrivate, long signedPrekeyPublic, long ephPrivate, long ephPublic, long theirIdentityKey, long theirBaseKey);
+  public static native boolean SessionRecord_IdentitySeenBefore(long s, long identityKey);
+  public static native long SessionRecord_InitializeAliceSession(long identityKeyPrivate, long identityKeyPublic, long basePrivate, long basePublic, long theirIdentityKey, long theirSignedPrekey, long theirOneTimePrekey, long theirRatchetKey);
+  public static native long SessionRecord_InitializeBobFromPreKeyMessage(long message, long identityKeyPrivate, long identityKeyPublic, long signedPrekeyPrivate, long signedPrekeyPublic, long prekeyPrivate, long prekeyPublic);
+  public static native long SessionRecord_InitializeBobSession(long identityKeyPrivate, long identityKeyPublic, long signedPrekeyPrivate, long signedPrekeyPublic, long prekeyPrivate, long prekeyPublic, long ephPrivate, long ephPublic, long theirIdentityKey, long theirBaseKey);
+  public static native boolean SessionRecord_NeedsUpgrade(long obj);
   public static native long SessionRecord_NewFresh();
+  public static native boolean SessionRecord_PreKeyMessageAlreadyProcessed(long s, long message);
   public static native byte[] SessionRecord_Serialize(long handle);
+  public static native int SessionRecord_StorageVersion(byte[] data);
+  public static native int SessionRecord_ValidateIntegrity(byte[] serialized);
+
+  public static native void SessionStore_ArchiveAllSessionsForName(String name, SessionStore sessionStore);
+  public static native int[] SessionStore_GetDeviceIdsForName(String name, SessionStore sessionStore);
+
+  public static native byte[] Sha256_Digest(byte[] input);
+
+  public static native byte[] Sha512_Digest(byte[] input);
 
   public static native long SignalMessage_Deserialize(byte[] data);
   public static native void SignalMessage_Destroy(long handle);
@@ -233,10 +332,14 @@ public final class Native {
   public static native byte[] SignalMessage_GetSenderRatchetKey(long handle);
   public static native byte[] SignalMessage_GetSerialized(long handle);
   public static native long SignalMessage_New(int messageVersion, byte[] macKey, long senderRatchetKey, int counter, int previousCounter, byte[] ciphertext, long senderIdentityKey, long receiverIdentityKey);
+  public static native long SignalMessage_ReserializeToVersion(long msg, int messageVersion, byte[] macKey, long senderIdentityKey, long receiverIdentityKey);
   public static native boolean SignalMessage_VerifyMac(long msg, long senderIdentityKey, long receiverIdentityKey, byte[] macKey);
 
+  public static native String SignalProtocolError_DescribeCode(int code);
+
   public static native long SignedPreKeyRecord_Deserialize(byte[] data);
   public static native void SignedPreKeyRecord_Destroy(long handle);
+  public static native long SignedPreKeyRecord_EarliestExpiry(byte[] serializedRecords, long maxAge);
   public static native int SignedPreKeyRecord_GetId(long obj);
   public static native long SignedPreKeyRecord_GetPrivateKey(long obj);
   public static native long SignedPreKeyRecord_GetPublicKey(long obj);
@@ -244,11 +347,19 @@ public final class Native {
   public static native byte[] SignedPreKeyRecord_GetSignature(long handle);
   public static native long SignedPreKeyRecord_GetTimestamp(long obj);
   public static native long SignedPreKeyRecord_New(int id, long timestamp, long pubKey, long privKey, byte[] signature);
+  public static native long SignedPreKeyRecord_NextRotationTime(long record, long interval);
+  public static native boolean SignedPreKeyRecord_VerifyKeyPair(long record);
+  public static native boolean SignedPreKeyRecord_VerifyOwnSignature(long record, long identityKey);
+
+  public static native boolean TestHarness_CanCommunicate(long aliceSession, long bobSession);
+  public static native long TestHarness_DeriveBaseKey(byte[] seed, long identityKey);
+  public static native long TestHarness_SealedSenderRoundTrip(long senderIdentityKey, long senderCert, long trustRoot, long timestamp, byte[] plaintext);
 
   public static native long UnidentifiedSenderMessageContent_Deserialize(byte[] data);
   public static native void UnidentifiedSenderMessageContent_Destroy(long handle);
   public static native byte[] UnidentifiedSenderMessageContent_GetContents(long handle);
   public static native int UnidentifiedSenderMessageContent_GetMsgType(long m);
+  public static native String UnidentifiedSenderMessageContent_GetMsgTypeName(int msgType);
   public static native long UnidentifiedSenderMessageContent_GetSenderCert(long m);
   public static native byte[] UnidentifiedSenderMessageContent_GetSerialized(long handle);
   public static native long UnidentifiedSenderMessageContent_New(int msgType, long sender, byte[] contents);
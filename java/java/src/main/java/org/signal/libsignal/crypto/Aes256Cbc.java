@@ -0,0 +1,29 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.crypto;
+
+import org.signal.client.internal.Native;
+import org.whispersystems.libsignal.InvalidMessageException;
+
+/**
+ * AES-256 in CBC mode with PKCS7 padding, as used for stored attachments.
+ */
+public final class Aes256Cbc {
+  private Aes256Cbc() {}
+
+  public static byte[] encrypt(byte[] key, byte[] iv, byte[] plaintext) {
+    return Native.Aes256Cbc_Encrypt(key, iv, plaintext);
+  }
+
+  /**
+   * @throws IllegalArgumentException if the ciphertext is not a whole number of blocks
+   * @throws InvalidMessageException if the decrypted padding is malformed
+   */
+  public static byte[] decrypt(byte[] key, byte[] iv, byte[] ciphertext)
+      throws InvalidMessageException {
+    return Native.Aes256Cbc_Decrypt(key, iv, ciphertext);
+  }
+}
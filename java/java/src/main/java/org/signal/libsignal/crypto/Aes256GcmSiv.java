@@ -9,6 +9,8 @@ import org.signal.client.internal.Native;
 import org.whispersystems.libsignal.InvalidMessageException;
 import org.whispersystems.libsignal.InvalidKeyException;
 
+import java.nio.ByteBuffer;
+
 class Aes256GcmSiv {
   private final long handle;
 
@@ -21,11 +23,34 @@ class Aes256GcmSiv {
     Native.Aes256GcmSiv_Destroy(this.handle);
   }
 
+  void rekey(byte[] key) throws InvalidKeyException {
+    Native.Aes256GcmSiv_Rekey(this.handle, key);
+  }
+
+  static int tagLength() {
+    return Native.Aes256GcmSiv_TagLength();
+  }
+
   byte[] encrypt(byte[] plaintext, byte[] nonce, byte[] associated_data)
       throws InvalidMessageException, IllegalArgumentException {
     return Native.Aes256GcmSiv_Encrypt(this.handle, plaintext, nonce, associated_data);
   }
 
+  /**
+   * Encrypts the remaining bytes of {@code buffer}, from its position to its limit, in place and
+   * writes the tag into {@code tagOut}, which must be exactly {@link #tagLength()} bytes long.
+   * The buffer's position is left unchanged.
+   *
+   * @param buffer a direct buffer, so that the plaintext is not copied
+   */
+  void encryptInPlace(ByteBuffer buffer, byte[] tagOut, byte[] nonce, byte[] associated_data)
+      throws IllegalArgumentException {
+    if (!buffer.isDirect()) {
+      throw new IllegalArgumentException("buffer must be a direct ByteBuffer");
+    }
+    Native.Aes256GcmSiv_EncryptInPlace(this.handle, buffer.slice(), tagOut, nonce, associated_data);
+  }
+
   byte[] decrypt(byte[] ciphertext, byte[] nonce, byte[] associated_data)
       throws InvalidMessageException {
     return Native.Aes256GcmSiv_Decrypt(this.handle, ciphertext, nonce, associated_data);
@@ -0,0 +1,25 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.crypto;
+
+import org.signal.client.internal.Native;
+
+/**
+ * Comparisons that do not leak timing information about their inputs.
+ */
+public final class ConstantTime {
+  private ConstantTime() {}
+
+  /**
+   * Returns whether {@code a} and {@code b} hold the same bytes.
+   *
+   * Inputs of different lengths are rejected immediately; otherwise every byte is compared, so
+   * this is safe to use on MACs, tags, and other secrets.
+   */
+  public static boolean isEqual(byte[] a, byte[] b) {
+    return Native.ConstantTimeEqual(a, b);
+  }
+}
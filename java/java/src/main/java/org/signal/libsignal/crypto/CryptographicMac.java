@@ -0,0 +1,37 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.crypto;
+
+import org.signal.client.internal.Native;
+
+/**
+ * An HMAC-SHA256 computation over input supplied in pieces, for data too large to buffer.
+ */
+public class CryptographicMac {
+  private final long handle;
+
+  public CryptographicMac(byte[] key) {
+    this.handle = Native.CryptographicMac_New(key);
+  }
+
+  @Override
+  protected void finalize() {
+    Native.CryptographicMac_Destroy(this.handle);
+  }
+
+  public void update(byte[] input) {
+    Native.CryptographicMac_Update(this.handle, input);
+  }
+
+  /**
+   * Returns the MAC of everything passed to {@link #update}.
+   *
+   * @throws IllegalStateException if the MAC has already been computed
+   */
+  public byte[] doFinal() {
+    return Native.CryptographicMac_Finalize(this.handle);
+  }
+}
@@ -58,6 +58,19 @@ public class SealedSessionCipher {
        this.signalProtocolStore);
   }
 
+  /**
+   * @throws NoSessionException if there is no current session with {@code destinationAddress}
+   */
+  public byte[] encrypt(SignalProtocolAddress destinationAddress, UnidentifiedSenderMessageContent content)
+      throws InvalidKeyException, UntrustedIdentityException, NoSessionException
+  {
+    return Native.SealedSessionCipher_EncryptFromUsmc(
+       destinationAddress.nativeHandle(),
+       content.nativeHandle(),
+       this.signalProtocolStore,
+       this.signalProtocolStore);
+  }
+
   public DecryptionResult decrypt(CertificateValidator validator, byte[] ciphertext, long timestamp)
       throws
       InvalidMetadataMessageException, InvalidMetadataVersionException,
@@ -27,6 +27,21 @@ public class CertificateValidator {
     }
   }
 
+  /**
+   * Validates the certificate like {@link #validate(SenderCertificate, long)}, but throws
+   * {@link ExpiredCertificateException} if the only problem is that the certificate has expired.
+   */
+  public void validateChain(SenderCertificate certificate, long validationTime) throws InvalidCertificateException {
+    try {
+      Native.SenderCertificate_ValidateChain(certificate.nativeHandle(), trustRoot.nativeHandle(), validationTime);
+    } catch (Exception e) {
+      if (e instanceof InvalidCertificateException) {
+        throw (InvalidCertificateException) e;
+      }
+      throw new InvalidCertificateException(e);
+    }
+  }
+
   // VisibleForTesting
   void validate(ServerCertificate certificate) throws InvalidCertificateException {
     try {
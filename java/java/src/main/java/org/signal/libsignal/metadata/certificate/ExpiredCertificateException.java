@@ -0,0 +1,8 @@
+package org.signal.libsignal.metadata.certificate;
+
+
+public class ExpiredCertificateException extends InvalidCertificateException {
+  public ExpiredCertificateException(String s) {
+    super(s);
+  }
+}
@@ -43,6 +43,18 @@ public class SenderCertificate {
     return Native.SenderCertificate_GetDeviceId(this.handle);
   }
 
+  public boolean isForDevice(int deviceId) {
+    return Native.SenderCertificate_IsForDevice(this.handle, deviceId);
+  }
+
+  public boolean hasValidUuid() {
+    return Native.SenderCertificate_HasValidUuid(this.handle);
+  }
+
+  public boolean signerKeyIdEquals(int keyId) {
+    return Native.SenderCertificate_SignerKeyIdEquals(this.handle, keyId);
+  }
+
   public String getSenderUuid() {
     return Native.SenderCertificate_GetSenderUuid(this.handle);
   }
@@ -47,6 +47,10 @@ public class ServerCertificate {
     return Native.ServerCertificate_GetSignature(this.handle);
   }
 
+  public boolean verify(ECPublicKey trustRoot) {
+    return Native.ServerCertificate_Verify(this.handle, trustRoot.nativeHandle());
+  }
+
   public long nativeHandle() {
     return this.handle;
   }
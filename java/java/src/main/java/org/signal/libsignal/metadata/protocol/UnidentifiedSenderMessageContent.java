@@ -30,10 +30,23 @@ public class UnidentifiedSenderMessageContent {
     this.handle = Native.UnidentifiedSenderMessageContent_New(type, senderCertificate.nativeHandle(), content);
   }
 
+  public long nativeHandle() {
+    return this.handle;
+  }
+
   public int getType() {
     return Native.UnidentifiedSenderMessageContent_GetMsgType(this.handle);
   }
 
+  /**
+   * Returns a short name for a message type as returned by {@link #getType()}, for logging.
+   *
+   * @throws IllegalArgumentException if the type is not a known CiphertextMessage type.
+   */
+  public static String getTypeName(int type) {
+    return Native.UnidentifiedSenderMessageContent_GetMsgTypeName(type);
+  }
+
   public SenderCertificate getSenderCertificate() {
     return new SenderCertificate(Native.UnidentifiedSenderMessageContent_GetSenderCert(this.handle));
   }
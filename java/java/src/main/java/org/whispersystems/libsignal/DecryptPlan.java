@@ -0,0 +1,48 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.whispersystems.libsignal;
+
+import org.signal.client.internal.Native;
+
+/**
+ * The store writes that decrypting a {@link org.whispersystems.libsignal.protocol.PreKeySignalMessage}
+ * will make, beyond saving the session.
+ *
+ * @see SessionCipher#planDecrypt
+ */
+public class DecryptPlan {
+  private final long handle;
+
+  DecryptPlan(long handle) {
+    this.handle = handle;
+  }
+
+  @Override
+  protected void finalize() {
+    Native.DecryptPlan_Destroy(this.handle);
+  }
+
+  /**
+   * @return true if a new session will be established rather than an existing one reused.
+   */
+  public boolean createsSession() {
+    return Native.DecryptPlan_CreatesSession(this.handle);
+  }
+
+  /**
+   * @return the ID of the one-time pre-key that will be removed, or -1 if none will be.
+   */
+  public int getConsumedPreKeyId() {
+    return Native.DecryptPlan_GetConsumedPreKeyId(this.handle);
+  }
+
+  /**
+   * @return true if the identity saved for the sender will be replaced.
+   */
+  public boolean changesIdentity() {
+    return Native.DecryptPlan_ChangesIdentity(this.handle);
+  }
+}
@@ -6,6 +6,7 @@
 package org.whispersystems.libsignal;
 
 
+import org.signal.client.internal.Native;
 import org.whispersystems.libsignal.ecc.Curve;
 import org.whispersystems.libsignal.ecc.ECPublicKey;
 import org.whispersystems.libsignal.util.Hex;
@@ -44,6 +45,24 @@ public class IdentityKey {
     return publicKey.serialize();
   }
 
+  public boolean verifyAlternateIdentity(IdentityKey other, byte[] signature) {
+    return Native.IdentityKey_VerifyAlternateIdentitySignature(this.nativeHandle(), other.nativeHandle(), signature);
+  }
+
+  /**
+   * @return a 16-byte digest of this key for detecting identity changes. Not a fingerprint.
+   */
+  public byte[] computeChangeHash() {
+    return Native.IdentityKey_ComputeChangeHash(this.nativeHandle());
+  }
+
+  /**
+   * @return the serialized key as eleven space-separated groups of six lowercase hex digits.
+   */
+  public String toDisplayString() {
+    return Native.IdentityKey_ToDisplayString(this.nativeHandle());
+  }
+
   public String getFingerprint() {
     return Hex.toString(publicKey.serialize());
   }
@@ -43,4 +43,8 @@ public class IdentityKeyPair {
   public byte[] serialize() {
     return Native.IdentityKeyPair_Serialize(this.publicKey.nativeHandle(), this.privateKey.nativeHandle());
   }
+
+  public byte[] signAlternateIdentity(IdentityKey other) {
+    return Native.IdentityKeyPair_SignAlternateIdentity(this.publicKey.nativeHandle(), this.privateKey.nativeHandle(), other.nativeHandle());
+  }
 }
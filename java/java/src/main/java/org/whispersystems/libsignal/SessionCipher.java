@@ -77,6 +77,36 @@ public class SessionCipher {
     }
   }
 
+  /**
+   * Compute the serialized size of the message {@link #encrypt(byte[])} would produce next.
+   *
+   * The result is exact for the next message; later messages may be a few bytes larger.
+   *
+   * @param  plaintextLength The length of the (padded) plaintext that will be encrypted.
+   * @return The size in bytes of the serialized ciphertext message.
+   */
+  public int estimateCiphertextSize(int plaintextLength) {
+    synchronized (SESSION_LOCK) {
+      SessionRecord record = sessionStore.loadSession(remoteAddress);
+      return Native.SessionCipher_EstimateCiphertextSize(plaintextLength, record.nativeHandle());
+    }
+  }
+
+  /**
+   * Compute the largest plaintext whose next message, as reported by
+   * {@link #estimateCiphertextSize(int)}, fits in the given number of bytes.
+   *
+   * @param  ciphertextBudget The maximum size in bytes of the serialized ciphertext message.
+   * @return The largest plaintext length that fits.
+   * @throws IllegalArgumentException if not even an empty message fits.
+   */
+  public int maxPlaintextSize(int ciphertextBudget) {
+    synchronized (SESSION_LOCK) {
+      SessionRecord record = sessionStore.loadSession(remoteAddress);
+      return Native.SessionCipher_MaxPlaintextSize(ciphertextBudget, record.nativeHandle());
+    }
+  }
+
   /**
    * Decrypt a message.
    *
@@ -130,6 +160,49 @@ public class SessionCipher {
     }
   }
 
+  /**
+   * Report the store writes {@link #decrypt(PreKeySignalMessage)} would make for a message,
+   * without decrypting it, so that callers can prepare a transaction.
+   *
+   * The plan only holds if the decryption then succeeds and the stores are not modified in between.
+   *
+   * @param  ciphertext The {@link PreKeySignalMessage} that will be decrypted.
+   * @return The planned store writes.
+   */
+  public DecryptPlan planDecrypt(PreKeySignalMessage ciphertext) {
+    synchronized (SESSION_LOCK) {
+      SessionRecord record         = sessionStore.loadSession(remoteAddress);
+      IdentityKey   storedIdentity = identityKeyStore.getIdentity(remoteAddress);
+
+      return new DecryptPlan(Native.SessionCipher_PlanDecrypt(ciphertext.nativeHandle(),
+                                                              record.nativeHandle(),
+                                                              storedIdentity != null ? storedIdentity.getPublicKey().nativeHandle() : 0));
+    }
+  }
+
+  /**
+   * Decide whether a message that failed to decrypt means the session with its sender should be
+   * archived and the sender asked to retry.
+   *
+   * @param  errorCode The numeric SignalErrorCode of the failure, as listed in signal_ffi.h.
+   * @param  message   The message that failed to decrypt.
+   * @return true if the session should be reset.
+   */
+  public static boolean shouldResetSession(int errorCode, CiphertextMessage message) {
+    return Native.SessionCipher_ShouldResetSession(errorCode, message.getType(), message.serialize());
+  }
+
+  /**
+   * Describe a numeric SignalErrorCode, such as the one passed to
+   * {@link #shouldResetSession(int, CiphertextMessage)}, in English for logging.
+   *
+   * @param  errorCode The numeric SignalErrorCode, as listed in signal_ffi.h.
+   * @return The description, or that of an unknown error if the code isn't recognized.
+   */
+  public static String describeErrorCode(int errorCode) {
+    return Native.SignalProtocolError_DescribeCode(errorCode);
+  }
+
   public int getRemoteRegistrationId() {
     synchronized (SESSION_LOCK) {
       SessionRecord record = sessionStore.loadSession(remoteAddress);
@@ -18,6 +18,22 @@ public class SignalProtocolAddress {
     this.handle = handle;
   }
 
+  /**
+   * Parses the canonical "name.deviceId" form produced by {@link #toCanonicalString}.
+   *
+   * @throws IllegalArgumentException if the device ID is missing or not a number.
+   */
+  public static SignalProtocolAddress fromCanonicalString(String address) {
+    return new SignalProtocolAddress(Native.ProtocolAddress_FromString(address));
+  }
+
+  /**
+   * @return the address as "name.deviceId", the form accepted by {@link #fromCanonicalString}.
+   */
+  public String toCanonicalString() {
+    return Native.ProtocolAddress_ToString(this.handle);
+  }
+
   @Override
   protected void finalize() {
     Native.ProtocolAddress_Destroy(this.handle);
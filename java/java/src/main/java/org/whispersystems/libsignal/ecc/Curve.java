@@ -4,15 +4,20 @@
  * Licensed according to the LICENSE file in this repository.
  */
 package org.whispersystems.libsignal.ecc;
+import org.signal.client.internal.Native;
 import org.whispersystems.libsignal.InvalidKeyException;
 
 public class Curve {
   public  static final int DJB_TYPE   = 0x05;
 
   public static ECKeyPair generateKeyPair() {
-    ECPrivateKey privateKey = ECPrivateKey.generate();
-    ECPublicKey publicKey = privateKey.publicKey();
-    return new ECKeyPair(publicKey, privateKey);
+    long keyPair = Native.KeyPair_Generate();
+    try {
+      return new ECKeyPair(new ECPublicKey(Native.KeyPair_GetPublicKey(keyPair)),
+                           new ECPrivateKey(Native.KeyPair_GetPrivateKey(keyPair)));
+    } finally {
+      Native.KeyPair_Destroy(keyPair);
+    }
   }
 
   public static ECPublicKey decodePoint(byte[] bytes, int offset)
@@ -6,6 +6,11 @@
 
 package org.whispersystems.libsignal.ecc;
 import org.signal.client.internal.Native;
+import org.whispersystems.libsignal.InvalidKeyException;
+
+import java.io.ByteArrayOutputStream;
+import java.nio.ByteBuffer;
+import java.util.List;
 
 public class ECPrivateKey {
   private long handle;
@@ -38,10 +43,27 @@ public class ECPrivateKey {
      return Native.ECPrivateKey_Sign(this.handle, message);
   }
 
+  /**
+   * Sign the canonical encoding of {@code fields}: the number of fields as a big-endian int,
+   * then each field preceded by its length as a big-endian int.
+   */
+  public byte[] calculateCanonicalSignature(List<byte[]> fields) {
+    ByteArrayOutputStream serialized = new ByteArrayOutputStream();
+    for (byte[] field : fields) {
+      serialized.write(ByteBuffer.allocate(4).putInt(field.length).array(), 0, 4);
+      serialized.write(field, 0, field.length);
+    }
+    return Native.ECPrivateKey_SignCanonical(this.handle, serialized.toByteArray());
+  }
+
   public byte[] calculateAgreement(ECPublicKey other) {
     return Native.ECPrivateKey_Agree(this.handle, other.nativeHandle());
   }
 
+  public byte[] calculateAgreementValidated(ECPublicKey other) throws InvalidKeyException {
+    return Native.ECPrivateKey_AgreeValidated(this.handle, other.nativeHandle());
+  }
+
   public long nativeHandle() {
     return this.handle;
   }
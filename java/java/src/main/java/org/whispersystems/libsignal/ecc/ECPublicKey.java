@@ -46,6 +46,13 @@ public class ECPublicKey implements Comparable<ECPublicKey> {
     return Native.ECPublicKey_Verify(this.handle, message, signature);
   }
 
+  /**
+   * Returns true if this key is a low-order point, which would yield an all-zero shared secret.
+   */
+  public boolean isWeak() {
+    return Native.ECPublicKey_IsWeak(this.handle);
+  }
+
   public byte[] serialize() {
     return Native.ECPublicKey_Serialize(this.handle);
   }
@@ -69,7 +76,7 @@ public class ECPublicKey implements Comparable<ECPublicKey> {
     if (!(other instanceof ECPublicKey)) return false;
 
     ECPublicKey that = (ECPublicKey)other;
-    return Arrays.equals(this.serialize(), that.serialize());
+    return Native.ECPublicKey_Equals(this.nativeHandle(), that.nativeHandle());
   }
 
   @Override
@@ -9,12 +9,21 @@ public class Fingerprint {
 
   private final DisplayableFingerprint displayableFingerprint;
   private final ScannableFingerprint   scannableFingerprint;
+  private final byte[]                 qrCodePayload;
 
   public Fingerprint(DisplayableFingerprint displayableFingerprint,
                      ScannableFingerprint scannableFingerprint)
+  {
+    this(displayableFingerprint, scannableFingerprint, scannableFingerprint.getSerialized());
+  }
+
+  public Fingerprint(DisplayableFingerprint displayableFingerprint,
+                     ScannableFingerprint scannableFingerprint,
+                     byte[] qrCodePayload)
   {
     this.displayableFingerprint = displayableFingerprint;
     this.scannableFingerprint   = scannableFingerprint;
+    this.qrCodePayload          = qrCodePayload;
   }
 
   /**
@@ -30,4 +39,11 @@ public class Fingerprint {
   public ScannableFingerprint getScannableFingerprint() {
     return scannableFingerprint;
   }
+
+  /**
+   * @return The exact bytes to encode into the safety-number QR code, version header included.
+   */
+  public byte[] getQrCodePayload() {
+    return qrCodePayload;
+  }
 }
@@ -45,19 +45,21 @@ public class NumericFingerprintGenerator implements FingerprintGenerator {
                                byte[] remoteStableIdentifier,
                                final IdentityKey remoteIdentityKey) {
 
-    long handle = Native.NumericFingerprintGenerator_New(this.iterations, version,
+    long handle = Native.Fingerprint_New(this.iterations, version,
                       localStableIdentifier,
-                      localIdentityKey.serialize(),
+                      localIdentityKey.getPublicKey().nativeHandle(),
                       remoteStableIdentifier,
-                      remoteIdentityKey.serialize());
+                      remoteIdentityKey.getPublicKey().nativeHandle());
 
     DisplayableFingerprint displayableFingerprint = new DisplayableFingerprint(Native.NumericFingerprintGenerator_GetDisplayString(handle));
 
     ScannableFingerprint scannableFingerprint = new ScannableFingerprint(Native.NumericFingerprintGenerator_GetScannableEncoding(handle));
 
+    byte[] qrCodePayload = Native.Fingerprint_GetQrCodePayload(handle);
+
     Native.NumericFingerprintGenerator_Destroy(handle);
 
-    return new Fingerprint(displayableFingerprint, scannableFingerprint);
+    return new Fingerprint(displayableFingerprint, scannableFingerprint, qrCodePayload);
   }
 
 }
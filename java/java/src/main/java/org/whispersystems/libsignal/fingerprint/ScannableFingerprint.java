@@ -14,6 +14,14 @@ public class ScannableFingerprint {
     this.encodedFingerprint = encodedFingerprint;
   }
 
+  /**
+   * Checks whether fingerprints of the two versions can be compared at all, so a mismatch can be
+   * reported as "update the app" instead of as a failed comparison.
+   */
+  public static boolean versionsCompatible(int ourVersion, int theirVersion) {
+    return Native.Fingerprint_VersionsCompatible(ourVersion, theirVersion);
+  }
+
   /**
    * @return A byte string to be displayed in a QR code.
    */
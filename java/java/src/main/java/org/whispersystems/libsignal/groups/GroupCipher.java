@@ -13,8 +13,12 @@ import org.whispersystems.libsignal.LegacyMessageException;
 import org.whispersystems.libsignal.NoSessionException;
 import org.whispersystems.libsignal.groups.state.SenderKeyStore;
 
+import java.io.ByteArrayOutputStream;
+import java.nio.ByteBuffer;
 import java.security.InvalidAlgorithmParameterException;
 import java.security.NoSuchAlgorithmException;
+import java.util.ArrayList;
+import java.util.List;
 
 /**
  * The main entry point for Signal Protocol group encrypt/decrypt operations.
@@ -75,4 +79,38 @@ public class GroupCipher {
       }
     }
   }
+
+  /**
+   * Decrypt a batch of SenderKey group messages in order, as if by repeated calls to
+   * {@link #decrypt(byte[])}.
+   *
+   * Errors from the sender key store, or a missing sender key, are thrown for the whole
+   * batch rather than reported per message.
+   *
+   * @param senderKeyMessages The received ciphertexts.
+   * @return The plaintexts, in the same order, with null for each message that could not be
+   *         decrypted (including duplicates).
+   */
+  public List<byte[]> decryptBatch(List<byte[]> senderKeyMessages) {
+    ByteArrayOutputStream serialized = new ByteArrayOutputStream();
+    for (byte[] message : senderKeyMessages) {
+      serialized.write(ByteBuffer.allocate(4).putInt(message.length).array(), 0, 4);
+      serialized.write(message, 0, message.length);
+    }
+
+    byte[] encoded;
+    synchronized (LOCK) {
+      encoded = Native.GroupCipher_DecryptBatch(this.senderKeyId.nativeHandle(), serialized.toByteArray(), this.senderKeyStore);
+    }
+
+    ByteBuffer results = ByteBuffer.wrap(encoded);
+    List<byte[]> plaintexts = new ArrayList<>(senderKeyMessages.size());
+    while (results.hasRemaining()) {
+      boolean success = results.get() != 0;
+      byte[] plaintext = new byte[results.getInt()];
+      results.get(plaintext);
+      plaintexts.add(success ? plaintext : null);
+    }
+    return plaintexts;
+  }
 }
@@ -22,6 +22,17 @@ public class SenderKeyName {
     this.handle = Native.SenderKeyName_New(groupId, senderName, senderDeviceId);
   }
 
+  private SenderKeyName(long handle) {
+    this.handle = handle;
+  }
+
+  /**
+   * Parse a key produced by {@link #toStorageKey}.
+   */
+  public static SenderKeyName fromStorageKey(String key) {
+    return new SenderKeyName(Native.SenderKeyName_FromStorageKey(key));
+  }
+
   @Override
   protected void finalize() {
     Native.SenderKeyName_Destroy(this.handle);
@@ -35,6 +46,13 @@ public class SenderKeyName {
     return new SignalProtocolAddress(Native.SenderKeyName_GetSenderName(this.handle), Native.SenderKeyName_GetSenderDeviceId(this.handle));
   }
 
+  /**
+   * The canonical "group:sender:device" key for sender-key storage.
+   */
+  public String toStorageKey() {
+    return Native.SenderKeyName_ToStorageKey(this.handle);
+  }
+
   @Override
   public boolean equals(Object other) {
     if (other == null)                     return false;
@@ -6,6 +6,7 @@
 package org.whispersystems.libsignal.groups.state;
 
 import org.signal.client.internal.Native;
+import org.whispersystems.libsignal.protocol.SenderKeyDistributionMessage;
 import java.io.IOException;
 
 /**
@@ -38,6 +39,23 @@ public class SenderKeyRecord {
     return Native.SenderKeyRecord_GetSerialized(this.handle);
   }
 
+  public SenderKeyDistributionMessage createDistributionMessage() {
+    return new SenderKeyDistributionMessage(Native.SenderKeyRecord_CreateDistributionMessage(this.handle));
+  }
+
+  /**
+   * Returns how many more messages can be sent with the current sender key before it must be
+   * rotated, as an unsigned 32-bit value.
+   */
+  public int getMessagesUntilRotation() {
+    return Native.SenderKeyRecord_MessagesUntilRotation(this.handle);
+  }
+
+  // For testing only: returns the message key seed for the given iteration of the current state.
+  public byte[] deriveMessageKeyAt(int iteration) {
+    return Native.SenderKeyRecord_DeriveMessageKeyAt(this.handle, iteration);
+  }
+
   public long nativeHandle() {
     return this.handle;
   }
@@ -8,6 +8,8 @@ package org.whispersystems.libsignal.kdf;
 
 import org.signal.client.internal.Native;
 
+import java.nio.ByteBuffer;
+
 public abstract class HKDF {
   private static final int HASH_OUTPUT_SIZE  = 32;
 
@@ -27,6 +29,41 @@ public abstract class HKDF {
     return Native.HKDF_DeriveSecrets(outputLength, getVersion(), inputKeyMaterial, info, salt);
   }
 
+  /**
+   * Derives one output for each of {@code infos}, of the corresponding length in
+   * {@code outputLengths}, performing the extract step only once.
+   *
+   * @param salt the salt, or null to use the default salt.
+   */
+  public byte[][] deriveSecrets(byte[] inputKeyMaterial, byte[] salt, byte[][] infos, int[] outputLengths) {
+    int labelsLength = 0;
+    for (byte[] info : infos) {
+      labelsLength += 4 + info.length;
+    }
+
+    ByteBuffer labels = ByteBuffer.allocate(labelsLength);
+    for (byte[] info : infos) {
+      labels.putInt(info.length);
+      labels.put(info);
+    }
+
+    ByteBuffer lengths = ByteBuffer.allocate(4 * outputLengths.length);
+    for (int outputLength : outputLengths) {
+      if (outputLength < 0) {
+        throw new IllegalArgumentException("negative output length: " + outputLength);
+      }
+      lengths.putInt(outputLength);
+    }
+
+    ByteBuffer outputs = ByteBuffer.wrap(Native.HKDF_DeriveMany(getVersion(), inputKeyMaterial, salt, labels.array(), lengths.array()));
+    byte[][]   result  = new byte[infos.length][];
+    for (int i = 0; i < result.length; i++) {
+      result[i] = new byte[outputs.getInt()];
+      outputs.get(result[i]);
+    }
+    return result;
+  }
+
   protected abstract int getVersion();
 
 }
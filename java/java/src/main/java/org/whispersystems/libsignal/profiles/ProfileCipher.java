@@ -0,0 +1,24 @@
+//
+// Copyright 2021 Signal Messenger, LLC
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.whispersystems.libsignal.profiles;
+
+import org.signal.client.internal.Native;
+
+public class ProfileCipher {
+  /**
+   * Encrypts a profile name under a 32-byte profile key, padding it to a fixed size bucket.
+   */
+  public static byte[] encryptName(String name, byte[] profileKey) {
+    return Native.ProfileCipher_EncryptName(name, profileKey);
+  }
+
+  /**
+   * Decrypts a profile name produced by {@link #encryptName}, removing the padding.
+   */
+  public static String decryptName(byte[] ciphertext, byte[] profileKey) {
+    return Native.ProfileCipher_DecryptName(ciphertext, profileKey);
+  }
+}
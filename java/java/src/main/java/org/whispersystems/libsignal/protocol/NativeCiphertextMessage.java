@@ -0,0 +1,52 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.whispersystems.libsignal.protocol;
+
+import org.signal.client.internal.Native;
+import org.whispersystems.libsignal.InvalidMessageException;
+import org.whispersystems.libsignal.LegacyMessageException;
+
+/**
+ * A {@link CiphertextMessage} of any type, reconstructed from its serialized form and type tag.
+ */
+public class NativeCiphertextMessage implements CiphertextMessage {
+  private final long handle;
+
+  @Override
+  protected void finalize() {
+    Native.CiphertextMessage_Destroy(this.handle);
+  }
+
+  private NativeCiphertextMessage(long handle) {
+    this.handle = handle;
+  }
+
+  /**
+   * Deserializes {@code data} as the kind of message identified by {@code type}, one of the
+   * {@code *_TYPE} constants in {@link CiphertextMessage}.
+   *
+   * @throws IllegalArgumentException if {@code type} is not a known message type
+   */
+  public static NativeCiphertextMessage fromParts(int type, byte[] data)
+      throws InvalidMessageException, LegacyMessageException
+  {
+    return new NativeCiphertextMessage(Native.CiphertextMessage_FromParts(type, data));
+  }
+
+  @Override
+  public byte[] serialize() {
+    return Native.CiphertextMessage_Serialize(this.handle);
+  }
+
+  @Override
+  public int getType() {
+    return Native.CiphertextMessage_Type(this.handle);
+  }
+
+  public long nativeHandle() {
+    return this.handle;
+  }
+}
@@ -34,6 +34,10 @@ public class SenderKeyDistributionMessage implements CiphertextMessage {
     handle = Native.SenderKeyDistributionMessage_Deserialize(serialized);
   }
 
+  public static int chainKeyLength() {
+    return Native.SenderKeyDistributionMessage_ChainKeyLength();
+  }
+
   @Override
   public byte[] serialize() {
     return Native.SenderKeyDistributionMessage_GetSerialized(this.handle);
@@ -52,6 +56,13 @@ public class SenderKeyDistributionMessage implements CiphertextMessage {
     return Native.SenderKeyDistributionMessage_GetChainKey(this.handle);
   }
 
+  /**
+   * @return a key for encrypting shared group state, derived from this message's chain key.
+   */
+  public byte[] deriveGroupKey() {
+    return Native.SenderKeyDistributionMessage_DeriveGroupKey(this.handle);
+  }
+
   public ECPublicKey getSignatureKey() {
     return new ECPublicKey(Native.SenderKeyDistributionMessage_GetSignatureKey(this.handle));
   }
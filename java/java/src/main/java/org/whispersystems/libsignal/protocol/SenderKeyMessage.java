@@ -54,6 +54,14 @@ public class SenderKeyMessage implements CiphertextMessage {
     }
   }
 
+  public void verifySignature(SenderKeyDistributionMessage distribution)
+      throws InvalidMessageException
+  {
+    if(!Native.GroupCipher_VerifyAgainstDistribution(this.handle, distribution.nativeHandle())) {
+      throw new InvalidMessageException("Invalid signature!");
+    }
+  }
+
   @Override
   public byte[] serialize() {
     return Native.SenderKeyMessage_GetSerialized(this.handle);
@@ -40,6 +40,40 @@ public class PreKeyBundle {
                       identityKey.getPublicKey().nativeHandle());
   }
 
+  /**
+   * Creates a bundle with no one-time pre-key, as served once the server has run out of them.
+   */
+  public PreKeyBundle(int registrationId, int deviceId,
+                      int signedPreKeyId, ECPublicKey signedPreKeyPublic, byte[] signedPreKeySignature,
+                      IdentityKey identityKey)
+  {
+    this.handle = Native.PreKeyBundle_NewWithoutOneTimePreKey(registrationId, deviceId,
+                      signedPreKeyId,
+                      signedPreKeyPublic.nativeHandle(),
+                      signedPreKeySignature,
+                      identityKey.getPublicKey().nativeHandle());
+  }
+
+  private PreKeyBundle(long handle) {
+    this.handle = handle;
+  }
+
+  /**
+   * Parses a bundle from the compact form produced by {@link #toLinkingPayload()}.
+   *
+   * @throws IllegalArgumentException if the payload is malformed.
+   */
+  public static PreKeyBundle fromLinkingPayload(byte[] payload) {
+    return new PreKeyBundle(Native.PreKeyBundle_FromLinkingPayload(payload));
+  }
+
+  /**
+   * @return a compact binary encoding of this bundle, suitable for a device-linking QR code.
+   */
+  public byte[] toLinkingPayload() {
+    return Native.PreKeyBundle_ToLinkingPayload(this.handle);
+  }
+
   /**
    * @return the device ID this PreKey belongs to.
    */
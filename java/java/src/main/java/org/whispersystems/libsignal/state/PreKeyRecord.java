@@ -11,7 +11,10 @@ import org.whispersystems.libsignal.ecc.ECKeyPair;
 import org.whispersystems.libsignal.ecc.ECPrivateKey;
 import org.whispersystems.libsignal.ecc.ECPublicKey;
 
+import java.io.ByteArrayOutputStream;
 import java.io.IOException;
+import java.nio.ByteBuffer;
+import java.util.List;
 
 public class PreKeyRecord {
   private long handle;
@@ -29,6 +32,24 @@ public class PreKeyRecord {
     this.handle = Native.PreKeyRecord_Deserialize(serialized);
   }
 
+  /**
+   * @return the ids of all records that share their public key with another record, in order.
+   */
+  public static int[] findDuplicateKeys(List<PreKeyRecord> records) {
+    ByteArrayOutputStream serialized = new ByteArrayOutputStream();
+    for (PreKeyRecord record : records) {
+      byte[] bytes = record.serialize();
+      serialized.write(ByteBuffer.allocate(4).putInt(bytes.length).array(), 0, 4);
+      serialized.write(bytes, 0, bytes.length);
+    }
+    ByteBuffer ids = ByteBuffer.wrap(Native.PreKeyRecord_FindDuplicateKeys(serialized.toByteArray()));
+    int[] result = new int[ids.remaining() / 4];
+    for (int i = 0; i < result.length; i++) {
+      result[i] = ids.getInt();
+    }
+    return result;
+  }
+
   public int getId() {
     return Native.PreKeyRecord_GetId(this.handle);
   }
@@ -6,12 +6,14 @@
 package org.whispersystems.libsignal.state;
 
 import java.io.IOException;
+import java.nio.ByteBuffer;
 import org.signal.client.internal.Native;
 import org.whispersystems.libsignal.IdentityKey;
 import org.whispersystems.libsignal.IdentityKeyPair;
 import org.whispersystems.libsignal.InvalidKeyException;
 import org.whispersystems.libsignal.ecc.ECKeyPair;
 import org.whispersystems.libsignal.ecc.ECPublicKey;
+import org.whispersystems.libsignal.protocol.PreKeySignalMessage;
 
 /**
  * A SessionRecord encapsulates the state of an ongoing session.
@@ -20,6 +22,36 @@ import org.whispersystems.libsignal.ecc.ECPublicKey;
  */
 public class SessionRecord {
 
+  /**
+   * The result of {@link #validateIntegrity}.
+   *
+   * The order of these values matches the native SessionRecordIntegrity.
+   */
+  public enum Integrity {
+    VALID,
+    UNPARSEABLE,
+    INVALID_VERSION,
+    INCONSISTENT_CHAIN_INDICES,
+  }
+
+  public static class RegistrationIds {
+    private final int localRegistrationId;
+    private final int remoteRegistrationId;
+
+    RegistrationIds(int localRegistrationId, int remoteRegistrationId) {
+      this.localRegistrationId  = localRegistrationId;
+      this.remoteRegistrationId = remoteRegistrationId;
+    }
+
+    public int getLocalRegistrationId() {
+      return localRegistrationId;
+    }
+
+    public int getRemoteRegistrationId() {
+      return remoteRegistrationId;
+    }
+  }
+
   long handle;
 
   @Override
@@ -43,6 +75,28 @@ public class SessionRecord {
     this.handle = Native.SessionRecord_Deserialize(serialized);
   }
 
+  /**
+   * Checks a serialized record for corruption, so that a damaged record can be set aside
+   * instead of failing when it is used.
+   */
+  public static Integrity validateIntegrity(byte[] serialized) {
+    return Integrity.values()[Native.SessionRecord_ValidateIntegrity(serialized)];
+  }
+
+  /**
+   * Reads the version of the current session in a serialized record without fully
+   * deserializing it, so a store can decide whether to migrate the record.
+   *
+   * @return the session version, or 0 if the record has no current session.
+   */
+  public static int getStorageVersion(byte[] serialized) throws IOException {
+    try {
+      return Native.SessionRecord_StorageVersion(serialized);
+    } catch (IllegalArgumentException e) {
+      throw new IOException(e);
+    }
+  }
+
   /**
    * Move the current SessionState into the list of "previous" session states, and replace
    * the current SessionState with a fresh reset instance.
@@ -63,6 +117,32 @@ public class SessionRecord {
     return Native.SessionRecord_GetLocalRegistrationId(this.handle);
   }
 
+  public RegistrationIds getRegistrationIds() {
+    ByteBuffer ids = ByteBuffer.wrap(Native.SessionRecord_GetRegistrationIds(this.handle));
+    int localRegistrationId = ids.getInt();
+    return new RegistrationIds(localRegistrationId, ids.getInt());
+  }
+
+  /**
+   * Returns a value that both sides of a freshly initialized session compute identically, for
+   * use as a key-confirmation MAC.
+   *
+   * @throws IllegalStateException if the session's initial chain has already been used
+   */
+  public byte[] computeKeyConfirmation() {
+    return Native.SessionRecord_ComputeKeyConfirmation(this.handle);
+  }
+
+  /**
+   * Returns a 16-byte identifier for the current session, for correlating log events. It is not
+   * secret, but reveals nothing about the session's keys or messages.
+   *
+   * @throws IllegalStateException if there is no current session
+   */
+  public byte[] computeSessionId() {
+    return Native.SessionRecord_ComputeSessionId(this.handle);
+  }
+
   public IdentityKey getRemoteIdentityKey() {
     byte[] keyBytes = Native.SessionRecord_GetRemoteIdentityKeyPublic(this.handle);
 
@@ -86,10 +166,50 @@ public class SessionRecord {
     }
   }
 
+  /**
+   * @return when the current session was established, in milliseconds since the epoch.
+   */
+  public long getCreationTimestamp() {
+    return Native.SessionRecord_GetCreationTimestamp(this.handle);
+  }
+
+  /**
+   * @return true if the current session is ready to encrypt messages.
+   */
+  public boolean canEncrypt() {
+    return Native.SessionRecord_CanEncrypt(this.handle);
+  }
+
   public boolean hasSenderChain() {
     return Native.SessionRecord_HasSenderChain(this.handle);
   }
 
+  /**
+   * @return true if the given identity belongs to the current session or any archived session.
+   */
+  public boolean identitySeenBefore(IdentityKey identityKey) {
+    return Native.SessionRecord_IdentitySeenBefore(this.handle, identityKey.getPublicKey().nativeHandle());
+  }
+
+  /**
+   * @return true if the given message set up the current session or any archived session, meaning
+   *         it is a retransmission whose one-time pre-key must not be consumed again.
+   */
+  public boolean preKeyMessageAlreadyProcessed(PreKeySignalMessage message) {
+    return Native.SessionRecord_PreKeyMessageAlreadyProcessed(this.handle, message.nativeHandle());
+  }
+
+  /**
+   * @return true if the current session is older than the version this library creates.
+   */
+  public boolean needsUpgrade() {
+    return Native.SessionRecord_NeedsUpgrade(this.handle);
+  }
+
+  public long nativeHandle() {
+    return this.handle;
+  }
+
   /** @return a serialized version of the current SessionRecord. */
   public byte[] serialize() {
     return Native.SessionRecord_Serialize(this.handle);
@@ -102,6 +222,10 @@ public class SessionRecord {
         this.handle, senderEphemeral.nativeHandle());
   }
 
+  public byte[] deriveMessageKeyAt(ECPublicKey ratchetKey, int index) {
+    return Native.SessionRecord_DeriveMessageKeyAt(this.handle, ratchetKey.nativeHandle(), index);
+  }
+
   public byte[] getSenderChainKeyValue() {
     return Native.SessionRecord_GetSenderChainKeyValue(this.handle);
   }
@@ -110,11 +234,33 @@ public class SessionRecord {
     return Native.SessionRecord_GetAliceBaseKey(this.handle);
   }
 
+  public byte[] getRootKey() {
+    return Native.SessionRecord_GetRootKey(this.handle);
+  }
+
+  /**
+   * Sets up Alice's side of a session directly from Bob's keys, for tests that need a matching
+   * pair of sessions. Use {@link org.whispersystems.libsignal.SessionBuilder} for real sessions.
+   */
+  public static SessionRecord initializeAliceSession(
+      IdentityKeyPair identityKey,
+      ECKeyPair baseKey,
+      IdentityKey theirIdentityKey,
+      ECPublicKey theirSignedPreKey,
+      ECPublicKey theirRatchetKey) {
+    return initializeAliceSession(identityKey, baseKey, theirIdentityKey, theirSignedPreKey, null,
+                                  theirRatchetKey);
+  }
+
+  /**
+   * @param theirOneTimePreKey The one-time pre-key from Bob's bundle, or null if it had none.
+   */
   public static SessionRecord initializeAliceSession(
       IdentityKeyPair identityKey,
       ECKeyPair baseKey,
       IdentityKey theirIdentityKey,
       ECPublicKey theirSignedPreKey,
+      ECPublicKey theirOneTimePreKey,
       ECPublicKey theirRatchetKey) {
     return new SessionRecord(
         Native.SessionRecord_InitializeAliceSession(
@@ -124,28 +270,65 @@ public class SessionRecord {
             baseKey.getPublicKey().nativeHandle(),
             theirIdentityKey.getPublicKey().nativeHandle(),
             theirSignedPreKey.nativeHandle(),
+            theirOneTimePreKey != null ? theirOneTimePreKey.nativeHandle() : 0,
             theirRatchetKey.nativeHandle()));
   }
 
+  /**
+   * Sets up Bob's side of a session to match {@link #initializeAliceSession}, for tests only.
+   */
   public static SessionRecord initializeBobSession(
       IdentityKeyPair identityKey,
       ECKeyPair signedPreKey,
       ECKeyPair ephemeralKey,
       IdentityKey theirIdentityKey,
       ECPublicKey theirBaseKey) {
+    return initializeBobSession(identityKey, signedPreKey, null, ephemeralKey, theirIdentityKey,
+                                theirBaseKey);
+  }
+
+  /**
+   * @param oneTimePreKey The one-time pre-key Alice used, or null if she used none.
+   */
+  public static SessionRecord initializeBobSession(
+      IdentityKeyPair identityKey,
+      ECKeyPair signedPreKey,
+      ECKeyPair oneTimePreKey,
+      ECKeyPair ephemeralKey,
+      IdentityKey theirIdentityKey,
+      ECPublicKey theirBaseKey) {
     return new SessionRecord(
         Native.SessionRecord_InitializeBobSession(
             identityKey.getPrivateKey().nativeHandle(),
             identityKey.getPublicKey().getPublicKey().nativeHandle(),
             signedPreKey.getPrivateKey().nativeHandle(),
             signedPreKey.getPublicKey().nativeHandle(),
+            oneTimePreKey != null ? oneTimePreKey.getPrivateKey().nativeHandle() : 0,
+            oneTimePreKey != null ? oneTimePreKey.getPublicKey().nativeHandle() : 0,
             ephemeralKey.getPrivateKey().nativeHandle(),
             ephemeralKey.getPublicKey().nativeHandle(),
             theirIdentityKey.getPublicKey().nativeHandle(),
             theirBaseKey.nativeHandle()));
   }
 
-  long nativeHandle() {
-    return this.handle;
+  /**
+   * Set up Bob's side of a session from Alice's first message, as decryption would.
+   *
+   * @param oneTimePreKey The one-time pre-key named by the message, or null if it names none.
+   */
+  public static SessionRecord initializeBobSessionFromPreKeyMessage(
+      IdentityKeyPair identityKey,
+      ECKeyPair signedPreKey,
+      ECKeyPair oneTimePreKey,
+      PreKeySignalMessage message) {
+    return new SessionRecord(
+        Native.SessionRecord_InitializeBobFromPreKeyMessage(
+            message.nativeHandle(),
+            identityKey.getPrivateKey().nativeHandle(),
+            identityKey.getPublicKey().getPublicKey().nativeHandle(),
+            signedPreKey.getPrivateKey().nativeHandle(),
+            signedPreKey.getPublicKey().nativeHandle(),
+            oneTimePreKey != null ? oneTimePreKey.getPrivateKey().nativeHandle() : 0,
+            oneTimePreKey != null ? oneTimePreKey.getPublicKey().nativeHandle() : 0));
   }
 }
@@ -11,7 +11,10 @@ import org.whispersystems.libsignal.ecc.ECKeyPair;
 import org.whispersystems.libsignal.ecc.ECPrivateKey;
 import org.whispersystems.libsignal.ecc.ECPublicKey;
 
+import java.io.ByteArrayOutputStream;
 import java.io.IOException;
+import java.nio.ByteBuffer;
+import java.util.List;
 
 public class SignedPreKeyRecord {
   private long handle;
@@ -50,6 +53,42 @@ public class SignedPreKeyRecord {
     return Native.SignedPreKeyRecord_GetSignature(this.handle);
   }
 
+  /**
+   * @return true if this record's signature is a valid signature of its public key by the given
+   *         identity key.
+   */
+  public boolean verifyOwnSignature(ECPublicKey identityKey) {
+    return Native.SignedPreKeyRecord_VerifyOwnSignature(this.handle, identityKey.nativeHandle());
+  }
+
+  /**
+   * @return true if this record's public key is the one derived from its private key. A mismatch
+   *         means the record has been corrupted in storage.
+   */
+  public boolean verifyKeyPair() {
+    return Native.SignedPreKeyRecord_VerifyKeyPair(this.handle);
+  }
+
+  /**
+   * @return the time at which this key should be rotated, interval after it was created.
+   */
+  public long getNextRotationTime(long interval) {
+    return Native.SignedPreKeyRecord_NextRotationTime(this.handle, interval);
+  }
+
+  /**
+   * @return the earliest time at which one of the given records becomes older than maxAge.
+   */
+  public static long earliestExpiry(List<SignedPreKeyRecord> records, long maxAge) {
+    ByteArrayOutputStream serialized = new ByteArrayOutputStream();
+    for (SignedPreKeyRecord record : records) {
+      byte[] bytes = record.serialize();
+      serialized.write(ByteBuffer.allocate(4).putInt(bytes.length).array(), 0, 4);
+      serialized.write(bytes, 0, bytes.length);
+    }
+    return Native.SignedPreKeyRecord_EarliestExpiry(serialized.toByteArray(), maxAge);
+  }
+
   public byte[] serialize() {
     return Native.SignedPreKeyRecord_GetSerialized(this.handle);
   }
@@ -0,0 +1,17 @@
+//
+// Copyright 2021 Signal Messenger, LLC
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.whispersystems.libsignal.util;
+
+import org.signal.client.internal.Native;
+
+public class AttachmentPadding {
+  /**
+   * Returns the size an attachment of {@code plaintextLength} bytes is padded to before upload.
+   */
+  public static long getPaddedSize(long plaintextLength) {
+    return Native.Attachment_PaddedSize(plaintextLength);
+  }
+}
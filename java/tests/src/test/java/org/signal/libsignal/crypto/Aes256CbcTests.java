@@ -0,0 +1,82 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.crypto;
+
+import java.util.Arrays;
+import junit.framework.TestCase;
+import org.whispersystems.libsignal.InvalidMessageException;
+import org.whispersystems.libsignal.util.Hex;
+
+public class Aes256CbcTests extends TestCase {
+
+  // NIST SP 800-38A F.2.5, followed by one block of PKCS7 padding.
+  private static final String KEY = "603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4";
+  private static final String IV = "000102030405060708090a0b0c0d0e0f";
+  private static final String PTEXT =
+      "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51" +
+      "30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710";
+  private static final String CTEXT =
+      "f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d" +
+      "39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c19078c6a9d1b" +
+      "3f461796d6b0d6b2e0c2a72b4d80e644";
+
+  public void testNistVector() throws Exception {
+    byte[] key = Hex.fromStringCondensed(KEY);
+    byte[] iv = Hex.fromStringCondensed(IV);
+
+    byte[] ctext = Aes256Cbc.encrypt(key, iv, Hex.fromStringCondensed(PTEXT));
+    assertEquals(CTEXT, Hex.toStringCondensed(ctext));
+
+    byte[] ptext = Aes256Cbc.decrypt(key, iv, ctext);
+    assertEquals(PTEXT, Hex.toStringCondensed(ptext));
+  }
+
+  public void testRoundTrip() throws Exception {
+    byte[] key = new byte[32];
+    byte[] iv = new byte[16];
+    for (int length = 0; length <= 33; length++) {
+      byte[] ptext = new byte[length];
+      Arrays.fill(ptext, (byte)length);
+      byte[] ctext = Aes256Cbc.encrypt(key, iv, ptext);
+      assertEquals((length / 16 + 1) * 16, ctext.length);
+      assertTrue(Arrays.equals(ptext, Aes256Cbc.decrypt(key, iv, ctext)));
+    }
+  }
+
+  public void testBadLength() throws Exception {
+    byte[] key = Hex.fromStringCondensed(KEY);
+    byte[] iv = Hex.fromStringCondensed(IV);
+    byte[] ctext = Hex.fromStringCondensed(CTEXT);
+
+    try {
+      Aes256Cbc.decrypt(key, iv, Arrays.copyOf(ctext, ctext.length - 1));
+      fail("accepted a partial block");
+    } catch (IllegalArgumentException e) {
+      // good
+    }
+
+    try {
+      Aes256Cbc.decrypt(key, iv, new byte[0]);
+      fail("accepted an empty ciphertext");
+    } catch (IllegalArgumentException e) {
+      // good
+    }
+  }
+
+  public void testBadPadding() throws Exception {
+    byte[] key = Hex.fromStringCondensed(KEY);
+    byte[] iv = Hex.fromStringCondensed(IV);
+    byte[] ctext = Hex.fromStringCondensed(CTEXT);
+
+    try {
+      // Dropping the padding block leaves NIST plaintext bytes where the padding should be.
+      Aes256Cbc.decrypt(key, iv, Arrays.copyOf(ctext, ctext.length - 16));
+      fail("accepted invalid padding");
+    } catch (InvalidMessageException e) {
+      // good
+    }
+  }
+}
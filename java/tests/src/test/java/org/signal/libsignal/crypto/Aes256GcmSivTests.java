@@ -6,6 +6,7 @@
 package org.signal.libsignal.crypto;
 
 import java.io.IOException;
+import java.nio.ByteBuffer;
 import junit.framework.TestCase;
 import org.whispersystems.libsignal.InvalidKeyException;
 import org.whispersystems.libsignal.InvalidMessageException;
@@ -36,6 +37,58 @@ public class Aes256GcmSivTests extends TestCase {
     }
   }
 
+  public void testAesGcmSivTagLength() throws Exception {
+    assertEquals(16, Aes256GcmSiv.tagLength());
+
+    Aes256GcmSiv gcm_siv = new Aes256GcmSiv(new byte[32]);
+    byte[] ptext = new byte[5];
+    byte[] ctext = gcm_siv.encrypt(ptext, new byte[12], new byte[0]);
+    assertEquals(Aes256GcmSiv.tagLength(), ctext.length - ptext.length);
+  }
+
+  public void testAesGcmSivEncryptInPlace() throws Exception {
+    Aes256GcmSiv gcm_siv = new Aes256GcmSiv(Hex.fromStringCondensed(
+        "bae8e37fc83441b16034566b7a806c46bb91c3c5aedb64a6c590bc84d1a5e269"));
+    byte[] ptext = Hex.fromStringCondensed("671fdd4fbdc66f146545fc880c94a95198");
+    byte[] nonce = Hex.fromStringCondensed("e4b47801afc0577e34699b9e");
+    byte[] ad = Hex.fromStringCondensed("874296d5cc1fd16132");
+
+    byte[] expected = gcm_siv.encrypt(ptext, nonce, ad);
+
+    ByteBuffer buffer = ByteBuffer.allocateDirect(ptext.length + 3);
+    buffer.put(new byte[3]).put(ptext);
+    buffer.position(3);
+    byte[] tag = new byte[Aes256GcmSiv.tagLength()];
+    gcm_siv.encryptInPlace(buffer, tag, nonce, ad);
+    assertEquals(3, buffer.position());
+
+    byte[] ctext = new byte[ptext.length];
+    buffer.get(ctext);
+    assertEquals(Hex.toStringCondensed(expected), Hex.toStringCondensed(ctext) + Hex.toStringCondensed(tag));
+
+    try {
+      gcm_siv.encryptInPlace(ByteBuffer.allocateDirect(ptext.length), new byte[15], nonce, ad);
+      throw new AssertionError("Short tag buffer accepted");
+    } catch (IllegalArgumentException e) {
+      /* good */
+    }
+
+    try {
+      gcm_siv.encryptInPlace(ByteBuffer.wrap(ptext.clone()), tag, nonce, ad);
+      throw new AssertionError("Heap buffer accepted");
+    } catch (IllegalArgumentException e) {
+      /* good */
+    }
+
+    try {
+      byte[] shared = new byte[Aes256GcmSiv.tagLength()];
+      gcm_siv.encryptInPlace(ByteBuffer.allocateDirect(ptext.length), shared, nonce, shared);
+      throw new AssertionError("Tag buffer aliasing the associated data accepted");
+    } catch (IllegalArgumentException e) {
+      /* good */
+    }
+  }
+
   public void testAesGcmSivKats() throws Exception {
     testAesGcmSivKat(
         "bae8e37fc83441b16034566b7a806c46bb91c3c5aedb64a6c590bc84d1a5e269",
@@ -0,0 +1,21 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.crypto;
+
+import junit.framework.TestCase;
+import org.whispersystems.libsignal.util.Hex;
+
+public class ConstantTimeTests extends TestCase {
+
+  public void testIsEqual() throws Exception {
+    byte[] mac = Hex.fromStringCondensed("0102030405060708");
+
+    assertTrue(ConstantTime.isEqual(mac, mac.clone()));
+    assertFalse(ConstantTime.isEqual(mac, Hex.fromStringCondensed("0102030405060709")));
+    assertFalse(ConstantTime.isEqual(mac, Hex.fromStringCondensed("01020304050607")));
+    assertTrue(ConstantTime.isEqual(new byte[0], new byte[0]));
+  }
+}
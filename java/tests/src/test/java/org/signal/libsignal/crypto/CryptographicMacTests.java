@@ -0,0 +1,60 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.signal.libsignal.crypto;
+
+import java.util.Arrays;
+import junit.framework.TestCase;
+import org.whispersystems.libsignal.util.Hex;
+
+public class CryptographicMacTests extends TestCase {
+
+  public void testRfc4231Vector() throws Exception {
+    byte[] key = Hex.fromStringCondensed("0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b0b");
+    CryptographicMac mac = new CryptographicMac(key);
+    mac.update("Hi There".getBytes("UTF-8"));
+    assertEquals("b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+                 Hex.toStringCondensed(mac.doFinal()));
+  }
+
+  public void testChunkedUpdatesMatchSingleUpdate() throws Exception {
+    byte[] key = new byte[32];
+    byte[] input = new byte[1000];
+    for (int i = 0; i < input.length; i++) {
+      input[i] = (byte)i;
+    }
+
+    CryptographicMac single = new CryptographicMac(key);
+    single.update(input);
+
+    CryptographicMac chunked = new CryptographicMac(key);
+    chunked.update(Arrays.copyOfRange(input, 0, 1));
+    chunked.update(Arrays.copyOfRange(input, 1, 1));
+    chunked.update(Arrays.copyOfRange(input, 1, 500));
+    chunked.update(Arrays.copyOfRange(input, 500, 1000));
+
+    assertTrue(Arrays.equals(single.doFinal(), chunked.doFinal()));
+  }
+
+  public void testDoubleFinalize() throws Exception {
+    CryptographicMac mac = new CryptographicMac(new byte[32]);
+    mac.update(new byte[10]);
+    mac.doFinal();
+
+    try {
+      mac.doFinal();
+      fail("MAC finalized twice");
+    } catch (IllegalStateException e) {
+      // good
+    }
+
+    try {
+      mac.update(new byte[10]);
+      fail("MAC updated after finalizing");
+    } catch (IllegalStateException e) {
+      // good
+    }
+  }
+}
@@ -7,6 +7,7 @@ import org.signal.libsignal.metadata.certificate.CertificateValidator;
 import org.signal.libsignal.metadata.certificate.InvalidCertificateException;
 import org.signal.libsignal.metadata.certificate.SenderCertificate;
 import org.signal.libsignal.metadata.certificate.ServerCertificate;
+import org.signal.libsignal.metadata.protocol.UnidentifiedSenderMessageContent;
 import org.whispersystems.libsignal.IdentityKeyPair;
 import org.whispersystems.libsignal.InvalidKeyException;
 import org.whispersystems.libsignal.SessionBuilder;
@@ -15,6 +16,7 @@ import org.whispersystems.libsignal.UntrustedIdentityException;
 import org.whispersystems.libsignal.ecc.Curve;
 import org.whispersystems.libsignal.ecc.ECKeyPair;
 import org.whispersystems.libsignal.ecc.ECPublicKey;
+import org.whispersystems.libsignal.protocol.CiphertextMessage;
 import org.whispersystems.libsignal.state.PreKeyBundle;
 import org.whispersystems.libsignal.state.PreKeyRecord;
 import org.whispersystems.libsignal.state.SignedPreKeyRecord;
@@ -131,7 +133,35 @@ public class SealedSessionCipherTest extends TestCase {
     }
   }
 
+  public void testRoundTripHarness() throws Exception {
+    ECKeyPair         trustRoot         = Curve.generateKeyPair();
+    ECKeyPair         senderIdentity    = Curve.generateKeyPair();
+    SenderCertificate senderCertificate = createCertificateFor(trustRoot, UUID.fromString("9d0652a3-dcc3-4d11-975f-74d61598733f"), "+14151111111", 7, senderIdentity.getPublicKey(), 31337);
 
+    SignalProtocolAddress sender = new SignalProtocolAddress(
+        Native.TestHarness_SealedSenderRoundTrip(senderIdentity.getPrivateKey().nativeHandle(),
+                                                 senderCertificate.nativeHandle(),
+                                                 trustRoot.getPublicKey().nativeHandle(),
+                                                 31335,
+                                                 "smert za smert".getBytes()));
+
+    assertEquals(sender.getName(), senderCertificate.getSenderUuid());
+    assertEquals(sender.getDeviceId(), 7);
+  }
+
+  public void testMessageTypeNames() {
+    assertEquals(UnidentifiedSenderMessageContent.getTypeName(CiphertextMessage.WHISPER_TYPE), "whisper");
+    assertEquals(UnidentifiedSenderMessageContent.getTypeName(CiphertextMessage.PREKEY_TYPE), "prekey");
+    assertEquals(UnidentifiedSenderMessageContent.getTypeName(CiphertextMessage.SENDERKEY_TYPE), "senderkey");
+    assertEquals(UnidentifiedSenderMessageContent.getTypeName(CiphertextMessage.SENDERKEY_DISTRIBUTION_TYPE), "senderkey-distribution");
+
+    try {
+      UnidentifiedSenderMessageContent.getTypeName(0x7f);
+      fail();
+    } catch (IllegalArgumentException e) {
+      // good
+    }
+  }
 
   private SenderCertificate createCertificateFor(ECKeyPair trustRoot, UUID uuid, String e164, int deviceId, ECPublicKey identityKey, long expires)
       throws InvalidKeyException, InvalidCertificateException {
@@ -54,6 +54,37 @@ public class SenderCertificateTest extends TestCase {
     }
   }
 
+  public void testValidateChainExpired() throws InvalidCertificateException, InvalidKeyException {
+    ECKeyPair key = Curve.generateKeyPair();
+
+    SenderCertificate   senderCertificate = createCertificateFor(trustRoot, UUID.fromString("9d0652a3-dcc3-4d11-975f-74d61598733f"), "+14151111111", 31338, key.getPublicKey(), 31337);
+    CertificateValidator validator         = new CertificateValidator(trustRoot.getPublicKey());
+
+    validator.validateChain(senderCertificate, 31336);
+
+    try {
+      validator.validateChain(senderCertificate, 31338);
+      throw new AssertionError();
+    } catch (ExpiredCertificateException e) {
+      // good
+    }
+  }
+
+  public void testValidateChainBadSignature() throws InvalidCertificateException, InvalidKeyException {
+    ECKeyPair key = Curve.generateKeyPair();
+
+    SenderCertificate senderCertificate = createCertificateFor(trustRoot, UUID.fromString("9d0652a3-dcc3-4d11-975f-74d61598733f"), "+14151111111", 31338, key.getPublicKey(), 31337);
+
+    try {
+      new CertificateValidator(Curve.generateKeyPair().getPublicKey()).validateChain(senderCertificate, 31336);
+      throw new AssertionError();
+    } catch (ExpiredCertificateException e) {
+      throw new AssertionError(e);
+    } catch (InvalidCertificateException e) {
+      assertTrue(e.getCause() instanceof InvalidKeyException);
+    }
+  }
+
   private SenderCertificate createCertificateFor(ECKeyPair trustRoot, UUID uuid, String e164, int deviceId, ECPublicKey identityKey, long expires)
       throws InvalidKeyException, InvalidCertificateException {
     ECKeyPair serverKey = Curve.generateKeyPair();
@@ -23,6 +23,17 @@ public class ServerCertificateTest extends TestCase {
     new CertificateValidator(trustRoot.getPublicKey()).validate(new ServerCertificate(serialized));
   }
 
+  public void testVerify() {
+    ECKeyPair trustRoot = Curve.generateKeyPair();
+    ECKeyPair keyPair   = Curve.generateKeyPair();
+
+    ServerCertificate certificate = new ServerCertificate(
+       Native.ServerCertificate_New(1, keyPair.getPublicKey().nativeHandle(), trustRoot.getPrivateKey().nativeHandle()));
+
+    assertTrue(certificate.verify(trustRoot.getPublicKey()));
+    assertFalse(certificate.verify(Curve.generateKeyPair().getPublicKey()));
+  }
+
   public void testBadSignature() throws Exception {
     ECKeyPair trustRoot = Curve.generateKeyPair();
     ECKeyPair keyPair   = Curve.generateKeyPair();
@@ -381,6 +381,37 @@ public class SessionBuilderTest extends TestCase {
     assertTrue(originalMessage.equals(new String(plaintext)));
   }
 
+  public void testBundleWithoutOneTimePreKey() throws Exception {
+    SignalProtocolStore aliceStore = new TestInMemorySignalProtocolStore();
+    SignalProtocolStore bobStore   = new TestInMemorySignalProtocolStore();
+
+    ECKeyPair bobSignedPreKeyPair      = Curve.generateKeyPair();
+    byte[]    bobSignedPreKeySignature = Curve.calculateSignature(bobStore.getIdentityKeyPair().getPrivateKey(),
+                                                                  bobSignedPreKeyPair.getPublicKey().serialize());
+
+    PreKeyBundle bobPreKey = new PreKeyBundle(bobStore.getLocalRegistrationId(), 1,
+                                              22, bobSignedPreKeyPair.getPublicKey(),
+                                              bobSignedPreKeySignature,
+                                              bobStore.getIdentityKeyPair().getPublicKey());
+
+    assertEquals(-1, bobPreKey.getPreKeyId());
+    assertNull(bobPreKey.getPreKey());
+
+    new SessionBuilder(aliceStore, BOB_ADDRESS).process(bobPreKey);
+    assertTrue(aliceStore.containsSession(BOB_ADDRESS));
+
+    String            originalMessage = "Good, fast, cheap: pick two";
+    CiphertextMessage outgoingMessage = new SessionCipher(aliceStore, BOB_ADDRESS).encrypt(originalMessage.getBytes());
+    assertEquals(CiphertextMessage.PREKEY_TYPE, outgoingMessage.getType());
+
+    PreKeySignalMessage incomingMessage = new PreKeySignalMessage(outgoingMessage.serialize());
+    assertFalse(incomingMessage.getPreKeyId().isPresent());
+
+    bobStore.storeSignedPreKey(22, new SignedPreKeyRecord(22, System.currentTimeMillis(), bobSignedPreKeyPair, bobSignedPreKeySignature));
+
+    byte[] plaintext = new SessionCipher(bobStore, ALICE_ADDRESS).decrypt(incomingMessage);
+    assertEquals(originalMessage, new String(plaintext));
+  }
 
   private void runInteraction(SignalProtocolStore aliceStore, SignalProtocolStore bobStore)
       throws DuplicateMessageException, LegacyMessageException, InvalidMessageException, NoSessionException, UntrustedIdentityException
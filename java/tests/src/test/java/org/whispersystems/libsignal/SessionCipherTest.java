@@ -6,14 +6,18 @@ import org.whispersystems.libsignal.ecc.Curve;
 import org.whispersystems.libsignal.ecc.ECKeyPair;
 import org.whispersystems.libsignal.ecc.ECPublicKey;
 import org.whispersystems.libsignal.ecc.ECPrivateKey;
+import org.whispersystems.libsignal.kdf.HKDF;
 import org.whispersystems.libsignal.protocol.CiphertextMessage;
 import org.whispersystems.libsignal.protocol.SignalMessage;
+import org.whispersystems.libsignal.protocol.SignalMessageTest;
 import org.whispersystems.libsignal.state.SignalProtocolStore;
 import org.whispersystems.libsignal.state.SessionRecord;
+import org.whispersystems.libsignal.util.ByteUtil;
 import org.whispersystems.libsignal.util.guava.Optional;
 import org.whispersystems.libsignal.util.Pair;
 
 import java.security.NoSuchAlgorithmException;
+import javax.crypto.spec.SecretKeySpec;
 import java.util.ArrayList;
 import java.util.Arrays;
 import java.util.Collections;
@@ -42,6 +46,62 @@ public class SessionCipherTest extends TestCase {
     runInteraction(sessions.aliceSession, sessions.bobSession);
   }
 
+  public void testRootKeysAfterInit() throws Exception {
+    ECKeyPair       aliceIdentityKeyPair = Curve.generateKeyPair();
+    IdentityKeyPair aliceIdentityKey     = new IdentityKeyPair(new IdentityKey(aliceIdentityKeyPair.getPublicKey()),
+                                                               aliceIdentityKeyPair.getPrivateKey());
+    ECKeyPair       aliceBaseKey         = Curve.generateKeyPair();
+
+    ECKeyPair       bobIdentityKeyPair   = Curve.generateKeyPair();
+    IdentityKeyPair bobIdentityKey       = new IdentityKeyPair(new IdentityKey(bobIdentityKeyPair.getPublicKey()),
+                                                               bobIdentityKeyPair.getPrivateKey());
+    ECKeyPair       bobSignedPreKey      = Curve.generateKeyPair();
+
+    SessionRecord aliceSession = SessionRecord.initializeAliceSession(aliceIdentityKey,
+                                                                      aliceBaseKey,
+                                                                      bobIdentityKey.getPublicKey(),
+                                                                      bobSignedPreKey.getPublicKey(),
+                                                                      bobSignedPreKey.getPublicKey());
+    SessionRecord bobSession   = SessionRecord.initializeBobSession(bobIdentityKey,
+                                                                    bobSignedPreKey,
+                                                                    bobSignedPreKey,
+                                                                    aliceIdentityKey.getPublicKey(),
+                                                                    aliceBaseKey.getPublicKey());
+
+    // Bob's root key comes straight out of the X3DH agreement.
+    byte[] discontinuity = new byte[32];
+    Arrays.fill(discontinuity, (byte)0xFF);
+    byte[] masterSecret = ByteUtil.combine(discontinuity,
+                                           Curve.calculateAgreement(aliceIdentityKey.getPublicKey().getPublicKey(), bobSignedPreKey.getPrivateKey()),
+                                           Curve.calculateAgreement(aliceBaseKey.getPublicKey(), bobIdentityKey.getPrivateKey()),
+                                           Curve.calculateAgreement(aliceBaseKey.getPublicKey(), bobSignedPreKey.getPrivateKey()));
+
+    HKDF   kdf            = HKDF.createFor(3);
+    byte[] initialRootKey = Arrays.copyOf(kdf.deriveSecrets(masterSecret, "WhisperText".getBytes(), 64), 32);
+
+    byte[] bobRootKey = bobSession.getRootKey();
+    assertEquals(32, bobRootKey.length);
+    assertTrue(Arrays.equals(initialRootKey, bobRootKey));
+
+    // Alice has already taken the first DH ratchet step against Bob's signed pre-key, so her root
+    // key is the one Bob derives when her first message arrives.
+    SignalProtocolAddress bobAddress = new SignalProtocolAddress("+14158888888", 1);
+    SignalProtocolStore   aliceStore = new TestInMemorySignalProtocolStore();
+    aliceStore.storeSession(bobAddress, aliceSession);
+
+    SignalMessage message = new SignalMessage(new SessionCipher(aliceStore, bobAddress).encrypt("hi".getBytes()).serialize());
+    byte[] ratchetSecret        = Curve.calculateAgreement(message.getSenderRatchetKey(), bobSignedPreKey.getPrivateKey());
+    byte[] expectedAliceRootKey = Arrays.copyOf(kdf.deriveSecrets(ratchetSecret, initialRootKey, "WhisperRatchet".getBytes(), 64), 32);
+
+    byte[] aliceRootKey = aliceStore.loadSession(bobAddress).getRootKey();
+    assertEquals(32, aliceRootKey.length);
+    assertTrue(Arrays.equals(expectedAliceRootKey, aliceRootKey));
+    assertTrue(Arrays.equals(expectedAliceRootKey, aliceSession.getRootKey()));
+
+    SessionRecord aliceCopy = new SessionRecord(aliceSession.serialize());
+    assertTrue(Arrays.equals(aliceCopy.getRootKey(), aliceRootKey));
+  }
+
   public void testMessageKeyLimits() throws Exception {
     PairOfSessions sessions = initializeSessionsV3();
 
@@ -102,6 +162,35 @@ public class SessionCipherTest extends TestCase {
     assertTrue(Arrays.equals(alicePlaintext, bobPlaintext2));
   }
 
+  public void testShouldResetSession() throws Exception {
+    PairOfSessions sessions = initializeSessionsV3();
+
+    SignalProtocolStore   aliceStore = new TestInMemorySignalProtocolStore();
+    SignalProtocolAddress bobAddress = new SignalProtocolAddress("+14158888888", 1);
+    aliceStore.storeSession(bobAddress, sessions.aliceSession);
+
+    CiphertextMessage message = new SessionCipher(aliceStore, bobAddress).encrypt("hi".getBytes());
+    assertEquals(CiphertextMessage.WHISPER_TYPE, message.getType());
+
+    // SignalErrorCode values from signal_ffi.h.
+    final int invalidMessage    = 30;
+    final int untrustedIdentity = 60;
+    final int sessionNotFound   = 80;
+    final int duplicatedMessage = 90;
+
+    assertTrue(SessionCipher.shouldResetSession(invalidMessage, message));
+    assertTrue(SessionCipher.shouldResetSession(sessionNotFound, message));
+    assertFalse(SessionCipher.shouldResetSession(untrustedIdentity, message));
+    assertFalse(SessionCipher.shouldResetSession(duplicatedMessage, message));
+  }
+
+  public void testDescribeErrorCode() {
+    assertEquals("invalid argument", SessionCipher.describeErrorCode(5));
+    assertEquals("untrusted identity", SessionCipher.describeErrorCode(60));
+    assertEquals("duplicated message", SessionCipher.describeErrorCode(90));
+    assertEquals("unknown error", SessionCipher.describeErrorCode(9999));
+  }
+
   private void runInteraction(SessionRecord aliceSessionRecord, SessionRecord bobSessionRecord)
       throws DuplicateMessageException, LegacyMessageException, InvalidMessageException, NoSuchAlgorithmException, NoSessionException, UntrustedIdentityException {
     SignalProtocolStore aliceStore = new TestInMemorySignalProtocolStore();
@@ -172,6 +261,39 @@ public class SessionCipherTest extends TestCase {
     }
   }
 
+  public void testDecryptAfterReserializeRoundTrip() throws Exception {
+    PairOfSessions sessions = initializeSessionsV3();
+
+    SignalProtocolStore aliceStore = new TestInMemorySignalProtocolStore();
+    SignalProtocolStore bobStore   = new TestInMemorySignalProtocolStore();
+
+    SignalProtocolAddress aliceAddress = new SignalProtocolAddress("+14159999999", 1);
+    SignalProtocolAddress bobAddress   = new SignalProtocolAddress("+14158888888", 1);
+
+    aliceStore.storeSession(bobAddress, sessions.aliceSession);
+    bobStore.storeSession(aliceAddress, sessions.bobSession);
+
+    SessionCipher aliceCipher = new SessionCipher(aliceStore, bobAddress);
+    SessionCipher bobCipher   = new SessionCipher(bobStore, aliceAddress);
+
+    byte[] plaintext = "This is a plaintext message.".getBytes();
+    bobCipher.decrypt(new SignalMessage(aliceCipher.encrypt(plaintext).serialize()));
+
+    SignalMessage original      = new SignalMessage(aliceCipher.encrypt(plaintext).serialize());
+    SessionRecord bobSession    = bobStore.loadSession(aliceAddress);
+    byte[]        keys          = bobSession.deriveMessageKeyAt(original.getSenderRatchetKey(), original.getCounter());
+    SecretKeySpec macKey        = new SecretKeySpec(Arrays.copyOfRange(keys, 32, 64), "HmacSHA256");
+    IdentityKey   aliceIdentity = bobSession.getRemoteIdentityKey();
+    IdentityKey   bobIdentity   = bobSession.getLocalIdentityKey();
+
+    SignalMessage upgraded = SignalMessageTest.reserialize(original, 4, macKey, aliceIdentity, bobIdentity);
+    upgraded.verifyMac(aliceIdentity, bobIdentity, macKey);
+
+    SignalMessage restored = SignalMessageTest.reserialize(upgraded, 3, macKey, aliceIdentity, bobIdentity);
+    assertTrue(Arrays.equals(original.serialize(), restored.serialize()));
+    assertTrue(Arrays.equals(plaintext, bobCipher.decrypt(restored)));
+  }
+
   private PairOfSessions initializeSessionsV3() throws InvalidKeyException {
     ECKeyPair       aliceIdentityKeyPair = Curve.generateKeyPair();
     IdentityKeyPair aliceIdentityKey     = new IdentityKeyPair(new IdentityKey(aliceIdentityKeyPair.getPublicKey()),
@@ -5,11 +5,23 @@
 
 package org.whispersystems.libsignal;
 
+import java.util.Arrays;
 import junit.framework.TestCase;
+import org.signal.client.internal.Native;
+import org.whispersystems.libsignal.ecc.Curve;
+import org.whispersystems.libsignal.ecc.ECKeyPair;
+import org.whispersystems.libsignal.ecc.ECPrivateKey;
+import org.whispersystems.libsignal.protocol.CiphertextMessage;
+import org.whispersystems.libsignal.protocol.PreKeySignalMessage;
+import org.whispersystems.libsignal.state.PreKeyBundle;
 import org.whispersystems.libsignal.state.SessionRecord;
+import org.whispersystems.libsignal.state.SignalProtocolStore;
 
 public class SessionRecordTest extends TestCase {
 
+  private static final SignalProtocolAddress ALICE_ADDRESS = new SignalProtocolAddress("+14151111111", 1);
+  private static final SignalProtocolAddress BOB_ADDRESS   = new SignalProtocolAddress("+14152222222", 1);
+
   public void testUninitAccess() {
     SessionRecord empty_record = new SessionRecord();
 
@@ -17,4 +29,129 @@ public class SessionRecordTest extends TestCase {
 
     assertEquals(empty_record.getSessionVersion(), 0);
   }
+
+  public void testInitializeBobFromPreKeyMessage() throws Exception {
+    SignalProtocolStore aliceStore = new TestInMemorySignalProtocolStore();
+    SignalProtocolStore bobStore   = new TestInMemorySignalProtocolStore();
+
+    ECKeyPair bobPreKeyPair            = Curve.generateKeyPair();
+    ECKeyPair bobSignedPreKeyPair      = Curve.generateKeyPair();
+    byte[]    bobSignedPreKeySignature = Curve.calculateSignature(bobStore.getIdentityKeyPair().getPrivateKey(),
+                                                                  bobSignedPreKeyPair.getPublicKey().serialize());
+
+    PreKeyBundle bobPreKey = new PreKeyBundle(bobStore.getLocalRegistrationId(), 1,
+                                              31337, bobPreKeyPair.getPublicKey(),
+                                              22, bobSignedPreKeyPair.getPublicKey(),
+                                              bobSignedPreKeySignature,
+                                              bobStore.getIdentityKeyPair().getPublicKey());
+
+    new SessionBuilder(aliceStore, BOB_ADDRESS).process(bobPreKey);
+
+    SessionRecord aliceRecord = aliceStore.loadSession(BOB_ADDRESS);
+    SessionRecord.RegistrationIds aliceIds = aliceRecord.getRegistrationIds();
+    assertEquals(aliceIds.getLocalRegistrationId(), aliceRecord.getLocalRegistrationId());
+    assertEquals(aliceIds.getRemoteRegistrationId(), aliceRecord.getRemoteRegistrationId());
+    assertEquals(aliceIds.getLocalRegistrationId(), aliceStore.getLocalRegistrationId());
+    assertEquals(aliceIds.getRemoteRegistrationId(), bobStore.getLocalRegistrationId());
+
+    String            originalMessage = "Good, fast, cheap: pick two";
+    CiphertextMessage outgoingMessage = new SessionCipher(aliceStore, BOB_ADDRESS).encrypt(originalMessage.getBytes());
+    assertEquals(outgoingMessage.getType(), CiphertextMessage.PREKEY_TYPE);
+
+    PreKeySignalMessage incomingMessage = new PreKeySignalMessage(outgoingMessage.serialize());
+    SessionRecord bobRecord = SessionRecord.initializeBobSessionFromPreKeyMessage(
+        bobStore.getIdentityKeyPair(), bobSignedPreKeyPair, bobPreKeyPair, incomingMessage);
+    assertEquals(bobRecord.getSessionVersion(), 3);
+    assertTrue(Arrays.equals(aliceRecord.computeKeyConfirmation(), bobRecord.computeKeyConfirmation()));
+
+    bobStore.storeSession(ALICE_ADDRESS, bobRecord);
+    byte[] plaintext = new SessionCipher(bobStore, ALICE_ADDRESS).decrypt(incomingMessage.getWhisperMessage());
+    assertEquals(new String(plaintext), originalMessage);
+
+    try {
+      bobStore.loadSession(ALICE_ADDRESS).computeKeyConfirmation();
+      fail("key confirmation should not be available once the session has been used");
+    } catch (IllegalStateException e) {
+      // good
+    }
+  }
+
+  public void testValidateIntegrity() {
+    IdentityKeyPair aliceIdentity = new TestInMemorySignalProtocolStore().getIdentityKeyPair();
+    IdentityKeyPair bobIdentity   = new TestInMemorySignalProtocolStore().getIdentityKeyPair();
+    ECKeyPair       bobSignedKey  = Curve.generateKeyPair();
+
+    SessionRecord record = SessionRecord.initializeAliceSession(
+        aliceIdentity, Curve.generateKeyPair(), bobIdentity.getPublicKey(), bobSignedKey.getPublicKey(), bobSignedKey.getPublicKey());
+    byte[] serialized = record.serialize();
+    assertEquals(SessionRecord.Integrity.VALID, SessionRecord.validateIntegrity(serialized));
+    assertEquals(SessionRecord.Integrity.VALID, SessionRecord.validateIntegrity(new SessionRecord().serialize()));
+
+    // Skip the current session's tag and varint length to reach its version field.
+    int offset = 1;
+    while ((serialized[offset] & 0x80) != 0) {
+      offset++;
+    }
+    offset++;
+    assertEquals(0x08, serialized[offset]);
+    assertEquals(3, serialized[offset + 1]);
+
+    byte[] flipped = serialized.clone();
+    flipped[offset + 1] ^= 0x01;
+    assertEquals(SessionRecord.Integrity.INVALID_VERSION, SessionRecord.validateIntegrity(flipped));
+
+    byte[] truncated = Arrays.copyOf(serialized, serialized.length - 1);
+    assertEquals(SessionRecord.Integrity.UNPARSEABLE, SessionRecord.validateIntegrity(truncated));
+  }
+
+  public void testCanCommunicate() {
+    IdentityKeyPair aliceIdentity = new TestInMemorySignalProtocolStore().getIdentityKeyPair();
+    IdentityKeyPair bobIdentity   = new TestInMemorySignalProtocolStore().getIdentityKeyPair();
+    ECKeyPair       aliceBaseKey  = Curve.generateKeyPair();
+    ECKeyPair       bobSignedKey  = Curve.generateKeyPair();
+
+    SessionRecord aliceRecord = SessionRecord.initializeAliceSession(
+        aliceIdentity, aliceBaseKey, bobIdentity.getPublicKey(), bobSignedKey.getPublicKey(), bobSignedKey.getPublicKey());
+    SessionRecord bobRecord = SessionRecord.initializeBobSession(
+        bobIdentity, bobSignedKey, bobSignedKey, aliceIdentity.getPublicKey(), aliceBaseKey.getPublicKey());
+    SessionRecord mismatchedBobRecord = SessionRecord.initializeBobSession(
+        bobIdentity, bobSignedKey, bobSignedKey, aliceIdentity.getPublicKey(), Curve.generateKeyPair().getPublicKey());
+
+    assertTrue(Native.TestHarness_CanCommunicate(aliceRecord.nativeHandle(), bobRecord.nativeHandle()));
+    assertFalse(Native.TestHarness_CanCommunicate(aliceRecord.nativeHandle(), mismatchedBobRecord.nativeHandle()));
+  }
+
+  public void testCanCommunicateWithOneTimePreKey() {
+    IdentityKeyPair aliceIdentity = new TestInMemorySignalProtocolStore().getIdentityKeyPair();
+    IdentityKeyPair bobIdentity   = new TestInMemorySignalProtocolStore().getIdentityKeyPair();
+    ECKeyPair       aliceBaseKey  = Curve.generateKeyPair();
+    ECKeyPair       bobSignedKey  = Curve.generateKeyPair();
+    ECKeyPair       bobOneTimeKey = Curve.generateKeyPair();
+
+    SessionRecord aliceRecord = SessionRecord.initializeAliceSession(
+        aliceIdentity, aliceBaseKey, bobIdentity.getPublicKey(), bobSignedKey.getPublicKey(), bobOneTimeKey.getPublicKey(), bobSignedKey.getPublicKey());
+    SessionRecord bobRecord = SessionRecord.initializeBobSession(
+        bobIdentity, bobSignedKey, bobOneTimeKey, bobSignedKey, aliceIdentity.getPublicKey(), aliceBaseKey.getPublicKey());
+    SessionRecord bobWithoutOneTimeKey = SessionRecord.initializeBobSession(
+        bobIdentity, bobSignedKey, null, bobSignedKey, aliceIdentity.getPublicKey(), aliceBaseKey.getPublicKey());
+
+    assertTrue(Native.TestHarness_CanCommunicate(aliceRecord.nativeHandle(), bobRecord.nativeHandle()));
+    assertFalse(Native.TestHarness_CanCommunicate(aliceRecord.nativeHandle(), bobWithoutOneTimeKey.nativeHandle()));
+  }
+
+  public void testDeriveBaseKeyIsDeterministic() {
+    IdentityKeyPair aliceIdentity = new TestInMemorySignalProtocolStore().getIdentityKeyPair();
+    IdentityKeyPair bobIdentity   = new TestInMemorySignalProtocolStore().getIdentityKeyPair();
+    byte[]          seed          = "interop seed".getBytes();
+
+    ECPrivateKey first  = new ECPrivateKey(Native.TestHarness_DeriveBaseKey(seed, aliceIdentity.getPublicKey().getPublicKey().nativeHandle()));
+    ECPrivateKey second = new ECPrivateKey(Native.TestHarness_DeriveBaseKey(seed, aliceIdentity.getPublicKey().getPublicKey().nativeHandle()));
+    assertTrue(Arrays.equals(first.serialize(), second.serialize()));
+    assertEquals(first.publicKey(), second.publicKey());
+
+    ECPrivateKey otherSeed     = new ECPrivateKey(Native.TestHarness_DeriveBaseKey("other seed".getBytes(), aliceIdentity.getPublicKey().getPublicKey().nativeHandle()));
+    ECPrivateKey otherIdentity = new ECPrivateKey(Native.TestHarness_DeriveBaseKey(seed, bobIdentity.getPublicKey().getPublicKey().nativeHandle()));
+    assertFalse(Arrays.equals(first.serialize(), otherSeed.serialize()));
+    assertFalse(Arrays.equals(first.serialize(), otherIdentity.serialize()));
+  }
 }
@@ -2,6 +2,9 @@ package org.whispersystems.libsignal.ecc;
 
 import junit.framework.TestCase;
 
+import org.signal.client.internal.Native;
+
+import org.whispersystems.libsignal.IdentityKeyPair;
 import org.whispersystems.libsignal.InvalidKeyException;
 
 import java.util.Arrays;
@@ -137,6 +140,61 @@ public class Curve25519Test extends TestCase {
     }
   }
 
+  public void testKeyPairSerializesAsIdentityKeyPair() {
+    long keyPair = Native.KeyPair_Generate();
+    try {
+      byte[] serialized = Native.KeyPair_SerializeAsIdentityKeyPair(keyPair);
+
+      ECPublicKey  publicKey  = new ECPublicKey(Native.KeyPair_GetPublicKey(keyPair));
+      ECPrivateKey privateKey = new ECPrivateKey(Native.KeyPair_GetPrivateKey(keyPair));
+      byte[] expected = Native.IdentityKeyPair_Serialize(publicKey.nativeHandle(), privateKey.nativeHandle());
+      assertTrue(Arrays.equals(expected, serialized));
+
+      IdentityKeyPair deserialized = new IdentityKeyPair(serialized);
+      assertTrue(Arrays.equals(publicKey.serialize(), deserialized.getPublicKey().serialize()));
+      assertTrue(Arrays.equals(privateKey.serialize(), deserialized.getPrivateKey().serialize()));
+    } finally {
+      Native.KeyPair_Destroy(keyPair);
+    }
+  }
+
+  public void testDeterministicSignature() throws InvalidKeyException {
+    ECKeyPair keyPair = Curve.generateKeyPair();
+    byte[]    message = "deterministic".getBytes();
+    byte[]    random  = new byte[64];
+    Arrays.fill(random, (byte)0x2a);
+
+    byte[] first  = Native.ECPrivateKey_SignDeterministic(keyPair.getPrivateKey().nativeHandle(), message, random);
+    byte[] second = Native.ECPrivateKey_SignDeterministic(keyPair.getPrivateKey().nativeHandle(), message, random);
+    assertTrue(Arrays.equals(first, second));
+    assertTrue(Curve.verifySignature(keyPair.getPublicKey(), message, first));
+
+    random[0] ^= 0x01;
+    byte[] third = Native.ECPrivateKey_SignDeterministic(keyPair.getPrivateKey().nativeHandle(), message, random);
+    assertFalse(Arrays.equals(first, third));
+    assertTrue(Curve.verifySignature(keyPair.getPublicKey(), message, third));
+
+    try {
+      Native.ECPrivateKey_SignDeterministic(keyPair.getPrivateKey().nativeHandle(), message, new byte[63]);
+      fail("accepted short randomness");
+    } catch (IllegalArgumentException e) {
+      // good
+    }
+  }
+
+  public void testGeneratedKeyPairIsConsistent() {
+    ECKeyPair keyPair = Curve.generateKeyPair();
+    assertTrue(Arrays.equals(keyPair.getPrivateKey().publicKey().serialize(),
+                             keyPair.getPublicKey().serialize()));
+  }
+
+  public void testPublicKeyEquality() throws InvalidKeyException {
+    byte[] serialized = Curve.generateKeyPair().getPublicKey().serialize();
+
+    assertEquals(Curve.decodePoint(serialized, 0), Curve.decodePoint(serialized, 0));
+    assertFalse(Curve.decodePoint(serialized, 0).equals(Curve.generateKeyPair().getPublicKey()));
+  }
+
   public void testDecodeSize() throws InvalidKeyException {
     ECKeyPair keyPair          = Curve.generateKeyPair();
     byte[]    serializedPublic = keyPair.getPublicKey().serialize();
@@ -2,6 +2,7 @@ package org.whispersystems.libsignal.fingerprint;
 
 import junit.framework.TestCase;
 
+import org.signal.client.internal.Native;
 import org.whispersystems.libsignal.IdentityKey;
 import org.whispersystems.libsignal.ecc.Curve;
 import org.whispersystems.libsignal.ecc.ECKeyPair;
@@ -44,6 +45,9 @@ public class NumericFingerprintGeneratorTest extends TestCase {
 
     assertTrue(Arrays.equals(aliceFingerprint.getScannableFingerprint().getSerialized(), ALICE_SCANNABLE_FINGERPRINT_V1));
     assertTrue(Arrays.equals(bobFingerprint.getScannableFingerprint().getSerialized(), BOB_SCANNABLE_FINGERPRINT_V1));
+
+    assertTrue(Arrays.equals(aliceFingerprint.getQrCodePayload(), ALICE_SCANNABLE_FINGERPRINT_V1));
+    assertTrue(Arrays.equals(bobFingerprint.getQrCodePayload(), BOB_SCANNABLE_FINGERPRINT_V1));
   }
 
   public void testVectorsVersion2() throws Exception {
@@ -67,6 +71,31 @@ public class NumericFingerprintGeneratorTest extends TestCase {
 
     assertTrue(Arrays.equals(aliceFingerprint.getScannableFingerprint().getSerialized(), ALICE_SCANNABLE_FINGERPRINT_V2));
     assertTrue(Arrays.equals(bobFingerprint.getScannableFingerprint().getSerialized(), BOB_SCANNABLE_FINGERPRINT_V2));
+
+    assertTrue(Arrays.equals(aliceFingerprint.getQrCodePayload(), ALICE_SCANNABLE_FINGERPRINT_V2));
+    assertTrue(Arrays.equals(bobFingerprint.getQrCodePayload(), BOB_SCANNABLE_FINGERPRINT_V2));
+  }
+
+  public void testTypedAndSerializedConstructorsMatch() throws Exception {
+    IdentityKey aliceIdentityKey = new IdentityKey(ALICE_IDENTITY, 0);
+    IdentityKey bobIdentityKey   = new IdentityKey(BOB_IDENTITY, 0);
+    byte[]      aliceStableId    = "+14152222222".getBytes();
+    byte[]      bobStableId      = "+14153333333".getBytes();
+
+    long typedHandle = Native.Fingerprint_New(5200, VERSION_2,
+                                              aliceStableId, aliceIdentityKey.getPublicKey().nativeHandle(),
+                                              bobStableId, bobIdentityKey.getPublicKey().nativeHandle());
+    long serializedHandle = Native.NumericFingerprintGenerator_New(5200, VERSION_2,
+                                                                   aliceStableId, aliceIdentityKey.serialize(),
+                                                                   bobStableId, bobIdentityKey.serialize());
+
+    assertEquals(Native.NumericFingerprintGenerator_GetDisplayString(typedHandle),
+                 Native.NumericFingerprintGenerator_GetDisplayString(serializedHandle));
+    assertTrue(Arrays.equals(Native.NumericFingerprintGenerator_GetScannableEncoding(typedHandle),
+                             Native.NumericFingerprintGenerator_GetScannableEncoding(serializedHandle)));
+
+    Native.NumericFingerprintGenerator_Destroy(typedHandle);
+    Native.NumericFingerprintGenerator_Destroy(serializedHandle);
   }
 
   public void testMatchingFingerprints() throws FingerprintVersionMismatchException, FingerprintIdentifierMismatchException, FingerprintParsingException {
@@ -199,6 +228,13 @@ public class NumericFingerprintGeneratorTest extends TestCase {
     }
   }
 
+  public void testVersionsCompatible() {
+    assertTrue(ScannableFingerprint.versionsCompatible(VERSION_1, VERSION_1));
+    assertTrue(ScannableFingerprint.versionsCompatible(VERSION_2, VERSION_2));
+    assertFalse(ScannableFingerprint.versionsCompatible(VERSION_1, VERSION_2));
+    assertFalse(ScannableFingerprint.versionsCompatible(VERSION_2, VERSION_1));
+  }
+
   public void testFingerprintParsingFail() throws Exception {
     IdentityKey aliceIdentityKey = new IdentityKey(ALICE_IDENTITY, 0);
     IdentityKey bobIdentityKey   = new IdentityKey(BOB_IDENTITY, 0);
@@ -32,6 +32,32 @@ public class HKDFTest extends TestCase {
     assertTrue(Arrays.equals(okm, actualOutput));
   }
 
+  public void testDeriveManyV3() {
+    byte[] ikm  = new byte[22];
+    Arrays.fill(ikm, (byte) 0x0b);
+
+    byte[] salt = {0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09,
+                   0x0a, 0x0b, 0x0c};
+
+    byte[] info  = {(byte) 0xf0, (byte) 0xf1, (byte) 0xf2, (byte) 0xf3, (byte) 0xf4,
+                    (byte) 0xf5, (byte) 0xf6, (byte) 0xf7, (byte) 0xf8, (byte) 0xf9};
+    byte[] other = "second".getBytes();
+
+    HKDF     hkdf    = HKDF.createFor(3);
+    byte[][] outputs = hkdf.deriveSecrets(ikm, salt, new byte[][] {info, other}, new int[] {42, 80});
+
+    assertEquals(2, outputs.length);
+    assertTrue(Arrays.equals(hkdf.deriveSecrets(ikm, salt, info, 42), outputs[0]));
+    assertTrue(Arrays.equals(hkdf.deriveSecrets(ikm, salt, other, 80), outputs[1]));
+
+    try {
+      hkdf.deriveSecrets(ikm, salt, new byte[][] {info}, new int[] {16, 16});
+      fail("mismatched labels and lengths should be rejected");
+    } catch (IllegalArgumentException e) {
+      // expected
+    }
+  }
+
   public void testVectorLongV3() {
     byte[] ikm  = {(byte) 0x00, (byte) 0x01, (byte) 0x02, (byte) 0x03, (byte) 0x04,
                    (byte) 0x05, (byte) 0x06, (byte) 0x07, (byte) 0x08, (byte) 0x09,
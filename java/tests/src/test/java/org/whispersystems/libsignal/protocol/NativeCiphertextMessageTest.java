@@ -0,0 +1,36 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.whispersystems.libsignal.protocol;
+
+import java.util.Arrays;
+import junit.framework.TestCase;
+import org.whispersystems.libsignal.ecc.Curve;
+
+public class NativeCiphertextMessageTest extends TestCase {
+
+  public void testFromParts() throws Exception {
+    SenderKeyDistributionMessage original =
+        new SenderKeyDistributionMessage(7, 42, new byte[32], Curve.generateKeyPair().getPublicKey());
+    byte[] serialized = original.serialize();
+
+    NativeCiphertextMessage message =
+        NativeCiphertextMessage.fromParts(CiphertextMessage.SENDERKEY_DISTRIBUTION_TYPE, serialized);
+    assertEquals(CiphertextMessage.SENDERKEY_DISTRIBUTION_TYPE, message.getType());
+    assertTrue(Arrays.equals(serialized, message.serialize()));
+  }
+
+  public void testUnknownType() throws Exception {
+    SenderKeyDistributionMessage original =
+        new SenderKeyDistributionMessage(7, 42, new byte[32], Curve.generateKeyPair().getPublicKey());
+
+    try {
+      NativeCiphertextMessage.fromParts(99, original.serialize());
+      fail("accepted an unknown message type");
+    } catch (IllegalArgumentException e) {
+      // good
+    }
+  }
+}
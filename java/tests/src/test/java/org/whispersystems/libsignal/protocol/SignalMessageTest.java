@@ -0,0 +1,66 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+package org.whispersystems.libsignal.protocol;
+
+import java.io.ByteArrayOutputStream;
+import java.util.Arrays;
+import javax.crypto.spec.SecretKeySpec;
+import junit.framework.TestCase;
+import org.signal.client.internal.Native;
+import org.whispersystems.libsignal.IdentityKey;
+import org.whispersystems.libsignal.ecc.Curve;
+
+public class SignalMessageTest extends TestCase {
+
+  public void testReserializeToVersion() throws Exception {
+    SecretKeySpec macKey           = new SecretKeySpec(new byte[32], "HmacSHA256");
+    IdentityKey   senderIdentity   = new IdentityKey(Curve.generateKeyPair().getPublicKey());
+    IdentityKey   receiverIdentity = new IdentityKey(Curve.generateKeyPair().getPublicKey());
+    byte[]        body             = new byte[] {1, 2, 3, 4, 5};
+
+    SignalMessage original = new SignalMessage(3, macKey, Curve.generateKeyPair().getPublicKey(),
+                                               7, 6, body, senderIdentity, receiverIdentity);
+
+    SignalMessage upgraded = reserialize(original, 4, macKey, senderIdentity, receiverIdentity);
+    assertEquals(4, upgraded.getMessageVersion());
+    assertEquals(7, upgraded.getCounter());
+    assertTrue(Arrays.equals(body, upgraded.getBody()));
+    upgraded.verifyMac(senderIdentity, receiverIdentity, macKey);
+
+    SignalMessage restored = reserialize(upgraded, 3, macKey, senderIdentity, receiverIdentity);
+    assertEquals(3, restored.getMessageVersion());
+    assertTrue(Arrays.equals(original.serialize(), restored.serialize()));
+    restored.verifyMac(senderIdentity, receiverIdentity, macKey);
+  }
+
+  public static SignalMessage reserialize(SignalMessage message, int messageVersion, SecretKeySpec macKey,
+                                          IdentityKey senderIdentity, IdentityKey receiverIdentity)
+  {
+    return new SignalMessage(Native.SignalMessage_ReserializeToVersion(message.nativeHandle(), messageVersion,
+                                                                       macKey.getEncoded(),
+                                                                       senderIdentity.getPublicKey().nativeHandle(),
+                                                                       receiverIdentity.getPublicKey().nativeHandle()));
+  }
+
+  public void testSenderRatchetKey() throws Exception {
+    // The same message and key are checked in the Swift PublicAPITests.
+    byte[] ratchetKey = new byte[33];
+    ratchetKey[0] = 0x05;
+    for (int i = 1; i < ratchetKey.length; i++) {
+      ratchetKey[i] = (byte)i;
+    }
+
+    ByteArrayOutputStream messageBytes = new ByteArrayOutputStream();
+    messageBytes.write(new byte[] {0x33, 0x0a, 0x21});
+    messageBytes.write(ratchetKey);
+    messageBytes.write(new byte[] {0x10, 0x07, 0x18, 0x03, 0x22, 0x04, (byte)0xde, (byte)0xad, (byte)0xbe, (byte)0xef});
+    messageBytes.write(new byte[8]);
+
+    SignalMessage message = new SignalMessage(messageBytes.toByteArray());
+    assertTrue(Arrays.equals(ratchetKey, message.getSenderRatchetKey().serialize()));
+    assertEquals(7, message.getCounter());
+  }
+}
@@ -131,6 +131,7 @@ public class RatchetingSessionTest extends TestCase {
     assertTrue(session.getLocalIdentityKey().equals(bobIdentityKey.getPublicKey()));
     assertTrue(session.getRemoteIdentityKey().equals(aliceIdentityPublicKey));
     assertTrue(Arrays.equals(session.getSenderChainKeyValue(), senderChain));
+    assertTrue(Arrays.equals(session.getAliceBaseKey(), aliceBasePublicKey.serialize()));
   }
 
 public void testRatchetingSessionAsAlice() throws InvalidKeyException {
@@ -246,6 +247,7 @@ public void testRatchetingSessionAsAlice() throws InvalidKeyException {
     assertTrue(session.getRemoteIdentityKey().equals(bobIdentityKey));
     assertTrue(Arrays.equals(session.getReceiverChainKeyValue(bobEphemeralPublicKey),
                              receiverChain));
+    assertTrue(Arrays.equals(session.getAliceBaseKey(), aliceBasePublicKey.serialize()));
 
   }
 }
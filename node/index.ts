@@ -9,7 +9,67 @@ import * as SignalClient from './libsignal_client';
 
 const SC = bindings('libsignal_client_' + os.platform()) as typeof SignalClient;
 
-export const { initLogger, LogLevel, CiphertextMessageType } = SC;
+export const {
+  initLogger,
+  LogLevel,
+  CiphertextMessageType,
+  SessionRecordIntegrity,
+} = SC;
+
+export function ciphertextMessageTypeMatchesBody(
+  messageType: SignalClient.CiphertextMessageType,
+  body: Buffer
+): boolean {
+  return SC.CiphertextMessage_TypeMatchesBody(messageType, body);
+}
+
+/**
+ * Returns whether a message that failed to decrypt with the numeric SignalErrorCode `errorCode`
+ * (as listed in signal_ffi.h) means the session with its sender should be archived and the
+ * sender asked to retry.
+ */
+export function shouldResetSession(
+  errorCode: number,
+  messageType: SignalClient.CiphertextMessageType,
+  message: Buffer
+): boolean {
+  return SC.SessionCipher_ShouldResetSession(errorCode, messageType, message);
+}
+
+/**
+ * Returns an English description of a numeric SignalErrorCode (as listed in signal_ffi.h),
+ * suitable for logging.
+ */
+export function describeErrorCode(errorCode: number): string {
+  return SC.SignalProtocolError_DescribeCode(errorCode);
+}
+
+export function attachmentPaddedSize(plaintextLength: number): number {
+  return SC.Attachment_PaddedSize(plaintextLength);
+}
+
+export function encryptProfileName(name: string, profileKey: Buffer): Buffer {
+  return SC.ProfileCipher_EncryptName(name, profileKey);
+}
+
+export function decryptProfileName(
+  ciphertext: Buffer,
+  profileKey: Buffer
+): string {
+  return SC.ProfileCipher_DecryptName(ciphertext, profileKey);
+}
+
+export function sha256(input: Buffer): Buffer {
+  return SC.Sha256_Digest(input);
+}
+
+export function sha512(input: Buffer): Buffer {
+  return SC.Sha512_Digest(input);
+}
+
+export function constantTimeEqual(a: Buffer, b: Buffer): boolean {
+  return SC.ConstantTimeEqual(a, b);
+}
 
 export class HKDF {
   private readonly version: number;
@@ -36,6 +96,41 @@ export class HKDF {
       salt
     );
   }
+
+  deriveManySecrets(
+    keyMaterial: Buffer,
+    labels: Buffer[],
+    outputLengths: number[],
+    salt: Buffer | null
+  ): Buffer[] {
+    const labelParts = [];
+    for (const label of labels) {
+      const length = Buffer.alloc(4);
+      length.writeUInt32BE(label.length, 0);
+      labelParts.push(length, label);
+    }
+    const encodedLabels = Buffer.concat(labelParts);
+    const encodedLengths = Buffer.alloc(4 * outputLengths.length);
+    outputLengths.forEach((length, i) => {
+      encodedLengths.writeUInt32BE(length, 4 * i);
+    });
+
+    const outputs = SC.HKDF_DeriveMany(
+      this.version,
+      keyMaterial,
+      salt,
+      encodedLabels,
+      encodedLengths
+    );
+    const result = [];
+    let offset = 0;
+    while (offset < outputs.length) {
+      const length = outputs.readUInt32BE(offset);
+      result.push(outputs.slice(offset + 4, offset + 4 + length));
+      offset += 4 + length;
+    }
+    return result;
+  }
 }
 
 export class ScannableFingerprint {
@@ -49,6 +144,10 @@ export class ScannableFingerprint {
     return new ScannableFingerprint(scannable);
   }
 
+  static versionsCompatible(ours: number, theirs: number): boolean {
+    return SC.Fingerprint_VersionsCompatible(ours, theirs);
+  }
+
   compare(other: ScannableFingerprint): boolean {
     return SC.ScannableFingerprint_Compare(this.scannable, other.scannable);
   }
@@ -107,11 +206,39 @@ export class Fingerprint {
     );
   }
 
+  public truncatedDisplayString(digits: number): string {
+    return SC.Fingerprint_GetTruncatedDisplayString(this.nativeHandle, digits);
+  }
+
   public scannableFingerprint(): ScannableFingerprint {
     return ScannableFingerprint._fromBuffer(
       SC.Fingerprint_ScannableEncoding(this.nativeHandle)
     );
   }
+
+  public qrCodePayload(): Buffer {
+    return SC.Fingerprint_GetQrCodePayload(this.nativeHandle);
+  }
+
+  public verifyQrPayload(scanned: Buffer): boolean {
+    return SC.Fingerprint_VerifyQrPayload(this.nativeHandle, scanned);
+  }
+}
+
+export function aes256CbcEncrypt(
+  key: Buffer,
+  iv: Buffer,
+  ptext: Buffer
+): Buffer {
+  return SC.Aes256Cbc_Encrypt(key, iv, ptext);
+}
+
+export function aes256CbcDecrypt(
+  key: Buffer,
+  iv: Buffer,
+  ctext: Buffer
+): Buffer {
+  return SC.Aes256Cbc_Decrypt(key, iv, ctext);
 }
 
 export class Aes256GcmSiv {
@@ -125,6 +252,14 @@ export class Aes256GcmSiv {
     return new Aes256GcmSiv(key);
   }
 
+  static tagLength(): number {
+    return SC.Aes256GcmSiv_TagLength();
+  }
+
+  rekey(key: Buffer): void {
+    SC.Aes256GcmSiv_Rekey(this.nativeHandle, key);
+  }
+
   encrypt(message: Buffer, nonce: Buffer, associated_data: Buffer): Buffer {
     return SC.Aes256GcmSiv_Encrypt(
       this.nativeHandle,
@@ -144,6 +279,26 @@ export class Aes256GcmSiv {
   }
 }
 
+export class CryptographicMac {
+  private readonly nativeHandle: SignalClient.CryptographicMac;
+
+  private constructor(key: Buffer) {
+    this.nativeHandle = SC.CryptographicMac_New(key);
+  }
+
+  static new(key: Buffer): CryptographicMac {
+    return new CryptographicMac(key);
+  }
+
+  update(input: Buffer): void {
+    SC.CryptographicMac_Update(this.nativeHandle, input);
+  }
+
+  finalize(): Buffer {
+    return SC.CryptographicMac_Finalize(this.nativeHandle);
+  }
+}
+
 export class ProtocolAddress {
   private readonly nativeHandle: SignalClient.ProtocolAddress;
 
@@ -161,6 +316,10 @@ export class ProtocolAddress {
     return new ProtocolAddress(SC.ProtocolAddress_New(name, deviceId));
   }
 
+  static fromString(s: string): ProtocolAddress {
+    return new ProtocolAddress(SC.ProtocolAddress_FromString(s));
+  }
+
   name(): string {
     return SC.ProtocolAddress_Name(this.nativeHandle);
   }
@@ -168,6 +327,10 @@ export class ProtocolAddress {
   deviceId(): number {
     return SC.ProtocolAddress_DeviceId(this.nativeHandle);
   }
+
+  toString(): string {
+    return SC.ProtocolAddress_ToString(this.nativeHandle);
+  }
 }
 
 export class PublicKey {
@@ -185,11 +348,19 @@ export class PublicKey {
     return new PublicKey(SC.PublicKey_Deserialize(buf));
   }
 
+  static isValid(buf: Buffer): boolean {
+    return SC.PublicKey_IsValid(buf);
+  }
+
   /// Returns -1, 0, or 1
   compare(other: PublicKey): number {
     return SC.PublicKey_Compare(this.nativeHandle, other.nativeHandle);
   }
 
+  equals(other: PublicKey): boolean {
+    return SC.PublicKey_Equals(this.nativeHandle, other.nativeHandle);
+  }
+
   serialize(): Buffer {
     return SC.PublicKey_Serialize(this.nativeHandle);
   }
@@ -202,6 +373,27 @@ export class PublicKey {
     return SC.PublicKey_Verify(this.nativeHandle, msg, sig);
   }
 
+  /// Returns true for low-order points, which yield an all-zero shared secret
+  isWeak(): boolean {
+    return SC.PublicKey_IsWeak(this.nativeHandle);
+  }
+
+  computeIdentityChangeHash(): Buffer {
+    return SC.IdentityKey_ComputeChangeHash(this.nativeHandle);
+  }
+
+  toIdentityDisplayString(): string {
+    return SC.IdentityKey_ToDisplayString(this.nativeHandle);
+  }
+
+  verifyAlternateIdentity(other: PublicKey, signature: Buffer): boolean {
+    return SC.IdentityKey_VerifyAlternateIdentitySignature(
+      this.nativeHandle,
+      other._unsafeGetNativeHandle(),
+      signature
+    );
+  }
+
   _unsafeGetNativeHandle(): SignalClient.PublicKey {
     return this.nativeHandle;
   }
@@ -238,6 +430,18 @@ export class PrivateKey {
     return SC.PrivateKey_Sign(this.nativeHandle, msg);
   }
 
+  signCanonical(fields: Buffer[]): Buffer {
+    const serialized = fields.map(field => {
+      const len = Buffer.alloc(4);
+      len.writeUInt32BE(field.length);
+      return Buffer.concat([len, field]);
+    });
+    return SC.PrivateKey_SignCanonical(
+      this.nativeHandle,
+      Buffer.concat(serialized)
+    );
+  }
+
   agree(other_key: PublicKey): Buffer {
     return SC.PrivateKey_Agree(
       this.nativeHandle,
@@ -245,6 +449,13 @@ export class PrivateKey {
     );
   }
 
+  agreeValidated(other_key: PublicKey): Buffer {
+    return SC.PrivateKey_AgreeValidated(
+      this.nativeHandle,
+      other_key._unsafeGetNativeHandle()
+    );
+  }
+
   getPublicKey(): PublicKey {
     return PublicKey._fromNativeHandle(
       SC.PrivateKey_GetPublicKey(this.nativeHandle)
@@ -265,12 +476,36 @@ export class IdentityKeyPair {
     return new IdentityKeyPair(publicKey, privateKey);
   }
 
+  static generate(): IdentityKeyPair {
+    const keyPair = SC.KeyPair_Generate();
+    return new IdentityKeyPair(
+      PublicKey._fromNativeHandle(SC.KeyPair_GetPublicKey(keyPair)),
+      PrivateKey._fromNativeHandle(SC.KeyPair_GetPrivateKey(keyPair))
+    );
+  }
+
+  static deserialize(buffer: Buffer): IdentityKeyPair {
+    const keyPair = SC.IdentityKeyPair_Deserialize(buffer);
+    return new IdentityKeyPair(
+      PublicKey._fromNativeHandle(SC.IdentityKeyPair_GetPublicKey(keyPair)),
+      PrivateKey._fromNativeHandle(SC.IdentityKeyPair_GetPrivateKey(keyPair))
+    );
+  }
+
   serialize(): Buffer {
     return SC.IdentityKeyPair_Serialize(
       this.publicKey._unsafeGetNativeHandle(),
       this.privateKey._unsafeGetNativeHandle()
     );
   }
+
+  signAlternateIdentity(other: PublicKey): Buffer {
+    return SC.IdentityKeyPair_SignAlternateIdentity(
+      this.publicKey._unsafeGetNativeHandle(),
+      this.privateKey._unsafeGetNativeHandle(),
+      other._unsafeGetNativeHandle()
+    );
+  }
 }
 
 export class PreKeyBundle {
@@ -305,6 +540,34 @@ export class PreKeyBundle {
     );
   }
 
+  static newWithoutOneTimePreKey(
+    registration_id: number,
+    device_id: number,
+    signed_prekey_id: number,
+    signed_prekey: PublicKey,
+    signed_prekey_signature: Buffer,
+    identity_key: PublicKey
+  ): PreKeyBundle {
+    return new PreKeyBundle(
+      SC.PreKeyBundle_NewWithoutOneTimePreKey(
+        registration_id,
+        device_id,
+        signed_prekey_id,
+        signed_prekey._unsafeGetNativeHandle(),
+        signed_prekey_signature,
+        identity_key._unsafeGetNativeHandle()
+      )
+    );
+  }
+
+  static fromLinkingPayload(payload: Buffer): PreKeyBundle {
+    return new PreKeyBundle(SC.PreKeyBundle_FromLinkingPayload(payload));
+  }
+
+  toLinkingPayload(): Buffer {
+    return SC.PreKeyBundle_ToLinkingPayload(this.nativeHandle);
+  }
+
   deviceId(): number {
     return SC.PreKeyBundle_GetDeviceId(this.nativeHandle);
   }
@@ -362,6 +625,21 @@ export class PreKeyRecord {
     return new PreKeyRecord(SC.PreKeyRecord_Deserialize(buffer));
   }
 
+  static findDuplicateKeys(records: PreKeyRecord[]): number[] {
+    const serialized = records.map(record => {
+      const bytes = record.serialize();
+      const len = Buffer.alloc(4);
+      len.writeUInt32BE(bytes.length);
+      return Buffer.concat([len, bytes]);
+    });
+    const ids = SC.PreKeyRecord_FindDuplicateKeys(Buffer.concat(serialized));
+    const result = [];
+    for (let i = 0; i < ids.length; i += 4) {
+      result.push(ids.readUInt32BE(i));
+    }
+    return result;
+  }
+
   id(): number {
     return SC.PreKeyRecord_GetId(this.nativeHandle);
   }
@@ -412,6 +690,22 @@ export class SignedPreKeyRecord {
     return new SignedPreKeyRecord(SC.SignedPreKeyRecord_Deserialize(buffer));
   }
 
+  static earliestExpiry(
+    records: SignedPreKeyRecord[],
+    maxAge: number
+  ): number {
+    const serialized = records.map(record => {
+      const bytes = record.serialize();
+      const len = Buffer.alloc(4);
+      len.writeUInt32BE(bytes.length);
+      return Buffer.concat([len, bytes]);
+    });
+    return SC.SignedPreKeyRecord_EarliestExpiry(
+      Buffer.concat(serialized),
+      maxAge
+    );
+  }
+
   id(): number {
     return SC.SignedPreKeyRecord_GetId(this.nativeHandle);
   }
@@ -439,6 +733,21 @@ export class SignedPreKeyRecord {
   timestamp(): number {
     return SC.SignedPreKeyRecord_GetTimestamp(this.nativeHandle);
   }
+
+  nextRotationTime(interval: number): number {
+    return SC.SignedPreKeyRecord_NextRotationTime(this.nativeHandle, interval);
+  }
+
+  verifyKeyPair(): boolean {
+    return SC.SignedPreKeyRecord_VerifyKeyPair(this.nativeHandle);
+  }
+
+  verifyOwnSignature(identityKey: PublicKey): boolean {
+    return SC.SignedPreKeyRecord_VerifyOwnSignature(
+      this.nativeHandle,
+      identityKey._unsafeGetNativeHandle()
+    );
+  }
 }
 
 export class SignalMessage {
@@ -555,6 +864,10 @@ export class PreKeySignalMessage {
     return SC.PreKeySignalMessage_GetSignedPreKeyId(this.nativeHandle);
   }
 
+  usedOneTimePreKey(): boolean {
+    return SC.PreKeySignalMessage_UsedOneTimePreKey(this.nativeHandle);
+  }
+
   version(): number {
     return SC.PreKeySignalMessage_GetVersion(this.nativeHandle);
   }
@@ -562,6 +875,10 @@ export class PreKeySignalMessage {
   serialize(): Buffer {
     return SC.PreKeySignalMessage_Serialize(this.nativeHandle);
   }
+
+  _unsafeGetNativeHandle(): SignalClient.PreKeySignalMessage {
+    return this.nativeHandle;
+  }
 }
 
 export class SessionRecord {
@@ -575,6 +892,71 @@ export class SessionRecord {
     return new SessionRecord(SC.SessionRecord_Deserialize(buffer));
   }
 
+  static validateIntegrity(
+    buffer: Buffer
+  ): SignalClient.SessionRecordIntegrity {
+    return SC.SessionRecord_ValidateIntegrity(buffer);
+  }
+
+  static storageVersion(buffer: Buffer): number {
+    return SC.SessionRecord_StorageVersion(buffer);
+  }
+
+  /**
+   * Sets up Alice's side of a session directly from Bob's keys, so tests can build a matching
+   * pair of sessions. This skips the identity checks of a real pre-key handshake.
+   */
+  static initializeAliceSession(
+    identityKey: PrivateKey,
+    baseKey: PrivateKey,
+    theirIdentityKey: PublicKey,
+    theirSignedPreKey: PublicKey,
+    theirOneTimePreKey: PublicKey | null,
+    theirRatchetKey: PublicKey
+  ): SessionRecord {
+    return new SessionRecord(
+      SC.SessionRecord_InitializeAliceSession(
+        identityKey._unsafeGetNativeHandle(),
+        identityKey.getPublicKey()._unsafeGetNativeHandle(),
+        baseKey._unsafeGetNativeHandle(),
+        baseKey.getPublicKey()._unsafeGetNativeHandle(),
+        theirIdentityKey._unsafeGetNativeHandle(),
+        theirSignedPreKey._unsafeGetNativeHandle(),
+        theirOneTimePreKey != null
+          ? theirOneTimePreKey._unsafeGetNativeHandle()
+          : null,
+        theirRatchetKey._unsafeGetNativeHandle()
+      )
+    );
+  }
+
+  /** Sets up Bob's side of a session to match initializeAliceSession. */
+  static initializeBobSession(
+    identityKey: PrivateKey,
+    signedPreKey: PrivateKey,
+    oneTimePreKey: PrivateKey | null,
+    ephemeralKey: PrivateKey,
+    theirIdentityKey: PublicKey,
+    theirBaseKey: PublicKey
+  ): SessionRecord {
+    return new SessionRecord(
+      SC.SessionRecord_InitializeBobSession(
+        identityKey._unsafeGetNativeHandle(),
+        identityKey.getPublicKey()._unsafeGetNativeHandle(),
+        signedPreKey._unsafeGetNativeHandle(),
+        signedPreKey.getPublicKey()._unsafeGetNativeHandle(),
+        oneTimePreKey != null ? oneTimePreKey._unsafeGetNativeHandle() : null,
+        oneTimePreKey != null
+          ? oneTimePreKey.getPublicKey()._unsafeGetNativeHandle()
+          : null,
+        ephemeralKey._unsafeGetNativeHandle(),
+        ephemeralKey.getPublicKey()._unsafeGetNativeHandle(),
+        theirIdentityKey._unsafeGetNativeHandle(),
+        theirBaseKey._unsafeGetNativeHandle()
+      )
+    );
+  }
+
   serialize(): Buffer {
     return SC.SessionRecord_Serialize(this.nativeHandle);
   }
@@ -583,6 +965,10 @@ export class SessionRecord {
     SC.SessionRecord_ArchiveCurrentState(this.nativeHandle);
   }
 
+  canEncrypt(): boolean {
+    return SC.SessionRecord_CanEncrypt(this.nativeHandle);
+  }
+
   localRegistrationId(): number {
     return SC.SessionRecord_GetLocalRegistrationId(this.nativeHandle);
   }
@@ -590,6 +976,41 @@ export class SessionRecord {
   remoteRegistrationId(): number {
     return SC.SessionRecord_GetRemoteRegistrationId(this.nativeHandle);
   }
+
+  registrationIds(): { local: number; remote: number } {
+    const ids = SC.SessionRecord_GetRegistrationIds(this.nativeHandle);
+    return { local: ids.readUInt32BE(0), remote: ids.readUInt32BE(4) };
+  }
+
+  computeKeyConfirmation(): Buffer {
+    return SC.SessionRecord_ComputeKeyConfirmation(this.nativeHandle);
+  }
+
+  computeSessionId(): Buffer {
+    return SC.SessionRecord_ComputeSessionId(this.nativeHandle);
+  }
+
+  creationTimestamp(): number {
+    return SC.SessionRecord_GetCreationTimestamp(this.nativeHandle);
+  }
+
+  identitySeenBefore(identityKey: PublicKey): boolean {
+    return SC.SessionRecord_IdentitySeenBefore(
+      this.nativeHandle,
+      identityKey._unsafeGetNativeHandle()
+    );
+  }
+
+  preKeyMessageAlreadyProcessed(message: PreKeySignalMessage): boolean {
+    return SC.SessionRecord_PreKeyMessageAlreadyProcessed(
+      this.nativeHandle,
+      message._unsafeGetNativeHandle()
+    );
+  }
+
+  needsUpgrade(): boolean {
+    return SC.SessionRecord_NeedsUpgrade(this.nativeHandle);
+  }
 }
 
 export class SenderKeyName {
@@ -609,6 +1030,10 @@ export class SenderKeyName {
     );
   }
 
+  static fromStorageKey(key: string): SenderKeyName {
+    return new SenderKeyName(SC.SenderKeyName_FromStorageKey(key));
+  }
+
   groupId(): string {
     return SC.SenderKeyName_GetGroupId(this.nativeHandle);
   }
@@ -619,6 +1044,11 @@ export class SenderKeyName {
   senderDeviceId(): number {
     return SC.SenderKeyName_GetSenderDeviceId(this.nativeHandle);
   }
+
+  /// The canonical "group:sender:device" key for sender-key storage
+  toStorageKey(): string {
+    return SC.SenderKeyName_ToStorageKey(this.nativeHandle);
+  }
 }
 
 export class ServerCertificate {
@@ -677,6 +1107,13 @@ export class ServerCertificate {
   signature(): Buffer {
     return SC.ServerCertificate_GetSignature(this.nativeHandle);
   }
+
+  verify(trustRoot: PublicKey): boolean {
+    return SC.ServerCertificate_Verify(
+      this.nativeHandle,
+      trustRoot._unsafeGetNativeHandle()
+    );
+  }
 }
 
 export class SenderKeyRecord {
@@ -697,6 +1134,16 @@ export class SenderKeyRecord {
   serialize(): Buffer {
     return SC.SenderKeyRecord_Serialize(this.nativeHandle);
   }
+
+  createDistributionMessage(): SenderKeyDistributionMessage {
+    return SenderKeyDistributionMessage._fromNativeHandle(
+      SC.SenderKeyRecord_CreateDistributionMessage(this.nativeHandle)
+    );
+  }
+
+  messagesUntilRotation(): number {
+    return SC.SenderKeyRecord_MessagesUntilRotation(this.nativeHandle);
+  }
 }
 
 export class SenderCertificate {
@@ -777,6 +1224,26 @@ export class SenderCertificate {
       time
     );
   }
+
+  validateChain(trustRoot: PublicKey, time: number): void {
+    SC.SenderCertificate_ValidateChain(
+      this.nativeHandle,
+      trustRoot._unsafeGetNativeHandle(),
+      time
+    );
+  }
+
+  isForDevice(deviceId: number): boolean {
+    return SC.SenderCertificate_IsForDevice(this.nativeHandle, deviceId);
+  }
+
+  hasValidUuid(): boolean {
+    return SC.SenderCertificate_HasValidUuid(this.nativeHandle);
+  }
+
+  signerKeyIdEquals(keyId: number): boolean {
+    return SC.SenderCertificate_SignerKeyIdEquals(this.nativeHandle, keyId);
+  }
 }
 
 export class SenderKeyDistributionMessage {
@@ -786,6 +1253,12 @@ export class SenderKeyDistributionMessage {
     this.nativeHandle = nativeHandle;
   }
 
+  static _fromNativeHandle(
+    nativeHandle: SignalClient.SenderKeyDistributionMessage
+  ): SenderKeyDistributionMessage {
+    return new SenderKeyDistributionMessage(nativeHandle);
+  }
+
   static new(
     keyId: number,
     iteration: number,
@@ -808,6 +1281,10 @@ export class SenderKeyDistributionMessage {
     );
   }
 
+  static chainKeyLength(): number {
+    return SC.SenderKeyDistributionMessage_ChainKeyLength();
+  }
+
   serialize(): Buffer {
     return SC.SenderKeyDistributionMessage_Serialize(this.nativeHandle);
   }
@@ -816,6 +1293,10 @@ export class SenderKeyDistributionMessage {
     return SC.SenderKeyDistributionMessage_GetChainKey(this.nativeHandle);
   }
 
+  deriveGroupKey(): Buffer {
+    return SC.SenderKeyDistributionMessage_DeriveGroupKey(this.nativeHandle);
+  }
+
   iteration(): number {
     return SC.SenderKeyDistributionMessage_GetIteration(this.nativeHandle);
   }
@@ -823,6 +1304,10 @@ export class SenderKeyDistributionMessage {
   id(): number {
     return SC.SenderKeyDistributionMessage_GetId(this.nativeHandle);
   }
+
+  _unsafeGetNativeHandle(): SignalClient.SenderKeyDistributionMessage {
+    return this.nativeHandle;
+  }
 }
 
 export class SenderKeyMessage {
@@ -874,6 +1359,61 @@ export class SenderKeyMessage {
       key._unsafeGetNativeHandle()
     );
   }
+
+  verifyAgainstDistribution(
+    distribution: SenderKeyDistributionMessage
+  ): boolean {
+    return SC.GroupCipher_VerifyAgainstDistribution(
+      this.nativeHandle,
+      distribution._unsafeGetNativeHandle()
+    );
+  }
+}
+
+export class UnidentifiedSenderMessage {
+  private readonly nativeHandle: SignalClient.UnidentifiedSenderMessage;
+
+  private constructor(nativeHandle: SignalClient.UnidentifiedSenderMessage) {
+    this.nativeHandle = nativeHandle;
+  }
+
+  static new(
+    ephemeralPublic: PublicKey,
+    encryptedStatic: Buffer,
+    encryptedMessage: Buffer
+  ): UnidentifiedSenderMessage {
+    return new UnidentifiedSenderMessage(
+      SC.UnidentifiedSenderMessage_New(
+        ephemeralPublic._unsafeGetNativeHandle(),
+        encryptedStatic,
+        encryptedMessage
+      )
+    );
+  }
+
+  static deserialize(buffer: Buffer): UnidentifiedSenderMessage {
+    return new UnidentifiedSenderMessage(
+      SC.UnidentifiedSenderMessage_Deserialize(buffer)
+    );
+  }
+
+  serialize(): Buffer {
+    return SC.UnidentifiedSenderMessage_GetSerialized(this.nativeHandle);
+  }
+
+  ephemeralPublic(): PublicKey {
+    return PublicKey._fromNativeHandle(
+      SC.UnidentifiedSenderMessage_GetEphemeralPublic(this.nativeHandle)
+    );
+  }
+
+  encryptedStatic(): Buffer {
+    return SC.UnidentifiedSenderMessage_GetEncryptedStatic(this.nativeHandle);
+  }
+
+  encryptedMessage(): Buffer {
+    return SC.UnidentifiedSenderMessage_GetEncryptedMessage(this.nativeHandle);
+  }
 }
 
 export class UnidentifiedSenderMessageContent {
@@ -903,6 +1443,10 @@ export class UnidentifiedSenderMessageContent {
     return SC.UnidentifiedSenderMessageContent_GetMsgType(this.nativeHandle);
   }
 
+  static msgTypeName(msgType: number): string {
+    return SC.UnidentifiedSenderMessageContent_GetMsgTypeName(msgType);
+  }
+
   senderCertificate(): SenderCertificate {
     return SenderCertificate._fromNativeHandle(
       SC.UnidentifiedSenderMessageContent_GetSenderCert(this.nativeHandle)
@@ -7,16 +7,46 @@
 
 export const enum CiphertextMessageType { Whisper = 2, PreKey = 3, SenderKey = 4, SenderKeyDistribution = 5 }
 export const enum LogLevel { Error, Warn, Info, Debug, Trace }
+export const enum SessionRecordIntegrity { Valid = 0, Unparseable = 1, InvalidVersion = 2, InconsistentChainIndices = 3 }
+export function Aes256Cbc_Decrypt(key: Buffer, iv: Buffer, ctext: Buffer): Buffer;
+export function Aes256Cbc_Encrypt(key: Buffer, iv: Buffer, ptext: Buffer): Buffer;
 export function Aes256GcmSiv_Decrypt(aesGcmSiv: Aes256GcmSiv, ctext: Buffer, nonce: Buffer, associatedData: Buffer): Buffer;
 export function Aes256GcmSiv_Encrypt(aesGcmSiv: Aes256GcmSiv, ptext: Buffer, nonce: Buffer, associatedData: Buffer): Buffer;
 export function Aes256GcmSiv_New(key: Buffer): Aes256GcmSiv;
+export function Aes256GcmSiv_Rekey(aesGcmSiv: Aes256GcmSiv, key: Buffer): void;
+export function Aes256GcmSiv_TagLength(): number;
+export function Attachment_PaddedSize(plaintextLength: number): number;
+export function CiphertextMessage_GetInnerCiphertext(obj: CiphertextMessage): Buffer;
 export function CiphertextMessage_Serialize(obj: CiphertextMessage): Buffer;
 export function CiphertextMessage_Type(msg: CiphertextMessage): number;
+export function CiphertextMessage_TypeMatchesBody(messageType: number, body: Buffer): boolean;
+export function ConstantTimeEqual(a: Buffer, b: Buffer): boolean;
+export function CryptographicMac_Finalize(mac: CryptographicMac): Buffer;
+export function CryptographicMac_New(key: Buffer): CryptographicMac;
+export function CryptographicMac_Update(mac: CryptographicMac, input: Buffer): void;
 export function Fingerprint_DisplayString(obj: Fingerprint): string;
+export function Fingerprint_GetQrCodePayload(fingerprint: Fingerprint): Buffer;
+export function Fingerprint_GetTruncatedDisplayString(fingerprint: Fingerprint, digits: number): string;
 export function Fingerprint_New(iterations: number, version: number, localIdentifier: Buffer, localKey: PublicKey, remoteIdentifier: Buffer, remoteKey: PublicKey): Fingerprint;
 export function Fingerprint_ScannableEncoding(obj: Fingerprint): Buffer;
+export function Fingerprint_VerifyQrPayload(fingerprint: Fingerprint, scanned: Buffer): boolean;
+export function Fingerprint_VersionsCompatible(ours: number, theirs: number): boolean;
+export function GroupCipher_VerifyAgainstDistribution(message: SenderKeyMessage, distribution: SenderKeyDistributionMessage): boolean;
+export function HKDF_DeriveMany(version: number, ikm: Buffer, salt: Buffer | null, labels: Buffer, lengths: Buffer): Buffer;
 export function HKDF_DeriveSecrets(outputLength: number, version: number, ikm: Buffer, label: Buffer, salt: Buffer | null): Buffer;
+export function IdentityKeyPair_Deserialize(buffer: Buffer): IdentityKeyPair;
+export function IdentityKeyPair_GetPrivateKey(keyPair: IdentityKeyPair): PrivateKey;
+export function IdentityKeyPair_GetPublicKey(keyPair: IdentityKeyPair): PublicKey;
 export function IdentityKeyPair_Serialize(publicKey: PublicKey, privateKey: PrivateKey): Buffer;
+export function IdentityKeyPair_SignAlternateIdentity(publicKey: PublicKey, privateKey: PrivateKey, otherIdentity: PublicKey): Buffer;
+export function IdentityKey_ComputeChangeHash(identityKey: PublicKey): Buffer;
+export function IdentityKey_ToDisplayString(identityKey: PublicKey): string;
+export function IdentityKey_VerifyAlternateIdentitySignature(identityKey: PublicKey, otherIdentity: PublicKey, signature: Buffer): boolean;
+export function KeyPair_Generate(): KeyPair;
+export function KeyPair_GetPrivateKey(keyPair: KeyPair): PrivateKey;
+export function KeyPair_GetPublicKey(keyPair: KeyPair): PublicKey;
+export function KeyPair_SerializeAsIdentityKeyPair(keyPair: KeyPair): Buffer;
+export function PreKeyBundle_FromLinkingPayload(payload: Buffer): PreKeyBundle;
 export function PreKeyBundle_GetDeviceId(obj: PreKeyBundle): number;
 export function PreKeyBundle_GetIdentityKey(p: PreKeyBundle): PublicKey;
 export function PreKeyBundle_GetPreKeyId(obj: PreKeyBundle): number | null;
@@ -26,7 +56,10 @@ export function PreKeyBundle_GetSignedPreKeyId(obj: PreKeyBundle): number;
 export function PreKeyBundle_GetSignedPreKeyPublic(obj: PreKeyBundle): PublicKey;
 export function PreKeyBundle_GetSignedPreKeySignature(obj: PreKeyBundle): Buffer;
 export function PreKeyBundle_New(registrationId: number, deviceId: number, prekeyId: number | null, prekey: PublicKey | null, signedPrekeyId: number, signedPrekey: PublicKey, signedPrekeySignature: Buffer, identityKey: PublicKey): PreKeyBundle;
+export function PreKeyBundle_NewWithoutOneTimePreKey(registrationId: number, deviceId: number, signedPrekeyId: number, signedPrekey: PublicKey, signedPrekeySignature: Buffer, identityKey: PublicKey): PreKeyBundle;
+export function PreKeyBundle_ToLinkingPayload(obj: PreKeyBundle): Buffer;
 export function PreKeyRecord_Deserialize(buffer: Buffer): PreKeyRecord;
+export function PreKeyRecord_FindDuplicateKeys(serializedRecords: Buffer): Buffer;
 export function PreKeyRecord_GetId(obj: PreKeyRecord): number;
 export function PreKeyRecord_GetPrivateKey(obj: PreKeyRecord): PrivateKey;
 export function PreKeyRecord_GetPublicKey(obj: PreKeyRecord): PublicKey;
@@ -39,18 +72,28 @@ export function PreKeySignalMessage_GetSignedPreKeyId(obj: PreKeySignalMessage):
 export function PreKeySignalMessage_GetVersion(obj: PreKeySignalMessage): number;
 export function PreKeySignalMessage_New(messageVersion: number, registrationId: number, preKeyId: number | null, signedPreKeyId: number, baseKey: PublicKey, identityKey: PublicKey, signalMessage: SignalMessage): PreKeySignalMessage;
 export function PreKeySignalMessage_Serialize(obj: PreKeySignalMessage): Buffer;
+export function PreKeySignalMessage_UsedOneTimePreKey(m: PreKeySignalMessage): boolean;
 export function PrivateKey_Agree(privateKey: PrivateKey, publicKey: PublicKey): Buffer;
+export function PrivateKey_AgreeValidated(privateKey: PrivateKey, publicKey: PublicKey): Buffer;
 export function PrivateKey_Deserialize(buffer: Buffer): PrivateKey;
 export function PrivateKey_Generate(): PrivateKey;
 export function PrivateKey_GetPublicKey(k: PrivateKey): PublicKey;
 export function PrivateKey_Serialize(obj: PrivateKey): Buffer;
 export function PrivateKey_Sign(key: PrivateKey, message: Buffer): Buffer;
+export function PrivateKey_SignCanonical(key: PrivateKey, fields: Buffer): Buffer;
 export function ProtocolAddress_DeviceId(obj: ProtocolAddress): number;
+export function ProtocolAddress_FromString(s: string): ProtocolAddress;
 export function ProtocolAddress_Name(obj: ProtocolAddress): string;
+export function ProfileCipher_DecryptName(ciphertext: Buffer, profileKey: Buffer): string;
+export function ProfileCipher_EncryptName(name: string, profileKey: Buffer): Buffer;
 export function ProtocolAddress_New(name: string, deviceId: number): ProtocolAddress;
+export function ProtocolAddress_ToString(addr: ProtocolAddress): string;
 export function PublicKey_Compare(key1: PublicKey, key2: PublicKey): number;
 export function PublicKey_Deserialize(buffer: Buffer): PublicKey;
+export function PublicKey_Equals(key1: PublicKey, key2: PublicKey): boolean;
 export function PublicKey_GetPublicKeyBytes(obj: PublicKey): Buffer;
+export function PublicKey_IsValid(data: Buffer): boolean;
+export function PublicKey_IsWeak(key: PublicKey): boolean;
 export function PublicKey_Serialize(obj: PublicKey): Buffer;
 export function PublicKey_Verify(key: PublicKey, message: Buffer, signature: Buffer): boolean;
 export function ScannableFingerprint_Compare(fprint1: Buffer, fprint2: Buffer): boolean;
@@ -64,8 +107,14 @@ export function SenderCertificate_GetSenderUuid(obj: SenderCertificate): string;
 export function SenderCertificate_GetSerialized(obj: SenderCertificate): Buffer;
 export function SenderCertificate_GetServerCertificate(cert: SenderCertificate): ServerCertificate;
 export function SenderCertificate_GetSignature(obj: SenderCertificate): Buffer;
+export function SenderCertificate_HasValidUuid(cert: SenderCertificate): boolean;
+export function SenderCertificate_IsForDevice(cert: SenderCertificate, deviceId: number): boolean;
 export function SenderCertificate_New(senderUuid: string, senderE164: string | null, senderDeviceId: number, senderKey: PublicKey, expiration: number, signerCert: ServerCertificate, signerKey: PrivateKey): SenderCertificate;
+export function SenderCertificate_SignerKeyIdEquals(cert: SenderCertificate, keyId: number): boolean;
 export function SenderCertificate_Validate(cert: SenderCertificate, key: PublicKey, time: number): boolean;
+export function SenderCertificate_ValidateChain(cert: SenderCertificate, trustRoot: PublicKey, time: number): boolean;
+export function SenderKeyDistributionMessage_ChainKeyLength(): number;
+export function SenderKeyDistributionMessage_DeriveGroupKey(obj: SenderKeyDistributionMessage): Buffer;
 export function SenderKeyDistributionMessage_Deserialize(buffer: Buffer): SenderKeyDistributionMessage;
 export function SenderKeyDistributionMessage_GetChainKey(obj: SenderKeyDistributionMessage): Buffer;
 export function SenderKeyDistributionMessage_GetId(obj: SenderKeyDistributionMessage): number;
@@ -79,11 +128,15 @@ export function SenderKeyMessage_GetKeyId(obj: SenderKeyMessage): number;
 export function SenderKeyMessage_New(keyId: number, iteration: number, ciphertext: Buffer, pk: PrivateKey): SenderKeyMessage;
 export function SenderKeyMessage_Serialize(obj: SenderKeyMessage): Buffer;
 export function SenderKeyMessage_VerifySignature(skm: SenderKeyMessage, pubkey: PublicKey): boolean;
+export function SenderKeyName_FromStorageKey(key: string): SenderKeyName;
 export function SenderKeyName_GetGroupId(obj: SenderKeyName): string;
 export function SenderKeyName_GetSenderDeviceId(skn: SenderKeyName): number;
 export function SenderKeyName_GetSenderName(obj: SenderKeyName): string;
 export function SenderKeyName_New(groupId: string, senderName: string, senderDeviceId: number): SenderKeyName;
+export function SenderKeyName_ToStorageKey(skn: SenderKeyName): string;
+export function SenderKeyRecord_CreateDistributionMessage(record: SenderKeyRecord): SenderKeyDistributionMessage;
 export function SenderKeyRecord_Deserialize(buffer: Buffer): SenderKeyRecord;
+export function SenderKeyRecord_MessagesUntilRotation(record: SenderKeyRecord): number;
 export function SenderKeyRecord_New(): SenderKeyRecord;
 export function SenderKeyRecord_Serialize(obj: SenderKeyRecord): Buffer;
 export function ServerCertificate_Deserialize(buffer: Buffer): ServerCertificate;
@@ -93,11 +146,27 @@ export function ServerCertificate_GetKeyId(obj: ServerCertificate): number;
 export function ServerCertificate_GetSerialized(obj: ServerCertificate): Buffer;
 export function ServerCertificate_GetSignature(obj: ServerCertificate): Buffer;
 export function ServerCertificate_New(keyId: number, serverKey: PublicKey, trustRoot: PrivateKey): ServerCertificate;
+export function ServerCertificate_Verify(cert: ServerCertificate, trustRoot: PublicKey): boolean;
+export function SessionCipher_ShouldResetSession(errorCode: number, messageType: number, message: Buffer): boolean;
 export function SessionRecord_ArchiveCurrentState(sessionRecord: SessionRecord): void;
+export function SessionRecord_CanEncrypt(obj: SessionRecord): boolean;
+export function SessionRecord_ComputeKeyConfirmation(obj: SessionRecord): Buffer;
+export function SessionRecord_ComputeSessionId(obj: SessionRecord): Buffer;
 export function SessionRecord_Deserialize(buffer: Buffer): SessionRecord;
+export function SessionRecord_GetCreationTimestamp(obj: SessionRecord): number;
 export function SessionRecord_GetLocalRegistrationId(obj: SessionRecord): number;
+export function SessionRecord_GetRegistrationIds(s: SessionRecord): Buffer;
 export function SessionRecord_GetRemoteRegistrationId(obj: SessionRecord): number;
+export function SessionRecord_IdentitySeenBefore(s: SessionRecord, identityKey: PublicKey): boolean;
+export function SessionRecord_InitializeAliceSession(identityKeyPrivate: PrivateKey, identityKeyPublic: PublicKey, basePrivate: PrivateKey, basePublic: PublicKey, theirIdentityKey: PublicKey, theirSignedPrekey: PublicKey, theirOneTimePrekey: PublicKey | null, theirRatchetKey: PublicKey): SessionRecord;
+export function SessionRecord_InitializeBobSession(identityKeyPrivate: PrivateKey, identityKeyPublic: PublicKey, signedPrekeyPrivate: PrivateKey, signedPrekeyPublic: PublicKey, prekeyPrivate: PrivateKey | null, prekeyPublic: PublicKey | null, ephPrivate: PrivateKey, ephPublic: PublicKey, theirIdentityKey: PublicKey, theirBaseKey: PublicKey): SessionRecord;
+export function SessionRecord_NeedsUpgrade(obj: SessionRecord): boolean;
+export function SessionRecord_PreKeyMessageAlreadyProcessed(s: SessionRecord, message: PreKeySignalMessage): boolean;
 export function SessionRecord_Serialize(obj: SessionRecord): Buffer;
+export function SessionRecord_StorageVersion(data: Buffer): number;
+export function SessionRecord_ValidateIntegrity(serialized: Buffer): number;
+export function Sha256_Digest(input: Buffer): Buffer;
+export function Sha512_Digest(input: Buffer): Buffer;
 export function SignalMessage_Deserialize(buffer: Buffer): SignalMessage;
 export function SignalMessage_GetBody(obj: SignalMessage): Buffer;
 export function SignalMessage_GetCounter(obj: SignalMessage): number;
@@ -105,23 +174,38 @@ export function SignalMessage_GetMessageVersion(obj: SignalMessage): number;
 export function SignalMessage_GetSerialized(obj: SignalMessage): Buffer;
 export function SignalMessage_New(messageVersion: number, macKey: Buffer, senderRatchetKey: PublicKey, counter: number, previousCounter: number, ciphertext: Buffer, senderIdentityKey: PublicKey, receiverIdentityKey: PublicKey): SignalMessage;
 export function SignalMessage_VerifyMac(msg: SignalMessage, senderIdentityKey: PublicKey, receiverIdentityKey: PublicKey, macKey: Buffer): boolean;
+export function SignalProtocolError_DescribeCode(code: number): string;
 export function SignedPreKeyRecord_Deserialize(buffer: Buffer): SignedPreKeyRecord;
+export function SignedPreKeyRecord_EarliestExpiry(serializedRecords: Buffer, maxAge: number): number;
 export function SignedPreKeyRecord_GetId(obj: SignedPreKeyRecord): number;
 export function SignedPreKeyRecord_GetPrivateKey(obj: SignedPreKeyRecord): PrivateKey;
 export function SignedPreKeyRecord_GetPublicKey(obj: SignedPreKeyRecord): PublicKey;
 export function SignedPreKeyRecord_GetSignature(obj: SignedPreKeyRecord): Buffer;
 export function SignedPreKeyRecord_GetTimestamp(obj: SignedPreKeyRecord): number;
 export function SignedPreKeyRecord_New(id: number, timestamp: number, pubKey: PublicKey, privKey: PrivateKey, signature: Buffer): SignedPreKeyRecord;
+export function SignedPreKeyRecord_NextRotationTime(record: SignedPreKeyRecord, interval: number): number;
 export function SignedPreKeyRecord_Serialize(obj: SignedPreKeyRecord): Buffer;
+export function SignedPreKeyRecord_VerifyKeyPair(record: SignedPreKeyRecord): boolean;
+export function SignedPreKeyRecord_VerifyOwnSignature(record: SignedPreKeyRecord, identityKey: PublicKey): boolean;
+export function UnidentifiedSenderMessage_Deserialize(buffer: Buffer): UnidentifiedSenderMessage;
+export function UnidentifiedSenderMessage_GetEncryptedMessage(obj: UnidentifiedSenderMessage): Buffer;
+export function UnidentifiedSenderMessage_GetEncryptedStatic(obj: UnidentifiedSenderMessage): Buffer;
+export function UnidentifiedSenderMessage_GetEphemeralPublic(obj: UnidentifiedSenderMessage): PublicKey;
+export function UnidentifiedSenderMessage_GetSerialized(obj: UnidentifiedSenderMessage): Buffer;
+export function UnidentifiedSenderMessage_New(publicKey: PublicKey, encryptedStatic: Buffer, encryptedMessage: Buffer): UnidentifiedSenderMessage;
 export function UnidentifiedSenderMessageContent_Deserialize(buffer: Buffer): UnidentifiedSenderMessageContent;
 export function UnidentifiedSenderMessageContent_GetContents(obj: UnidentifiedSenderMessageContent): Buffer;
 export function UnidentifiedSenderMessageContent_GetMsgType(m: UnidentifiedSenderMessageContent): number;
+export function UnidentifiedSenderMessageContent_GetMsgTypeName(msgType: number): string;
 export function UnidentifiedSenderMessageContent_GetSenderCert(m: UnidentifiedSenderMessageContent): SenderCertificate;
 export function UnidentifiedSenderMessageContent_Serialize(obj: UnidentifiedSenderMessageContent): Buffer;
 export function initLogger(maxLevel: LogLevel, callback: (level: LogLevel, target: string, file: string | null, line: number | null, message: string) => void): void
 interface Aes256GcmSiv { readonly __type: unique symbol; }
 interface CiphertextMessage { readonly __type: unique symbol; }
+interface CryptographicMac { readonly __type: unique symbol; }
 interface Fingerprint { readonly __type: unique symbol; }
+interface IdentityKeyPair { readonly __type: unique symbol; }
+interface KeyPair { readonly __type: unique symbol; }
 interface PreKeyBundle { readonly __type: unique symbol; }
 interface PreKeyRecord { readonly __type: unique symbol; }
 interface PreKeySignalMessage { readonly __type: unique symbol; }
@@ -137,4 +221,5 @@ interface ServerCertificate { readonly __type: unique symbol; }
 interface SessionRecord { readonly __type: unique symbol; }
 interface SignalMessage { readonly __type: unique symbol; }
 interface SignedPreKeyRecord { readonly __type: unique symbol; }
+interface UnidentifiedSenderMessage { readonly __type: unique symbol; }
 interface UnidentifiedSenderMessageContent { readonly __type: unique symbol; }
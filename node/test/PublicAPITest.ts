@@ -4,6 +4,7 @@
 //
 
 import { assert } from 'chai';
+import * as crypto from 'crypto';
 import * as SignalClient from '../index';
 
 SignalClient.initLogger(
@@ -18,6 +19,119 @@ SignalClient.initLogger(
 );
 
 describe('SignalClient', () => {
+  it('SHA-2 test vectors', () => {
+    const empty = Buffer.alloc(0);
+    const abc = Buffer.from('abc');
+
+    assert.equal(
+      SignalClient.sha256(empty).toString('hex'),
+      'e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855'
+    );
+    assert.equal(
+      SignalClient.sha256(abc).toString('hex'),
+      'ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad'
+    );
+    assert.equal(
+      SignalClient.sha512(empty).toString('hex'),
+      'cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce' +
+        '47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e'
+    );
+    assert.equal(
+      SignalClient.sha512(abc).toString('hex'),
+      'ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a' +
+        '2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f'
+    );
+  });
+  it('constant-time equality', () => {
+    const mac = Buffer.from('0102030405060708', 'hex');
+
+    assert(SignalClient.constantTimeEqual(mac, Buffer.from(mac)));
+    assert(
+      !SignalClient.constantTimeEqual(
+        mac,
+        Buffer.from('0102030405060709', 'hex')
+      )
+    );
+    assert(!SignalClient.constantTimeEqual(mac, mac.slice(0, 7)));
+    assert(SignalClient.constantTimeEqual(Buffer.alloc(0), Buffer.alloc(0)));
+  });
+  it('AES-256-CBC', () => {
+    // NIST SP 800-38A F.2.5, followed by one block of PKCS7 padding.
+    const key = Buffer.from(
+      '603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4',
+      'hex'
+    );
+    const iv = Buffer.from('000102030405060708090a0b0c0d0e0f', 'hex');
+    const ptext = Buffer.from(
+      '6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51' +
+        '30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710',
+      'hex'
+    );
+    const expectedCtext =
+      'f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d' +
+      '39f23369a9d9bacfa530e26304231461b2eb05e2c39be9fcda6c19078c6a9d1b' +
+      '3f461796d6b0d6b2e0c2a72b4d80e644';
+
+    const ctext = SignalClient.aes256CbcEncrypt(key, iv, ptext);
+    assert.equal(ctext.toString('hex'), expectedCtext);
+    assert.deepEqual(SignalClient.aes256CbcDecrypt(key, iv, ctext), ptext);
+
+    for (let length = 0; length <= 33; length++) {
+      const message = Buffer.alloc(length, length);
+      const encrypted = SignalClient.aes256CbcEncrypt(key, iv, message);
+      assert.equal(encrypted.length, (Math.floor(length / 16) + 1) * 16);
+      assert.deepEqual(
+        SignalClient.aes256CbcDecrypt(key, iv, encrypted),
+        message
+      );
+    }
+
+    assert.throws(
+      () => SignalClient.aes256CbcDecrypt(key, iv, ctext.slice(0, -1)),
+      'invalid argument'
+    );
+    assert.throws(
+      () => SignalClient.aes256CbcDecrypt(key, iv, Buffer.alloc(0)),
+      'invalid argument'
+    );
+    assert.throws(
+      () => SignalClient.aes256CbcDecrypt(key, iv, ctext.slice(0, -16)),
+      'invalid ciphertext message'
+    );
+  });
+  it('CryptographicMac', () => {
+    const key = Buffer.alloc(20, 0x0b);
+    const mac = SignalClient.CryptographicMac.new(key);
+    mac.update(Buffer.from('Hi There'));
+    assert.equal(
+      mac.finalize().toString('hex'),
+      'b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7'
+    );
+
+    assert.throws(() => mac.finalize(), 'MAC has already been finalized');
+    assert.throws(
+      () => mac.update(Buffer.from('more')),
+      'MAC has already been finalized'
+    );
+  });
+  it('CryptographicMac chunked updates', () => {
+    const key = Buffer.alloc(32, 0x42);
+    const input = Buffer.alloc(1000);
+    for (let i = 0; i < input.length; i++) {
+      input[i] = i & 0xff;
+    }
+
+    const single = SignalClient.CryptographicMac.new(key);
+    single.update(input);
+
+    const chunked = SignalClient.CryptographicMac.new(key);
+    chunked.update(input.slice(0, 1));
+    chunked.update(input.slice(1, 1));
+    chunked.update(input.slice(1, 500));
+    chunked.update(input.slice(500));
+
+    assert.deepEqual(chunked.finalize(), single.finalize());
+  });
   it('HKDF test vector', () => {
     const hkdf = SignalClient.HKDF.new(3);
 
@@ -45,10 +159,116 @@ describe('SignalClient', () => {
       '3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865'
     );
   });
+  it('HKDF derives many secrets at once', () => {
+    const hkdf = SignalClient.HKDF.new(3);
+
+    const secret = Buffer.from(
+      '0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B0B',
+      'hex'
+    );
+    const salt = Buffer.from('000102030405060708090A0B0C', 'hex');
+    const label = Buffer.from('F0F1F2F3F4F5F6F7F8F9', 'hex');
+    const other = Buffer.from('second', 'utf8');
+
+    const outputs = hkdf.deriveManySecrets(
+      secret,
+      [label, other],
+      [42, 80],
+      salt
+    );
+    assert.equal(outputs.length, 2);
+    assert.deepEqual(
+      outputs[0].toString('hex'),
+      '3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865'
+    );
+    assert.deepEqual(outputs[1], hkdf.deriveSecrets(80, secret, other, salt));
+
+    assert.throws(() =>
+      hkdf.deriveManySecrets(secret, [label], [16, 16], salt)
+    );
+  });
+  it('Attachment padding buckets', () => {
+    assert.equal(SignalClient.attachmentPaddedSize(0), 541);
+    assert.equal(SignalClient.attachmentPaddedSize(1), 541);
+    assert.equal(SignalClient.attachmentPaddedSize(541), 541);
+    assert.equal(SignalClient.attachmentPaddedSize(542), 568);
+    assert.equal(SignalClient.attachmentPaddedSize(568), 568);
+    assert.equal(SignalClient.attachmentPaddedSize(569), 596);
+    assert.equal(SignalClient.attachmentPaddedSize(1000000), 1041743);
+  });
+  it('Profile name encryption', () => {
+    const profileKey = Buffer.alloc(32, 0x42);
+    const roundTrip = (name: string, expectedLength: number) => {
+      const ciphertext = SignalClient.encryptProfileName(name, profileKey);
+      assert.equal(ciphertext.length, 12 + expectedLength + 16);
+      assert.equal(
+        SignalClient.decryptProfileName(ciphertext, profileKey),
+        name
+      );
+    };
+
+    roundTrip('', 53);
+    roundTrip('Alice', 53);
+    roundTrip('a'.repeat(53), 53);
+    roundTrip('a'.repeat(54), 257);
+    roundTrip('a'.repeat(257), 257);
+    assert.throws(() =>
+      SignalClient.encryptProfileName('a'.repeat(258), profileKey)
+    );
+  });
+  it('Profile name decryption rejects tampering and bad padding', () => {
+    const profileKey = Buffer.alloc(32, 0x42);
+
+    const tampered = SignalClient.encryptProfileName('Alice', profileKey);
+    tampered[tampered.length - 1] ^= 1;
+    assert.throws(
+      () => SignalClient.decryptProfileName(tampered, profileKey),
+      'invalid ciphertext'
+    );
+
+    const encryptRaw = (padded: Buffer): Buffer => {
+      const nonce = crypto.randomBytes(12);
+      const cipher = crypto.createCipheriv('aes-256-gcm', profileKey, nonce);
+      const ctext = Buffer.concat([cipher.update(padded), cipher.final()]);
+      return Buffer.concat([nonce, ctext, cipher.getAuthTag()]);
+    };
+
+    const goodPadding = Buffer.alloc(53);
+    goodPadding.write('Alice');
+    assert.equal(
+      SignalClient.decryptProfileName(encryptRaw(goodPadding), profileKey),
+      'Alice'
+    );
+
+    const junkAfterName = Buffer.from(goodPadding);
+    junkAfterName[52] = 0x01;
+    assert.throws(
+      () =>
+        SignalClient.decryptProfileName(encryptRaw(junkAfterName), profileKey),
+      'padding'
+    );
+
+    const wrongLength = Buffer.alloc(54);
+    wrongLength.write('Alice');
+    assert.throws(
+      () =>
+        SignalClient.decryptProfileName(encryptRaw(wrongLength), profileKey),
+      'padded length'
+    );
+  });
   it('ProtocolAddress', () => {
     const addr = SignalClient.ProtocolAddress.new('name', 42);
     assert.deepEqual(addr.name(), 'name');
     assert.deepEqual(addr.deviceId(), 42);
+    assert.deepEqual(addr.toString(), 'name.42');
+
+    const dotted = SignalClient.ProtocolAddress.new('a.b.c', 7);
+    const parsed = SignalClient.ProtocolAddress.fromString(dotted.toString());
+    assert.deepEqual(parsed.name(), 'a.b.c');
+    assert.deepEqual(parsed.deviceId(), 7);
+
+    assert.throws(() => SignalClient.ProtocolAddress.fromString('name'));
+    assert.throws(() => SignalClient.ProtocolAddress.fromString('name.x'));
   });
   it('SenderKeyName', () => {
     const addr = SignalClient.SenderKeyName.new('group', 'sender', 42);
@@ -56,6 +276,33 @@ describe('SignalClient', () => {
     assert.deepEqual(addr.senderName(), 'sender');
     assert.deepEqual(addr.senderDeviceId(), 42);
   });
+  it('SenderKeyName storage key', () => {
+    const name = SignalClient.SenderKeyName.new('group', 'sender', 42);
+    assert.equal(name.toStorageKey(), 'group:sender:42');
+
+    const awkward = SignalClient.SenderKeyName.new('a:b', 'c\\d', 1);
+    assert.equal(awkward.toStorageKey(), 'a\\:b:c\\\\d:1');
+
+    const parsed = SignalClient.SenderKeyName.fromStorageKey(
+      awkward.toStorageKey()
+    );
+    assert.equal(parsed.groupId(), 'a:b');
+    assert.equal(parsed.senderName(), 'c\\d');
+    assert.equal(parsed.senderDeviceId(), 1);
+
+    for (const malformed of [
+      'group:sender',
+      'a:b:c:1',
+      'a:b:01',
+      'a:b:',
+      'a\\x:b:1',
+    ]) {
+      assert.throws(
+        () => SignalClient.SenderKeyName.fromStorageKey(malformed),
+        'malformed sender key storage key'
+      );
+    }
+  });
   it('Fingerprint', () => {
     const aliceKey = SignalClient.PublicKey.deserialize(
       Buffer.from(
@@ -128,6 +375,79 @@ describe('SignalClient', () => {
     assert.isNotTrue(
       bFprint1.scannableFingerprint().compare(bFprint1.scannableFingerprint())
     );
+
+    assert.deepEqual(aFprint1.truncatedDisplayString(12), '300354477692');
+    assert.throws(() => aFprint1.truncatedDisplayString(61));
+
+    assert.deepEqual(
+      aFprint1.qrCodePayload().toString('hex'),
+      '080112220a201e301a0353dce3dbe7684cb8336e85136cdc0ee96219494ada305d62a7bd61df1a220a20d62cbf73a11592015b6b9f1682ac306fea3aaf3885b84d12bca631e9d4fb3a4d'
+    );
+
+    const bQrPayload = bFprint1.qrCodePayload();
+    assert(aFprint1.verifyQrPayload(bQrPayload));
+    assert.isNotTrue(
+      aFprint1.verifyQrPayload(aFprint1.scannableFingerprint().toBuffer())
+    );
+
+    const aFprint2 = SignalClient.Fingerprint.new(
+      iterations,
+      2,
+      aliceIdentifier,
+      aliceKey,
+      bobIdentifier,
+      bobKey
+    );
+    assert.throws(() => aFprint2.verifyQrPayload(bQrPayload));
+
+    assert.throws(() =>
+      aFprint1.verifyQrPayload(Buffer.from('not a fingerprint'))
+    );
+
+    assert(SignalClient.ScannableFingerprint.versionsCompatible(1, 1));
+    assert(SignalClient.ScannableFingerprint.versionsCompatible(2, 2));
+    assert.isNotTrue(
+      SignalClient.ScannableFingerprint.versionsCompatible(1, 2)
+    );
+    assert.isNotTrue(
+      SignalClient.ScannableFingerprint.versionsCompatible(2, 1)
+    );
+  });
+  it('UnidentifiedSenderMessage', () => {
+    const ephemeralPublic = SignalClient.PrivateKey.generate().getPublicKey();
+    const encryptedStatic = Buffer.from('static');
+    const encryptedMessage = Buffer.from('message');
+
+    const message = SignalClient.UnidentifiedSenderMessage.new(
+      ephemeralPublic,
+      encryptedStatic,
+      encryptedMessage
+    );
+    const parsed = SignalClient.UnidentifiedSenderMessage.deserialize(
+      message.serialize()
+    );
+    assert.deepEqual(parsed.serialize(), message.serialize());
+    assert.deepEqual(
+      parsed.ephemeralPublic().serialize(),
+      ephemeralPublic.serialize()
+    );
+    assert.deepEqual(parsed.encryptedStatic(), encryptedStatic);
+    assert.deepEqual(parsed.encryptedMessage(), encryptedMessage);
+
+    const futureVersion = message.serialize();
+    futureVersion[0] = 0x22;
+    assert.throws(
+      () => SignalClient.UnidentifiedSenderMessage.deserialize(futureVersion),
+      'unknown sealed sender message version 2'
+    );
+  });
+  it('UnidentifiedSenderMessageContent type names', () => {
+    const typeName = SignalClient.UnidentifiedSenderMessageContent.msgTypeName;
+    assert.equal(typeName(2), 'whisper');
+    assert.equal(typeName(3), 'prekey');
+    assert.equal(typeName(4), 'senderkey');
+    assert.equal(typeName(5), 'senderkey-distribution');
+    assert.throws(() => typeName(0x7f), 'unknown ciphertext message type 127');
   });
   it('SenderCertificate', () => {
     const trustRoot = SignalClient.PrivateKey.generate();
@@ -148,6 +468,10 @@ describe('SignalClient', () => {
     );
     assert.deepEqual(serverCert, serverCertFromBytes);
 
+    assert(serverCert.verify(trustRoot.getPublicKey()));
+    const unrelatedKey = SignalClient.PrivateKey.generate();
+    assert(!serverCert.verify(unrelatedKey.getPublicKey()));
+
     const senderUuid = 'fedfe51e-2b91-4156-8710-7cc1bdd57cd8';
     const senderE164 = '555-123-4567';
     const senderDeviceId = 9;
@@ -176,6 +500,35 @@ describe('SignalClient', () => {
 
     assert(senderCert.validate(trustRoot.getPublicKey(), expiration - 1000));
     assert(!senderCert.validate(trustRoot.getPublicKey(), expiration + 10)); // expired
+
+    senderCert.validateChain(trustRoot.getPublicKey(), expiration - 1000);
+    assert.throws(
+      () => senderCert.validateChain(trustRoot.getPublicKey(), expiration + 10),
+      /certificate expired/
+    );
+    const otherRoot = SignalClient.PrivateKey.generate();
+    assert.throws(
+      () => senderCert.validateChain(otherRoot.getPublicKey(), expiration - 1000),
+      /invalid signature/
+    );
+
+    assert(senderCert.isForDevice(senderDeviceId));
+    assert(!senderCert.isForDevice(senderDeviceId + 1));
+
+    assert(senderCert.signerKeyIdEquals(keyId));
+    assert(!senderCert.signerKeyIdEquals(keyId + 1));
+
+    assert(senderCert.hasValidUuid());
+    const junkSenderCert = SignalClient.SenderCertificate.new(
+      'fedfe51e-2b91-4156-8710-7cc1bdd57cdX',
+      senderE164,
+      senderDeviceId,
+      senderKey.getPublicKey(),
+      expiration,
+      serverCert,
+      serverKey
+    );
+    assert(!junkSenderCert.hasValidUuid());
   });
   it('SenderKeyMessage', () => {
     const keyId = 9;
@@ -221,6 +574,48 @@ describe('SignalClient', () => {
     );
     assert.deepEqual(skdm, skdmFromBytes);
   });
+  it('SenderKeyDistributionMessage checks the chain key length', () => {
+    const pk = SignalClient.PrivateKey.generate();
+    const chainKeyLength =
+      SignalClient.SenderKeyDistributionMessage.chainKeyLength();
+    assert.equal(chainKeyLength, 32);
+
+    for (const badLength of [0, chainKeyLength - 1, chainKeyLength + 1]) {
+      assert.throws(() =>
+        SignalClient.SenderKeyDistributionMessage.new(
+          9,
+          101,
+          Buffer.alloc(badLength, 0xfe),
+          pk.getPublicKey()
+        )
+      );
+    }
+  });
+  it('SenderKeyMessage verifies against a distribution message', () => {
+    const pk = SignalClient.PrivateKey.generate();
+    const skdm = SignalClient.SenderKeyDistributionMessage.new(
+      9,
+      101,
+      Buffer.alloc(32, 0xfe),
+      pk.getPublicKey()
+    );
+
+    const skm = SignalClient.SenderKeyMessage.new(
+      9,
+      101,
+      Buffer.alloc(32, 0xab),
+      pk
+    );
+    assert.isTrue(skm.verifyAgainstDistribution(skdm));
+
+    const forged = SignalClient.SenderKeyMessage.new(
+      9,
+      101,
+      Buffer.alloc(32, 0xab),
+      SignalClient.PrivateKey.generate()
+    );
+    assert.isFalse(forged.verifyAgainstDistribution(skdm));
+  });
   it('PublicKeyBundle', () => {
     const registrationId = 5;
     const deviceId = 23;
@@ -273,6 +668,27 @@ describe('SignalClient', () => {
     assert.deepEqual(pkb2.signedPreKeyPublic(), signedPrekey);
     assert.deepEqual(pkb2.signedPreKeySignature(), signedPrekeySignature);
     assert.deepEqual(pkb2.identityKey(), identityKey);
+
+    for (const bundle of [pkb, pkb2]) {
+      const payload = bundle.toLinkingPayload();
+      const parsed = SignalClient.PreKeyBundle.fromLinkingPayload(payload);
+      assert.deepEqual(parsed.registrationId(), bundle.registrationId());
+      assert.deepEqual(parsed.deviceId(), bundle.deviceId());
+      assert.deepEqual(parsed.preKeyId(), bundle.preKeyId());
+      assert.deepEqual(parsed.signedPreKeyId(), bundle.signedPreKeyId());
+      assert.deepEqual(
+        parsed.signedPreKeySignature(),
+        bundle.signedPreKeySignature()
+      );
+      assert.deepEqual(
+        parsed.identityKey().serialize(),
+        identityKey.serialize()
+      );
+      assert.deepEqual(parsed.toLinkingPayload(), payload);
+      assert.throws(() =>
+        SignalClient.PreKeyBundle.fromLinkingPayload(payload.slice(1))
+      );
+    }
   });
   it('PreKeyRecord', () => {
     const privKey = SignalClient.PrivateKey.generate();
@@ -288,6 +704,66 @@ describe('SignalClient', () => {
     assert.deepEqual(pkr2.publicKey(), pubKey);
     assert.deepEqual(pkr2.privateKey(), privKey);
   });
+  it('PreKeyRecord duplicate keys', () => {
+    const privKey = SignalClient.PrivateKey.generate();
+    const otherPrivKey = SignalClient.PrivateKey.generate();
+    const records = [
+      SignalClient.PreKeyRecord.new(1, privKey.getPublicKey(), privKey),
+      SignalClient.PreKeyRecord.new(
+        2,
+        otherPrivKey.getPublicKey(),
+        otherPrivKey
+      ),
+      SignalClient.PreKeyRecord.new(3, privKey.getPublicKey(), privKey),
+    ];
+
+    assert.deepEqual(
+      SignalClient.PreKeyRecord.findDuplicateKeys(records),
+      [1, 3]
+    );
+    assert.deepEqual(
+      SignalClient.PreKeyRecord.findDuplicateKeys(records.slice(0, 2)),
+      []
+    );
+  });
+  it('SignedPreKeyRecord earliest expiry', () => {
+    const privKey = SignalClient.PrivateKey.generate();
+    const pubKey = privKey.getPublicKey();
+    const signature = Buffer.alloc(64, 64);
+    const records = [3000, 1000, 2000].map((timestamp, i) =>
+      SignalClient.SignedPreKeyRecord.new(
+        i,
+        timestamp,
+        pubKey,
+        privKey,
+        signature
+      )
+    );
+
+    assert.deepEqual(
+      SignalClient.SignedPreKeyRecord.earliestExpiry(records, 500),
+      1500
+    );
+    assert.deepEqual(
+      SignalClient.SignedPreKeyRecord.earliestExpiry([records[0]], 500),
+      3500
+    );
+    assert.throws(() =>
+      SignalClient.SignedPreKeyRecord.earliestExpiry([], 500)
+    );
+  });
+  it('SignedPreKeyRecord next rotation time', () => {
+    const privKey = SignalClient.PrivateKey.generate();
+    const spkr = SignalClient.SignedPreKeyRecord.new(
+      1,
+      1000,
+      privKey.getPublicKey(),
+      privKey,
+      Buffer.alloc(64, 64)
+    );
+    assert.deepEqual(spkr.nextRotationTime(500), 1500);
+    assert.deepEqual(spkr.nextRotationTime(0), 1000);
+  });
   it('SignedPreKeyRecord', () => {
     const privKey = SignalClient.PrivateKey.generate();
     const pubKey = privKey.getPublicKey();
@@ -313,6 +789,58 @@ describe('SignalClient', () => {
     );
     assert.deepEqual(spkrFromBytes, spkr);
   });
+  it('SignedPreKeyRecord signature verification', () => {
+    const identityKey = SignalClient.PrivateKey.generate();
+    const privKey = SignalClient.PrivateKey.generate();
+    const pubKey = privKey.getPublicKey();
+    const signature = identityKey.sign(pubKey.serialize());
+
+    const spkr = SignalClient.SignedPreKeyRecord.new(
+      23,
+      9000,
+      pubKey,
+      privKey,
+      signature
+    );
+    assert.isTrue(spkr.verifyOwnSignature(identityKey.getPublicKey()));
+    assert.isFalse(
+      spkr.verifyOwnSignature(SignalClient.PrivateKey.generate().getPublicKey())
+    );
+
+    const tamperedSignature = Buffer.from(signature);
+    tamperedSignature[0] ^= 0x01;
+    const tampered = SignalClient.SignedPreKeyRecord.new(
+      23,
+      9000,
+      pubKey,
+      privKey,
+      tamperedSignature
+    );
+    assert.isFalse(tampered.verifyOwnSignature(identityKey.getPublicKey()));
+  });
+  it('SignedPreKeyRecord key pair verification', () => {
+    const privKey = SignalClient.PrivateKey.generate();
+    const pubKey = privKey.getPublicKey();
+    const signature = Buffer.alloc(64, 0);
+
+    const spkr = SignalClient.SignedPreKeyRecord.new(
+      23,
+      9000,
+      pubKey,
+      privKey,
+      signature
+    );
+    assert.isTrue(spkr.verifyKeyPair());
+
+    const corrupted = SignalClient.SignedPreKeyRecord.new(
+      23,
+      9000,
+      SignalClient.PrivateKey.generate().getPublicKey(),
+      privKey,
+      signature
+    );
+    assert.isFalse(corrupted.verifyKeyPair());
+  });
   it('SenderKeyRecord', () => {
     const skr = SignalClient.SenderKeyRecord.new();
     const skrFromBytes = SignalClient.SenderKeyRecord.deserialize(
@@ -320,6 +848,94 @@ describe('SignalClient', () => {
     );
     assert.deepEqual(skr, skrFromBytes);
   });
+  it('SessionRecord.validateIntegrity', () => {
+    const { SessionRecord, SessionRecordIntegrity } = SignalClient;
+
+    // A record whose current session holds only a version field.
+    const record = Buffer.from([0x0a, 0x02, 0x08, 0x03]);
+    assert.equal(
+      SessionRecord.validateIntegrity(record),
+      SessionRecordIntegrity.Valid
+    );
+    assert.equal(
+      SessionRecord.validateIntegrity(Buffer.alloc(0)),
+      SessionRecordIntegrity.Valid
+    );
+
+    const flipped = Buffer.from(record);
+    flipped[3] ^= 0x01;
+    assert.equal(
+      SessionRecord.validateIntegrity(flipped),
+      SessionRecordIntegrity.InvalidVersion
+    );
+
+    assert.equal(
+      SessionRecord.validateIntegrity(record.slice(0, -1)),
+      SessionRecordIntegrity.Unparseable
+    );
+  });
+  it('SessionRecord.storageVersion', () => {
+    const { SessionRecord } = SignalClient;
+
+    assert.equal(SessionRecord.storageVersion(Buffer.alloc(0)), 0);
+
+    const current = Buffer.from([0x0a, 0x02, 0x08, 0x03]);
+    assert.equal(SessionRecord.storageVersion(current), 3);
+    assert(!SessionRecord.deserialize(current).needsUpgrade());
+
+    const legacy = Buffer.from([0x0a, 0x02, 0x08, 0x02]);
+    assert.equal(SessionRecord.storageVersion(legacy), 2);
+    assert(SessionRecord.deserialize(legacy).needsUpgrade());
+  });
+  it('SessionRecord Alice/Bob initialization', () => {
+    const { PrivateKey, SessionRecord } = SignalClient;
+    const aliceIdentity = PrivateKey.generate();
+    const aliceBase = PrivateKey.generate();
+    const bobIdentity = PrivateKey.generate();
+    const bobSignedPreKey = PrivateKey.generate();
+    const bobOneTimePreKey = PrivateKey.generate();
+    const bobEphemeral = PrivateKey.generate();
+
+    const initializePair = (
+      aliceUsesPreKey: boolean,
+      bobUsesPreKey: boolean
+    ) => {
+      const alice = SessionRecord.initializeAliceSession(
+        aliceIdentity,
+        aliceBase,
+        bobIdentity.getPublicKey(),
+        bobSignedPreKey.getPublicKey(),
+        aliceUsesPreKey ? bobOneTimePreKey.getPublicKey() : null,
+        bobEphemeral.getPublicKey()
+      );
+      const bob = SessionRecord.initializeBobSession(
+        bobIdentity,
+        bobSignedPreKey,
+        bobUsesPreKey ? bobOneTimePreKey : null,
+        bobEphemeral,
+        aliceIdentity.getPublicKey(),
+        aliceBase.getPublicKey()
+      );
+      return { alice, bob };
+    };
+
+    for (const usePreKey of [false, true]) {
+      const { alice, bob } = initializePair(usePreKey, usePreKey);
+      assert.isTrue(alice.canEncrypt());
+      assert.isTrue(bob.canEncrypt());
+      // Bob's first sending chain is Alice's first receiving chain.
+      assert.deepEqual(
+        alice.computeKeyConfirmation(),
+        bob.computeKeyConfirmation()
+      );
+    }
+
+    const mismatched = initializePair(true, false);
+    assert.notDeepEqual(
+      mismatched.alice.computeKeyConfirmation(),
+      mismatched.bob.computeKeyConfirmation()
+    );
+  });
   it('SignalMessage and PreKeySignalMessage', () => {
     const messageVersion = 2;
     const macKey = Buffer.alloc(32, 0xab);
@@ -369,12 +985,132 @@ describe('SignalClient', () => {
     assert.deepEqual(pkm.registrationId(), registrationId);
     assert.deepEqual(pkm.signedPreKeyId(), signedPreKeyId);
     assert.deepEqual(pkm.version(), messageVersion);
+    assert.isTrue(pkm.usedOneTimePreKey());
 
     const pkm_bytes = pkm.serialize();
 
     const pkm2 = SignalClient.PreKeySignalMessage.deserialize(pkm_bytes);
 
     assert.deepEqual(pkm2.serialize(), pkm_bytes);
+
+    const pkmWithoutPreKey = SignalClient.PreKeySignalMessage.new(
+      messageVersion,
+      registrationId,
+      null,
+      signedPreKeyId,
+      baseKey,
+      identityKey,
+      sm
+    );
+    assert.isNull(pkmWithoutPreKey.preKeyId());
+    assert.isFalse(pkmWithoutPreKey.usedOneTimePreKey());
+
+    const { CiphertextMessageType } = SignalClient;
+    assert.isTrue(
+      SignalClient.ciphertextMessageTypeMatchesBody(
+        CiphertextMessageType.Whisper,
+        sm_bytes
+      )
+    );
+    assert.isTrue(
+      SignalClient.ciphertextMessageTypeMatchesBody(
+        CiphertextMessageType.PreKey,
+        pkm_bytes
+      )
+    );
+    assert.isFalse(
+      SignalClient.ciphertextMessageTypeMatchesBody(
+        CiphertextMessageType.PreKey,
+        sm_bytes
+      )
+    );
+    assert.isFalse(
+      SignalClient.ciphertextMessageTypeMatchesBody(
+        CiphertextMessageType.Whisper,
+        pkm_bytes
+      )
+    );
+  });
+  it('describeErrorCode', () => {
+    assert.equal(SignalClient.describeErrorCode(5), 'invalid argument');
+    assert.equal(SignalClient.describeErrorCode(60), 'untrusted identity');
+    assert.equal(SignalClient.describeErrorCode(90), 'duplicated message');
+    assert.equal(SignalClient.describeErrorCode(9999), 'unknown error');
+  });
+  it('shouldResetSession', () => {
+    const sm = SignalClient.SignalMessage.new(
+      3,
+      Buffer.alloc(32, 0xab),
+      SignalClient.PrivateKey.generate().getPublicKey(),
+      9,
+      8,
+      Buffer.from('01020304', 'hex'),
+      SignalClient.PrivateKey.generate().getPublicKey(),
+      SignalClient.PrivateKey.generate().getPublicKey()
+    );
+    const pkm = SignalClient.PreKeySignalMessage.new(
+      3,
+      9,
+      23,
+      802,
+      SignalClient.PrivateKey.generate().getPublicKey(),
+      SignalClient.PrivateKey.generate().getPublicKey(),
+      sm
+    );
+
+    // SignalErrorCode values from signal_ffi.h.
+    const invalidMessage = 30;
+    const untrustedIdentity = 60;
+    const sessionNotFound = 80;
+    const duplicatedMessage = 90;
+
+    const { CiphertextMessageType } = SignalClient;
+    assert.isTrue(
+      SignalClient.shouldResetSession(
+        invalidMessage,
+        CiphertextMessageType.Whisper,
+        sm.serialize()
+      )
+    );
+    assert.isTrue(
+      SignalClient.shouldResetSession(
+        sessionNotFound,
+        CiphertextMessageType.PreKey,
+        pkm.serialize()
+      )
+    );
+    assert.isFalse(
+      SignalClient.shouldResetSession(
+        duplicatedMessage,
+        CiphertextMessageType.Whisper,
+        sm.serialize()
+      )
+    );
+    assert.isFalse(
+      SignalClient.shouldResetSession(
+        untrustedIdentity,
+        CiphertextMessageType.PreKey,
+        pkm.serialize()
+      )
+    );
+    // The body has to actually be a message of the given type.
+    assert.isFalse(
+      SignalClient.shouldResetSession(
+        invalidMessage,
+        CiphertextMessageType.Whisper,
+        pkm.serialize()
+      )
+    );
+    assert.isFalse(
+      SignalClient.shouldResetSession(
+        invalidMessage,
+        CiphertextMessageType.SenderKey,
+        Buffer.alloc(0)
+      )
+    );
+    assert.throws(() =>
+      SignalClient.shouldResetSession(invalidMessage, 99, sm.serialize())
+    );
   });
   it('AES-GCM-SIV test vector', () => {
     // RFC 8452, appendix C.2
@@ -400,6 +1136,20 @@ describe('SignalClient', () => {
 
     assert.deepEqual(decrypted.toString('hex'), '02000000');
   });
+  it('AES-GCM-SIV tag length', () => {
+    const tagLength = SignalClient.Aes256GcmSiv.tagLength();
+    assert.equal(tagLength, 16);
+
+    const key = Buffer.alloc(32);
+    const nonce = Buffer.alloc(12);
+    const ptext = Buffer.from('attack at dawn');
+    const ctext = SignalClient.Aes256GcmSiv.new(key).encrypt(
+      ptext,
+      nonce,
+      Buffer.alloc(0)
+    );
+    assert.equal(ctext.length - ptext.length, tagLength);
+  });
   it('ECC signatures work', () => {
     const priv_a = SignalClient.PrivateKey.generate();
     const priv_b = SignalClient.PrivateKey.generate();
@@ -429,6 +1179,29 @@ describe('SignalClient', () => {
     assert(!pub_a.verify(msg, sig_b));
   });
 
+  it('signs canonical field encodings', () => {
+    const priv = SignalClient.PrivateKey.generate();
+    const pub = priv.getPublicKey();
+    const fields = [Buffer.from('ab'), Buffer.from('c'), Buffer.alloc(0)];
+
+    const sig = priv.signCanonical(fields);
+    assert.lengthOf(sig, 64, 'signature length');
+
+    const canonical = Buffer.concat([
+      Buffer.from([0, 0, 0, 3]),
+      Buffer.from([0, 0, 0, 2]),
+      Buffer.from('ab'),
+      Buffer.from([0, 0, 0, 1]),
+      Buffer.from('c'),
+      Buffer.from([0, 0, 0, 0]),
+    ]);
+    assert(pub.verify(canonical, sig));
+
+    // Same bytes, different field boundaries
+    const regrouped = priv.signCanonical([Buffer.from('a'), Buffer.from('bc')]);
+    assert(!pub.verify(canonical, regrouped));
+  });
+
   it('ECC key agreement work', () => {
     const priv_a = SignalClient.PrivateKey.generate();
     const priv_b = SignalClient.PrivateKey.generate();
@@ -442,6 +1215,120 @@ describe('SignalClient', () => {
     assert.deepEqual(shared_a, shared_b, 'key agreement works');
   });
 
+  it('computes identity change hashes', () => {
+    const key = SignalClient.PrivateKey.generate().getPublicKey();
+    const other = SignalClient.PrivateKey.generate().getPublicKey();
+
+    const hash = key.computeIdentityChangeHash();
+    assert.lengthOf(hash, 16);
+    const roundTripped = SignalClient.PublicKey.deserialize(key.serialize());
+    assert.deepEqual(roundTripped.computeIdentityChangeHash(), hash);
+    assert.notDeepEqual(other.computeIdentityChangeHash(), hash);
+  });
+
+  it('formats identity keys for display', () => {
+    const key = SignalClient.PrivateKey.generate().getPublicKey();
+    const display = key.toIdentityDisplayString();
+    assert.match(display, /^05[0-9a-f]{4}( [0-9a-f]{6}){10}$/);
+    assert.deepEqual(
+      display.replace(/ /g, ''),
+      key.serialize().toString('hex')
+    );
+  });
+
+  it('IdentityKeyPair', () => {
+    const priv = SignalClient.PrivateKey.generate();
+    const pair = SignalClient.IdentityKeyPair.new(priv.getPublicKey(), priv);
+
+    const deserialized = SignalClient.IdentityKeyPair.deserialize(
+      pair.serialize()
+    );
+    assert.deepEqual(deserialized.serialize(), pair.serialize());
+
+    const other = SignalClient.PrivateKey.generate().getPublicKey();
+    const signature = deserialized.signAlternateIdentity(other);
+    assert.isTrue(
+      priv.getPublicKey().verifyAlternateIdentity(other, signature)
+    );
+
+    assert.throws(() =>
+      SignalClient.IdentityKeyPair.deserialize(Buffer.from('not a key pair'))
+    );
+  });
+  it('IdentityKeyPair.generate', () => {
+    const pair = SignalClient.IdentityKeyPair.generate();
+    // Deserialization rejects a public key that doesn't match the private key.
+    const deserialized = SignalClient.IdentityKeyPair.deserialize(
+      pair.serialize()
+    );
+    assert.deepEqual(deserialized.serialize(), pair.serialize());
+  });
+
+  it('verifies alternate identity signatures', () => {
+    const primary = SignalClient.PrivateKey.generate();
+    const secondary = SignalClient.PrivateKey.generate();
+    const primaryPair = SignalClient.IdentityKeyPair.new(
+      primary.getPublicKey(),
+      primary
+    );
+
+    const signature = primaryPair.signAlternateIdentity(
+      secondary.getPublicKey()
+    );
+    assert.isTrue(
+      primary
+        .getPublicKey()
+        .verifyAlternateIdentity(secondary.getPublicKey(), signature)
+    );
+    assert.isFalse(
+      secondary
+        .getPublicKey()
+        .verifyAlternateIdentity(primary.getPublicKey(), signature)
+    );
+
+    const forged = primary.sign(secondary.getPublicKey().serialize());
+    assert.isFalse(
+      primary
+        .getPublicKey()
+        .verifyAlternateIdentity(secondary.getPublicKey(), forged)
+    );
+  });
+
+  it('ECC validated key agreement rejects low-order points', () => {
+    const priv_a = SignalClient.PrivateKey.generate();
+    const priv_b = SignalClient.PrivateKey.generate();
+
+    assert.deepEqual(
+      priv_a.agreeValidated(priv_b.getPublicKey()),
+      priv_b.agree(priv_a.getPublicKey())
+    );
+
+    const lowOrder = SignalClient.PublicKey.deserialize(
+      Buffer.concat([Buffer.of(0x05), Buffer.alloc(32)])
+    );
+    assert.deepEqual(priv_a.agree(lowOrder), Buffer.alloc(32));
+    assert.throws(() => priv_a.agreeValidated(lowOrder));
+  });
+
+  it('PublicKey.isWeak detects low-order points', () => {
+    const pub = SignalClient.PrivateKey.generate().getPublicKey();
+    assert.isFalse(pub.isWeak());
+
+    const weakPoints = [
+      '0000000000000000000000000000000000000000000000000000000000000000',
+      '0100000000000000000000000000000000000000000000000000000000000000',
+      'e0eb7a7c3b41b8ae1656e3faf19fc46ada098deb9c32b1fd866205165f49b800',
+      '5f9c95bca3508c24b1d0b1559c83ef5b04445cc4581c8e86d8224eddd09f1157',
+      'ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f',
+    ];
+    for (const point of weakPoints) {
+      const weak = SignalClient.PublicKey.deserialize(
+        Buffer.concat([Buffer.of(0x05), Buffer.from(point, 'hex')])
+      );
+      assert.isTrue(weak.isWeak(), point);
+    }
+  });
+
   it('ECC keys roundtrip through serialization', () => {
     const key = Buffer.alloc(32, 0xab);
     const priv = SignalClient.PrivateKey.deserialize(key);
@@ -464,6 +1351,14 @@ describe('SignalClient', () => {
     assert.deepEqual(pub.compare(anotherKey), 1);
     assert.deepEqual(anotherKey.compare(pub), -1);
 
+    assert.isTrue(pub.equals(pub2));
+    assert.isTrue(
+      SignalClient.PublicKey.deserialize(pub_bytes).equals(
+        SignalClient.PublicKey.deserialize(pub_bytes)
+      )
+    );
+    assert.isFalse(pub.equals(anotherKey));
+
     assert.lengthOf(pub.getPublicKeyBytes(), 32);
   });
 
@@ -478,4 +1373,15 @@ describe('SignalClient', () => {
       SignalClient.PublicKey.deserialize(invalid_key);
     }, 'bad key type <0xab>');
   });
+
+  it('PublicKey.isValid checks serialized keys', () => {
+    const pub = SignalClient.PrivateKey.generate().getPublicKey();
+    const pub_bytes = pub.serialize();
+    assert.lengthOf(pub_bytes, 33);
+    assert.isTrue(SignalClient.PublicKey.isValid(pub_bytes));
+
+    assert.isFalse(SignalClient.PublicKey.isValid(Buffer.alloc(33, 0xab)));
+    assert.isFalse(SignalClient.PublicKey.isValid(pub_bytes.slice(0, 20)));
+    assert.isFalse(SignalClient.PublicKey.isValid(Buffer.alloc(0)));
+  });
 });
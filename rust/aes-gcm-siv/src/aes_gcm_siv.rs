@@ -29,6 +29,12 @@ impl Aes256GcmSiv {
         })
     }
 
+    /// Replaces the key in place. On error the existing key is left unchanged.
+    pub fn rekey(&mut self, key: &[u8]) -> Result<()> {
+        self.key_generator = Aes256::new(key)?;
+        Ok(())
+    }
+
     fn derive_keys(&self, nonce: &[u8]) -> Result<([u8; AES_KEY_SIZE], [u8; POLYVAL_KEY_SIZE])> {
         if nonce.len() != NONCE_SIZE {
             return Err(Error::InvalidNonceSize);
@@ -136,15 +142,24 @@ impl Aes256GcmSiv {
         Ok(())
     }
 
+    /// Checks plaintext and associated data lengths against the limits in RFC 8452.
+    pub fn check_input_sizes(ptext_len: usize, aad_len: usize) -> Result<()> {
+        if ptext_len as u64 > PTEXT_MAX {
+            return Err(Error::PlaintextTooLarge);
+        }
+        if aad_len as u64 > AAD_MAX {
+            return Err(Error::AssociatedDataTooLarge);
+        }
+        Ok(())
+    }
+
     pub fn encrypt(
         &self,
         buffer: &mut [u8],
         nonce: &[u8],
         associated_data: &[u8],
     ) -> Result<[u8; TAG_SIZE]> {
-        if buffer.len() as u64 > PTEXT_MAX || associated_data.len() as u64 > AAD_MAX {
-            return Err(Error::InvalidInputSize);
-        }
+        Self::check_input_sizes(buffer.len(), associated_data.len())?;
 
         let keys = self.derive_keys(nonce)?;
         let aes256 = Aes256::new(&keys.0)?;
@@ -166,9 +181,7 @@ impl Aes256GcmSiv {
         if tag.len() != TAG_SIZE {
             return Err(Error::InvalidTag);
         }
-        if buffer.len() as u64 > PTEXT_MAX || associated_data.len() as u64 > AAD_MAX {
-            return Err(Error::InvalidInputSize);
-        }
+        Self::check_input_sizes(buffer.len(), associated_data.len())?;
         let keys = self.derive_keys(nonce)?;
         let aes256 = Aes256::new(&keys.0)?;
         Self::ctr32(buffer, &tag, &aes256)?;
@@ -10,6 +10,8 @@ pub enum Error {
     InvalidKeySize,
     InvalidNonceSize,
     InvalidInputSize,
+    PlaintextTooLarge,
+    AssociatedDataTooLarge,
     InvalidTag,
 }
 
@@ -21,6 +23,8 @@ impl fmt::Display for Error {
             Error::InvalidKeySize => "invalid AES-GCM-SIV key size",
             Error::InvalidNonceSize => "invalid AES-GCM-SIV nonce size",
             Error::InvalidInputSize => "invalid AES-GCM-SIV input size",
+            Error::PlaintextTooLarge => "AES-GCM-SIV plaintext exceeds maximum length",
+            Error::AssociatedDataTooLarge => "AES-GCM-SIV associated data exceeds maximum length",
             Error::InvalidTag => "invalid AES-GCM-SIV tag",
         };
 
@@ -12,5 +12,5 @@ mod cpuid;
 mod error;
 mod polyval;
 
-pub use crate::aes_gcm_siv::Aes256GcmSiv;
+pub use crate::aes_gcm_siv::{Aes256GcmSiv, TAG_SIZE};
 pub use crate::error::Error;
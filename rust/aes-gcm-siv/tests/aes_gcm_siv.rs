@@ -175,6 +175,54 @@ fn boringssl_tests() {
     }
 }
 
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn input_size_limits() {
+    use aes_gcm_siv::{Aes256GcmSiv, Error};
+
+    const MAX: usize = 1 << 36;
+
+    assert_eq!(Aes256GcmSiv::check_input_sizes(MAX, MAX), Ok(()));
+    assert_eq!(
+        Aes256GcmSiv::check_input_sizes(MAX + 1, 0),
+        Err(Error::PlaintextTooLarge)
+    );
+    assert_eq!(
+        Aes256GcmSiv::check_input_sizes(0, MAX + 1),
+        Err(Error::AssociatedDataTooLarge)
+    );
+    assert_eq!(
+        Aes256GcmSiv::check_input_sizes(MAX + 1, MAX + 1),
+        Err(Error::PlaintextTooLarge)
+    );
+}
+
+#[test]
+fn rekey_matches_fresh_instance() {
+    use aes_gcm_siv::{Aes256GcmSiv, Error};
+
+    let key1 = [0x01u8; 32];
+    let key2 = [0x02u8; 32];
+    let nonce = [0x03u8; 12];
+    let aad = b"associated data";
+
+    let mut aead = Aes256GcmSiv::new(&key1).unwrap();
+    aead.rekey(&key2).unwrap();
+    let fresh = Aes256GcmSiv::new(&key2).unwrap();
+
+    let mut rekeyed_buf = b"a new key, the same handle".to_vec();
+    let mut fresh_buf = rekeyed_buf.clone();
+    let rekeyed_tag = aead.encrypt(&mut rekeyed_buf, &nonce, aad).unwrap();
+    let fresh_tag = fresh.encrypt(&mut fresh_buf, &nonce, aad).unwrap();
+    assert_eq!(rekeyed_buf, fresh_buf);
+    assert_eq!(rekeyed_tag, fresh_tag);
+
+    // A rejected key leaves the previous one in place
+    assert_eq!(aead.rekey(&key1[..16]), Err(Error::InvalidKeySize));
+    let mut buf = b"a new key, the same handle".to_vec();
+    assert_eq!(aead.encrypt(&mut buf, &nonce, aad).unwrap(), fresh_tag);
+}
+
 // This test takes several minutes when compiled without optimizations.
 #[cfg(not(debug_assertions))]
 #[test]
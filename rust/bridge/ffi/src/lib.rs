@@ -39,6 +39,14 @@ pub unsafe extern "C" fn signal_free_buffer(buf: *const c_uchar, buf_len: size_t
     Box::from_raw(std::slice::from_raw_parts_mut(buf as *mut c_uchar, buf_len));
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn signal_free_device_id_list(buf: *const u32, buf_len: size_t) {
+    if buf.is_null() {
+        return;
+    }
+    Box::from_raw(std::slice::from_raw_parts_mut(buf as *mut u32, buf_len));
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn signal_error_get_message(
     err: *const SignalFfiError,
@@ -474,6 +482,15 @@ type StoreSession = extern "C" fn(
     record: *const SessionRecord,
     ctx: *mut c_void,
 ) -> c_int;
+/// The list written to `device_idsp` must be allocated with `malloc`; Rust takes ownership of it
+/// and releases it with `free`.
+type GetDeviceIdsForName = extern "C" fn(
+    store_ctx: *mut c_void,
+    device_idsp: *mut *mut u32,
+    device_ids_len: *mut size_t,
+    name: *const c_char,
+    ctx: *mut c_void,
+) -> c_int;
 
 #[repr(C)]
 #[derive(Copy, Clone)]
@@ -481,6 +498,12 @@ pub struct FfiSessionStoreStruct {
     ctx: *mut c_void,
     load_session: LoadSession,
     store_session: StoreSession,
+    /// May be NULL for stores that cannot list their sessions, in which case listing or archiving
+    /// every session for a name fails with an invalid state error.
+    ///
+    /// This field was added after `store_session`, so callers compiled against an older header
+    /// must be rebuilt.
+    get_device_ids_for_name: Option<GetDeviceIdsForName>,
 }
 
 pub struct FfiSessionStore {
@@ -540,6 +563,91 @@ impl SessionStore for FfiSessionStore {
 
         Ok(())
     }
+
+    async fn get_device_ids_for_name(
+        &self,
+        name: &str,
+        ctx: Context,
+    ) -> Result<Vec<u32>, SignalProtocolError> {
+        let get_device_ids_for_name = self.store.get_device_ids_for_name.ok_or_else(|| {
+            SignalProtocolError::InvalidState(
+                "get_device_ids_for_name",
+                "this session store cannot list its sessions".to_owned(),
+            )
+        })?;
+        let ctx = ctx.unwrap_or(std::ptr::null_mut());
+        let name = CString::new(name).map_err(|_| {
+            SignalProtocolError::InvalidArgument("name contains a NUL byte".to_string())
+        })?;
+        let mut device_ids = std::ptr::null_mut();
+        let mut device_ids_len = 0;
+        let result = get_device_ids_for_name(
+            self.store.ctx,
+            &mut device_ids,
+            &mut device_ids_len,
+            name.as_ptr(),
+            ctx,
+        );
+
+        if let Some(error) = CallbackError::check(result) {
+            return Err(SignalProtocolError::ApplicationCallbackError(
+                "get_device_ids_for_name",
+                Box::new(error),
+            ));
+        }
+
+        if device_ids.is_null() {
+            return Ok(vec![]);
+        }
+
+        let result = unsafe { std::slice::from_raw_parts(device_ids, device_ids_len) }.to_vec();
+        unsafe { libc::free(device_ids as *mut c_void) };
+
+        Ok(result)
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn signal_session_store_get_device_ids_for_name(
+    out: *mut *const u32,
+    out_len: *mut size_t,
+    name: *const c_char,
+    session_store: *const FfiSessionStoreStruct,
+    ctx: *mut c_void,
+) -> *mut SignalFfiError {
+    run_ffi_safe(|| {
+        if out.is_null() || out_len.is_null() {
+            return Err(SignalFfiError::NullPointer);
+        }
+        let name = read_optional_c_string(name)?.ok_or(SignalFfiError::NullPointer)?;
+        let session_store = FfiSessionStore::new(session_store)?;
+
+        let device_ids = expect_ready(session_store.get_device_ids_for_name(&name, Some(ctx)))?;
+
+        let device_ids = device_ids.into_boxed_slice();
+        *out_len = device_ids.len();
+        *out = Box::into_raw(device_ids) as *const u32;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn signal_session_store_archive_all_sessions_for_name(
+    name: *const c_char,
+    session_store: *const FfiSessionStoreStruct,
+    ctx: *mut c_void,
+) -> *mut SignalFfiError {
+    run_ffi_safe(|| {
+        let name = read_optional_c_string(name)?.ok_or(SignalFfiError::NullPointer)?;
+        let mut session_store = FfiSessionStore::new(session_store)?;
+
+        expect_ready(archive_all_sessions_for_name(
+            &name,
+            &mut session_store,
+            Some(ctx),
+        ))?;
+        Ok(())
+    })
 }
 
 #[no_mangle]
@@ -666,6 +774,49 @@ pub unsafe extern "C" fn signal_decrypt_pre_key_message(
     })
 }
 
+/// Like signal_decrypt_pre_key_message, but also reports the version of the session the message
+/// was decrypted with, as stored in the session store afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn signal_decrypt_pre_key_message_with_session_version(
+    result: *mut *const c_uchar,
+    result_len: *mut size_t,
+    session_version: *mut u32,
+    message: *const PreKeySignalMessage,
+    protocol_address: *const ProtocolAddress,
+    session_store: *const FfiSessionStoreStruct,
+    identity_key_store: *const FfiIdentityKeyStoreStruct,
+    prekey_store: *const FfiPreKeyStoreStruct,
+    signed_prekey_store: *const FfiSignedPreKeyStoreStruct,
+    ctx: *mut c_void,
+) -> *mut SignalFfiError {
+    run_ffi_safe(|| {
+        let message = native_handle_cast::<PreKeySignalMessage>(message)?;
+        let protocol_address = native_handle_cast::<ProtocolAddress>(protocol_address)?;
+        let mut identity_key_store = FfiIdentityKeyStore::new(identity_key_store)?;
+        let mut session_store = FfiSessionStore::new(session_store)?;
+        let mut prekey_store = FfiPreKeyStore::new(prekey_store)?;
+        let mut signed_prekey_store = FfiSignedPreKeyStore::new(signed_prekey_store)?;
+
+        let mut csprng = rand::rngs::OsRng;
+        let ptext = expect_ready(message_decrypt_prekey(
+            &message,
+            &protocol_address,
+            &mut session_store,
+            &mut identity_key_store,
+            &mut prekey_store,
+            &mut signed_prekey_store,
+            &mut csprng,
+            Some(ctx),
+        ))?;
+
+        // Report the version of the session that was actually stored, not the message's header.
+        let record = expect_ready(session_store.load_session(&protocol_address, Some(ctx)))?
+            .ok_or_else(|| SignalProtocolError::SessionNotFound(protocol_address.to_string()))?;
+        write_uint32_to(session_version, record.session_version())?;
+        write_bytearray_to(result, result_len, ptext)
+    })
+}
+
 type LoadSenderKey = extern "C" fn(
     store_ctx: *mut c_void,
     *mut *mut SenderKeyRecord,
@@ -850,6 +1001,31 @@ pub unsafe extern "C" fn signal_group_decrypt_message(
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn signal_group_decrypt_batch(
+    out: *mut *const c_uchar,
+    out_len: *mut size_t,
+    sender_key_name: *const SenderKeyName,
+    messages: *const c_uchar,
+    messages_len: size_t,
+    store: *const FfiSenderKeyStoreStruct,
+    ctx: *mut c_void,
+) -> *mut SignalFfiError {
+    run_ffi_safe(|| {
+        let sender_key_name = native_handle_cast::<SenderKeyName>(sender_key_name)?;
+        let messages = split_length_prefixed_records(as_slice(messages, messages_len)?)?;
+        let mut sender_key_store = FfiSenderKeyStore::new(store)?;
+
+        let results = expect_ready(group_decrypt_batch(
+            &messages,
+            &mut sender_key_store,
+            &sender_key_name,
+            Some(ctx),
+        ))?;
+        write_bytearray_to(out, out_len, encode_batch_decrypt_results(results))
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn signal_sealed_session_cipher_encrypt(
     out: *mut *const c_uchar,
@@ -885,6 +1061,37 @@ pub unsafe extern "C" fn signal_sealed_session_cipher_encrypt(
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn signal_sealed_session_cipher_encrypt_from_usmc(
+    out: *mut *const c_uchar,
+    out_len: *mut size_t,
+    destination: *const ProtocolAddress,
+    content: *const UnidentifiedSenderMessageContent,
+    session_store: *const FfiSessionStoreStruct,
+    identity_key_store: *const FfiIdentityKeyStoreStruct,
+    ctx: *mut c_void,
+) -> *mut SignalFfiError {
+    run_ffi_safe(|| {
+        let destination = native_handle_cast::<ProtocolAddress>(destination)?;
+        let content = native_handle_cast::<UnidentifiedSenderMessageContent>(content)?;
+
+        let mut identity_store = FfiIdentityKeyStore::new(identity_key_store)?;
+        let mut session_store = FfiSessionStore::new(session_store)?;
+
+        let mut rng = rand::rngs::OsRng;
+
+        let ctext = expect_ready(sealed_sender_encrypt_from_usmc(
+            destination,
+            content,
+            &mut session_store,
+            &mut identity_store,
+            Some(ctx),
+            &mut rng,
+        ))?;
+        write_bytearray_to(out, out_len, ctext)
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn signal_sealed_session_cipher_decrypt_to_usmc(
     out: *mut *mut UnidentifiedSenderMessageContent,
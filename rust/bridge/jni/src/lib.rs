@@ -5,9 +5,10 @@
 
 #![allow(clippy::missing_safety_doc)]
 
+use aes_gcm_siv::Aes256GcmSiv;
 use async_trait::async_trait;
-use jni::objects::{JClass, JObject, JValue};
-use jni::sys::{jbyteArray, jlongArray, jobject};
+use jni::objects::{JByteBuffer, JClass, JObject, JString, JValue};
+use jni::sys::{jbyte, jbyteArray, jint, jintArray, jlongArray, jobject};
 use jni::JNIEnv;
 use std::convert::TryFrom;
 
@@ -25,6 +26,7 @@ type JavaPreKeyStore = jobject;
 type JavaSignedPreKeyStore = jobject;
 type JavaCiphertextMessage = jobject;
 type JavaSenderKeyStore = jobject;
+type JavaByteBuffer = jobject;
 
 #[no_mangle]
 pub unsafe extern "C" fn Java_org_signal_client_internal_Native_IdentityKeyPair_1Deserialize(
@@ -46,6 +48,49 @@ pub unsafe extern "C" fn Java_org_signal_client_internal_Native_IdentityKeyPair_
     })
 }
 
+/// Encrypts the whole of the direct ByteBuffer `buffer` in place, so large plaintexts are never
+/// copied across JNI, and writes the tag to `tag_out`.
+#[no_mangle]
+pub unsafe extern "C" fn Java_org_signal_client_internal_Native_Aes256GcmSiv_1EncryptInPlace(
+    env: JNIEnv,
+    _class: JClass,
+    aes_gcm_siv: ObjectHandle,
+    buffer: JavaByteBuffer,
+    tag_out: jbyteArray,
+    nonce: jbyteArray,
+    associated_data: jbyteArray,
+) {
+    run_ffi_safe(&env, || {
+        check_distinct_arrays(&env, tag_out, nonce)?;
+        check_distinct_arrays(&env, tag_out, associated_data)?;
+
+        let aes_gcm_siv = native_handle_cast::<Aes256GcmSiv>(aes_gcm_siv)?;
+        let tag_len = env.get_array_length(tag_out)?;
+        if tag_len as usize != aes_gcm_siv::TAG_SIZE {
+            return Err(SignalProtocolError::InvalidArgument(format!(
+                "tag buffer must be {} bytes, got {}",
+                aes_gcm_siv::TAG_SIZE,
+                tag_len
+            ))
+            .into());
+        }
+        let nonce = env.convert_byte_array(nonce)?;
+        let associated_data = env.convert_byte_array(associated_data)?;
+        let buffer = env
+            .get_direct_buffer_address(JByteBuffer::from(buffer))
+            .map_err(|_| {
+                SignalProtocolError::InvalidArgument(
+                    "buffer must be a direct ByteBuffer".to_owned(),
+                )
+            })?;
+
+        let tag = aes_gcm_siv.encrypt(buffer, &nonce, &associated_data)?;
+        let tag: Vec<jbyte> = tag.iter().map(|&b| b as jbyte).collect();
+        env.set_byte_array_region(tag_out, 0, &tag)?;
+        Ok(())
+    })
+}
+
 /* SenderKeyName */
 
 fn sender_key_name_to_jobject<'a>(
@@ -516,6 +561,66 @@ impl<'a> JniSessionStore<'a> {
         )?;
         Ok(())
     }
+
+    fn do_get_device_ids_for_name(&self, name: &str) -> Result<Vec<u32>, SignalJniError> {
+        let name_jobject = self.env.new_string(name)?;
+
+        let callback_sig = "(Ljava/lang/String;)Ljava/util/List;";
+        let callback_args = [name_jobject.into()];
+        let list = match call_method_checked(
+            self.env,
+            self.store,
+            "getSubDeviceSessions",
+            callback_sig,
+            &callback_args,
+        )? {
+            JValue::Object(o) if !o.is_null() => o,
+            JValue::Object(_) => return Ok(vec![]),
+            rvalue => {
+                return Err(SignalJniError::UnexpectedJniResultType(
+                    "getSubDeviceSessions",
+                    rvalue.type_name(),
+                ))
+            }
+        };
+
+        let len = call_method_checked(self.env, list, "size", "()I", &[])?.i()?;
+        let mut device_ids = Vec::with_capacity(len as usize);
+        for i in 0..len {
+            let device_id = call_method_checked(
+                self.env,
+                list,
+                "get",
+                "(I)Ljava/lang/Object;",
+                &[JValue::Int(i)],
+            )?
+            .l()?;
+            let device_id =
+                call_method_checked(self.env, device_id, "intValue", "()I", &[])?.i()?;
+            device_ids.push(device_id as u32);
+        }
+
+        // Java's getSubDeviceSessions leaves out the primary device.
+        if !device_ids.contains(&1) {
+            let address_jobject =
+                protocol_address_to_jobject(self.env, &ProtocolAddress::new(name.to_string(), 1))?;
+            let callback_sig = "(Lorg/whispersystems/libsignal/SignalProtocolAddress;)Z";
+            let callback_args = [address_jobject.into()];
+            if call_method_checked(
+                self.env,
+                self.store,
+                "containsSession",
+                callback_sig,
+                &callback_args,
+            )?
+            .z()?
+            {
+                device_ids.push(1);
+            }
+        }
+        device_ids.sort_unstable();
+        Ok(device_ids)
+    }
 }
 
 #[async_trait(?Send)]
@@ -536,6 +641,54 @@ impl<'a> SessionStore for JniSessionStore<'a> {
     ) -> Result<(), SignalProtocolError> {
         Ok(self.do_store_session(address, record)?)
     }
+
+    async fn get_device_ids_for_name(
+        &self,
+        name: &str,
+        _ctx: Context,
+    ) -> Result<Vec<u32>, SignalProtocolError> {
+        Ok(self.do_get_device_ids_for_name(name)?)
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Java_org_signal_client_internal_Native_SessionStore_1GetDeviceIdsForName(
+    env: JNIEnv,
+    _class: JClass,
+    name: JString,
+    session_store: JavaSessionStore,
+) -> jintArray {
+    run_ffi_safe(&env, || {
+        let name: String = env.get_string(name)?.into();
+        let session_store = JniSessionStore::new(&env, session_store)?;
+
+        let device_ids = expect_ready(session_store.get_device_ids_for_name(&name, None))?;
+        let device_ids: Vec<jint> = device_ids.into_iter().map(|id| id as jint).collect();
+
+        let result = env.new_int_array(device_ids.len() as i32)?;
+        env.set_int_array_region(result, 0, &device_ids)?;
+        Ok(result)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn Java_org_signal_client_internal_Native_SessionStore_1ArchiveAllSessionsForName(
+    env: JNIEnv,
+    _class: JClass,
+    name: JString,
+    session_store: JavaSessionStore,
+) {
+    run_ffi_safe(&env, || {
+        let name: String = env.get_string(name)?.into();
+        let mut session_store = JniSessionStore::new(&env, session_store)?;
+
+        expect_ready(archive_all_sessions_for_name(
+            &name,
+            &mut session_store,
+            None,
+        ))?;
+        Ok(())
+    })
 }
 
 #[no_mangle]
@@ -859,6 +1012,31 @@ pub unsafe extern "C" fn Java_org_signal_client_internal_Native_GroupCipher_1Dec
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn Java_org_signal_client_internal_Native_GroupCipher_1DecryptBatch(
+    env: JNIEnv,
+    _class: JClass,
+    sender_key_name: ObjectHandle,
+    messages: jbyteArray,
+    store: JavaSenderKeyStore,
+) -> jbyteArray {
+    run_ffi_safe(&env, || {
+        let sender_key_name = native_handle_cast::<SenderKeyName>(sender_key_name)?;
+        let messages = env.convert_byte_array(messages)?;
+        let messages = split_length_prefixed_records(&messages)?;
+        let mut sender_key_store = JniSenderKeyStore::new(&env, store)?;
+
+        let results = expect_ready(group_decrypt_batch(
+            &messages,
+            &mut sender_key_store,
+            &sender_key_name,
+            None,
+        ))?;
+
+        to_jbytearray(&env, Ok(encode_batch_decrypt_results(results)))
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn Java_org_signal_client_internal_Native_SealedSessionCipher_1Encrypt(
     env: JNIEnv,
@@ -892,6 +1070,36 @@ pub unsafe extern "C" fn Java_org_signal_client_internal_Native_SealedSessionCip
     })
 }
 
+#[no_mangle]
+pub unsafe extern "C" fn Java_org_signal_client_internal_Native_SealedSessionCipher_1EncryptFromUsmc(
+    env: JNIEnv,
+    _class: JClass,
+    destination: ObjectHandle,
+    content: ObjectHandle,
+    session_store: JavaSessionStore,
+    identity_store: JavaIdentityKeyStore,
+) -> jbyteArray {
+    run_ffi_safe(&env, || {
+        let destination = native_handle_cast::<ProtocolAddress>(destination)?;
+        let content = native_handle_cast::<UnidentifiedSenderMessageContent>(content)?;
+
+        let mut identity_store = JniIdentityKeyStore::new(&env, identity_store)?;
+        let mut session_store = JniSessionStore::new(&env, session_store)?;
+
+        let mut rng = rand::rngs::OsRng;
+
+        let ctext = expect_ready(sealed_sender_encrypt_from_usmc(
+            destination,
+            content,
+            &mut session_store,
+            &mut identity_store,
+            None,
+            &mut rng,
+        ))?;
+        to_jbytearray(&env, Ok(ctext))
+    })
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn Java_org_signal_client_internal_Native_SealedSessionCipher_1DecryptToUsmc(
     env: JNIEnv,
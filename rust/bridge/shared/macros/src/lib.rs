@@ -206,6 +206,30 @@ fn jni_bridge_fn(name: String, sig: &Signature, result_kind: ResultKind) -> Toke
         })
         .unzip3();
 
+    // A `&mut [u8]` argument backed by the same Java array as any other byte slice argument would
+    // alias it.
+    let byte_slices: Vec<(&Ident, bool)> = sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(PatType {
+                pat: box Pat::Ident(name),
+                ty,
+                ..
+            }) => byte_slice_mutability(ty).map(|is_mut| (&name.ident, is_mut)),
+            _ => None,
+        })
+        .collect();
+    let alias_checks = byte_slices
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (a, a_is_mut))| {
+            byte_slices[i + 1..]
+                .iter()
+                .filter(move |(_, b_is_mut)| *a_is_mut || *b_is_mut)
+                .map(move |(b, _)| quote!(jni::check_distinct_arrays(&env, #a, #b)?))
+        });
+
     let orig_name = sig.ident.clone();
 
     quote! {
@@ -217,6 +241,7 @@ fn jni_bridge_fn(name: String, sig: &Signature, result_kind: ResultKind) -> Toke
             #(#input_args),*
         ) #output {
             jni::run_ffi_safe(&env, || {
+                #(#alias_checks;)*
                 #(#input_processing);*;
                 jni::ResultTypeInfo::convert_into(#orig_name(#env_arg #(#input_names),*), &env)
             })
@@ -224,6 +249,36 @@ fn jni_bridge_fn(name: String, sig: &Signature, result_kind: ResultKind) -> Toke
     }
 }
 
+/// Returns `Some(true)` for `&mut [u8]`, `Some(false)` for `&[u8]` or `Option<&[u8]>`, and `None`
+/// for any other type.
+fn byte_slice_mutability(ty: &Type) -> Option<bool> {
+    match ty {
+        Type::Reference(TypeReference {
+            mutability,
+            elem: box Type::Slice(TypeSlice { elem, .. }),
+            ..
+        }) if matches!(&**elem, Type::Path(path) if path.path.is_ident("u8")) => {
+            Some(mutability.is_some())
+        }
+        Type::Path(TypePath { path, .. }) => {
+            let segment = path.segments.last()?;
+            if segment.ident != "Option" {
+                return None;
+            }
+            match &segment.arguments {
+                PathArguments::AngleBracketed(args) => match args.args.first()? {
+                    GenericArgument::Type(inner) if byte_slice_mutability(inner) == Some(false) => {
+                        Some(false)
+                    }
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
 fn jni_name_from_ident(ident: &Ident) -> String {
     ident.to_string().replace("_", "_1")
 }
@@ -0,0 +1,148 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+#[derive(Debug)]
+#[repr(C)]
+pub enum SignalErrorCode {
+    UnknownError = 1,
+    InvalidState = 2,
+    InternalError = 3,
+    NullParameter = 4,
+    InvalidArgument = 5,
+    InvalidType = 6,
+    InvalidUtf8String = 7,
+    InsufficientOutputSize = 8,
+
+    ProtobufError = 10,
+
+    InvalidCiphertext = 20,
+    LegacyCiphertextVersion = 21,
+    UnknownCiphertextVersion = 22,
+    UnrecognizedMessageVersion = 23,
+    InvalidMessage = 30,
+    SealedSenderSelfSend = 31,
+    ExpiredCertificate = 32,
+
+    InvalidKey = 40,
+    InvalidSignature = 41,
+
+    FingerprintIdentifierMismatch = 50,
+    FingerprintVersionMismatch = 51,
+    FingerprintParsingError = 52,
+
+    UntrustedIdentity = 60,
+
+    InvalidKeyIdentifier = 70,
+
+    SessionNotFound = 80,
+
+    DuplicatedMessage = 90,
+
+    CallbackError = 100,
+}
+
+impl SignalErrorCode {
+    pub fn from_code(code: u32) -> Option<Self> {
+        match code {
+            1 => Some(SignalErrorCode::UnknownError),
+            2 => Some(SignalErrorCode::InvalidState),
+            3 => Some(SignalErrorCode::InternalError),
+            4 => Some(SignalErrorCode::NullParameter),
+            5 => Some(SignalErrorCode::InvalidArgument),
+            6 => Some(SignalErrorCode::InvalidType),
+            7 => Some(SignalErrorCode::InvalidUtf8String),
+            8 => Some(SignalErrorCode::InsufficientOutputSize),
+            10 => Some(SignalErrorCode::ProtobufError),
+            20 => Some(SignalErrorCode::InvalidCiphertext),
+            21 => Some(SignalErrorCode::LegacyCiphertextVersion),
+            22 => Some(SignalErrorCode::UnknownCiphertextVersion),
+            23 => Some(SignalErrorCode::UnrecognizedMessageVersion),
+            30 => Some(SignalErrorCode::InvalidMessage),
+            31 => Some(SignalErrorCode::SealedSenderSelfSend),
+            32 => Some(SignalErrorCode::ExpiredCertificate),
+            40 => Some(SignalErrorCode::InvalidKey),
+            41 => Some(SignalErrorCode::InvalidSignature),
+            50 => Some(SignalErrorCode::FingerprintIdentifierMismatch),
+            51 => Some(SignalErrorCode::FingerprintVersionMismatch),
+            52 => Some(SignalErrorCode::FingerprintParsingError),
+            60 => Some(SignalErrorCode::UntrustedIdentity),
+            70 => Some(SignalErrorCode::InvalidKeyIdentifier),
+            80 => Some(SignalErrorCode::SessionNotFound),
+            90 => Some(SignalErrorCode::DuplicatedMessage),
+            100 => Some(SignalErrorCode::CallbackError),
+            _ => None,
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            SignalErrorCode::UnknownError => "unknown error",
+            SignalErrorCode::InvalidState => "invalid state",
+            SignalErrorCode::InternalError => "internal error",
+            SignalErrorCode::NullParameter => "null parameter",
+            SignalErrorCode::InvalidArgument => "invalid argument",
+            SignalErrorCode::InvalidType => "invalid type",
+            SignalErrorCode::InvalidUtf8String => "invalid UTF-8 string",
+            SignalErrorCode::InsufficientOutputSize => "insufficient output size",
+            SignalErrorCode::ProtobufError => "protobuf encoding or decoding error",
+            SignalErrorCode::InvalidCiphertext => "invalid ciphertext",
+            SignalErrorCode::LegacyCiphertextVersion => "legacy ciphertext version",
+            SignalErrorCode::UnknownCiphertextVersion => "unknown ciphertext version",
+            SignalErrorCode::UnrecognizedMessageVersion => "unrecognized message version",
+            SignalErrorCode::InvalidMessage => "invalid message",
+            SignalErrorCode::SealedSenderSelfSend => "sealed sender message sent to self",
+            SignalErrorCode::ExpiredCertificate => "expired certificate",
+            SignalErrorCode::InvalidKey => "invalid key",
+            SignalErrorCode::InvalidSignature => "invalid signature",
+            SignalErrorCode::FingerprintIdentifierMismatch => "fingerprint identifier mismatch",
+            SignalErrorCode::FingerprintVersionMismatch => "fingerprint version mismatch",
+            SignalErrorCode::FingerprintParsingError => "fingerprint parsing error",
+            SignalErrorCode::UntrustedIdentity => "untrusted identity",
+            SignalErrorCode::InvalidKeyIdentifier => "invalid key identifier",
+            SignalErrorCode::SessionNotFound => "session not found",
+            SignalErrorCode::DuplicatedMessage => "duplicated message",
+            SignalErrorCode::CallbackError => "application callback error",
+        }
+    }
+
+    /// Whether failing to decrypt a message with this error means the session it was sent on
+    /// can't be recovered, so the recipient should archive it and ask the sender to retry.
+    pub fn requires_session_reset(&self) -> bool {
+        match self {
+            SignalErrorCode::InvalidCiphertext
+            | SignalErrorCode::InvalidMessage
+            | SignalErrorCode::InvalidKey
+            | SignalErrorCode::InvalidKeyIdentifier
+            | SignalErrorCode::SessionNotFound => true,
+
+            // Messages we will never be able to read, or that were already read.
+            SignalErrorCode::LegacyCiphertextVersion
+            | SignalErrorCode::UnknownCiphertextVersion
+            | SignalErrorCode::UnrecognizedMessageVersion
+            | SignalErrorCode::SealedSenderSelfSend
+            | SignalErrorCode::DuplicatedMessage => false,
+
+            // The user has to decide whether to trust the new identity first.
+            SignalErrorCode::UntrustedIdentity => false,
+
+            // Local failures that say nothing about the state of the session.
+            SignalErrorCode::UnknownError
+            | SignalErrorCode::InvalidState
+            | SignalErrorCode::InternalError
+            | SignalErrorCode::NullParameter
+            | SignalErrorCode::InvalidArgument
+            | SignalErrorCode::InvalidType
+            | SignalErrorCode::InvalidUtf8String
+            | SignalErrorCode::InsufficientOutputSize
+            | SignalErrorCode::ProtobufError
+            | SignalErrorCode::InvalidSignature
+            | SignalErrorCode::ExpiredCertificate
+            | SignalErrorCode::FingerprintIdentifierMismatch
+            | SignalErrorCode::FingerprintVersionMismatch
+            | SignalErrorCode::FingerprintParsingError
+            | SignalErrorCode::CallbackError => false,
+        }
+    }
+}
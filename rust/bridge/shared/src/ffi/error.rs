@@ -8,6 +8,8 @@ use std::fmt;
 use aes_gcm_siv::Error as AesGcmSivError;
 use libsignal_protocol::*;
 
+use crate::SignalErrorCode;
+
 #[derive(Debug)]
 pub enum SignalFfiError {
     Signal(SignalProtocolError),
@@ -52,3 +54,122 @@ impl From<AesGcmSivError> for SignalFfiError {
         SignalFfiError::AesGcmSiv(e)
     }
 }
+
+impl From<&SignalFfiError> for SignalErrorCode {
+    fn from(err: &SignalFfiError) -> Self {
+        match err {
+            SignalFfiError::NullPointer => SignalErrorCode::NullParameter,
+            SignalFfiError::InvalidType => SignalErrorCode::InvalidType,
+
+            SignalFfiError::UnexpectedPanic(_)
+            | SignalFfiError::Signal(SignalProtocolError::InternalError(_))
+            | SignalFfiError::Signal(SignalProtocolError::FfiBindingError(_))
+            | SignalFfiError::Signal(SignalProtocolError::InvalidChainKeyLength(_))
+            | SignalFfiError::Signal(SignalProtocolError::InvalidRootKeyLength(_))
+            | SignalFfiError::Signal(SignalProtocolError::InvalidCipherCryptographicParameters(
+                _,
+                _,
+            ))
+            | SignalFfiError::Signal(SignalProtocolError::InvalidMacKeyLength(_)) => {
+                SignalErrorCode::InternalError
+            }
+
+            SignalFfiError::InvalidUtf8String => SignalErrorCode::InvalidUtf8String,
+            SignalFfiError::InsufficientOutputSize(_, _) => SignalErrorCode::InsufficientOutputSize,
+
+            SignalFfiError::Signal(SignalProtocolError::ProtobufEncodingError(_))
+            | SignalFfiError::Signal(SignalProtocolError::ProtobufDecodingError(_)) => {
+                SignalErrorCode::ProtobufError
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::DuplicatedMessage(_, _)) => {
+                SignalErrorCode::DuplicatedMessage
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::InvalidPreKeyId)
+            | SignalFfiError::Signal(SignalProtocolError::InvalidSignedPreKeyId)
+            | SignalFfiError::Signal(SignalProtocolError::InvalidSenderKeyId) => {
+                SignalErrorCode::InvalidKeyIdentifier
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::SealedSenderSelfSend) => {
+                SignalErrorCode::SealedSenderSelfSend
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::ExpiredCertificate(_)) => {
+                SignalErrorCode::ExpiredCertificate
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::SignatureValidationFailed) => {
+                SignalErrorCode::InvalidSignature
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::NoKeyTypeIdentifier)
+            | SignalFfiError::Signal(SignalProtocolError::BadKeyType(_))
+            | SignalFfiError::Signal(SignalProtocolError::BadKeyLength(_, _))
+            | SignalFfiError::Signal(SignalProtocolError::LowOrderPublicKey)
+            | SignalFfiError::AesGcmSiv(AesGcmSivError::InvalidKeySize) => {
+                SignalErrorCode::InvalidKey
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::SessionNotFound(_)) => {
+                SignalErrorCode::SessionNotFound
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::FingerprintIdentifierMismatch) => {
+                SignalErrorCode::FingerprintIdentifierMismatch
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::FingerprintParsingError) => {
+                SignalErrorCode::FingerprintParsingError
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::FingerprintVersionMismatch(_, _)) => {
+                SignalErrorCode::FingerprintVersionMismatch
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::CiphertextMessageTooShort(_))
+            | SignalFfiError::Signal(SignalProtocolError::InvalidCiphertext)
+            | SignalFfiError::AesGcmSiv(AesGcmSivError::InvalidTag) => {
+                SignalErrorCode::InvalidCiphertext
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::UnrecognizedMessageVersion(_))
+            | SignalFfiError::Signal(SignalProtocolError::UnknownSealedSenderVersion(_)) => {
+                SignalErrorCode::UnrecognizedMessageVersion
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::UnrecognizedCiphertextVersion(_)) => {
+                SignalErrorCode::UnknownCiphertextVersion
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::InvalidMessage(_))
+            | SignalFfiError::Signal(SignalProtocolError::MessageDecryptionFailed(_))
+            | SignalFfiError::Signal(SignalProtocolError::InvalidProtobufEncoding)
+            | SignalFfiError::Signal(SignalProtocolError::InvalidSealedSenderMessage(_)) => {
+                SignalErrorCode::InvalidMessage
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::LegacyCiphertextVersion(_)) => {
+                SignalErrorCode::LegacyCiphertextVersion
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::UntrustedIdentity(_)) => {
+                SignalErrorCode::UntrustedIdentity
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::InvalidState(_, _))
+            | SignalFfiError::Signal(SignalProtocolError::NoSenderKeyState)
+            | SignalFfiError::Signal(SignalProtocolError::InvalidSessionStructure) => {
+                SignalErrorCode::InvalidState
+            }
+
+            SignalFfiError::Signal(SignalProtocolError::InvalidArgument(_))
+            | SignalFfiError::AesGcmSiv(_) => SignalErrorCode::InvalidArgument,
+
+            SignalFfiError::Signal(SignalProtocolError::ApplicationCallbackError(_, _)) => {
+                SignalErrorCode::CallbackError
+            }
+        }
+    }
+}
@@ -14,7 +14,9 @@ pub(crate) use convert::*;
 mod error;
 pub use error::*;
 
-pub use crate::support::expect_ready;
+pub use crate::support::{
+    encode_batch_decrypt_results, expect_ready, split_length_prefixed_records,
+};
 
 pub fn run_ffi_safe<F: FnOnce() -> Result<(), SignalFfiError> + std::panic::UnwindSafe>(
     f: F,
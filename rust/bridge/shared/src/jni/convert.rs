@@ -121,6 +121,27 @@ impl<'a> ArgTypeInfo<'a> for &'a [u8] {
     }
 }
 
+impl<'a> ArgTypeInfo<'a> for &'a mut [u8] {
+    type ArgType = jbyteArray;
+    type StoredType = AutoByteSlice<'a>;
+    fn borrow(env: &'a JNIEnv, foreign: Self::ArgType) -> Result<Self::StoredType, SignalJniError> {
+        let len = env.get_array_length(foreign)?;
+        assert!(len >= 0);
+        Ok(AutoByteSlice {
+            jni_array: env.get_auto_byte_array_elements(foreign, ReleaseMode::CopyBack)?,
+            len: len as usize,
+        })
+    }
+    fn load_from(
+        _env: &'a JNIEnv,
+        stored: &mut Self::StoredType,
+    ) -> Result<&'a mut [u8], SignalJniError> {
+        Ok(unsafe {
+            std::slice::from_raw_parts_mut(stored.jni_array.as_ptr() as *mut u8, stored.len)
+        })
+    }
+}
+
 impl<'a> ArgTypeInfo<'a> for Option<&'a [u8]> {
     type ArgType = jbyteArray;
     type StoredType = Option<AutoByteSlice<'a>>;
@@ -367,6 +388,9 @@ macro_rules! jni_arg_type {
     (Option<&[u8]>) => {
         jni::jbyteArray
     };
+    (&mut [u8]) => {
+        jni::jbyteArray
+    };
     (& $typ:ty) => {
         jni::ObjectHandle
     };
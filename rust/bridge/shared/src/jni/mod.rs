@@ -22,7 +22,9 @@ pub(crate) use convert::*;
 mod error;
 pub use error::*;
 
-pub use crate::support::expect_ready;
+pub use crate::support::{
+    encode_batch_decrypt_results, expect_ready, split_length_prefixed_records,
+};
 
 pub type ObjectHandle = jlong;
 
@@ -95,6 +97,8 @@ fn throw_error(env: &JNIEnv, error: SignalJniError) {
 
         SignalJniError::Signal(SignalProtocolError::InvalidArgument(_))
         | SignalJniError::AesGcmSiv(AesGcmSivError::InvalidInputSize)
+        | SignalJniError::AesGcmSiv(AesGcmSivError::PlaintextTooLarge)
+        | SignalJniError::AesGcmSiv(AesGcmSivError::AssociatedDataTooLarge)
         | SignalJniError::AesGcmSiv(AesGcmSivError::InvalidNonceSize) => {
             "java/lang/IllegalArgumentException"
         }
@@ -130,6 +134,7 @@ fn throw_error(env: &JNIEnv, error: SignalJniError) {
         | SignalJniError::Signal(SignalProtocolError::SignatureValidationFailed)
         | SignalJniError::Signal(SignalProtocolError::BadKeyType(_))
         | SignalJniError::Signal(SignalProtocolError::BadKeyLength(_, _))
+        | SignalJniError::Signal(SignalProtocolError::LowOrderPublicKey)
         | SignalJniError::AesGcmSiv(AesGcmSivError::InvalidKeySize) => {
             "org/whispersystems/libsignal/InvalidKeyException"
         }
@@ -163,6 +168,10 @@ fn throw_error(env: &JNIEnv, error: SignalJniError) {
             "org/signal/libsignal/metadata/SelfSendException"
         }
 
+        SignalJniError::Signal(SignalProtocolError::ExpiredCertificate(_)) => {
+            "org/signal/libsignal/metadata/certificate/ExpiredCertificateException"
+        }
+
         SignalJniError::Signal(SignalProtocolError::UntrustedIdentity(_))
         | SignalJniError::Signal(SignalProtocolError::FingerprintVersionMismatch(_, _)) => {
             unreachable!("already handled in prior match")
@@ -284,6 +293,22 @@ pub fn to_jbytearray<T: AsRef<[u8]>>(
     Ok(env.byte_array_from_slice(data)?)
 }
 
+/// Fails if `a` and `b` are the same Java array, which would let a `&mut [u8]` argument alias
+/// another byte slice argument. Null arrays are left for the argument conversion to reject.
+pub fn check_distinct_arrays(
+    env: &JNIEnv,
+    a: jbyteArray,
+    b: jbyteArray,
+) -> Result<(), SignalJniError> {
+    if !a.is_null() && env.is_same_object(a, b)? {
+        return Err(SignalProtocolError::InvalidArgument(
+            "mutable buffer arguments must not share an array with other arguments".to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
 pub fn call_method_checked<'a>(
     env: &JNIEnv<'a>,
     obj: impl Into<JObject<'a>>,
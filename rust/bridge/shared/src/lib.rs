@@ -5,11 +5,22 @@
 
 #![allow(clippy::missing_safety_doc)]
 
+use aes::Aes256;
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::{Aead, NewAead};
+use aes_gcm::Aes256Gcm;
 use aes_gcm_siv::Aes256GcmSiv;
+use block_modes::block_padding::{NoPadding, Pkcs7};
+use block_modes::{BlockMode, Cbc};
+use hmac::{Hmac, Mac, NewMac};
 use libsignal_bridge_macros::*;
 use libsignal_protocol::*;
+use rand::RngCore;
+use sha2::{Digest, Sha256, Sha512};
 use static_assertions::const_assert_eq;
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use subtle::{Choice, ConstantTimeEq};
 
 #[cfg(not(any(feature = "ffi", feature = "jni", feature = "node")))]
 compile_error!("Feature \"ffi\", \"jni\", or \"node\" must be enabled for this crate.");
@@ -30,9 +41,16 @@ pub mod node;
 mod support;
 use support::*;
 
-bridge_handle!(Aes256GcmSiv, clone = false);
-bridge_handle!(CiphertextMessage, clone = false, jni = false);
+mod error_code;
+pub use error_code::SignalErrorCode;
+
+bridge_handle!(Aes256GcmSiv, clone = false, mut = true);
+bridge_handle!(CiphertextMessage, clone = false);
+bridge_handle!(CryptographicMac, mut = true);
+bridge_handle!(DecryptPlan, clone = false, node = false);
 bridge_handle!(Fingerprint, jni = NumericFingerprintGenerator);
+bridge_handle!(IdentityKeyPair, ffi = false, jni = false);
+bridge_handle!(KeyPair);
 bridge_handle!(PreKeyBundle);
 bridge_handle!(PreKeyRecord);
 bridge_handle!(PreKeySignalMessage);
@@ -48,7 +66,7 @@ bridge_handle!(ServerCertificate);
 bridge_handle!(SessionRecord, mut = true);
 bridge_handle!(SignalMessage, ffi = message);
 bridge_handle!(SignedPreKeyRecord);
-bridge_handle!(UnidentifiedSenderMessage, ffi = false, node = false);
+bridge_handle!(UnidentifiedSenderMessage, clone = false);
 bridge_handle!(UnidentifiedSenderMessageContent, clone = false);
 
 #[bridge_fn(ffi = false)]
@@ -71,6 +89,45 @@ fn HKDF_DeriveSecrets(
     })
 }
 
+/// Derives several secrets from one input key material, with a single extract step.
+///
+/// `labels` holds the labels, each preceded by its length as a big-endian u32, and `lengths`
+/// holds the matching output lengths, each a big-endian u32. The outputs are returned in the same
+/// order, each preceded by its length as a big-endian u32.
+#[bridge_fn_buffer(ffi = false)]
+fn HKDF_DeriveMany<E: Env>(
+    env: E,
+    version: u32,
+    ikm: &[u8],
+    salt: Option<&[u8]>,
+    labels: &[u8],
+    lengths: &[u8],
+) -> Result<E::Buffer, SignalProtocolError> {
+    let kdf = HKDF::new(version)?;
+
+    let labels = split_length_prefixed_records(labels)?;
+    if lengths.len() % 4 != 0 {
+        return Err(SignalProtocolError::InvalidArgument(
+            "truncated output length".to_owned(),
+        ));
+    }
+    let lengths: Vec<usize> = lengths
+        .chunks_exact(4)
+        .map(|len| u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize)
+        .collect();
+
+    // The same all-zero default salt as HKDF::derive_secrets.
+    let salt = salt.unwrap_or(&[0u8; 32]);
+    let outputs = kdf.derive_salted_secrets_many(ikm, salt, &labels, &lengths)?;
+
+    let mut result = Vec::with_capacity(outputs.iter().map(|o| 4 + o.len()).sum());
+    for output in outputs {
+        result.extend_from_slice(&(output.len() as u32).to_be_bytes());
+        result.extend_from_slice(&output);
+    }
+    Ok(env.buffer(result))
+}
+
 // Alternate implementation to fill an existing buffer.
 #[bridge_fn_void(jni = false, node = false)]
 fn HKDF_Derive(
@@ -86,6 +143,68 @@ fn HKDF_Derive(
     Ok(())
 }
 
+#[bridge_fn_buffer]
+fn Sha256_Digest<E: Env>(env: E, input: &[u8]) -> Result<E::Buffer, SignalProtocolError> {
+    Ok(env.buffer(&Sha256::digest(input)[..]))
+}
+
+#[bridge_fn_buffer]
+fn Sha512_Digest<E: Env>(env: E, input: &[u8]) -> Result<E::Buffer, SignalProtocolError> {
+    Ok(env.buffer(&Sha512::digest(input)[..]))
+}
+
+/// Compares two byte strings without revealing where they differ.
+///
+/// Only the lengths are compared early; inputs of equal length are always compared in full, so
+/// this is safe to use on MACs, tags, and other secrets.
+#[bridge_fn]
+fn ConstantTimeEqual(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// An HMAC-SHA256 computation that accepts its input incrementally.
+///
+/// Once finalized, the MAC cannot be updated or finalized again.
+#[derive(Clone)]
+pub struct CryptographicMac {
+    hmac: Option<Hmac<Sha256>>,
+}
+
+fn mac_already_finalized(func: &'static str) -> SignalProtocolError {
+    SignalProtocolError::InvalidState(func, "MAC has already been finalized".to_string())
+}
+
+#[bridge_fn]
+fn CryptographicMac_New(key: &[u8]) -> Result<CryptographicMac, SignalProtocolError> {
+    let hmac = Hmac::<Sha256>::new_varkey(key)
+        .map_err(|_| SignalProtocolError::InvalidArgument("invalid HMAC key".to_string()))?;
+    Ok(CryptographicMac { hmac: Some(hmac) })
+}
+
+#[bridge_fn_void]
+fn CryptographicMac_Update(
+    mac: &mut CryptographicMac,
+    input: &[u8],
+) -> Result<(), SignalProtocolError> {
+    mac.hmac
+        .as_mut()
+        .ok_or_else(|| mac_already_finalized("CryptographicMac_Update"))?
+        .update(input);
+    Ok(())
+}
+
+#[bridge_fn_buffer]
+fn CryptographicMac_Finalize<E: Env>(
+    env: E,
+    mac: &mut CryptographicMac,
+) -> Result<E::Buffer, SignalProtocolError> {
+    let hmac = mac
+        .hmac
+        .take()
+        .ok_or_else(|| mac_already_finalized("CryptographicMac_Finalize"))?;
+    Ok(env.buffer(&hmac.finalize().into_bytes()[..]))
+}
+
 #[bridge_fn(ffi = "address_new")]
 fn ProtocolAddress_New(name: String, device_id: u32) -> ProtocolAddress {
     ProtocolAddress::new(name, device_id)
@@ -111,6 +230,16 @@ bridge_get_bytearray!(
 bridge_get!(ProtocolAddress::device_id as DeviceId -> u32, ffi = "address_get_device_id");
 bridge_get!(ProtocolAddress::name as Name -> &str, ffi = "address_get_name");
 
+#[bridge_fn(ffi = "address_from_string")]
+fn ProtocolAddress_FromString(s: String) -> Result<ProtocolAddress, SignalProtocolError> {
+    ProtocolAddress::from_canonical_string(&s)
+}
+
+#[bridge_fn(ffi = "address_to_string")]
+fn ProtocolAddress_ToString(addr: &ProtocolAddress) -> String {
+    addr.to_string()
+}
+
 #[bridge_fn(ffi = "publickey_compare", node = "PublicKey_Compare")]
 fn ECPublicKey_Compare(key1: &PublicKey, key2: &PublicKey) -> i32 {
     match key1.cmp(&key2) {
@@ -120,6 +249,11 @@ fn ECPublicKey_Compare(key1: &PublicKey, key2: &PublicKey) -> i32 {
     }
 }
 
+#[bridge_fn(ffi = "publickey_equals", node = "PublicKey_Equals")]
+fn ECPublicKey_Equals(key1: &PublicKey, key2: &PublicKey) -> bool {
+    key1 == key2
+}
+
 #[bridge_fn(ffi = "publickey_verify", node = "PublicKey_Verify")]
 fn ECPublicKey_Verify(
     key: &PublicKey,
@@ -129,6 +263,16 @@ fn ECPublicKey_Verify(
     key.verify_signature(&message, &signature)
 }
 
+#[bridge_fn(ffi = "publickey_is_valid", node = "PublicKey_IsValid")]
+fn ECPublicKey_IsValid(data: &[u8]) -> bool {
+    PublicKey::deserialize(data).is_ok()
+}
+
+#[bridge_fn(ffi = "publickey_is_weak", node = "PublicKey_IsWeak")]
+fn ECPublicKey_IsWeak(key: &PublicKey) -> bool {
+    key.is_weak()
+}
+
 bridge_deserialize!(
     PrivateKey::deserialize,
     ffi = privatekey,
@@ -148,6 +292,34 @@ fn ECPrivateKey_Generate() -> PrivateKey {
     keypair.private_key
 }
 
+/// Generates both halves at once, so the public key is only derived once.
+#[bridge_fn]
+fn KeyPair_Generate() -> KeyPair {
+    let mut rng = rand::rngs::OsRng;
+    KeyPair::generate(&mut rng)
+}
+
+#[bridge_fn]
+fn KeyPair_GetPublicKey(key_pair: &KeyPair) -> PublicKey {
+    key_pair.public_key
+}
+
+#[bridge_fn]
+fn KeyPair_GetPrivateKey(key_pair: &KeyPair) -> PrivateKey {
+    key_pair.private_key
+}
+
+/// Produces the same bytes as IdentityKeyPair_Serialize given the two halves of `key_pair`.
+#[bridge_fn_buffer]
+fn KeyPair_SerializeAsIdentityKeyPair<T: Env>(
+    env: T,
+    key_pair: &KeyPair,
+) -> Result<T::Buffer, SignalProtocolError> {
+    let identity_key_pair =
+        IdentityKeyPair::new(IdentityKey::new(key_pair.public_key), key_pair.private_key);
+    Ok(env.buffer(identity_key_pair.serialize().into_vec()))
+}
+
 #[bridge_fn(ffi = "privatekey_get_public_key", node = "PrivateKey_GetPublicKey")]
 fn ECPrivateKey_GetPublicKey(k: &PrivateKey) -> Result<PublicKey, SignalProtocolError> {
     k.public_key()
@@ -164,6 +336,82 @@ fn ECPrivateKey_Sign<T: Env>(
     Ok(env.buffer(sig.into_vec()))
 }
 
+// For testing only
+#[cfg(feature = "testing")]
+#[bridge_fn_buffer(ffi = false, node = false)]
+fn ECPrivateKey_SignDeterministic<T: Env>(
+    env: T,
+    key: &PrivateKey,
+    message: &[u8],
+    random: &[u8],
+) -> Result<T::Buffer, SignalProtocolError> {
+    const SIGNATURE_RANDOM_LENGTH: usize = 64;
+    if random.len() != SIGNATURE_RANDOM_LENGTH {
+        return Err(SignalProtocolError::InvalidArgument(format!(
+            "signature randomness must be {} bytes, got {}",
+            SIGNATURE_RANDOM_LENGTH,
+            random.len()
+        )));
+    }
+
+    /// Hands out caller-provided bytes in place of real randomness, so signatures can be
+    /// reproduced.
+    struct FixedRandomness<'a>(&'a [u8]);
+
+    impl RngCore for FixedRandomness<'_> {
+        fn next_u32(&mut self) -> u32 {
+            let mut bytes = [0u8; 4];
+            self.fill_bytes(&mut bytes);
+            u32::from_le_bytes(bytes)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            let mut bytes = [0u8; 8];
+            self.fill_bytes(&mut bytes);
+            u64::from_le_bytes(bytes)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            let (head, rest) = self.0.split_at(dest.len());
+            dest.copy_from_slice(head);
+            self.0 = rest;
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl rand::CryptoRng for FixedRandomness<'_> {}
+
+    let sig = key.calculate_signature(&message, &mut FixedRandomness(random))?;
+    Ok(env.buffer(sig.into_vec()))
+}
+
+/// Signs the canonical encoding of a list of fields: the number of fields as a big-endian u32,
+/// then each field preceded by its length as a big-endian u32.
+///
+/// `fields` holds the fields, each preceded by its length as a big-endian u32.
+#[bridge_fn_buffer(ffi = "privatekey_sign_canonical", node = "PrivateKey_SignCanonical")]
+fn ECPrivateKey_SignCanonical<T: Env>(
+    env: T,
+    key: &PrivateKey,
+    fields: &[u8],
+) -> Result<T::Buffer, SignalProtocolError> {
+    let fields = split_length_prefixed_records(fields)?;
+    let mut message = Vec::with_capacity(4 + fields.iter().map(|f| 4 + f.len()).sum::<usize>());
+    message.extend_from_slice(&(fields.len() as u32).to_be_bytes());
+    for field in fields {
+        message.extend_from_slice(&(field.len() as u32).to_be_bytes());
+        message.extend_from_slice(field);
+    }
+
+    let mut rng = rand::rngs::OsRng;
+    let sig = key.calculate_signature(&message, &mut rng)?;
+    Ok(env.buffer(sig.into_vec()))
+}
+
 #[bridge_fn_buffer(ffi = "privatekey_agree", node = "PrivateKey_Agree")]
 fn ECPrivateKey_Agree<T: Env>(
     env: T,
@@ -174,6 +422,29 @@ fn ECPrivateKey_Agree<T: Env>(
     Ok(env.buffer(dh_secret.into_vec()))
 }
 
+#[bridge_fn_buffer(ffi = "privatekey_agree_validated", node = "PrivateKey_AgreeValidated")]
+fn ECPrivateKey_AgreeValidated<T: Env>(
+    env: T,
+    private_key: &PrivateKey,
+    public_key: &PublicKey,
+) -> Result<T::Buffer, SignalProtocolError> {
+    let dh_secret = private_key.calculate_agreement_validated(&public_key)?;
+    Ok(env.buffer(dh_secret.into_vec()))
+}
+
+// FFI and JNI deserialize into separate key handles by hand; see signal_identitykeypair_deserialize.
+bridge_deserialize!(IdentityKeyPair::try_from, ffi = false, jni = false);
+
+#[bridge_fn(ffi = false, jni = false)]
+fn IdentityKeyPair_GetPublicKey(key_pair: &IdentityKeyPair) -> PublicKey {
+    *key_pair.public_key()
+}
+
+#[bridge_fn(ffi = false, jni = false)]
+fn IdentityKeyPair_GetPrivateKey(key_pair: &IdentityKeyPair) -> PrivateKey {
+    *key_pair.private_key()
+}
+
 #[bridge_fn_buffer(ffi = "identitykeypair_serialize")]
 fn IdentityKeyPair_Serialize<T: Env>(
     env: T,
@@ -184,7 +455,45 @@ fn IdentityKeyPair_Serialize<T: Env>(
     Ok(env.buffer(identity_key_pair.serialize().into_vec()))
 }
 
-#[bridge_fn(jni = false)]
+#[bridge_fn_buffer(ffi = "identitykeypair_sign_alternate_identity")]
+fn IdentityKeyPair_SignAlternateIdentity<T: Env>(
+    env: T,
+    public_key: &PublicKey,
+    private_key: &PrivateKey,
+    other_identity: &PublicKey,
+) -> Result<T::Buffer, SignalProtocolError> {
+    let identity_key_pair = IdentityKeyPair::new(IdentityKey::new(*public_key), *private_key);
+    let other_identity = IdentityKey::new(*other_identity);
+    let signature =
+        identity_key_pair.sign_alternate_identity(&other_identity, &mut rand::rngs::OsRng)?;
+    Ok(env.buffer(signature.into_vec()))
+}
+
+#[bridge_fn(ffi = "identitykey_verify_alternate_identity_signature")]
+fn IdentityKey_VerifyAlternateIdentitySignature(
+    identity_key: &PublicKey,
+    other_identity: &PublicKey,
+    signature: &[u8],
+) -> Result<bool, SignalProtocolError> {
+    let identity = IdentityKey::new(*identity_key);
+    let other_identity = IdentityKey::new(*other_identity);
+    identity.verify_alternate_identity(&other_identity, signature)
+}
+
+#[bridge_fn(ffi = "identitykey_to_display_string")]
+fn IdentityKey_ToDisplayString(identity_key: &PublicKey) -> String {
+    IdentityKey::new(*identity_key).display_string()
+}
+
+#[bridge_fn_buffer(ffi = "identitykey_compute_change_hash")]
+fn IdentityKey_ComputeChangeHash<T: Env>(
+    env: T,
+    identity_key: &PublicKey,
+) -> Result<T::Buffer, SignalProtocolError> {
+    Ok(env.buffer(&IdentityKey::new(*identity_key).change_hash()[..]))
+}
+
+#[bridge_fn]
 fn Fingerprint_New(
     iterations: u32,
     version: u32,
@@ -244,8 +553,39 @@ fn ScannableFingerprint_Compare(
     ScannableFingerprint::deserialize(&fprint1)?.compare(fprint2)
 }
 
+#[bridge_fn(jni = false)]
+fn Fingerprint_GetTruncatedDisplayString(
+    fingerprint: &Fingerprint,
+    digits: u32,
+) -> Result<String, SignalProtocolError> {
+    fingerprint.truncated_display_string(digits as usize)
+}
+
+/// Returns false if the scanned payload is for a different pair of identities; a payload from a
+/// different fingerprint version fails with FingerprintVersionMismatch instead.
+#[bridge_fn(jni = false)]
+fn Fingerprint_VerifyQrPayload(
+    fingerprint: &Fingerprint,
+    scanned: &[u8],
+) -> Result<bool, SignalProtocolError> {
+    fingerprint.scannable.compare(scanned)
+}
+
+#[bridge_fn_buffer]
+fn Fingerprint_GetQrCodePayload<E: Env>(
+    env: E,
+    fingerprint: &Fingerprint,
+) -> Result<E::Buffer, SignalProtocolError> {
+    Ok(env.buffer(fingerprint.qr_code_payload()?))
+}
+
+#[bridge_fn]
+fn Fingerprint_VersionsCompatible(ours: u32, theirs: u32) -> bool {
+    ScannableFingerprint::versions_compatible(ours, theirs)
+}
+
 bridge_deserialize!(SignalMessage::try_from, ffi = message);
-bridge_get_bytearray!(GetSenderRatchetKey(SignalMessage), ffi = false, node = false =>
+bridge_get_bytearray!(GetSenderRatchetKey(SignalMessage), ffi = "message_get_sender_ratchet_key_bytes", node = false =>
     |m| Ok(m.sender_ratchet_key().serialize())
 );
 bridge_get_bytearray!(GetBody(SignalMessage), ffi = "message_get_body" =>
@@ -299,6 +639,35 @@ fn SignalMessage_GetSenderRatchetKey(m: &SignalMessage) -> PublicKey {
     *m.sender_ratchet_key()
 }
 
+// For testing only
+#[cfg(feature = "testing")]
+#[bridge_fn(ffi = false, node = false)]
+fn SignalMessage_ReserializeToVersion(
+    msg: &SignalMessage,
+    message_version: u8,
+    mac_key: &[u8],
+    sender_identity_key: &PublicKey,
+    receiver_identity_key: &PublicKey,
+) -> Result<SignalMessage, SignalProtocolError> {
+    if message_version > 0xF {
+        return Err(SignalProtocolError::InvalidArgument(format!(
+            "message version {} does not fit in the version header",
+            message_version
+        )));
+    }
+    // The MAC covers the version header, so the message has to be rebuilt rather than patched.
+    SignalMessage::new(
+        message_version,
+        mac_key,
+        *msg.sender_ratchet_key(),
+        msg.counter(),
+        msg.previous_counter(),
+        msg.body(),
+        &IdentityKey::new(*sender_identity_key),
+        &IdentityKey::new(*receiver_identity_key),
+    )
+}
+
 #[bridge_fn]
 fn PreKeySignalMessage_New(
     message_version: u8,
@@ -335,6 +704,11 @@ fn PreKeySignalMessage_GetSignalMessage(m: &PreKeySignalMessage) -> SignalMessag
     m.message().clone()
 }
 
+#[bridge_fn]
+fn PreKeySignalMessage_UsedOneTimePreKey(m: &PreKeySignalMessage) -> bool {
+    m.pre_key_id().is_some()
+}
+
 bridge_deserialize!(PreKeySignalMessage::try_from);
 bridge_get_bytearray!(Serialize(PreKeySignalMessage), jni = "PreKeySignalMessage_1GetSerialized" =>
     |m| Ok(m.serialized())
@@ -388,6 +762,9 @@ bridge_get_bytearray!(Serialize(SenderKeyDistributionMessage), jni = "SenderKeyD
 );
 bridge_get!(SenderKeyDistributionMessage::id -> u32);
 bridge_get!(SenderKeyDistributionMessage::iteration -> u32);
+bridge_get_bytearray!(DeriveGroupKey(SenderKeyDistributionMessage) =>
+    SenderKeyDistributionMessage::derive_group_key
+);
 
 #[bridge_fn]
 fn SenderKeyDistributionMessage_New(
@@ -399,6 +776,11 @@ fn SenderKeyDistributionMessage_New(
     SenderKeyDistributionMessage::new(key_id, iteration, &chainkey, *pk)
 }
 
+#[bridge_fn]
+fn SenderKeyDistributionMessage_ChainKeyLength() -> u32 {
+    SenderKeyDistributionMessage::CHAIN_KEY_LENGTH as u32
+}
+
 #[bridge_fn(jni = false, node = false)]
 fn SenderKeyDistributionMessage_GetSignatureKey(
     m: &SenderKeyDistributionMessage,
@@ -406,6 +788,14 @@ fn SenderKeyDistributionMessage_GetSignatureKey(
     Ok(*m.signing_key()?)
 }
 
+#[bridge_fn]
+fn GroupCipher_VerifyAgainstDistribution(
+    message: &SenderKeyMessage,
+    distribution: &SenderKeyDistributionMessage,
+) -> Result<bool, SignalProtocolError> {
+    message.verify_signature(distribution.signing_key()?)
+}
+
 #[bridge_fn]
 fn PreKeyBundle_New(
     registration_id: u32,
@@ -440,6 +830,26 @@ fn PreKeyBundle_New(
     )
 }
 
+#[bridge_fn]
+fn PreKeyBundle_NewWithoutOneTimePreKey(
+    registration_id: u32,
+    device_id: u32,
+    signed_prekey_id: u32,
+    signed_prekey: &PublicKey,
+    signed_prekey_signature: &[u8],
+    identity_key: &PublicKey,
+) -> Result<PreKeyBundle, SignalProtocolError> {
+    PreKeyBundle::new(
+        registration_id,
+        device_id,
+        None,
+        signed_prekey_id,
+        *signed_prekey,
+        signed_prekey_signature.to_vec(),
+        IdentityKey::new(*identity_key),
+    )
+}
+
 #[bridge_fn]
 fn PreKeyBundle_GetIdentityKey(p: &PreKeyBundle) -> Result<PublicKey, SignalProtocolError> {
     Ok(*p.identity_key()?.public_key())
@@ -452,6 +862,12 @@ bridge_get!(PreKeyBundle::signed_pre_key_id -> u32);
 bridge_get!(PreKeyBundle::pre_key_id -> Option<u32>);
 bridge_get!(PreKeyBundle::pre_key_public -> Option<PublicKey>);
 bridge_get!(PreKeyBundle::signed_pre_key_public -> PublicKey);
+bridge_get_bytearray!(ToLinkingPayload(PreKeyBundle) => PreKeyBundle::to_linking_payload);
+
+#[bridge_fn]
+fn PreKeyBundle_FromLinkingPayload(payload: &[u8]) -> Result<PreKeyBundle, SignalProtocolError> {
+    PreKeyBundle::from_linking_payload(payload)
+}
 
 bridge_deserialize!(SignedPreKeyRecord::deserialize);
 bridge_get_bytearray!(GetSignature(SignedPreKeyRecord) => SignedPreKeyRecord::signature);
@@ -475,6 +891,51 @@ fn SignedPreKeyRecord_New(
     SignedPreKeyRecord::new(id, timestamp, &keypair, &signature)
 }
 
+#[bridge_fn]
+fn SignedPreKeyRecord_VerifyOwnSignature(
+    record: &SignedPreKeyRecord,
+    identity_key: &PublicKey,
+) -> Result<bool, SignalProtocolError> {
+    identity_key.verify_signature(&record.public_key()?.serialize(), &record.signature()?)
+}
+
+/// Checks that the record's public key is the one derived from its private key.
+#[bridge_fn]
+fn SignedPreKeyRecord_VerifyKeyPair(
+    record: &SignedPreKeyRecord,
+) -> Result<bool, SignalProtocolError> {
+    Ok(record.private_key()?.public_key()? == record.public_key()?)
+}
+
+#[bridge_fn]
+fn SignedPreKeyRecord_NextRotationTime(
+    record: &SignedPreKeyRecord,
+    interval: u64,
+) -> Result<u64, SignalProtocolError> {
+    record.next_rotation_time(interval)
+}
+
+/// `serialized_records` holds one or more serialized records, each preceded by its length as a
+/// big-endian u32.
+#[bridge_fn]
+fn SignedPreKeyRecord_EarliestExpiry(
+    serialized_records: &[u8],
+    max_age: u64,
+) -> Result<u64, SignalProtocolError> {
+    let mut earliest: Option<u64> = None;
+
+    for record in split_length_prefixed_records(serialized_records)? {
+        let expiry = SignedPreKeyRecord::deserialize(record)?
+            .timestamp()?
+            .saturating_add(max_age);
+        earliest = Some(earliest.map_or(expiry, |e| e.min(expiry)));
+    }
+
+    earliest.ok_or_else(|| {
+        SignalProtocolError::InvalidArgument("no signed pre-key records provided".to_owned())
+    })
+}
+
 bridge_deserialize!(PreKeyRecord::deserialize);
 bridge_get_bytearray!(Serialize(PreKeyRecord), jni = "PreKeyRecord_1GetSerialized" =>
     PreKeyRecord::serialize
@@ -483,6 +944,34 @@ bridge_get!(PreKeyRecord::id -> u32);
 bridge_get!(PreKeyRecord::public_key -> PublicKey);
 bridge_get!(PreKeyRecord::private_key -> PrivateKey);
 
+/// Returns the ids (each a big-endian u32, in input order) of every record whose public key also
+/// appears in another record. `serialized_records` is encoded as for
+/// `SignedPreKeyRecord_EarliestExpiry`.
+///
+/// Pre-keys are generated independently, so any shared key points at a broken RNG.
+#[bridge_fn_buffer]
+fn PreKeyRecord_FindDuplicateKeys<E: Env>(
+    env: E,
+    serialized_records: &[u8],
+) -> Result<E::Buffer, SignalProtocolError> {
+    let mut records = vec![];
+    let mut key_counts = HashMap::new();
+    for record in split_length_prefixed_records(serialized_records)? {
+        let record = PreKeyRecord::deserialize(record)?;
+        let public_key = record.public_key()?.serialize();
+        *key_counts.entry(public_key.clone()).or_insert(0) += 1;
+        records.push((record.id()?, public_key));
+    }
+
+    let mut result = vec![];
+    for (id, public_key) in records {
+        if key_counts[&public_key] > 1 {
+            result.extend_from_slice(&id.to_be_bytes());
+        }
+    }
+    Ok(env.buffer(result))
+}
+
 #[bridge_fn]
 fn PreKeyRecord_New(id: u32, pub_key: &PublicKey, priv_key: &PrivateKey) -> PreKeyRecord {
     let keypair = KeyPair::new(*pub_key, *priv_key);
@@ -513,6 +1002,16 @@ fn SenderKeyName_GetSenderDeviceId(skn: &SenderKeyName) -> Result<u32, SignalPro
     Ok(skn.sender()?.device_id())
 }
 
+#[bridge_fn]
+fn SenderKeyName_ToStorageKey(skn: &SenderKeyName) -> String {
+    skn.storage_key()
+}
+
+#[bridge_fn]
+fn SenderKeyName_FromStorageKey(key: String) -> Result<SenderKeyName, SignalProtocolError> {
+    SenderKeyName::from_storage_key(&key)
+}
+
 bridge_deserialize!(SenderKeyRecord::deserialize);
 bridge_get_bytearray!(Serialize(SenderKeyRecord), jni = "SenderKeyRecord_1GetSerialized" =>
     SenderKeyRecord::serialize
@@ -523,6 +1022,32 @@ fn SenderKeyRecord_New() -> SenderKeyRecord {
     SenderKeyRecord::new_empty()
 }
 
+#[bridge_fn]
+fn SenderKeyRecord_CreateDistributionMessage(
+    record: &SenderKeyRecord,
+) -> Result<SenderKeyDistributionMessage, SignalProtocolError> {
+    record.create_distribution_message()
+}
+
+#[bridge_fn]
+fn SenderKeyRecord_MessagesUntilRotation(
+    record: &SenderKeyRecord,
+) -> Result<u32, SignalProtocolError> {
+    record.messages_until_rotation()
+}
+
+// For testing only
+#[cfg(feature = "testing")]
+#[bridge_fn_buffer(ffi = false, node = false)]
+fn SenderKeyRecord_DeriveMessageKeyAt<E: Env>(
+    env: E,
+    record: &SenderKeyRecord,
+    iteration: u32,
+) -> Result<E::Buffer, SignalProtocolError> {
+    let message_key = record.derive_message_key_at(iteration)?;
+    Ok(env.buffer(message_key.seed()?))
+}
+
 bridge_deserialize!(ServerCertificate::deserialize);
 bridge_get_bytearray!(GetSerialized(ServerCertificate) => ServerCertificate::serialized);
 bridge_get_bytearray!(GetCertificate(ServerCertificate) => ServerCertificate::certificate);
@@ -540,6 +1065,14 @@ fn ServerCertificate_New(
     ServerCertificate::new(key_id, *server_key, trust_root, &mut rng)
 }
 
+#[bridge_fn]
+fn ServerCertificate_Verify(
+    cert: &ServerCertificate,
+    trust_root: &PublicKey,
+) -> Result<bool, SignalProtocolError> {
+    cert.validate(trust_root)
+}
+
 bridge_deserialize!(SenderCertificate::deserialize);
 bridge_get_bytearray!(GetSerialized(SenderCertificate) => SenderCertificate::serialized);
 bridge_get_bytearray!(GetCertificate(SenderCertificate) => SenderCertificate::certificate);
@@ -559,6 +1092,39 @@ fn SenderCertificate_Validate(
     cert.validate(key, time)
 }
 
+/// Validates the whole certificate chain against `trust_root`, returning `ExpiredCertificate` or
+/// `SignatureValidationFailed` rather than `false` so callers can tell the failures apart.
+#[bridge_fn]
+fn SenderCertificate_ValidateChain(
+    cert: &SenderCertificate,
+    trust_root: &PublicKey,
+    time: u64,
+) -> Result<bool, SignalProtocolError> {
+    cert.validate_chain(trust_root, time)?;
+    Ok(true)
+}
+
+#[bridge_fn]
+fn SenderCertificate_IsForDevice(
+    cert: &SenderCertificate,
+    device_id: u32,
+) -> Result<bool, SignalProtocolError> {
+    Ok(cert.sender_device_id()? == device_id)
+}
+
+#[bridge_fn]
+fn SenderCertificate_HasValidUuid(cert: &SenderCertificate) -> Result<bool, SignalProtocolError> {
+    cert.has_valid_uuid()
+}
+
+#[bridge_fn]
+fn SenderCertificate_SignerKeyIdEquals(
+    cert: &SenderCertificate,
+    key_id: u32,
+) -> Result<bool, SignalProtocolError> {
+    Ok(cert.signer()?.key_id()? == key_id)
+}
+
 #[bridge_fn]
 fn SenderCertificate_GetServerCertificate(
     cert: &SenderCertificate,
@@ -614,6 +1180,26 @@ fn UnidentifiedSenderMessageContent_GetMsgType(
     Ok(m.msg_type()? as u8)
 }
 
+/// Returns a short name for a message type as returned by GetMsgType, for logging.
+#[bridge_fn]
+fn UnidentifiedSenderMessageContent_GetMsgTypeName(
+    msg_type: u8,
+) -> Result<String, SignalProtocolError> {
+    let name = match msg_type {
+        x if x == FfiCiphertextMessageType::Whisper as u8 => "whisper",
+        x if x == FfiCiphertextMessageType::PreKey as u8 => "prekey",
+        x if x == FfiCiphertextMessageType::SenderKey as u8 => "senderkey",
+        x if x == FfiCiphertextMessageType::SenderKeyDistribution as u8 => "senderkey-distribution",
+        x => {
+            return Err(SignalProtocolError::InvalidArgument(format!(
+                "unknown ciphertext message type {}",
+                x
+            )))
+        }
+    };
+    Ok(name.to_owned())
+}
+
 // For testing only
 #[bridge_fn(ffi = false, node = false)]
 fn UnidentifiedSenderMessageContent_New(
@@ -634,24 +1220,19 @@ fn UnidentifiedSenderMessageContent_New(
     UnidentifiedSenderMessageContent::new(msg_type, sender.clone(), contents.to_owned())
 }
 
-bridge_deserialize!(
-    UnidentifiedSenderMessage::deserialize,
-    ffi = false,
-    node = false
-);
-bridge_get_bytearray!(GetSerialized(UnidentifiedSenderMessage), ffi = false, node = false =>
+bridge_deserialize!(UnidentifiedSenderMessage::deserialize);
+bridge_get_bytearray!(GetSerialized(UnidentifiedSenderMessage) =>
     UnidentifiedSenderMessage::serialized
 );
-bridge_get_bytearray!(GetEncryptedMessage(UnidentifiedSenderMessage), ffi = false, node = false =>
+bridge_get_bytearray!(GetEncryptedMessage(UnidentifiedSenderMessage) =>
     UnidentifiedSenderMessage::encrypted_message
 );
-bridge_get_bytearray!(GetEncryptedStatic(UnidentifiedSenderMessage), ffi = false, node = false =>
+bridge_get_bytearray!(GetEncryptedStatic(UnidentifiedSenderMessage) =>
     UnidentifiedSenderMessage::encrypted_static
 );
-bridge_get!(UnidentifiedSenderMessage::ephemeral_public -> PublicKey, ffi = false, node = false);
+bridge_get!(UnidentifiedSenderMessage::ephemeral_public -> PublicKey);
 
-// For testing only
-#[bridge_fn(ffi = false, node = false)]
+#[bridge_fn]
 fn UnidentifiedSenderMessage_New(
     public_key: &PublicKey,
     encrypted_static: &[u8],
@@ -664,6 +1245,116 @@ fn UnidentifiedSenderMessage_New(
     )
 }
 
+// For testing only
+#[cfg(feature = "testing")]
+#[bridge_fn(ffi = false, node = false)]
+fn TestHarness_SealedSenderRoundTrip(
+    sender_identity_key: &PrivateKey,
+    sender_cert: &SenderCertificate,
+    trust_root: &PublicKey,
+    timestamp: u64,
+    plaintext: &[u8],
+) -> Result<ProtocolAddress, SignalProtocolError> {
+    let mut rng = rand::rngs::OsRng;
+
+    let sender_identity = IdentityKeyPair::new(
+        IdentityKey::new(sender_identity_key.public_key()?),
+        *sender_identity_key,
+    );
+    let mut sender_store = InMemSignalProtocolStore::new(sender_identity, 1)?;
+
+    let recipient_uuid = "00000000-0000-0000-0000-000000000000";
+    let recipient_device_id = 1;
+    let recipient_address = ProtocolAddress::new(recipient_uuid.to_owned(), recipient_device_id);
+    let recipient_identity = IdentityKeyPair::generate(&mut rng);
+    let mut recipient_store = InMemSignalProtocolStore::new(recipient_identity, 2)?;
+
+    let pre_key_id = 1;
+    let pre_key_pair = KeyPair::generate(&mut rng);
+    let signed_pre_key_id = 2;
+    let signed_pre_key_pair = KeyPair::generate(&mut rng);
+    let signed_pre_key_signature = recipient_identity
+        .private_key()
+        .calculate_signature(&signed_pre_key_pair.public_key.serialize(), &mut rng)?;
+
+    let bundle = PreKeyBundle::new(
+        2,
+        recipient_device_id,
+        Some((pre_key_id, pre_key_pair.public_key)),
+        signed_pre_key_id,
+        signed_pre_key_pair.public_key,
+        signed_pre_key_signature.to_vec(),
+        *recipient_identity.identity_key(),
+    )?;
+
+    let result = expect_ready(async {
+        recipient_store
+            .save_pre_key(
+                pre_key_id,
+                &PreKeyRecord::new(pre_key_id, &pre_key_pair),
+                None,
+            )
+            .await?;
+        recipient_store
+            .save_signed_pre_key(
+                signed_pre_key_id,
+                &SignedPreKeyRecord::new(
+                    signed_pre_key_id,
+                    timestamp,
+                    &signed_pre_key_pair,
+                    &signed_pre_key_signature,
+                ),
+                None,
+            )
+            .await?;
+
+        process_prekey_bundle(
+            &recipient_address,
+            &mut sender_store.session_store,
+            &mut sender_store.identity_store,
+            &bundle,
+            &mut rng,
+            None,
+        )
+        .await?;
+
+        let ciphertext = sealed_sender_encrypt(
+            &recipient_address,
+            sender_cert,
+            plaintext,
+            &mut sender_store.session_store,
+            &mut sender_store.identity_store,
+            None,
+            &mut rng,
+        )
+        .await?;
+
+        sealed_sender_decrypt(
+            &ciphertext,
+            trust_root,
+            timestamp,
+            None,
+            recipient_uuid.to_owned(),
+            recipient_device_id,
+            &mut recipient_store.identity_store,
+            &mut recipient_store.session_store,
+            &mut recipient_store.pre_key_store,
+            &mut recipient_store.signed_pre_key_store,
+            None,
+        )
+        .await
+    })?;
+
+    if result.message != plaintext {
+        return Err(SignalProtocolError::InvalidState(
+            "TestHarness_SealedSenderRoundTrip",
+            "decrypted message does not match plaintext".to_owned(),
+        ));
+    }
+
+    Ok(ProtocolAddress::new(result.sender_uuid, result.device_id))
+}
+
 /// ts: export const enum CiphertextMessageType { Whisper = 2, PreKey = 3, SenderKey = 4, SenderKeyDistribution = 5 }
 #[derive(Debug)]
 #[repr(C)]
@@ -691,12 +1382,98 @@ const_assert_eq!(
     CiphertextMessageType::SenderKeyDistribution as u8
 );
 
-#[bridge_fn(jni = false)]
+#[bridge_fn]
 fn CiphertextMessage_Type(msg: &CiphertextMessage) -> u8 {
     msg.message_type() as u8
 }
 
-bridge_get_bytearray!(serialize(CiphertextMessage), jni = false => |m| Ok(m.serialize()));
+bridge_get_bytearray!(serialize(CiphertextMessage) => |m| Ok(m.serialize()));
+bridge_get_bytearray!(GetInnerCiphertext(CiphertextMessage), jni = false =>
+    CiphertextMessage::inner_ciphertext
+);
+
+#[bridge_fn(ffi = false, node = false)]
+fn CiphertextMessage_FromParts(
+    msg_type: u8,
+    data: &[u8],
+) -> Result<CiphertextMessage, SignalProtocolError> {
+    Ok(match msg_type {
+        x if x == FfiCiphertextMessageType::Whisper as u8 => {
+            CiphertextMessage::SignalMessage(SignalMessage::try_from(data)?)
+        }
+        x if x == FfiCiphertextMessageType::PreKey as u8 => {
+            CiphertextMessage::PreKeySignalMessage(PreKeySignalMessage::try_from(data)?)
+        }
+        x if x == FfiCiphertextMessageType::SenderKey as u8 => {
+            CiphertextMessage::SenderKeyMessage(SenderKeyMessage::try_from(data)?)
+        }
+        x if x == FfiCiphertextMessageType::SenderKeyDistribution as u8 => {
+            CiphertextMessage::SenderKeyDistributionMessage(SenderKeyDistributionMessage::try_from(
+                data,
+            )?)
+        }
+        x => {
+            return Err(SignalProtocolError::InvalidArgument(format!(
+                "unknown ciphertext message type {}",
+                x
+            )))
+        }
+    })
+}
+
+#[bridge_fn]
+fn CiphertextMessage_TypeMatchesBody(message_type: u8, body: &[u8]) -> bool {
+    let message_type = match message_type {
+        x if x == CiphertextMessageType::Whisper as u8 => CiphertextMessageType::Whisper,
+        x if x == CiphertextMessageType::PreKey as u8 => CiphertextMessageType::PreKey,
+        x if x == CiphertextMessageType::SenderKey as u8 => CiphertextMessageType::SenderKey,
+        x if x == CiphertextMessageType::SenderKeyDistribution as u8 => {
+            CiphertextMessageType::SenderKeyDistribution
+        }
+        _ => return false,
+    };
+    message_type.matches_body(body)
+}
+
+/// Returns an English description of a `SignalErrorCode` value, suitable for logging.
+#[bridge_fn(ffi = "error_describe_code")]
+fn SignalProtocolError_DescribeCode(code: u32) -> String {
+    SignalErrorCode::from_code(code)
+        .unwrap_or(SignalErrorCode::UnknownError)
+        .description()
+        .to_string()
+}
+
+/// Returns whether failing to decrypt `message` with `error_code` (a `SignalErrorCode` value) means
+/// the session with its sender should be archived and the sender asked to retry.
+#[bridge_fn]
+fn SessionCipher_ShouldResetSession(
+    error_code: u32,
+    message_type: u8,
+    message: &[u8],
+) -> Result<bool, SignalProtocolError> {
+    let message_type = match message_type {
+        x if x == CiphertextMessageType::Whisper as u8 => CiphertextMessageType::Whisper,
+        x if x == CiphertextMessageType::PreKey as u8 => CiphertextMessageType::PreKey,
+        // Group messages don't use the pairwise session.
+        x if x == CiphertextMessageType::SenderKey as u8
+            || x == CiphertextMessageType::SenderKeyDistribution as u8 =>
+        {
+            return Ok(false)
+        }
+        x => {
+            return Err(SignalProtocolError::InvalidArgument(format!(
+                "unknown ciphertext message type {}",
+                x
+            )))
+        }
+    };
+    // A message that doesn't even parse says nothing about the session it claims to belong to.
+    if !message_type.matches_body(message) {
+        return Ok(false);
+    }
+    Ok(SignalErrorCode::from_code(error_code).map_or(false, |code| code.requires_session_reset()))
+}
 
 #[bridge_fn(ffi = false, node = false)]
 fn SessionRecord_NewFresh() -> SessionRecord {
@@ -728,6 +1505,7 @@ fn SessionRecord_ArchiveCurrentState(
 }
 
 bridge_get!(SessionRecord::has_current_session_state as HasCurrentState -> bool, jni = false, node = false);
+bridge_get!(SessionRecord::can_encrypt as CanEncrypt -> bool);
 
 bridge_deserialize!(SessionRecord::deserialize);
 bridge_get_bytearray!(Serialize(SessionRecord) => SessionRecord::serialize);
@@ -742,13 +1520,125 @@ bridge_get_optional_bytearray!(GetRemoteIdentityKeyPublic(SessionRecord), ffi =
 );
 bridge_get!(SessionRecord::local_registration_id -> u32);
 bridge_get!(SessionRecord::remote_registration_id -> u32);
+
+/// Returns both registration ids in one crossing, as 8 bytes: the local registration id (4
+/// bytes, big-endian) followed by the remote registration id (4 bytes, big-endian). Each matches
+/// the corresponding SessionRecord_Get*RegistrationId getter.
+#[bridge_fn_buffer]
+fn SessionRecord_GetRegistrationIds<E: Env>(
+    env: E,
+    s: &SessionRecord,
+) -> Result<E::Buffer, SignalProtocolError> {
+    let mut result = Vec::with_capacity(8);
+    result.extend_from_slice(&s.local_registration_id()?.to_be_bytes());
+    result.extend_from_slice(&s.remote_registration_id()?.to_be_bytes());
+    Ok(env.buffer(result))
+}
+
+bridge_get_bytearray!(ComputeKeyConfirmation(SessionRecord) =>
+    SessionRecord::compute_key_confirmation
+);
+bridge_get_bytearray!(ComputeSessionId(SessionRecord) => SessionRecord::compute_session_id);
+bridge_get!(SessionRecord::creation_timestamp -> u64);
+bridge_get!(SessionRecord::needs_upgrade as NeedsUpgrade -> bool);
+
+#[bridge_fn]
+fn SessionRecord_StorageVersion(data: &[u8]) -> Result<u32, SignalProtocolError> {
+    SessionRecord::storage_version(data)
+}
 bridge_get!(SessionRecord::has_sender_chain as HasSenderChain -> bool, ffi = false, node = false);
 
+#[bridge_fn]
+fn SessionRecord_IdentitySeenBefore(
+    s: &SessionRecord,
+    identity_key: &PublicKey,
+) -> Result<bool, SignalProtocolError> {
+    s.identity_seen_before(&IdentityKey::new(*identity_key))
+}
+
+#[bridge_fn]
+fn SessionRecord_PreKeyMessageAlreadyProcessed(
+    s: &SessionRecord,
+    message: &PreKeySignalMessage,
+) -> Result<bool, SignalProtocolError> {
+    s.pre_key_message_already_processed(message)
+}
+
+/// ts: export const enum SessionRecordIntegrity { Valid = 0, Unparseable = 1, InvalidVersion = 2, InconsistentChainIndices = 3 }
+#[derive(Debug)]
+#[repr(C)]
+pub enum FfiSessionRecordIntegrity {
+    Valid = 0,
+    Unparseable = 1,
+    InvalidVersion = 2,
+    InconsistentChainIndices = 3,
+}
+
+const_assert_eq!(
+    FfiSessionRecordIntegrity::Valid as u8,
+    SessionRecordIntegrity::Valid as u8
+);
+const_assert_eq!(
+    FfiSessionRecordIntegrity::Unparseable as u8,
+    SessionRecordIntegrity::Unparseable as u8
+);
+const_assert_eq!(
+    FfiSessionRecordIntegrity::InvalidVersion as u8,
+    SessionRecordIntegrity::InvalidVersion as u8
+);
+const_assert_eq!(
+    FfiSessionRecordIntegrity::InconsistentChainIndices as u8,
+    SessionRecordIntegrity::InconsistentChainIndices as u8
+);
+
+#[bridge_fn]
+fn SessionRecord_ValidateIntegrity(serialized: &[u8]) -> u8 {
+    SessionRecord::validate_integrity(serialized) as u8
+}
+
+#[bridge_fn(node = false)]
+fn SessionCipher_PlanDecrypt(
+    message: &PreKeySignalMessage,
+    session_record: Option<&SessionRecord>,
+    stored_identity: Option<&PublicKey>,
+) -> Result<DecryptPlan, SignalProtocolError> {
+    plan_decrypt_prekey(
+        message,
+        session_record,
+        stored_identity.map(|key| IdentityKey::new(*key)).as_ref(),
+    )
+}
+
+#[bridge_fn(node = false)]
+fn SessionCipher_EstimateCiphertextSize(
+    plaintext_length: u32,
+    session_record: &SessionRecord,
+) -> Result<u32, SignalProtocolError> {
+    Ok(estimate_ciphertext_size(plaintext_length as usize, session_record)? as u32)
+}
+
+#[bridge_fn(node = false)]
+fn SessionCipher_MaxPlaintextSize(
+    ciphertext_budget: u32,
+    session_record: &SessionRecord,
+) -> Result<u32, SignalProtocolError> {
+    Ok(max_plaintext_size(ciphertext_budget as usize, session_record)? as u32)
+}
+
+bridge_get!(DecryptPlan::creates_session as CreatesSession -> bool, node = false);
+bridge_get!(DecryptPlan::consumed_pre_key_id -> Option<u32>, node = false);
+bridge_get!(DecryptPlan::changes_identity as ChangesIdentity -> bool, node = false);
+
 // The following SessionRecord APIs are just exposed to make it possible to retain some of the Java tests:
 
 bridge_get_bytearray!(GetSenderChainKeyValue(SessionRecord), ffi = false, node = false =>
     SessionRecord::get_sender_chain_key_bytes
 );
+// For testing only
+#[cfg(feature = "testing")]
+bridge_get_bytearray!(GetRootKey(SessionRecord), ffi = false, node = false =>
+    SessionRecord::get_root_key_bytes
+);
 #[bridge_fn_buffer(ffi = false, node = false)]
 fn SessionRecord_GetReceiverChainKeyValue<E: Env>(
     env: E,
@@ -759,7 +1649,48 @@ fn SessionRecord_GetReceiverChainKeyValue<E: Env>(
     Ok(chain_key.map(|ck| env.buffer(&ck.key()[..])))
 }
 
-#[bridge_fn(ffi = false, node = false)]
+// For testing only
+#[cfg(feature = "testing")]
+#[bridge_fn_buffer(ffi = false, node = false)]
+fn SessionRecord_DeriveMessageKeyAt<E: Env>(
+    env: E,
+    session_state: &SessionRecord,
+    ratchet_key: &PublicKey,
+    index: u32,
+) -> Result<E::Buffer, SignalProtocolError> {
+    Ok(env.buffer(session_state.message_key_bytes_at(ratchet_key, index)?))
+}
+
+/// Returns every chain key in the record as a sequence of fixed-size entries: state index (1
+/// byte, 0 = current), is-sender flag (1 byte), ratchet public key (33 bytes), chain index (4
+/// bytes, big-endian), chain key (32 bytes).
+///
+/// These keys can decrypt all future traffic on their chains. Only for authorized
+/// device-recovery tooling; see [SessionRecord::export_all_chain_keys].
+#[cfg(feature = "forensics")]
+#[bridge_fn_buffer(ffi = false, node = false)]
+fn SessionRecord_ExportAllChainKeys<E: Env>(
+    env: E,
+    session_record: &SessionRecord,
+) -> Result<E::Buffer, SignalProtocolError> {
+    let keys = session_record.export_all_chain_keys()?;
+    let mut result = Vec::with_capacity(keys.len() * (1 + 1 + 33 + 4 + 32));
+    for key in keys {
+        result.push(key.state_index() as u8);
+        result.push(key.is_sender() as u8);
+        result.extend_from_slice(&key.ratchet_key().serialize());
+        result.extend_from_slice(&key.index().to_be_bytes());
+        result.extend_from_slice(key.key());
+    }
+    Ok(env.buffer(result))
+}
+
+/// Builds Alice's side of a session straight from the X3DH keys, without a pre-key bundle.
+///
+/// This is exposed on every platform (not just with `testing`) because the Swift and Node test
+/// suites need it to build matching sessions and those builds have no test-only feature. Clients
+/// should keep using `process_prekey_bundle`, which also checks and records Bob's identity.
+#[bridge_fn]
 fn SessionRecord_InitializeAliceSession(
     identity_key_private: &PrivateKey,
     identity_key_public: &PublicKey,
@@ -767,6 +1698,7 @@ fn SessionRecord_InitializeAliceSession(
     base_public: &PublicKey,
     their_identity_key: &PublicKey,
     their_signed_prekey: &PublicKey,
+    their_one_time_prekey: Option<&PublicKey>,
     their_ratchet_key: &PublicKey,
 ) -> Result<SessionRecord, SignalProtocolError> {
     let our_identity_key_pair = IdentityKeyPair::new(
@@ -785,24 +1717,39 @@ fn SessionRecord_InitializeAliceSession(
         our_base_key_pair,
         their_identity_key,
         *their_signed_prekey,
-        None,
+        their_one_time_prekey.copied(),
         *their_ratchet_key,
     );
 
     initialize_alice_session_record(&parameters, &mut csprng)
 }
 
-#[bridge_fn(ffi = false, node = false)]
+/// Builds Bob's side of a session to match [SessionRecord_InitializeAliceSession]; see there for
+/// why this is not testing-only. `prekey_private` and `prekey_public` must both be present or
+/// both be absent.
+#[bridge_fn]
 fn SessionRecord_InitializeBobSession(
     identity_key_private: &PrivateKey,
     identity_key_public: &PublicKey,
     signed_prekey_private: &PrivateKey,
     signed_prekey_public: &PublicKey,
+    prekey_private: Option<&PrivateKey>,
+    prekey_public: Option<&PublicKey>,
     eph_private: &PrivateKey,
     eph_public: &PublicKey,
     their_identity_key: &PublicKey,
     their_base_key: &PublicKey,
 ) -> Result<SessionRecord, SignalProtocolError> {
+    let our_one_time_pre_key_pair = match (prekey_private, prekey_public) {
+        (None, None) => None,
+        (Some(private), Some(public)) => Some(KeyPair::new(*public, *private)),
+        _ => {
+            return Err(SignalProtocolError::InvalidArgument(
+                "Must supply both halves of the one-time prekey or neither".to_owned(),
+            ))
+        }
+    };
+
     let our_identity_key_pair = IdentityKeyPair::new(
         IdentityKey::new(*identity_key_public),
         *identity_key_private,
@@ -817,7 +1764,7 @@ fn SessionRecord_InitializeBobSession(
     let parameters = BobSignalProtocolParameters::new(
         our_identity_key_pair,
         our_signed_pre_key_pair,
-        None,
+        our_one_time_pre_key_pair,
         our_ratchet_key_pair,
         their_identity_key,
         *their_base_key,
@@ -826,8 +1773,120 @@ fn SessionRecord_InitializeBobSession(
     initialize_bob_session_record(&parameters)
 }
 
+/// Sets up Bob's side of a session from Alice's first message, taking the keys Alice used from
+/// `message` the same way decryption does. `prekey_private`/`prekey_public` must be the
+/// one-time pre-key the message names, if any.
+#[cfg(feature = "testing")]
+#[bridge_fn(ffi = false, node = false)]
+fn SessionRecord_InitializeBobFromPreKeyMessage(
+    message: &PreKeySignalMessage,
+    identity_key_private: &PrivateKey,
+    identity_key_public: &PublicKey,
+    signed_prekey_private: &PrivateKey,
+    signed_prekey_public: &PublicKey,
+    prekey_private: Option<&PrivateKey>,
+    prekey_public: Option<&PublicKey>,
+) -> Result<SessionRecord, SignalProtocolError> {
+    let our_one_time_pre_key_pair = match (prekey_private, prekey_public, message.pre_key_id()) {
+        (None, None, None) => None,
+        (Some(private), Some(public), Some(_)) => Some(KeyPair::new(*public, *private)),
+        _ => {
+            return Err(SignalProtocolError::InvalidArgument(
+                "Must supply a one-time prekey exactly when the message uses one".to_owned(),
+            ))
+        }
+    };
+
+    let our_identity_key_pair = IdentityKeyPair::new(
+        IdentityKey::new(*identity_key_public),
+        *identity_key_private,
+    );
+
+    let our_signed_pre_key_pair = KeyPair::new(*signed_prekey_public, *signed_prekey_private);
+
+    let parameters = BobSignalProtocolParameters::new(
+        our_identity_key_pair,
+        our_signed_pre_key_pair,
+        our_one_time_pre_key_pair,
+        our_signed_pre_key_pair,
+        *message.identity_key(),
+        *message.base_key(),
+    );
+
+    initialize_bob_session_record(&parameters)
+}
+
 // End SessionRecord testing functions
 
+// For testing only
+#[cfg(feature = "testing")]
+#[bridge_fn(ffi = false, node = false)]
+fn TestHarness_CanCommunicate(
+    alice_session: &SessionRecord,
+    bob_session: &SessionRecord,
+) -> Result<bool, SignalProtocolError> {
+    let mut rng = rand::rngs::OsRng;
+
+    let alice_address = ProtocolAddress::new("alice".to_owned(), 1);
+    let bob_address = ProtocolAddress::new("bob".to_owned(), 1);
+
+    let mut alice_store = InMemSignalProtocolStore::new(IdentityKeyPair::generate(&mut rng), 1)?;
+    let mut bob_store = InMemSignalProtocolStore::new(IdentityKeyPair::generate(&mut rng), 2)?;
+
+    expect_ready(async {
+        alice_store
+            .store_session(&bob_address, alice_session, None)
+            .await?;
+        bob_store
+            .store_session(&alice_address, bob_session, None)
+            .await?;
+
+        let ciphertext = message_encrypt(
+            b"can you hear me?",
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            None,
+        )
+        .await?;
+
+        let result = message_decrypt(
+            &ciphertext,
+            &alice_address,
+            &mut bob_store.session_store,
+            &mut bob_store.identity_store,
+            &mut bob_store.pre_key_store,
+            &mut bob_store.signed_pre_key_store,
+            &mut rng,
+            None,
+        )
+        .await;
+
+        match result {
+            Ok(plaintext) => Ok(plaintext == b"can you hear me?"),
+            Err(SignalProtocolError::InvalidMessage(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    })
+}
+
+// For testing only
+#[cfg(feature = "testing")]
+#[bridge_fn(ffi = false, node = false)]
+fn TestHarness_DeriveBaseKey(
+    seed: &[u8],
+    identity_key: &PublicKey,
+) -> Result<PrivateKey, SignalProtocolError> {
+    // Salting with the identity key keeps runs that share a seed but not an identity apart.
+    let base_key = HKDF::new(3)?.derive_salted_secrets(
+        seed,
+        &identity_key.serialize(),
+        b"Signal Test Harness Base Key",
+        32,
+    )?;
+    PrivateKey::deserialize(&base_key)
+}
+
 #[bridge_fn]
 fn Aes256GcmSiv_New(key: &[u8]) -> Result<Aes256GcmSiv, aes_gcm_siv::Error> {
     aes_gcm_siv::Aes256GcmSiv::new(&key)
@@ -841,7 +1900,9 @@ fn Aes256GcmSiv_Encrypt<T: Env>(
     nonce: &[u8],
     associated_data: &[u8],
 ) -> Result<T::Buffer, aes_gcm_siv::Error> {
-    let mut buf = Vec::with_capacity(ptext.len() + 16);
+    Aes256GcmSiv::check_input_sizes(ptext.len(), associated_data.len())?;
+
+    let mut buf = Vec::with_capacity(ptext.len() + aes_gcm_siv::TAG_SIZE);
     buf.extend_from_slice(ptext);
 
     let gcm_tag = aes_gcm_siv.encrypt(&mut buf, &nonce, &associated_data)?;
@@ -850,6 +1911,32 @@ fn Aes256GcmSiv_Encrypt<T: Env>(
     Ok(env.buffer(buf))
 }
 
+/// Encrypts `buffer` in place and writes the tag to `tag_out`, which must be exactly
+/// [TAG_SIZE](aes_gcm_siv::TAG_SIZE) bytes.
+// JNI has a hand-written version that encrypts a direct ByteBuffer without copying it.
+#[bridge_fn_void(jni = false, node = false)]
+fn Aes256GcmSiv_EncryptInPlace(
+    aes_gcm_siv: &Aes256GcmSiv,
+    buffer: &mut [u8],
+    tag_out: &mut [u8],
+    nonce: &[u8],
+    associated_data: &[u8],
+) -> Result<(), SignalProtocolError> {
+    if tag_out.len() != aes_gcm_siv::TAG_SIZE {
+        return Err(SignalProtocolError::InvalidArgument(format!(
+            "tag buffer must be {} bytes, got {}",
+            aes_gcm_siv::TAG_SIZE,
+            tag_out.len()
+        )));
+    }
+
+    let gcm_tag = aes_gcm_siv
+        .encrypt(buffer, &nonce, &associated_data)
+        .map_err(|e| SignalProtocolError::InvalidArgument(e.to_string()))?;
+    tag_out.copy_from_slice(&gcm_tag);
+    Ok(())
+}
+
 #[bridge_fn_buffer]
 fn Aes256GcmSiv_Decrypt<T: Env>(
     env: T,
@@ -862,3 +1949,182 @@ fn Aes256GcmSiv_Decrypt<T: Env>(
     aes_gcm_siv.decrypt_with_appended_tag(&mut buf, &nonce, &associated_data)?;
     Ok(env.buffer(buf))
 }
+
+#[bridge_fn_void]
+fn Aes256GcmSiv_Rekey(
+    aes_gcm_siv: &mut Aes256GcmSiv,
+    key: &[u8],
+) -> Result<(), aes_gcm_siv::Error> {
+    aes_gcm_siv.rekey(key)
+}
+
+#[bridge_fn]
+fn Aes256GcmSiv_TagLength() -> u32 {
+    aes_gcm_siv::TAG_SIZE as u32
+}
+
+const AES_CBC_BLOCK_SIZE: usize = 16;
+
+fn aes_cbc_parameter_error(key: &[u8], iv: &[u8]) -> SignalProtocolError {
+    SignalProtocolError::InvalidCipherCryptographicParameters(key.len(), iv.len())
+}
+
+/// Checks the PKCS7 padding at the end of `last_block` without branching on its contents.
+fn pkcs7_padding_is_valid(last_block: &[u8]) -> Choice {
+    let padding_len = last_block[AES_CBC_BLOCK_SIZE - 1];
+
+    // Wrapping subtraction sets the high bit exactly when the right-hand side is larger.
+    let len_too_large =
+        (((AES_CBC_BLOCK_SIZE as u16).wrapping_sub(padding_len as u16)) >> 15) as u8;
+    let mut valid = !padding_len.ct_eq(&0) & !Choice::from(len_too_large);
+
+    for (i, byte) in last_block.iter().rev().enumerate() {
+        let in_padding = (((i as u16).wrapping_sub(padding_len as u16)) >> 15) as u8;
+        valid &= !Choice::from(in_padding) | byte.ct_eq(&padding_len);
+    }
+    valid
+}
+
+#[bridge_fn_buffer]
+fn Aes256Cbc_Encrypt<E: Env>(
+    env: E,
+    key: &[u8],
+    iv: &[u8],
+    ptext: &[u8],
+) -> Result<E::Buffer, SignalProtocolError> {
+    let mode =
+        Cbc::<Aes256, Pkcs7>::new_var(key, iv).map_err(|_| aes_cbc_parameter_error(key, iv))?;
+    Ok(env.buffer(mode.encrypt_vec(ptext)))
+}
+
+#[bridge_fn_buffer]
+fn Aes256Cbc_Decrypt<E: Env>(
+    env: E,
+    key: &[u8],
+    iv: &[u8],
+    ctext: &[u8],
+) -> Result<E::Buffer, SignalProtocolError> {
+    if ctext.is_empty() || ctext.len() % AES_CBC_BLOCK_SIZE != 0 {
+        return Err(SignalProtocolError::InvalidArgument(format!(
+            "AES-CBC ciphertext length must be a nonzero multiple of {}, got {}",
+            AES_CBC_BLOCK_SIZE,
+            ctext.len()
+        )));
+    }
+
+    let mode =
+        Cbc::<Aes256, NoPadding>::new_var(key, iv).map_err(|_| aes_cbc_parameter_error(key, iv))?;
+    let mut ptext = mode
+        .decrypt_vec(ctext)
+        .map_err(|_| SignalProtocolError::InvalidCiphertext)?;
+
+    let last_block = &ptext[ptext.len() - AES_CBC_BLOCK_SIZE..];
+    if !bool::from(pkcs7_padding_is_valid(last_block)) {
+        return Err(SignalProtocolError::InvalidCiphertext);
+    }
+    let padding_len = last_block[AES_CBC_BLOCK_SIZE - 1] as usize;
+    ptext.truncate(ptext.len() - padding_len);
+    Ok(env.buffer(ptext))
+}
+
+/// Returns the size an attachment of `plaintext_length` bytes is padded to before upload.
+///
+/// Sizes are rounded up to the next power of 1.05, with a minimum of 541 bytes. This is the
+/// attachment scheme, not the one used for message bodies.
+#[bridge_fn]
+fn Attachment_PaddedSize(plaintext_length: u64) -> u64 {
+    const MIN_PADDED_SIZE: u64 = 541;
+    const BUCKET_BASE: f64 = 1.05;
+    let exponent = ((plaintext_length as f64).ln() / BUCKET_BASE.ln()).ceil();
+    std::cmp::max(MIN_PADDED_SIZE, BUCKET_BASE.powf(exponent).floor() as u64)
+}
+
+const PROFILE_KEY_LENGTH: usize = 32;
+const PROFILE_CIPHER_NONCE_LENGTH: usize = 12;
+const PROFILE_CIPHER_TAG_LENGTH: usize = 16;
+const PROFILE_NAME_PADDED_LENGTHS: [usize; 2] = [53, 257];
+
+fn profile_cipher(profile_key: &[u8]) -> Result<Aes256Gcm, SignalProtocolError> {
+    if profile_key.len() != PROFILE_KEY_LENGTH {
+        return Err(SignalProtocolError::InvalidArgument(format!(
+            "profile key must be {} bytes",
+            PROFILE_KEY_LENGTH
+        )));
+    }
+    Ok(Aes256Gcm::new(GenericArray::from_slice(profile_key)))
+}
+
+/// Encrypts a profile name, zero-padding it to 53 or 257 bytes so that only the size bucket is
+/// revealed. The result is the random nonce followed by the AES-256-GCM ciphertext and tag.
+#[bridge_fn_buffer]
+fn ProfileCipher_EncryptName<E: Env>(
+    env: E,
+    name: String,
+    profile_key: &[u8],
+) -> Result<E::Buffer, SignalProtocolError> {
+    let cipher = profile_cipher(profile_key)?;
+
+    let padded_length = PROFILE_NAME_PADDED_LENGTHS
+        .iter()
+        .copied()
+        .find(|&len| name.len() <= len)
+        .ok_or_else(|| {
+            SignalProtocolError::InvalidArgument(format!(
+                "profile name is longer than {} bytes",
+                PROFILE_NAME_PADDED_LENGTHS[PROFILE_NAME_PADDED_LENGTHS.len() - 1]
+            ))
+        })?;
+    let mut padded = name.into_bytes();
+    padded.resize(padded_length, 0);
+
+    let mut nonce = [0u8; PROFILE_CIPHER_NONCE_LENGTH];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+
+    let ctext = cipher
+        .encrypt(GenericArray::from_slice(&nonce), padded.as_slice())
+        .map_err(|_| SignalProtocolError::InternalError("AES-GCM encryption failed"))?;
+
+    let mut result = Vec::with_capacity(nonce.len() + ctext.len());
+    result.extend_from_slice(&nonce);
+    result.extend_from_slice(&ctext);
+    Ok(env.buffer(result))
+}
+
+/// Decrypts a profile name produced by [ProfileCipher_EncryptName], removing the padding.
+///
+/// A ciphertext that fails authentication produces [SignalProtocolError::InvalidCiphertext];
+/// one that authenticates but does not hold a correctly padded UTF-8 name produces
+/// [SignalProtocolError::InvalidMessage].
+#[bridge_fn]
+fn ProfileCipher_DecryptName(
+    ciphertext: &[u8],
+    profile_key: &[u8],
+) -> Result<String, SignalProtocolError> {
+    let cipher = profile_cipher(profile_key)?;
+
+    if ciphertext.len() < PROFILE_CIPHER_NONCE_LENGTH + PROFILE_CIPHER_TAG_LENGTH {
+        return Err(SignalProtocolError::InvalidCiphertext);
+    }
+    let (nonce, ctext) = ciphertext.split_at(PROFILE_CIPHER_NONCE_LENGTH);
+
+    let mut padded = cipher
+        .decrypt(GenericArray::from_slice(nonce), ctext)
+        .map_err(|_| SignalProtocolError::InvalidCiphertext)?;
+
+    if !PROFILE_NAME_PADDED_LENGTHS.contains(&padded.len()) {
+        return Err(SignalProtocolError::InvalidMessage(
+            "profile name has an unexpected padded length",
+        ));
+    }
+    if let Some(end) = padded.iter().position(|&b| b == 0) {
+        if padded[end..].iter().any(|&b| b != 0) {
+            return Err(SignalProtocolError::InvalidMessage(
+                "profile name padding is not all zeros",
+            ));
+        }
+        padded.truncate(end);
+    }
+
+    String::from_utf8(padded)
+        .map_err(|_| SignalProtocolError::InvalidMessage("profile name is not valid UTF-8"))
+}
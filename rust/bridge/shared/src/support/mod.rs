@@ -5,6 +5,7 @@
 
 use futures::pin_mut;
 use futures::task::noop_waker_ref;
+use libsignal_protocol::SignalProtocolError;
 use std::borrow::Cow;
 use std::future::Future;
 use std::task::{self, Poll};
@@ -24,6 +25,46 @@ pub fn expect_ready<F: Future>(future: F) -> F::Output {
     }
 }
 
+/// Splits a buffer of records, each preceded by its length as a big-endian u32.
+pub fn split_length_prefixed_records(
+    mut remaining: &[u8],
+) -> Result<Vec<&[u8]>, SignalProtocolError> {
+    let mut records = vec![];
+    while !remaining.is_empty() {
+        if remaining.len() < 4 {
+            return Err(SignalProtocolError::InvalidArgument(
+                "truncated record length".to_owned(),
+            ));
+        }
+        let (len, rest) = remaining.split_at(4);
+        let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+        if rest.len() < len {
+            return Err(SignalProtocolError::InvalidArgument(
+                "truncated record".to_owned(),
+            ));
+        }
+        let (record, rest) = rest.split_at(len);
+        records.push(record);
+        remaining = rest;
+    }
+    Ok(records)
+}
+
+/// Encodes the results of a batch decryption as, for each message in order, a success flag
+/// (1 byte), the plaintext length as a big-endian u32, and the plaintext. Failed messages have
+/// an empty plaintext.
+#[allow(dead_code)] // not used in Node-only builds
+pub fn encode_batch_decrypt_results(results: Vec<Result<Vec<u8>, SignalProtocolError>>) -> Vec<u8> {
+    let mut encoded = vec![];
+    for result in results {
+        let plaintext = result.as_deref().unwrap_or(&[]);
+        encoded.push(result.is_ok() as u8);
+        encoded.extend_from_slice(&(plaintext.len() as u32).to_be_bytes());
+        encoded.extend_from_slice(plaintext);
+    }
+    encoded
+}
+
 /// Used for returning newly-allocated buffers as efficiently as possible.
 pub(crate) trait Env {
     type Buffer;
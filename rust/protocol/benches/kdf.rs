@@ -0,0 +1,35 @@
+//
+// Copyright 2021 Signal Messenger, LLC.
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use libsignal_protocol::*;
+
+pub fn kdf_derive_many(c: &mut Criterion) {
+    let kdf = HKDF::new(3).expect("valid version");
+    let ikm = [0x0bu8; 32];
+    let salt = [0x00u8; 32];
+    let labels: [&[u8]; 4] = [b"RootKey", b"ChainKey", b"MessageKeys", b"WhisperText"];
+    let lengths = [32, 32, 80, 64];
+
+    c.bench_function("kdf derive separately", |b| {
+        b.iter(|| {
+            for (label, &length) in labels.iter().zip(&lengths) {
+                kdf.derive_salted_secrets(&ikm, &salt, label, length)
+                    .expect("success");
+            }
+        })
+    });
+
+    c.bench_function("kdf derive many", |b| {
+        b.iter(|| {
+            kdf.derive_salted_secrets_many(&ikm, &salt, &labels, &lengths)
+                .expect("success");
+        })
+    });
+}
+
+criterion_group!(benches, kdf_derive_many);
+
+criterion_main!(benches);
@@ -3,6 +3,8 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+use crate::error::{Result, SignalProtocolError};
+
 use std::fmt;
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq, PartialOrd, Ord)]
@@ -23,6 +25,25 @@ impl ProtocolAddress {
     pub fn device_id(&self) -> u32 {
         self.device_id
     }
+
+    /// Parses the canonical `name.deviceId` form produced by [Display](fmt::Display).
+    ///
+    /// The device id is everything after the last `.`, so the name may itself contain dots.
+    pub fn from_canonical_string(s: &str) -> Result<Self> {
+        let malformed =
+            || SignalProtocolError::InvalidArgument(format!("malformed protocol address {:?}", s));
+
+        let separator = s.rfind('.').ok_or_else(malformed)?;
+        let (name, device_id) = (&s[..separator], &s[separator + 1..]);
+
+        let parsed_device_id: u32 = device_id.parse().map_err(|_| malformed())?;
+        if parsed_device_id.to_string() != device_id {
+            // Reject non-canonical spellings such as leading zeros or a '+' sign.
+            return Err(malformed());
+        }
+
+        Ok(Self::new(name.to_owned(), parsed_device_id))
+    }
 }
 
 impl fmt::Display for ProtocolAddress {
@@ -30,3 +51,51 @@ impl fmt::Display for ProtocolAddress {
         write!(f, "{}.{}", self.name, self.device_id)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_string_round_trip() -> Result<()> {
+        for address in &[
+            ProtocolAddress::new("+14151111111".to_owned(), 1),
+            ProtocolAddress::new("name.with.dots".to_owned(), 42),
+            ProtocolAddress::new("trailing.".to_owned(), 0),
+            ProtocolAddress::new("".to_owned(), u32::MAX),
+        ] {
+            let s = address.to_string();
+            assert_eq!(&ProtocolAddress::from_canonical_string(&s)?, address);
+        }
+        assert_eq!(
+            ProtocolAddress::from_canonical_string("a.b.7")?,
+            ProtocolAddress::new("a.b".to_owned(), 7)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_canonical_string() {
+        for s in &[
+            "",
+            "name",
+            "name.",
+            "name.abc",
+            "name.1a",
+            "name.01",
+            "name.+1",
+            "name.-1",
+            "name.4294967296",
+            "name.1.",
+        ] {
+            assert!(
+                matches!(
+                    ProtocolAddress::from_canonical_string(s),
+                    Err(SignalProtocolError::InvalidArgument(_))
+                ),
+                "{:?} should be rejected",
+                s
+            );
+        }
+    }
+}
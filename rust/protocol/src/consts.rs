@@ -8,3 +8,6 @@ pub const MAX_MESSAGE_KEYS: usize = 2000;
 pub const MAX_RECEIVER_CHAINS: usize = 5;
 pub const ARCHIVED_STATES_MAX_LENGTH: usize = 40;
 pub const MAX_SENDER_KEY_STATES: usize = 5;
+/// Sender key chains must be rotated once they reach this iteration, which keeps every
+/// iteration representable by libsignal-protocol-java, where it is a signed 32-bit `int`.
+pub const MAX_SENDER_KEY_ITERATION: u32 = i32::MAX as u32;
@@ -80,6 +80,12 @@ impl PublicKey {
         }
     }
 
+    pub(crate) fn from_djb_public_key_bytes(bytes: &[u8]) -> Result<Self> {
+        let key = <[u8; 32]>::try_from(bytes)
+            .map_err(|_| SignalProtocolError::BadKeyLength(KeyType::Djb, bytes.len()))?;
+        Ok(Self::new(PublicKeyData::DjbPublicKey(key)))
+    }
+
     pub fn public_key_bytes(&self) -> Result<&[u8]> {
         match self.key {
             PublicKeyData::DjbPublicKey(ref v) => Ok(v),
@@ -113,6 +119,14 @@ impl PublicKey {
         }
     }
 
+    /// Returns true if this key is a low-order point, which would yield an all-zero shared
+    /// secret in any agreement. Such keys should be rejected before use.
+    pub fn is_weak(&self) -> bool {
+        match self.key {
+            PublicKeyData::DjbPublicKey(ref k) => curve25519::is_low_order(k),
+        }
+    }
+
     fn key_data(&self) -> &[u8] {
         match self.key {
             PublicKeyData::DjbPublicKey(ref k) => k.as_ref(),
@@ -230,6 +244,18 @@ impl PrivateKey {
             }
         }
     }
+
+    /// Like [`calculate_agreement`](Self::calculate_agreement), but fails with
+    /// [`LowOrderPublicKey`](SignalProtocolError::LowOrderPublicKey) if `their_key` is a
+    /// low-order point, which would produce an all-zero shared secret.
+    pub fn calculate_agreement_validated(&self, their_key: &PublicKey) -> Result<Box<[u8]>> {
+        let shared_secret = self.calculate_agreement(their_key)?;
+        let all_zero = shared_secret.iter().fold(0u8, |acc, b| acc | b).ct_eq(&0u8);
+        if bool::from(all_zero) {
+            return Err(SignalProtocolError::LowOrderPublicKey);
+        }
+        Ok(shared_secret)
+    }
 }
 
 impl From<PrivateKeyData> for PrivateKey {
@@ -331,6 +357,76 @@ mod tests {
         assert!(verify_signature(&public_key, &message, &signature).unwrap());
     }
 
+    #[test]
+    fn test_agreement_with_low_order_point() {
+        let mut csprng = OsRng;
+        let key_pair = KeyPair::generate(&mut csprng);
+
+        // u = 0 is a point of order 4 on Curve25519.
+        let mut low_order = [0u8; 33];
+        low_order[0] = 0x05;
+        let low_order = decode_point(&low_order).unwrap();
+
+        assert_eq!(
+            &key_pair
+                .private_key
+                .calculate_agreement(&low_order)
+                .unwrap()[..],
+            &[0u8; 32][..]
+        );
+        assert!(matches!(
+            key_pair
+                .private_key
+                .calculate_agreement_validated(&low_order),
+            Err(SignalProtocolError::LowOrderPublicKey)
+        ));
+
+        let other = KeyPair::generate(&mut csprng);
+        assert_eq!(
+            key_pair
+                .private_key
+                .calculate_agreement_validated(&other.public_key)
+                .unwrap(),
+            other
+                .private_key
+                .calculate_agreement(&key_pair.public_key)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_is_weak() {
+        let mut csprng = OsRng;
+        let key_pair = KeyPair::generate(&mut csprng);
+        assert!(!key_pair.public_key.is_weak());
+
+        // The points of order 1, 2, 4 and 8, plus non-canonical encodings of some of them.
+        let weak_points = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "e0eb7a7c3b41b8ae1656e3faf19fc46ada098deb9c32b1fd866205165f49b800",
+            "5f9c95bca3508c24b1d0b1559c83ef5b04445cc4581c8e86d8224eddd09f1157",
+            "ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "edffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            "0000000000000000000000000000000000000000000000000000000000000080",
+        ];
+        for point in weak_points.iter() {
+            let mut serialized = vec![0x05];
+            serialized.extend_from_slice(&hex::decode(point).unwrap());
+            let public_key = decode_point(&serialized).unwrap();
+
+            assert!(public_key.is_weak(), "{} should be weak", point);
+            assert_eq!(
+                &key_pair
+                    .private_key
+                    .calculate_agreement(&public_key)
+                    .unwrap()[..],
+                &[0u8; 32][..]
+            );
+        }
+    }
+
     #[test]
     fn test_decode_size() {
         let mut csprng = OsRng;
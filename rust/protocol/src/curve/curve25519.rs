@@ -146,6 +146,15 @@ pub fn derive_public_key(private_key: &[u8; 32]) -> [u8; 32] {
     *PublicKey::from(&StaticSecret::from(*private_key)).as_bytes()
 }
 
+/// Returns true if `public_key` is a point whose order divides the cofactor 8.
+///
+/// X25519 private keys are clamped to multiples of 8, so agreement with such a point always
+/// produces an all-zero shared secret.
+pub fn is_low_order(public_key: &[u8; PUBLIC_KEY_LENGTH]) -> bool {
+    let cleared = MontgomeryPoint(*public_key) * Scalar::from(8u8);
+    cleared.as_bytes().ct_eq(&[0u8; 32]).into()
+}
+
 impl From<[u8; PRIVATE_KEY_LENGTH]> for KeyPair {
     fn from(private_key: [u8; 32]) -> Self {
         let private_key = StaticSecret::from(private_key);
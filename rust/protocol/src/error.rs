@@ -34,6 +34,7 @@ pub enum SignalProtocolError {
     BadKeyLength(KeyType, usize),
 
     SignatureValidationFailed,
+    LowOrderPublicKey,
 
     UntrustedIdentity(crate::ProtocolAddress),
 
@@ -63,6 +64,7 @@ pub enum SignalProtocolError {
     InvalidSealedSenderMessage(String),
     UnknownSealedSenderVersion(u8),
     SealedSenderSelfSend,
+    ExpiredCertificate(u64),
 }
 
 impl Error for SignalProtocolError {
@@ -158,6 +160,9 @@ impl fmt::Display for SignalProtocolError {
             SignalProtocolError::SignatureValidationFailed => {
                 write!(f, "invalid signature detected")
             }
+            SignalProtocolError::LowOrderPublicKey => {
+                write!(f, "key agreement with low-order public key")
+            }
             SignalProtocolError::InvalidCiphertext => write!(f, "invalid ciphertext message"),
             SignalProtocolError::SessionNotFound(who) => {
                 write!(f, "session with '{}' not found", who)
@@ -185,6 +190,9 @@ impl fmt::Display for SignalProtocolError {
             SignalProtocolError::SealedSenderSelfSend => {
                 write!(f, "self send of a sealed sender message")
             }
+            SignalProtocolError::ExpiredCertificate(expiration) => {
+                write!(f, "certificate expired at {}", expiration)
+            }
             SignalProtocolError::MessageDecryptionFailed(info) => {
                 write!(f, "{}", info)
             }
@@ -116,6 +116,13 @@ impl ScannableFingerprint {
         Ok(buf)
     }
 
+    /// Returns whether fingerprints of versions `ours` and `theirs` can be meaningfully compared.
+    ///
+    /// A scanned payload without a version is treated as version 0, which never matches.
+    pub fn versions_compatible(ours: u32, theirs: u32) -> bool {
+        ours != 0 && ours == theirs
+    }
+
     pub fn compare(&self, combined: &[u8]) -> Result<bool> {
         let combined = proto::fingerprint::CombinedFingerprints::decode(combined)
             .map_err(|_| SignalProtocolError::FingerprintParsingError)?;
@@ -215,6 +222,28 @@ impl Fingerprint {
     pub fn display_string(&self) -> Result<String> {
         Ok(format!("{}", self.display))
     }
+
+    /// Returns the exact bytes to encode into the safety-number QR code.
+    ///
+    /// The fingerprint version is the payload's leading field, so callers must not prepend a
+    /// header of their own.
+    pub fn qr_code_payload(&self) -> Result<Vec<u8>> {
+        self.scannable.serialize()
+    }
+
+    /// Returns the first `digits` digits of the 60-digit display string.
+    pub fn truncated_display_string(&self, digits: usize) -> Result<String> {
+        let mut display = self.display_string()?;
+        if digits > display.len() {
+            return Err(SignalProtocolError::InvalidArgument(format!(
+                "cannot truncate a {} digit fingerprint to {} digits",
+                display.len(),
+                digits
+            )));
+        }
+        display.truncate(digits);
+        Ok(display)
+    }
 }
 
 #[cfg(test)]
@@ -303,6 +332,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn qr_code_payload_test() {
+        let a_key = IdentityKey::decode(&hex::decode(ALICE_IDENTITY).unwrap()).unwrap();
+        let b_key = IdentityKey::decode(&hex::decode(BOB_IDENTITY).unwrap()).unwrap();
+
+        for (version, expected) in &[
+            (1, ALICE_SCANNABLE_FINGERPRINT_V1),
+            (2, ALICE_SCANNABLE_FINGERPRINT_V2),
+        ] {
+            let a_fprint = Fingerprint::new(
+                *version,
+                5200,
+                ALICE_STABLE_ID.as_bytes(),
+                &a_key,
+                BOB_STABLE_ID.as_bytes(),
+                &b_key,
+            )
+            .unwrap();
+
+            assert_eq!(hex::encode(a_fprint.qr_code_payload().unwrap()), *expected);
+        }
+    }
+
     #[test]
     fn fingerprint_test_v2() {
         // testVectorsVersion2 in Java
@@ -356,6 +408,35 @@ mod test {
         );
     }
 
+    #[test]
+    fn fingerprint_truncated_display_string() {
+        let a_key = IdentityKey::decode(&hex::decode(ALICE_IDENTITY).unwrap()).unwrap();
+        let b_key = IdentityKey::decode(&hex::decode(BOB_IDENTITY).unwrap()).unwrap();
+
+        let fprint = Fingerprint::new(
+            1,
+            5200,
+            ALICE_STABLE_ID.as_bytes(),
+            &a_key,
+            BOB_STABLE_ID.as_bytes(),
+            &b_key,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fprint.truncated_display_string(12).unwrap(),
+            &DISPLAYABLE_FINGERPRINT_V1[..12]
+        );
+        assert_eq!(
+            fprint.truncated_display_string(60).unwrap(),
+            DISPLAYABLE_FINGERPRINT_V1
+        );
+        assert!(matches!(
+            fprint.truncated_display_string(61),
+            Err(SignalProtocolError::InvalidArgument(_))
+        ));
+    }
+
     #[test]
     fn fingerprint_matching_identifiers() {
         // testMatchingFingerprints
@@ -581,4 +662,13 @@ mod test {
             hex::encode(a_fprint_v2.scannable.serialize().unwrap())
         );
     }
+
+    #[test]
+    fn fingerprint_versions_compatible() {
+        assert!(ScannableFingerprint::versions_compatible(1, 1));
+        assert!(ScannableFingerprint::versions_compatible(2, 2));
+        assert!(!ScannableFingerprint::versions_compatible(1, 2));
+        assert!(!ScannableFingerprint::versions_compatible(2, 1));
+        assert!(!ScannableFingerprint::versions_compatible(0, 0));
+    }
 }
@@ -53,6 +53,12 @@ pub async fn group_encrypt<R: Rng + CryptoRng>(
 }
 
 fn get_sender_key(state: &mut SenderKeyState, iteration: u32) -> Result<SenderMessageKey> {
+    if iteration >= consts::MAX_SENDER_KEY_ITERATION {
+        return Err(SignalProtocolError::InvalidMessage(
+            "message past the sender key iteration limit",
+        ));
+    }
+
     let sender_chain_key = state.sender_chain_key()?;
 
     if sender_chain_key.iteration()? > iteration {
@@ -95,6 +101,16 @@ pub async fn group_decrypt(
         .await?
         .ok_or(SignalProtocolError::InvalidSenderKeyId)?;
 
+    let plaintext = decrypt_with_record(skm_bytes, &mut record)?;
+
+    sender_key_store
+        .store_sender_key(sender_key_id, &record, ctx)
+        .await?;
+
+    Ok(plaintext)
+}
+
+fn decrypt_with_record(skm_bytes: &[u8], record: &mut SenderKeyRecord) -> Result<Vec<u8>> {
     let skm = SenderKeyMessage::try_from(skm_bytes)?;
 
     let mut sender_key_state = record.sender_key_state_for_keyid(skm.key_id())?;
@@ -106,17 +122,47 @@ pub async fn group_decrypt(
 
     let sender_key = get_sender_key(&mut sender_key_state, skm.iteration())?;
 
-    let plaintext = crypto::aes_256_cbc_decrypt(
+    crypto::aes_256_cbc_decrypt(
         skm.ciphertext(),
         &sender_key.cipher_key()?,
         &sender_key.iv()?,
-    )?;
+    )
+}
 
-    sender_key_store
-        .store_sender_key(sender_key_id, &record, ctx)
-        .await?;
+/// Decrypts `messages` in order, as if by repeated calls to [group_decrypt].
+///
+/// A message that fails to decrypt (including a duplicate of an earlier one) yields an error in
+/// its slot without affecting the rest of the batch. Errors loading or storing the sender key
+/// record fail the whole batch.
+pub async fn group_decrypt_batch(
+    messages: &[&[u8]],
+    sender_key_store: &mut dyn SenderKeyStore,
+    sender_key_id: &SenderKeyName,
+    ctx: Context,
+) -> Result<Vec<Result<Vec<u8>>>> {
+    let mut record = sender_key_store
+        .load_sender_key(sender_key_id, ctx)
+        .await?
+        .ok_or(SignalProtocolError::InvalidSenderKeyId)?;
 
-    Ok(plaintext)
+    let mut results = Vec::with_capacity(messages.len());
+    for message in messages {
+        // Only keep changes to the record from messages that decrypted successfully.
+        let mut updated_record = record.clone();
+        let result = decrypt_with_record(message, &mut updated_record);
+        if result.is_ok() {
+            record = updated_record;
+        }
+        results.push(result);
+    }
+
+    if results.iter().any(Result::is_ok) {
+        sender_key_store
+            .store_sender_key(sender_key_id, &record, ctx)
+            .await?;
+    }
+
+    Ok(results)
 }
 
 pub async fn process_sender_key_distribution_message(
@@ -172,13 +218,5 @@ pub async fn create_sender_key_distribution_message<R: Rng + CryptoRng>(
             .await?;
     }
 
-    let state = sender_key_record.sender_key_state()?;
-    let sender_chain_key = state.sender_chain_key()?;
-
-    SenderKeyDistributionMessage::new(
-        state.sender_key_id()?,
-        sender_chain_key.iteration()?,
-        &sender_chain_key.seed()?,
-        state.signing_key_public()?,
-    )
+    sender_key_record.create_distribution_message()
 }
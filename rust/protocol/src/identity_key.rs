@@ -12,6 +12,27 @@ use rand::{CryptoRng, Rng};
 use std::convert::TryFrom;
 
 use prost::Message;
+use sha2::{Digest, Sha256};
+
+// Domain separation for signatures linking one identity key to another (e.g. an ACI identity
+// vouching for a PNI identity), so they can't be confused with any other signed message.
+const ALTERNATE_IDENTITY_SIGNATURE_PREFIX_1: &[u8] = &[0xFF; 32];
+const ALTERNATE_IDENTITY_SIGNATURE_PREFIX_2: &[u8] = b"Signal_PNI_Signature";
+
+const CHANGE_HASH_LABEL: &[u8] = b"Signal_IdentityKey_ChangeHash";
+
+fn alternate_identity_signature_message(other: &IdentityKey) -> Vec<u8> {
+    let other = other.serialize();
+    let mut message = Vec::with_capacity(
+        ALTERNATE_IDENTITY_SIGNATURE_PREFIX_1.len()
+            + ALTERNATE_IDENTITY_SIGNATURE_PREFIX_2.len()
+            + other.len(),
+    );
+    message.extend_from_slice(ALTERNATE_IDENTITY_SIGNATURE_PREFIX_1);
+    message.extend_from_slice(ALTERNATE_IDENTITY_SIGNATURE_PREFIX_2);
+    message.extend_from_slice(&other);
+    message
+}
 
 #[derive(Debug, PartialOrd, Ord, PartialEq, Eq, Clone, Copy)]
 pub struct IdentityKey {
@@ -37,6 +58,35 @@ impl IdentityKey {
         let pk = curve::PublicKey::deserialize(value)?;
         Ok(Self { public_key: pk })
     }
+
+    /// Formats the serialized key for display: lowercase hex, in eleven space-separated groups
+    /// of six digits (three bytes each).
+    pub fn display_string(&self) -> String {
+        self.serialize()
+            .chunks(3)
+            .map(hex::encode)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Returns a short digest of the serialized key, for clients that only need to notice when
+    /// a remote identity changes. This is not a fingerprint and must not be shown to users for
+    /// verification.
+    pub fn change_hash(&self) -> [u8; 16] {
+        let mut sha256 = Sha256::new();
+        sha256.update(CHANGE_HASH_LABEL);
+        sha256.update(&self.serialize());
+        let mut result = [0u8; 16];
+        result.copy_from_slice(&sha256.finalize()[..16]);
+        result
+    }
+
+    /// Checks a signature produced by [`IdentityKeyPair::sign_alternate_identity`], showing
+    /// that the holder of this identity also controls `other`.
+    pub fn verify_alternate_identity(&self, other: &IdentityKey, signature: &[u8]) -> Result<bool> {
+        self.public_key
+            .verify_signature(&alternate_identity_signature_message(other), signature)
+    }
 }
 
 impl TryFrom<&[u8]> for IdentityKey {
@@ -91,6 +141,16 @@ impl IdentityKeyPair {
         &self.private_key
     }
 
+    /// Signs `other` with this identity, linking the two identities together.
+    pub fn sign_alternate_identity<R: CryptoRng + Rng>(
+        &self,
+        other: &IdentityKey,
+        csprng: &mut R,
+    ) -> Result<Box<[u8]>> {
+        self.private_key
+            .calculate_signature(&alternate_identity_signature_message(other), csprng)
+    }
+
     pub fn serialize(&self) -> Box<[u8]> {
         let structure = proto::storage::IdentityKeyPairStructure {
             public_key: self.identity_key.serialize().to_vec(),
@@ -106,10 +166,24 @@ impl TryFrom<&[u8]> for IdentityKeyPair {
     type Error = SignalProtocolError;
 
     fn try_from(value: &[u8]) -> Result<Self> {
-        let structure = proto::storage::IdentityKeyPairStructure::decode(value)?;
+        fn malformed<E>(_: E) -> SignalProtocolError {
+            SignalProtocolError::InvalidArgument("malformed identity key pair".to_owned())
+        }
+
+        let structure =
+            proto::storage::IdentityKeyPairStructure::decode(value).map_err(malformed)?;
+        let identity_key = IdentityKey::try_from(&structure.public_key[..]).map_err(malformed)?;
+        let private_key =
+            curve::PrivateKey::deserialize(&structure.private_key).map_err(malformed)?;
+        if private_key.public_key()? != *identity_key.public_key() {
+            return Err(SignalProtocolError::InvalidArgument(
+                "identity key pair's public key does not match its private key".to_owned(),
+            ));
+        }
+
         Ok(Self {
-            identity_key: IdentityKey::try_from(&structure.public_key[..])?,
-            private_key: curve::PrivateKey::deserialize(&structure.private_key)?,
+            identity_key,
+            private_key,
         })
     }
 }
@@ -137,6 +211,77 @@ mod tests {
         assert_eq!(key_pair_public_serialized, identity_key.serialize());
     }
 
+    #[test]
+    fn test_identity_key_display_string() {
+        let identity_key = *IdentityKeyPair::generate(&mut OsRng).identity_key();
+        let display = identity_key.display_string();
+
+        let groups: Vec<&str> = display.split(' ').collect();
+        assert_eq!(groups.len(), 11);
+        assert!(groups.iter().all(|g| g.len() == 6));
+        assert!(groups[0].starts_with("05"));
+        assert_eq!(groups.concat(), hex::encode(identity_key.serialize()));
+    }
+
+    #[test]
+    fn test_identity_key_change_hash() {
+        let identity_key = *IdentityKeyPair::generate(&mut OsRng).identity_key();
+        let other_key = *IdentityKeyPair::generate(&mut OsRng).identity_key();
+
+        let hash = identity_key.change_hash();
+        assert_eq!(hash, identity_key.change_hash());
+        assert_eq!(
+            hash,
+            IdentityKey::decode(&identity_key.serialize())
+                .unwrap()
+                .change_hash()
+        );
+        assert_ne!(hash, other_key.change_hash());
+
+        // Pin the construction so it stays stable across releases and platforms.
+        let fixed_key = IdentityKey::decode(&[5u8; 33]).unwrap();
+        let mut expected = Sha256::new();
+        expected.update(b"Signal_IdentityKey_ChangeHash");
+        expected.update(&[5u8; 33]);
+        assert_eq!(fixed_key.change_hash()[..], expected.finalize()[..16]);
+    }
+
+    #[test]
+    fn test_alternate_identity_signing() {
+        let primary = IdentityKeyPair::generate(&mut OsRng);
+        let secondary = IdentityKeyPair::generate(&mut OsRng);
+
+        let signature = primary
+            .sign_alternate_identity(secondary.identity_key(), &mut OsRng)
+            .unwrap();
+        assert!(primary
+            .identity_key()
+            .verify_alternate_identity(secondary.identity_key(), &signature)
+            .unwrap());
+        // Not symmetric
+        assert!(!secondary
+            .identity_key()
+            .verify_alternate_identity(primary.identity_key(), &signature)
+            .unwrap());
+
+        // A plain signature over the other key does not count as a linkage
+        let forged = primary
+            .private_key()
+            .calculate_signature(&secondary.identity_key().serialize(), &mut OsRng)
+            .unwrap();
+        assert!(!primary
+            .identity_key()
+            .verify_alternate_identity(secondary.identity_key(), &forged)
+            .unwrap());
+
+        let mut tampered = signature.clone();
+        tampered[5] ^= 1;
+        assert!(!primary
+            .identity_key()
+            .verify_alternate_identity(secondary.identity_key(), &tampered)
+            .unwrap());
+    }
+
     #[test]
     fn test_serialize_identity_key_pair() {
         let identity_key_pair = IdentityKeyPair::generate(&mut OsRng);
@@ -154,5 +299,37 @@ mod tests {
             identity_key_pair.private_key().serialize(),
             deserialized_identity_key_pair.private_key().serialize()
         );
+        assert_eq!(
+            deserialized_identity_key_pair
+                .private_key()
+                .public_key()
+                .unwrap(),
+            *deserialized_identity_key_pair.public_key()
+        );
+    }
+
+    #[test]
+    fn test_deserialize_invalid_identity_key_pair() {
+        let identity_key_pair = IdentityKeyPair::generate(&mut OsRng);
+        let serialized = identity_key_pair.serialize();
+
+        assert!(matches!(
+            IdentityKeyPair::try_from(&serialized[..serialized.len() - 1]),
+            Err(SignalProtocolError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            IdentityKeyPair::try_from(&b"not a key pair"[..]),
+            Err(SignalProtocolError::InvalidArgument(_))
+        ));
+
+        let other_key_pair = IdentityKeyPair::generate(&mut OsRng);
+        let mismatched = IdentityKeyPair::new(
+            *identity_key_pair.identity_key(),
+            *other_key_pair.private_key(),
+        );
+        assert!(matches!(
+            IdentityKeyPair::try_from(&mismatched.serialize()[..]),
+            Err(SignalProtocolError::InvalidArgument(_))
+        ));
     }
 }
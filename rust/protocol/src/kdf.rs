@@ -55,6 +55,33 @@ impl HKDF {
         self.expand(&prk, info, output_length)
     }
 
+    /// Derives one output for each of `infos`, of the corresponding length in `output_lengths`.
+    ///
+    /// Each output is identical to the one `derive_salted_secrets` would produce, but the
+    /// extract step is only performed once.
+    pub fn derive_salted_secrets_many(
+        self,
+        input_key_material: &[u8],
+        salt: &[u8],
+        infos: &[&[u8]],
+        output_lengths: &[usize],
+    ) -> Result<Vec<Box<[u8]>>> {
+        if infos.len() != output_lengths.len() {
+            return Err(SignalProtocolError::InvalidArgument(format!(
+                "{} labels but {} output lengths",
+                infos.len(),
+                output_lengths.len()
+            )));
+        }
+
+        let prk = self.extract(salt, input_key_material)?;
+        infos
+            .iter()
+            .zip(output_lengths)
+            .map(|(info, &output_length)| self.expand(&prk, info, output_length))
+            .collect()
+    }
+
     fn extract(
         self,
         salt: &[u8],
@@ -185,4 +212,36 @@ mod tests {
 
         assert_eq!(&okm[..], &output[..]);
     }
+
+    #[test]
+    fn test_vector_many_v3() {
+        let ikm = [0x0bu8; 22];
+        let salt = [
+            0x00u8, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
+        ];
+        let info = [0xf0u8, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9];
+        let okm = [
+            0x3cu8, 0xb2, 0x5f, 0x25, 0xfa, 0xac, 0xd5, 0x7a, 0x90, 0x43, 0x4f, 0x64, 0xd0, 0x36,
+            0x2f, 0x2a, 0x2d, 0x2d, 0x0a, 0x90, 0xcf, 0x1a, 0x5a, 0x4c, 0x5d, 0xb0, 0x2d, 0x56,
+            0xec, 0xc4, 0xc5, 0xbf, 0x34, 0x00, 0x72, 0x08, 0xd5, 0xb8, 0x87, 0x18, 0x58, 0x65,
+        ];
+
+        let kdf = HKDF::new(3).unwrap();
+        let outputs = kdf
+            .derive_salted_secrets_many(&ikm, &salt, &[&info[..], b"second"], &[okm.len(), 80])
+            .unwrap();
+
+        assert_eq!(outputs.len(), 2);
+        assert_eq!(&okm[..], &outputs[0][..]);
+        assert_eq!(
+            kdf.derive_salted_secrets(&ikm, &salt, b"second", 80)
+                .unwrap(),
+            outputs[1]
+        );
+
+        assert!(matches!(
+            kdf.derive_salted_secrets_many(&ikm, &salt, &[&info[..]], &[16, 16]),
+            Err(SignalProtocolError::InvalidArgument(_))
+        ));
+    }
 }
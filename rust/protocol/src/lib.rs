@@ -31,7 +31,7 @@ pub use {
     error::SignalProtocolError,
     fingerprint::{DisplayableFingerprint, Fingerprint, ScannableFingerprint},
     group_cipher::{
-        create_sender_key_distribution_message, group_decrypt, group_encrypt,
+        create_sender_key_distribution_message, group_decrypt, group_decrypt_batch, group_encrypt,
         process_sender_key_distribution_message,
     },
     identity_key::{IdentityKey, IdentityKeyPair},
@@ -41,23 +41,34 @@ pub use {
         SenderKeyDistributionMessage, SenderKeyMessage, SignalMessage,
     },
     ratchet::{
-        initialize_alice_session_record, initialize_bob_session_record,
+        initialize_alice_session_record, initialize_alice_session_record_with_timestamp,
+        initialize_bob_session_record, initialize_bob_session_record_with_timestamp,
         AliceSignalProtocolParameters, BobSignalProtocolParameters,
     },
     sealed_sender::{
         sealed_sender_decrypt, sealed_sender_decrypt_to_usmc, sealed_sender_encrypt,
-        SealedSenderDecryptionResult, SenderCertificate, ServerCertificate,
-        UnidentifiedSenderMessage, UnidentifiedSenderMessageContent,
+        sealed_sender_encrypt_from_usmc, SealedSenderDecryptionResult, SenderCertificate,
+        ServerCertificate, UnidentifiedSenderMessage, UnidentifiedSenderMessageContent,
     },
     sender_keys::{SenderKeyName, SenderKeyRecord},
-    session::{process_prekey, process_prekey_bundle},
+    session::{
+        archive_all_sessions_for_name, process_prekey, process_prekey_bundle,
+        process_prekey_bundle_with_timestamp, process_prekey_with_timestamp,
+    },
     session_cipher::{
-        message_decrypt, message_decrypt_prekey, message_decrypt_signal, message_encrypt,
+        estimate_ciphertext_size, max_plaintext_size, message_decrypt, message_decrypt_prekey,
+        message_decrypt_prekey_with_timestamp, message_decrypt_signal,
+        message_decrypt_with_timestamp, message_encrypt, plan_decrypt_prekey, DecryptPlan,
+    },
+    state::{
+        PreKeyBundle, PreKeyRecord, SessionRecord, SessionRecordIntegrity, SignedPreKeyRecord,
     },
-    state::{PreKeyBundle, PreKeyRecord, SessionRecord, SignedPreKeyRecord},
     storage::{
         Context, Direction, IdentityKeyStore, InMemIdentityKeyStore, InMemPreKeyStore,
         InMemSenderKeyStore, InMemSessionStore, InMemSignalProtocolStore, InMemSignedPreKeyStore,
         PreKeyStore, ProtocolStore, SenderKeyStore, SessionStore, SignedPreKeyStore,
     },
 };
+
+#[cfg(feature = "forensics")]
+pub use state::ExportedChainKey;
@@ -4,6 +4,7 @@
 //
 
 use crate::error::{Result, SignalProtocolError};
+use crate::sender_keys::SenderChainKey;
 use crate::IdentityKey;
 use crate::{curve, proto};
 
@@ -32,6 +33,22 @@ pub enum CiphertextMessageType {
     SenderKeyDistribution = 5,
 }
 
+impl CiphertextMessageType {
+    /// Returns whether `body` parses as a message of this type.
+    ///
+    /// A body that only parses as some other type suggests the type tag was tampered with.
+    pub fn matches_body(self, body: &[u8]) -> bool {
+        match self {
+            CiphertextMessageType::Whisper => SignalMessage::try_from(body).is_ok(),
+            CiphertextMessageType::PreKey => PreKeySignalMessage::try_from(body).is_ok(),
+            CiphertextMessageType::SenderKey => SenderKeyMessage::try_from(body).is_ok(),
+            CiphertextMessageType::SenderKeyDistribution => {
+                SenderKeyDistributionMessage::try_from(body).is_ok()
+            }
+        }
+    }
+}
+
 impl CiphertextMessage {
     pub fn message_type(&self) -> CiphertextMessageType {
         match self {
@@ -52,6 +69,22 @@ impl CiphertextMessage {
             CiphertextMessage::SenderKeyDistributionMessage(x) => x.serialized(),
         }
     }
+
+    /// Returns the encrypted payload of the message, without any of the surrounding framing.
+    ///
+    /// Sender key distribution messages carry no encrypted payload and produce an error.
+    pub fn inner_ciphertext(&self) -> Result<&[u8]> {
+        match self {
+            CiphertextMessage::SignalMessage(x) => Ok(x.body()),
+            CiphertextMessage::PreKeySignalMessage(x) => Ok(x.message().body()),
+            CiphertextMessage::SenderKeyMessage(x) => Ok(x.ciphertext()),
+            CiphertextMessage::SenderKeyDistributionMessage(_) => {
+                Err(SignalProtocolError::InvalidArgument(
+                    "sender key distribution messages have no ciphertext".to_owned(),
+                ))
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -59,14 +92,13 @@ pub struct SignalMessage {
     message_version: u8,
     sender_ratchet_key: curve::PublicKey,
     counter: u32,
-    #[allow(dead_code)]
     previous_counter: u32,
     ciphertext: Box<[u8]>,
     serialized: Box<[u8]>,
 }
 
 impl SignalMessage {
-    const MAC_LENGTH: usize = 8;
+    pub(crate) const MAC_LENGTH: usize = 8;
 
     pub fn new(
         message_version: u8,
@@ -121,6 +153,11 @@ impl SignalMessage {
         self.counter
     }
 
+    #[inline]
+    pub fn previous_counter(&self) -> u32 {
+        self.previous_counter
+    }
+
     #[inline]
     pub fn serialized(&self) -> &[u8] {
         &*self.serialized
@@ -508,12 +545,22 @@ pub struct SenderKeyDistributionMessage {
 }
 
 impl SenderKeyDistributionMessage {
+    /// The length in bytes of the chain key carried by a distribution message.
+    pub const CHAIN_KEY_LENGTH: usize = 32;
+
     pub fn new(
         id: u32,
         iteration: u32,
         chain_key: &[u8],
         signing_key: curve::PublicKey,
     ) -> Result<Self> {
+        if chain_key.len() != Self::CHAIN_KEY_LENGTH {
+            return Err(SignalProtocolError::InvalidArgument(format!(
+                "chain key must be {} bytes, got {}",
+                Self::CHAIN_KEY_LENGTH,
+                chain_key.len()
+            )));
+        }
         let proto_message = proto::wire::SenderKeyDistributionMessage {
             id: Some(id),
             iteration: Some(iteration),
@@ -560,6 +607,12 @@ impl SenderKeyDistributionMessage {
         Ok(&self.signing_key)
     }
 
+    /// Derives the group-state key for the chain key carried by this message, so that every
+    /// member who received it arrives at the same key.
+    pub fn derive_group_key(&self) -> Result<Vec<u8>> {
+        SenderChainKey::new(self.iteration, self.chain_key.clone())?.group_key()
+    }
+
     #[inline]
     pub fn serialized(&self) -> &[u8] {
         &*self.serialized
@@ -609,7 +662,7 @@ impl TryFrom<&[u8]> for SenderKeyDistributionMessage {
             .signing_key
             .ok_or(SignalProtocolError::InvalidProtobufEncoding)?;
 
-        if chain_key.len() != 32 || signing_key.len() != 33 {
+        if chain_key.len() != Self::CHAIN_KEY_LENGTH || signing_key.len() != 33 {
             return Err(SignalProtocolError::InvalidProtobufEncoding);
         }
 
@@ -733,6 +786,114 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_message_type_matches_body() {
+        let mut csprng = OsRng;
+        let message = create_signal_message(&mut csprng);
+        let identity_key_pair = curve::KeyPair::generate(&mut csprng);
+        let base_key_pair = curve::KeyPair::generate(&mut csprng);
+        let pre_key_signal_message = PreKeySignalMessage::new(
+            3,
+            365,
+            None,
+            97,
+            base_key_pair.public_key,
+            identity_key_pair.public_key.into(),
+            message.clone(),
+        )
+        .unwrap();
+
+        assert!(CiphertextMessageType::Whisper.matches_body(message.as_ref()));
+        assert!(CiphertextMessageType::PreKey.matches_body(pre_key_signal_message.as_ref()));
+
+        assert!(!CiphertextMessageType::PreKey.matches_body(message.as_ref()));
+        assert!(!CiphertextMessageType::Whisper.matches_body(pre_key_signal_message.as_ref()));
+        assert!(!CiphertextMessageType::SenderKey.matches_body(message.as_ref()));
+        assert!(!CiphertextMessageType::SenderKeyDistribution.matches_body(message.as_ref()));
+    }
+
+    #[test]
+    fn test_inner_ciphertext() {
+        let mut csprng = OsRng;
+        let message = create_signal_message(&mut csprng);
+        let ciphertext = message.body().to_vec();
+
+        let identity_key_pair = curve::KeyPair::generate(&mut csprng);
+        let base_key_pair = curve::KeyPair::generate(&mut csprng);
+        let pre_key_signal_message = PreKeySignalMessage::new(
+            3,
+            365,
+            None,
+            97,
+            base_key_pair.public_key,
+            identity_key_pair.public_key.into(),
+            message.clone(),
+        )
+        .unwrap();
+
+        let signature_key_pair = curve::KeyPair::generate(&mut csprng);
+        let sender_key_message = SenderKeyMessage::new(
+            42,
+            7,
+            &[1u8, 2, 3],
+            &mut csprng,
+            &signature_key_pair.private_key,
+        )
+        .unwrap();
+        let sender_key_distribution_message =
+            SenderKeyDistributionMessage::new(42, 7, &[0u8; 32], signature_key_pair.public_key)
+                .unwrap();
+
+        assert_eq!(
+            CiphertextMessage::SignalMessage(message)
+                .inner_ciphertext()
+                .unwrap(),
+            &ciphertext[..]
+        );
+        assert_eq!(
+            CiphertextMessage::PreKeySignalMessage(pre_key_signal_message)
+                .inner_ciphertext()
+                .unwrap(),
+            &ciphertext[..]
+        );
+        assert_eq!(
+            CiphertextMessage::SenderKeyMessage(sender_key_message)
+                .inner_ciphertext()
+                .unwrap(),
+            &[1u8, 2, 3]
+        );
+        assert!(
+            CiphertextMessage::SenderKeyDistributionMessage(sender_key_distribution_message)
+                .inner_ciphertext()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_sender_key_distribution_message_chain_key_length() {
+        let mut csprng = OsRng;
+        let signing_key = curve::KeyPair::generate(&mut csprng).public_key;
+
+        let message = SenderKeyDistributionMessage::new(
+            42,
+            7,
+            &[0x11u8; SenderKeyDistributionMessage::CHAIN_KEY_LENGTH],
+            signing_key,
+        )
+        .expect("valid chain key length");
+        assert_eq!(
+            message.chain_key().unwrap().len(),
+            SenderKeyDistributionMessage::CHAIN_KEY_LENGTH
+        );
+
+        for bad_length in &[0, 16, 31, 33] {
+            assert!(matches!(
+                SenderKeyDistributionMessage::new(42, 7, &vec![0x11u8; *bad_length], signing_key),
+                Err(SignalProtocolError::InvalidArgument(_))
+            ));
+        }
+    }
+
     #[test]
     fn test_sender_key_message_serialize_deserialize() {
         let mut csprng = OsRng;
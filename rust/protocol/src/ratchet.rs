@@ -13,6 +13,7 @@ use crate::error::Result;
 use crate::proto::storage::SessionStructure;
 use crate::protocol::CIPHERTEXT_MESSAGE_CURRENT_VERSION;
 use crate::state::{SessionRecord, SessionState};
+use crate::utils::current_timestamp;
 use rand::{CryptoRng, Rng};
 
 fn derive_keys(secret_input: &[u8]) -> Result<(RootKey, ChainKey)> {
@@ -26,8 +27,17 @@ fn derive_keys(secret_input: &[u8]) -> Result<(RootKey, ChainKey)> {
     Ok((root_key, chain_key))
 }
 
+/// Both sides derive the same initial root key, but Alice immediately ratchets hers forward, so
+/// the key confirmation value has to be computed and kept at initialization.
+fn derive_key_confirmation(root_key: &RootKey, alice_base_key: &[u8]) -> Result<Vec<u8>> {
+    let kdf = crate::kdf::HKDF::new(3)?;
+    let info = [b"Signal Key Confirmation".as_ref(), alice_base_key].concat();
+    Ok(kdf.derive_secrets(root_key.key(), &info, 32)?.into_vec())
+}
+
 pub(crate) fn initialize_alice_session<R: Rng + CryptoRng>(
     parameters: &AliceSignalProtocolParameters,
+    creation_timestamp: u64,
     mut csprng: &mut R,
 ) -> Result<SessionState> {
     let local_identity = parameters.our_identity_key_pair().identity_key();
@@ -64,6 +74,13 @@ pub(crate) fn initialize_alice_session<R: Rng + CryptoRng>(
 
     let (root_key, chain_key) = derive_keys(&secrets)?;
 
+    let alice_base_key = parameters
+        .our_base_key_pair()
+        .public_key
+        .serialize()
+        .to_vec();
+    let key_confirmation = derive_key_confirmation(&root_key, &alice_base_key)?;
+
     let (sending_chain_root_key, sending_chain_chain_key) = root_key.create_chain(
         parameters.their_ratchet_key(),
         &sending_ratchet_key.private_key,
@@ -81,7 +98,9 @@ pub(crate) fn initialize_alice_session<R: Rng + CryptoRng>(
         remote_registration_id: 0,
         local_registration_id: 0,
         needs_refresh: false,
-        alice_base_key: vec![],
+        alice_base_key,
+        creation_timestamp,
+        key_confirmation,
     };
 
     let mut session = SessionState::new(session);
@@ -94,6 +113,7 @@ pub(crate) fn initialize_alice_session<R: Rng + CryptoRng>(
 
 pub(crate) fn initialize_bob_session(
     parameters: &BobSignalProtocolParameters,
+    creation_timestamp: u64,
 ) -> Result<SessionState> {
     let local_identity = parameters.our_identity_key_pair().identity_key();
 
@@ -125,6 +145,9 @@ pub(crate) fn initialize_bob_session(
 
     let (root_key, chain_key) = derive_keys(&secrets)?;
 
+    let alice_base_key = parameters.their_base_key().serialize().to_vec();
+    let key_confirmation = derive_key_confirmation(&root_key, &alice_base_key)?;
+
     let session = SessionStructure {
         session_version: CIPHERTEXT_MESSAGE_CURRENT_VERSION as u32,
         local_identity_public: local_identity.public_key().serialize().to_vec(),
@@ -137,7 +160,9 @@ pub(crate) fn initialize_bob_session(
         remote_registration_id: 0,
         local_registration_id: 0,
         needs_refresh: false,
-        alice_base_key: vec![],
+        alice_base_key,
+        creation_timestamp,
+        key_confirmation,
     };
 
     let mut session = SessionState::new(session);
@@ -150,14 +175,38 @@ pub(crate) fn initialize_bob_session(
 pub fn initialize_alice_session_record<R: Rng + CryptoRng>(
     parameters: &AliceSignalProtocolParameters,
     csprng: &mut R,
+) -> Result<SessionRecord> {
+    initialize_alice_session_record_with_timestamp(parameters, current_timestamp(), csprng)
+}
+
+/// Like [initialize_alice_session_record], but records `creation_timestamp` (milliseconds since
+/// the Unix epoch) as the session's creation time.
+pub fn initialize_alice_session_record_with_timestamp<R: Rng + CryptoRng>(
+    parameters: &AliceSignalProtocolParameters,
+    creation_timestamp: u64,
+    csprng: &mut R,
 ) -> Result<SessionRecord> {
     Ok(SessionRecord::new(initialize_alice_session(
-        parameters, csprng,
+        parameters,
+        creation_timestamp,
+        csprng,
     )?))
 }
 
 pub fn initialize_bob_session_record(
     parameters: &BobSignalProtocolParameters,
 ) -> Result<SessionRecord> {
-    Ok(SessionRecord::new(initialize_bob_session(parameters)?))
+    initialize_bob_session_record_with_timestamp(parameters, current_timestamp())
+}
+
+/// Like [initialize_bob_session_record], but records `creation_timestamp` (milliseconds since the
+/// Unix epoch) as the session's creation time.
+pub fn initialize_bob_session_record_with_timestamp(
+    parameters: &BobSignalProtocolParameters,
+    creation_timestamp: u64,
+) -> Result<SessionRecord> {
+    Ok(SessionRecord::new(initialize_bob_session(
+        parameters,
+        creation_timestamp,
+    )?))
 }
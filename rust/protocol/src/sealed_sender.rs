@@ -258,8 +258,22 @@ impl SenderCertificate {
     }
 
     pub fn validate(&self, trust_root: &PublicKey, validation_time: u64) -> Result<bool> {
+        match self.validate_chain(trust_root, validation_time) {
+            Ok(()) => Ok(true),
+            Err(SignalProtocolError::SignatureValidationFailed)
+            | Err(SignalProtocolError::ExpiredCertificate(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Like `validate`, but reports why validation failed.
+    ///
+    /// Returns `SignatureValidationFailed` if the server certificate is revoked or not signed by
+    /// `trust_root`, or if this certificate is not signed by the server certificate's key.
+    /// Returns `ExpiredCertificate` if the certificate expired before `validation_time`.
+    pub fn validate_chain(&self, trust_root: &PublicKey, validation_time: u64) -> Result<()> {
         if !self.signer.validate(&trust_root)? {
-            return Ok(false);
+            return Err(SignalProtocolError::SignatureValidationFailed);
         }
 
         if !self
@@ -267,14 +281,14 @@ impl SenderCertificate {
             .public_key()?
             .verify_signature(&self.certificate, &self.signature)?
         {
-            return Ok(false);
+            return Err(SignalProtocolError::SignatureValidationFailed);
         }
 
         if validation_time > self.expiration {
-            return Ok(false);
+            return Err(SignalProtocolError::ExpiredCertificate(self.expiration));
         }
 
-        Ok(true)
+        Ok(())
     }
 
     pub fn signer(&self) -> Result<&ServerCertificate> {
@@ -297,6 +311,16 @@ impl SenderCertificate {
         Ok(self.sender_e164.as_deref())
     }
 
+    /// Returns true if the sender UUID is in the hyphenated 8-4-4-4-12 hex form, in either case.
+    pub fn has_valid_uuid(&self) -> Result<bool> {
+        let uuid = self.sender_uuid.as_bytes();
+        Ok(uuid.len() == 36
+            && uuid.iter().enumerate().all(|(i, c)| match i {
+                8 | 13 | 18 | 23 => *c == b'-',
+                _ => c.is_ascii_hexdigit(),
+            }))
+    }
+
     pub fn expiration(&self) -> Result<u64> {
         Ok(self.expiration)
     }
@@ -575,6 +599,37 @@ pub async fn sealed_sender_encrypt<R: Rng + CryptoRng>(
     rng: &mut R,
 ) -> Result<Vec<u8>> {
     let message = message_encrypt(ptext, destination, session_store, identity_store, ctx).await?;
+    let usmc = UnidentifiedSenderMessageContent::new(
+        message.message_type(),
+        sender_cert.clone(),
+        message.serialize().to_vec(),
+    )?;
+    sealed_sender_encrypt_from_usmc(destination, &usmc, session_store, identity_store, ctx, rng)
+        .await
+}
+
+/// Seals already-encrypted message content for `destination`.
+///
+/// Fails with [SessionNotFound](SignalProtocolError::SessionNotFound) unless `session_store` has
+/// a current session for `destination` and its identity is known to `identity_store`.
+pub async fn sealed_sender_encrypt_from_usmc<R: Rng + CryptoRng>(
+    destination: &ProtocolAddress,
+    usmc: &UnidentifiedSenderMessageContent,
+    session_store: &mut dyn SessionStore,
+    identity_store: &mut dyn IdentityKeyStore,
+    ctx: Context,
+    rng: &mut R,
+) -> Result<Vec<u8>> {
+    let has_session = session_store
+        .load_session(destination, ctx)
+        .await?
+        .map_or(false, |record| record.has_current_session_state());
+    if !has_session {
+        return Err(SignalProtocolError::SessionNotFound(format!(
+            "{}",
+            destination
+        )));
+    }
 
     let our_identity = identity_store.get_identity_key_pair(ctx).await?;
     let their_identity = identity_store
@@ -604,11 +659,6 @@ pub async fn sealed_sender_encrypt<R: Rng + CryptoRng>(
         &static_key_ctext,
     )?;
 
-    let usmc = UnidentifiedSenderMessageContent::new(
-        message.message_type(),
-        sender_cert.clone(),
-        message.serialize().to_vec(),
-    )?;
     let message_data = crypto::aes256_ctr_hmacsha256_encrypt(
         usmc.serialized()?,
         &static_keys.cipher_key()?,
@@ -641,7 +691,12 @@ pub async fn sealed_sender_decrypt_to_usmc(
         usm.encrypted_static()?,
         &eph_keys.cipher_key()?,
         &eph_keys.mac_key()?,
-    )?;
+    )
+    .map_err(|_| {
+        SignalProtocolError::InvalidSealedSenderMessage(
+            "failed to decrypt sender static key".to_string(),
+        )
+    })?;
 
     let static_key = PublicKey::deserialize(&static_key_bytes)?;
 
@@ -9,6 +9,7 @@ use crate::curve;
 use crate::error::{Result, SignalProtocolError};
 use crate::kdf::HKDF;
 use crate::proto::storage as storage_proto;
+use crate::protocol::SenderKeyDistributionMessage;
 use crate::ProtocolAddress;
 
 use prost::Message;
@@ -40,6 +41,72 @@ impl SenderKeyName {
     pub fn sender(&self) -> Result<ProtocolAddress> {
         Ok(self.sender.clone())
     }
+
+    /// Returns the canonical string used to key sender-key storage for this name.
+    ///
+    /// The format is `<group id>:<sender name>:<device id>`, where any `\` or `:` inside the
+    /// group id or sender name is escaped with a preceding `\`.
+    pub fn storage_key(&self) -> String {
+        let mut key = String::new();
+        escape_storage_key_component(&self.group_id, &mut key);
+        key.push(':');
+        escape_storage_key_component(self.sender.name(), &mut key);
+        key.push(':');
+        key.push_str(&self.sender.device_id().to_string());
+        key
+    }
+
+    /// Parses a string produced by [`storage_key`](Self::storage_key).
+    pub fn from_storage_key(key: &str) -> Result<Self> {
+        let malformed = || {
+            SignalProtocolError::InvalidArgument(format!(
+                "malformed sender key storage key {:?}",
+                key
+            ))
+        };
+
+        let mut components = vec![String::new()];
+        let mut chars = key.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some(escaped @ '\\') | Some(escaped @ ':') => {
+                        components.last_mut().expect("non-empty").push(escaped)
+                    }
+                    _ => return Err(malformed()),
+                },
+                ':' => components.push(String::new()),
+                c => components.last_mut().expect("non-empty").push(c),
+            }
+        }
+
+        if components.len() != 3 {
+            return Err(malformed());
+        }
+        let device_id = components.pop().expect("three components");
+        let sender_name = components.pop().expect("three components");
+        let group_id = components.pop().expect("three components");
+
+        let parsed_device_id: u32 = device_id.parse().map_err(|_| malformed())?;
+        if parsed_device_id.to_string() != device_id {
+            // Reject non-canonical spellings such as leading zeros or a '+' sign.
+            return Err(malformed());
+        }
+
+        Self::new(
+            group_id,
+            ProtocolAddress::new(sender_name, parsed_device_id),
+        )
+    }
+}
+
+fn escape_storage_key_component(component: &str, out: &mut String) {
+    for c in component.chars() {
+        if c == '\\' || c == ':' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -105,6 +172,7 @@ pub struct SenderChainKey {
 impl SenderChainKey {
     const MESSAGE_KEY_SEED: u8 = 0x01;
     const CHAIN_KEY_SEED: u8 = 0x02;
+    const GROUP_KEY_INFO: &'static [u8] = b"Signal_Group_State_Key";
 
     pub fn new(iteration: u32, chain_key: Vec<u8>) -> Result<Self> {
         Ok(Self {
@@ -135,6 +203,15 @@ impl SenderChainKey {
         )?)
     }
 
+    /// Derives a key for encrypting shared group state. It is independent of the message keys
+    /// derived from this chain key.
+    pub fn group_key(&self) -> Result<Vec<u8>> {
+        let hkdf = HKDF::new(3)?;
+        Ok(hkdf
+            .derive_secrets(&self.chain_key, Self::GROUP_KEY_INFO, 32)?
+            .to_vec())
+    }
+
     fn get_derivative(&self, label: u8) -> Result<Vec<u8>> {
         let label = [label];
         Ok(hmac_sha256(&self.chain_key, &label)?.to_vec())
@@ -243,6 +320,39 @@ impl SenderKeyState {
         Ok(self.state.clone())
     }
 
+    pub fn derive_message_key_at(&self, iteration: u32) -> Result<SenderMessageKey> {
+        let sender_chain_key = self.sender_chain_key()?;
+
+        if sender_chain_key.iteration()? > iteration {
+            if let Some(smk) = self
+                .state
+                .sender_message_keys
+                .iter()
+                .find(|x| x.iteration == iteration)
+            {
+                return SenderMessageKey::from_protobuf(smk.clone());
+            } else {
+                return Err(SignalProtocolError::DuplicatedMessage(
+                    sender_chain_key.iteration()?,
+                    iteration,
+                ));
+            }
+        }
+
+        let jump = (iteration - sender_chain_key.iteration()?) as usize;
+        if jump > consts::MAX_FORWARD_JUMPS {
+            return Err(SignalProtocolError::InvalidMessage(
+                "message from too far into the future",
+            ));
+        }
+
+        let mut sender_chain_key = sender_chain_key;
+        while sender_chain_key.iteration()? < iteration {
+            sender_chain_key = sender_chain_key.next()?;
+        }
+        sender_chain_key.sender_message_key()
+    }
+
     pub fn add_sender_message_key(&mut self, sender_message_key: &SenderMessageKey) -> Result<()> {
         self.state
             .sender_message_keys
@@ -304,6 +414,42 @@ impl SenderKeyRecord {
         Err(SignalProtocolError::NoSenderKeyState)
     }
 
+    /// Returns the message key for `iteration` of the current sender key state, without
+    /// advancing the chain or consuming any stored message keys.
+    pub fn derive_message_key_at(&self, iteration: u32) -> Result<SenderMessageKey> {
+        self.states
+            .front()
+            .ok_or(SignalProtocolError::NoSenderKeyState)?
+            .derive_message_key_at(iteration)
+    }
+
+    /// Returns how many more messages can be sent on the current sender key state before its
+    /// chain reaches the iteration limit and the sender key must be rotated.
+    pub fn messages_until_rotation(&self) -> Result<u32> {
+        let state = self
+            .states
+            .front()
+            .ok_or(SignalProtocolError::NoSenderKeyState)?;
+        Ok(consts::MAX_SENDER_KEY_ITERATION.saturating_sub(state.sender_chain_key()?.iteration()?))
+    }
+
+    /// Builds a distribution message for the current sender key state, starting at the
+    /// chain's current iteration.
+    pub fn create_distribution_message(&self) -> Result<SenderKeyDistributionMessage> {
+        let state = self
+            .states
+            .front()
+            .ok_or(SignalProtocolError::NoSenderKeyState)?;
+        let sender_chain_key = state.sender_chain_key()?;
+
+        SenderKeyDistributionMessage::new(
+            state.sender_key_id()?,
+            sender_chain_key.iteration()?,
+            &sender_chain_key.seed()?,
+            state.signing_key_public()?,
+        )
+    }
+
     pub fn sender_key_state_for_keyid(&mut self, key_id: u32) -> Result<&mut SenderKeyState> {
         for i in 0..self.states.len() {
             if self.states[i].sender_key_id()? == key_id {
@@ -370,3 +516,53 @@ impl SenderKeyRecord {
         Ok(buf)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sender_key_name_storage_key() -> Result<()> {
+        let name = SenderKeyName::new(
+            "group".to_owned(),
+            ProtocolAddress::new("+14151111111".to_owned(), 3),
+        )?;
+        assert_eq!(name.storage_key(), "group:+14151111111:3");
+        assert_eq!(SenderKeyName::from_storage_key(&name.storage_key())?, name);
+
+        let awkward = SenderKeyName::new(
+            "a:b\\c".to_owned(),
+            ProtocolAddress::new(":\\".to_owned(), 0),
+        )?;
+        assert_eq!(awkward.storage_key(), "a\\:b\\\\c:\\:\\\\:0");
+        assert_eq!(
+            SenderKeyName::from_storage_key(&awkward.storage_key())?,
+            awkward
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sender_key_name_malformed_storage_key() {
+        for key in &[
+            "",
+            "group:sender",
+            "group:sender:1:2",
+            "group:sender:",
+            "group:sender:01",
+            "group:sender:+1",
+            "group:sender:4294967296",
+            "gr\\oup:sender:1",
+            "group:sender\\",
+        ] {
+            assert!(
+                matches!(
+                    SenderKeyName::from_storage_key(key),
+                    Err(SignalProtocolError::InvalidArgument(_))
+                ),
+                "{:?} should be rejected",
+                key
+            );
+        }
+    }
+}
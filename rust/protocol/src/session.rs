@@ -15,6 +15,7 @@ use crate::ratchet;
 use crate::ratchet::{AliceSignalProtocolParameters, BobSignalProtocolParameters};
 use crate::state::{PreKeyBundle, PreKeyId};
 use crate::storage::Direction;
+use crate::utils::current_timestamp;
 use rand::{CryptoRng, Rng};
 
 /*
@@ -34,6 +35,32 @@ pub async fn process_prekey(
     pre_key_store: &mut dyn PreKeyStore,
     signed_prekey_store: &mut dyn SignedPreKeyStore,
     ctx: Context,
+) -> Result<Option<PreKeyId>> {
+    process_prekey_with_timestamp(
+        message,
+        remote_address,
+        session_record,
+        identity_store,
+        pre_key_store,
+        signed_prekey_store,
+        current_timestamp(),
+        ctx,
+    )
+    .await
+}
+
+/// Like [process_prekey], but records `timestamp` (milliseconds since the Unix epoch) as the
+/// creation time of any new session.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_prekey_with_timestamp(
+    message: &PreKeySignalMessage,
+    remote_address: &ProtocolAddress,
+    session_record: &mut SessionRecord,
+    identity_store: &mut dyn IdentityKeyStore,
+    pre_key_store: &mut dyn PreKeyStore,
+    signed_prekey_store: &mut dyn SignedPreKeyStore,
+    timestamp: u64,
+    ctx: Context,
 ) -> Result<Option<PreKeyId>> {
     let their_identity_key = message.identity_key();
 
@@ -57,6 +84,7 @@ pub async fn process_prekey(
         signed_prekey_store,
         pre_key_store,
         identity_store,
+        timestamp,
         ctx,
     )
     .await?;
@@ -74,6 +102,7 @@ async fn process_prekey_v3(
     signed_prekey_store: &mut dyn SignedPreKeyStore,
     pre_key_store: &mut dyn PreKeyStore,
     identity_store: &mut dyn IdentityKeyStore,
+    timestamp: u64,
     ctx: Context,
 ) -> Result<Option<PreKeyId>> {
     if session_record.has_session_state(
@@ -112,11 +141,10 @@ async fn process_prekey_v3(
 
     session_record.archive_current_state()?;
 
-    let mut new_session = ratchet::initialize_bob_session(&parameters)?;
+    let mut new_session = ratchet::initialize_bob_session(&parameters, timestamp)?;
 
     new_session.set_local_registration_id(identity_store.get_local_registration_id(ctx).await?)?;
     new_session.set_remote_registration_id(message.registration_id())?;
-    new_session.set_alice_base_key(&message.base_key().serialize())?;
 
     session_record.promote_state(new_session)?;
 
@@ -128,6 +156,29 @@ pub async fn process_prekey_bundle<R: Rng + CryptoRng>(
     session_store: &mut dyn SessionStore,
     identity_store: &mut dyn IdentityKeyStore,
     bundle: &PreKeyBundle,
+    csprng: &mut R,
+    ctx: Context,
+) -> Result<()> {
+    process_prekey_bundle_with_timestamp(
+        remote_address,
+        session_store,
+        identity_store,
+        bundle,
+        current_timestamp(),
+        csprng,
+        ctx,
+    )
+    .await
+}
+
+/// Like [process_prekey_bundle], but records `timestamp` (milliseconds since the Unix epoch) as
+/// the new session's creation time.
+pub async fn process_prekey_bundle_with_timestamp<R: Rng + CryptoRng>(
+    remote_address: &ProtocolAddress,
+    session_store: &mut dyn SessionStore,
+    identity_store: &mut dyn IdentityKeyStore,
+    bundle: &PreKeyBundle,
+    timestamp: u64,
     mut csprng: &mut R,
     ctx: Context,
 ) -> Result<()> {
@@ -172,7 +223,7 @@ pub async fn process_prekey_bundle<R: Rng + CryptoRng>(
         their_signed_prekey,
     );
 
-    let mut session = ratchet::initialize_alice_session(&parameters, csprng)?;
+    let mut session = ratchet::initialize_alice_session(&parameters, timestamp, csprng)?;
 
     log::info!(
         "set_unacknowledged_pre_key_message for: {} with preKeyId: {}",
@@ -188,7 +239,6 @@ pub async fn process_prekey_bundle<R: Rng + CryptoRng>(
 
     session.set_local_registration_id(identity_store.get_local_registration_id(ctx).await?)?;
     session.set_remote_registration_id(bundle.registration_id()?)?;
-    session.set_alice_base_key(&our_base_key_pair.public_key.serialize())?;
 
     identity_store
         .save_identity(&remote_address, their_identity_key, ctx)
@@ -202,3 +252,23 @@ pub async fn process_prekey_bundle<R: Rng + CryptoRng>(
 
     Ok(())
 }
+
+/// Archives the current state of every session stored for `name`, as when that user's identity
+/// key changes.
+pub async fn archive_all_sessions_for_name(
+    name: &str,
+    session_store: &mut dyn SessionStore,
+    ctx: Context,
+) -> Result<()> {
+    for device_id in session_store.get_device_ids_for_name(name, ctx).await? {
+        let address = ProtocolAddress::new(name.to_owned(), device_id);
+        if let Some(mut session_record) = session_store.load_session(&address, ctx).await? {
+            session_record.archive_current_state()?;
+            session_store
+                .store_session(&address, &session_record, ctx)
+                .await?;
+        }
+    }
+
+    Ok(())
+}
@@ -4,8 +4,8 @@
 //
 
 use crate::{
-    Context, IdentityKeyStore, PreKeyStore, ProtocolAddress, SessionRecord, SessionStore,
-    SignalProtocolError, SignedPreKeyStore,
+    Context, IdentityKey, IdentityKeyStore, PreKeyStore, ProtocolAddress, SessionRecord,
+    SessionStore, SignalProtocolError, SignedPreKeyStore,
 };
 
 use crate::consts::MAX_FORWARD_JUMPS;
@@ -15,7 +15,7 @@ use crate::error::Result;
 use crate::protocol::{CiphertextMessage, PreKeySignalMessage, SignalMessage};
 use crate::ratchet::{ChainKey, MessageKeys};
 use crate::session;
-use crate::state::SessionState;
+use crate::state::{PreKeyId, SessionState};
 use crate::storage::Direction;
 
 use rand::{CryptoRng, Rng};
@@ -137,6 +137,34 @@ pub async fn message_decrypt<R: Rng + CryptoRng>(
     signed_pre_key_store: &mut dyn SignedPreKeyStore,
     csprng: &mut R,
     ctx: Context,
+) -> Result<Vec<u8>> {
+    message_decrypt_with_timestamp(
+        ciphertext,
+        remote_address,
+        session_store,
+        identity_store,
+        pre_key_store,
+        signed_pre_key_store,
+        current_timestamp(),
+        csprng,
+        ctx,
+    )
+    .await
+}
+
+/// Like [message_decrypt], but records `timestamp` (milliseconds since the Unix epoch) as the
+/// creation time of any session set up by a PreKeySignalMessage.
+#[allow(clippy::too_many_arguments)]
+pub async fn message_decrypt_with_timestamp<R: Rng + CryptoRng>(
+    ciphertext: &CiphertextMessage,
+    remote_address: &ProtocolAddress,
+    session_store: &mut dyn SessionStore,
+    identity_store: &mut dyn IdentityKeyStore,
+    pre_key_store: &mut dyn PreKeyStore,
+    signed_pre_key_store: &mut dyn SignedPreKeyStore,
+    timestamp: u64,
+    csprng: &mut R,
+    ctx: Context,
 ) -> Result<Vec<u8>> {
     match ciphertext {
         CiphertextMessage::SignalMessage(m) => {
@@ -151,13 +179,14 @@ pub async fn message_decrypt<R: Rng + CryptoRng>(
             .await
         }
         CiphertextMessage::PreKeySignalMessage(m) => {
-            message_decrypt_prekey(
+            message_decrypt_prekey_with_timestamp(
                 m,
                 remote_address,
                 session_store,
                 identity_store,
                 pre_key_store,
                 signed_pre_key_store,
+                timestamp,
                 csprng,
                 ctx,
             )
@@ -178,19 +207,48 @@ pub async fn message_decrypt_prekey<R: Rng + CryptoRng>(
     signed_pre_key_store: &mut dyn SignedPreKeyStore,
     csprng: &mut R,
     ctx: Context,
+) -> Result<Vec<u8>> {
+    message_decrypt_prekey_with_timestamp(
+        ciphertext,
+        remote_address,
+        session_store,
+        identity_store,
+        pre_key_store,
+        signed_pre_key_store,
+        current_timestamp(),
+        csprng,
+        ctx,
+    )
+    .await
+}
+
+/// Like [message_decrypt_prekey], but records `timestamp` (milliseconds since the Unix epoch) as
+/// the creation time of any new session.
+#[allow(clippy::too_many_arguments)]
+pub async fn message_decrypt_prekey_with_timestamp<R: Rng + CryptoRng>(
+    ciphertext: &PreKeySignalMessage,
+    remote_address: &ProtocolAddress,
+    session_store: &mut dyn SessionStore,
+    identity_store: &mut dyn IdentityKeyStore,
+    pre_key_store: &mut dyn PreKeyStore,
+    signed_pre_key_store: &mut dyn SignedPreKeyStore,
+    timestamp: u64,
+    csprng: &mut R,
+    ctx: Context,
 ) -> Result<Vec<u8>> {
     let mut session_record = session_store
         .load_session(&remote_address, ctx)
         .await?
         .unwrap_or_else(SessionRecord::new_fresh);
 
-    let pre_key_id = session::process_prekey(
+    let pre_key_id = session::process_prekey_with_timestamp(
         ciphertext,
         &remote_address,
         &mut session_record,
         identity_store,
         pre_key_store,
         signed_pre_key_store,
+        timestamp,
         ctx,
     )
     .await?;
@@ -213,6 +271,130 @@ pub async fn message_decrypt_prekey<R: Rng + CryptoRng>(
     Ok(ptext)
 }
 
+/// The store writes [message_decrypt_prekey] makes when it succeeds, beyond saving the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecryptPlan {
+    creates_session: bool,
+    consumed_pre_key_id: Option<PreKeyId>,
+    changes_identity: bool,
+}
+
+impl DecryptPlan {
+    /// Whether a new session is established rather than an existing one being reused.
+    pub fn creates_session(&self) -> bool {
+        self.creates_session
+    }
+
+    /// The one-time pre-key that will be removed from the pre-key store, if any.
+    pub fn consumed_pre_key_id(&self) -> Option<PreKeyId> {
+        self.consumed_pre_key_id
+    }
+
+    /// Whether the identity saved for the sender will be replaced.
+    pub fn changes_identity(&self) -> bool {
+        self.changes_identity
+    }
+}
+
+/// Predicts the store writes [message_decrypt_prekey] will make for `ciphertext`, without
+/// decrypting it or touching any store.
+///
+/// `session_record` and `stored_identity` are what the session and identity stores currently
+/// hold for the sender. The plan only holds if decryption then succeeds.
+pub fn plan_decrypt_prekey(
+    ciphertext: &PreKeySignalMessage,
+    session_record: Option<&SessionRecord>,
+    stored_identity: Option<&IdentityKey>,
+) -> Result<DecryptPlan> {
+    let already_processed = match session_record {
+        Some(record) => record.pre_key_message_already_processed(ciphertext)?,
+        None => false,
+    };
+
+    Ok(DecryptPlan {
+        creates_session: !already_processed,
+        consumed_pre_key_id: if already_processed {
+            None
+        } else {
+            ciphertext.pre_key_id()
+        },
+        changes_identity: stored_identity != Some(ciphertext.identity_key()),
+    })
+}
+
+/// Returns the serialized size of the message [message_encrypt] would produce next for a
+/// plaintext of `ptext_len` bytes, given the sender's current `session_record`.
+///
+/// The result is exact for the next message. Counters grow as more messages are sent, so later
+/// messages on the same session may be a few bytes larger.
+pub fn estimate_ciphertext_size(ptext_len: usize, session_record: &SessionRecord) -> Result<usize> {
+    use prost::encoding::{encoded_len_varint, key_len};
+
+    fn bytes_field_len(tag: u32, len: usize) -> usize {
+        key_len(tag) + encoded_len_varint(len as u64) + len
+    }
+    fn uint32_field_len(tag: u32, value: u32) -> usize {
+        key_len(tag) + encoded_len_varint(value as u64)
+    }
+
+    let session_state = session_record.session_state()?;
+
+    // AES-256-CBC with PKCS#7 padding always adds between 1 and 16 bytes.
+    let ctext_len = (ptext_len / 16 + 1) * 16;
+
+    let signal_message_len = 1
+        + bytes_field_len(1, session_state.sender_ratchet_key()?.serialize().len())
+        + uint32_field_len(2, session_state.get_sender_chain_key()?.index())
+        + uint32_field_len(3, session_state.previous_counter()?)
+        + bytes_field_len(4, ctext_len)
+        + SignalMessage::MAC_LENGTH;
+
+    let items = match session_state.unacknowledged_pre_key_message_items()? {
+        Some(items) => items,
+        None => return Ok(signal_message_len),
+    };
+
+    let pre_key_id_len = match items.pre_key_id()? {
+        Some(id) => uint32_field_len(1, id),
+        None => 0,
+    };
+    let pre_key_message_len = 1
+        + uint32_field_len(5, session_state.local_registration_id()?)
+        + pre_key_id_len
+        + uint32_field_len(6, items.signed_pre_key_id()?)
+        + bytes_field_len(2, items.base_key()?.serialize().len())
+        + bytes_field_len(3, session_state.local_identity_key()?.serialize().len())
+        + bytes_field_len(4, signal_message_len);
+    Ok(pre_key_message_len)
+}
+
+/// Returns the largest plaintext length whose next message, as computed by
+/// [estimate_ciphertext_size], fits within `ctext_budget` bytes.
+///
+/// Fails with [SignalProtocolError::InvalidArgument] if not even an empty message fits.
+pub fn max_plaintext_size(ctext_budget: usize, session_record: &SessionRecord) -> Result<usize> {
+    if estimate_ciphertext_size(0, session_record)? > ctext_budget {
+        return Err(SignalProtocolError::InvalidArgument(format!(
+            "a ciphertext budget of {} bytes cannot fit any message",
+            ctext_budget
+        )));
+    }
+
+    // The estimate never shrinks as the plaintext grows, and is always longer than the plaintext,
+    // so the answer is in [0, ctext_budget).
+    let mut fits = 0;
+    let mut too_big = ctext_budget;
+    while too_big - fits > 1 {
+        let mid = fits + (too_big - fits) / 2;
+        if estimate_ciphertext_size(mid, session_record)? <= ctext_budget {
+            fits = mid;
+        } else {
+            too_big = mid;
+        }
+    }
+    Ok(fits)
+}
+
 pub async fn message_decrypt_signal<R: Rng + CryptoRng>(
     ciphertext: &SignalMessage,
     remote_address: &ProtocolAddress,
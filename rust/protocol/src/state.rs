@@ -10,6 +10,8 @@ mod signed_prekey;
 
 pub use bundle::PreKeyBundle;
 pub use prekey::{PreKeyId, PreKeyRecord};
-pub use session::SessionRecord;
+#[cfg(feature = "forensics")]
+pub use session::ExportedChainKey;
 pub(crate) use session::SessionState;
+pub use session::{SessionRecord, SessionRecordIntegrity};
 pub use signed_prekey::{SignedPreKeyId, SignedPreKeyRecord};
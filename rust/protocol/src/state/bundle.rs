@@ -6,9 +6,15 @@
 use crate::curve;
 use crate::IdentityKey;
 
-use crate::error::Result;
+use crate::error::{Result, SignalProtocolError};
 use crate::state::{PreKeyId, SignedPreKeyId};
 
+/// Format version of [PreKeyBundle::to_linking_payload], stored in the high nibble of the first
+/// byte. The low nibble holds flags.
+const LINKING_PAYLOAD_VERSION: u8 = 1;
+const LINKING_PAYLOAD_HAS_PRE_KEY: u8 = 0x01;
+const SIGNATURE_LENGTH: usize = 64;
+
 #[derive(Debug, Clone)]
 pub struct PreKeyBundle {
     registration_id: u32,
@@ -79,4 +85,191 @@ impl PreKeyBundle {
     pub fn identity_key(&self) -> Result<&IdentityKey> {
         Ok(&self.identity_key)
     }
+
+    /// Encodes the bundle compactly for device-linking QR codes.
+    ///
+    /// Integers are written as fixed-width big-endian values and public keys without their
+    /// key-type byte; the one-time pre-key is present only if the bundle has one.
+    pub fn to_linking_payload(&self) -> Result<Vec<u8>> {
+        if self.signed_pre_key_signature.len() != SIGNATURE_LENGTH {
+            return Err(SignalProtocolError::InvalidArgument(format!(
+                "signed pre-key signature must be {} bytes to fit a linking payload",
+                SIGNATURE_LENGTH
+            )));
+        }
+
+        let pre_key = self.pre_key_id.zip(self.pre_key_public);
+        let flags = if pre_key.is_some() {
+            LINKING_PAYLOAD_HAS_PRE_KEY
+        } else {
+            0
+        };
+
+        let mut result = vec![(LINKING_PAYLOAD_VERSION << 4) | flags];
+        result.extend_from_slice(&self.registration_id.to_be_bytes());
+        result.extend_from_slice(&self.device_id.to_be_bytes());
+        result.extend_from_slice(&self.signed_pre_key_id.to_be_bytes());
+        result.extend_from_slice(self.signed_pre_key_public.public_key_bytes()?);
+        result.extend_from_slice(&self.signed_pre_key_signature);
+        result.extend_from_slice(self.identity_key.public_key().public_key_bytes()?);
+        if let Some((pre_key_id, pre_key_public)) = pre_key {
+            result.extend_from_slice(&pre_key_id.to_be_bytes());
+            result.extend_from_slice(pre_key_public.public_key_bytes()?);
+        }
+        Ok(result)
+    }
+
+    /// Decodes a payload produced by [to_linking_payload](Self::to_linking_payload).
+    pub fn from_linking_payload(payload: &[u8]) -> Result<Self> {
+        let mut input = payload;
+        let header = take(&mut input, 1)?[0];
+        if header >> 4 != LINKING_PAYLOAD_VERSION {
+            return Err(SignalProtocolError::InvalidArgument(format!(
+                "unsupported linking payload version {}",
+                header >> 4
+            )));
+        }
+        let has_pre_key = match header & 0x0F {
+            0 => false,
+            LINKING_PAYLOAD_HAS_PRE_KEY => true,
+            _ => return Err(malformed_linking_payload()),
+        };
+
+        let registration_id = take_u32(&mut input)?;
+        let device_id = take_u32(&mut input)?;
+        let signed_pre_key_id = take_u32(&mut input)?;
+        let signed_pre_key_public = take_public_key(&mut input)?;
+        let signed_pre_key_signature = take(&mut input, SIGNATURE_LENGTH)?.to_vec();
+        let identity_key = IdentityKey::new(take_public_key(&mut input)?);
+        let pre_key = if has_pre_key {
+            Some((take_u32(&mut input)?, take_public_key(&mut input)?))
+        } else {
+            None
+        };
+        if !input.is_empty() {
+            return Err(malformed_linking_payload());
+        }
+
+        Self::new(
+            registration_id,
+            device_id,
+            pre_key,
+            signed_pre_key_id,
+            signed_pre_key_public,
+            signed_pre_key_signature,
+            identity_key,
+        )
+    }
+}
+
+fn malformed_linking_payload() -> SignalProtocolError {
+    SignalProtocolError::InvalidArgument("malformed pre-key bundle linking payload".to_owned())
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if input.len() < len {
+        return Err(malformed_linking_payload());
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Ok(head)
+}
+
+fn take_u32(input: &mut &[u8]) -> Result<u32> {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(take(input, 4)?);
+    Ok(u32::from_be_bytes(bytes))
+}
+
+fn take_public_key(input: &mut &[u8]) -> Result<curve::PublicKey> {
+    curve::PublicKey::from_djb_public_key_bytes(take(input, 32)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::rngs::OsRng;
+
+    fn test_bundle(with_pre_key: bool) -> PreKeyBundle {
+        let mut csprng = OsRng;
+        let identity_key_pair = curve::KeyPair::generate(&mut csprng);
+        let signed_pre_key_pair = curve::KeyPair::generate(&mut csprng);
+        let signature = identity_key_pair
+            .calculate_signature(&signed_pre_key_pair.public_key.serialize(), &mut csprng)
+            .unwrap();
+        let pre_key = if with_pre_key {
+            Some((31337, curve::KeyPair::generate(&mut csprng).public_key))
+        } else {
+            None
+        };
+
+        PreKeyBundle::new(
+            1234,
+            5,
+            pre_key,
+            22,
+            signed_pre_key_pair.public_key,
+            signature.to_vec(),
+            identity_key_pair.public_key.into(),
+        )
+        .unwrap()
+    }
+
+    fn assert_bundles_equal(a: &PreKeyBundle, b: &PreKeyBundle) {
+        assert_eq!(a.registration_id().unwrap(), b.registration_id().unwrap());
+        assert_eq!(a.device_id().unwrap(), b.device_id().unwrap());
+        assert_eq!(a.pre_key_id().unwrap(), b.pre_key_id().unwrap());
+        assert_eq!(a.pre_key_public().unwrap(), b.pre_key_public().unwrap());
+        assert_eq!(
+            a.signed_pre_key_id().unwrap(),
+            b.signed_pre_key_id().unwrap()
+        );
+        assert_eq!(
+            a.signed_pre_key_public().unwrap(),
+            b.signed_pre_key_public().unwrap()
+        );
+        assert_eq!(
+            a.signed_pre_key_signature().unwrap(),
+            b.signed_pre_key_signature().unwrap()
+        );
+        assert_eq!(a.identity_key().unwrap(), b.identity_key().unwrap());
+    }
+
+    #[test]
+    fn test_linking_payload_round_trip() {
+        for &with_pre_key in &[true, false] {
+            let bundle = test_bundle(with_pre_key);
+            let payload = bundle.to_linking_payload().unwrap();
+            assert_eq!(payload.len(), if with_pre_key { 177 } else { 141 });
+
+            let decoded = PreKeyBundle::from_linking_payload(&payload).unwrap();
+            assert_bundles_equal(&bundle, &decoded);
+            assert_eq!(decoded.to_linking_payload().unwrap(), payload);
+        }
+    }
+
+    #[test]
+    fn test_malformed_linking_payload() {
+        let payload = test_bundle(true).to_linking_payload().unwrap();
+
+        for len in 0..payload.len() {
+            assert!(matches!(
+                PreKeyBundle::from_linking_payload(&payload[..len]),
+                Err(SignalProtocolError::InvalidArgument(_))
+            ));
+        }
+
+        let mut trailing = payload.clone();
+        trailing.push(0);
+        assert!(PreKeyBundle::from_linking_payload(&trailing).is_err());
+
+        let mut bad_version = payload.clone();
+        bad_version[0] = (2 << 4) | LINKING_PAYLOAD_HAS_PRE_KEY;
+        assert!(PreKeyBundle::from_linking_payload(&bad_version).is_err());
+
+        let mut bad_flags = payload;
+        bad_flags[0] |= 0x02;
+        assert!(PreKeyBundle::from_linking_payload(&bad_flags).is_err());
+    }
 }
@@ -11,13 +11,85 @@ use crate::consts;
 use crate::curve;
 use crate::kdf;
 use crate::proto::storage::session_structure;
-use crate::proto::storage::{RecordStructure, SessionStructure};
+use crate::proto::storage::{RecordStructure, RecordStructureVersion, SessionStructure};
+use crate::protocol::{PreKeySignalMessage, CIPHERTEXT_MESSAGE_CURRENT_VERSION};
 use crate::state::prekey::PreKeyId;
 use crate::state::signed_prekey::SignedPreKeyId;
 use prost::Message;
+use sha2::{Digest, Sha256};
 
 use std::collections::VecDeque;
 
+/// A chain key copied out of a [SessionRecord] by
+/// [export_all_chain_keys](SessionRecord::export_all_chain_keys).
+///
+/// Anyone holding one of these can decrypt every message on its chain from `index` onward, so
+/// it must be handled at least as carefully as the session record itself.
+#[cfg(feature = "forensics")]
+#[derive(Clone)]
+pub struct ExportedChainKey {
+    chain_key: ChainKey,
+    ratchet_key: curve::PublicKey,
+    state_index: usize,
+    is_sender: bool,
+}
+
+#[cfg(feature = "forensics")]
+impl ExportedChainKey {
+    /// 0 for the current session state, `n` for the `n`th archived state (most recent first).
+    pub fn state_index(&self) -> usize {
+        self.state_index
+    }
+
+    pub fn is_sender(&self) -> bool {
+        self.is_sender
+    }
+
+    pub fn ratchet_key(&self) -> &curve::PublicKey {
+        &self.ratchet_key
+    }
+
+    pub fn index(&self) -> u32 {
+        self.chain_key.index()
+    }
+
+    pub fn key(&self) -> &[u8; 32] {
+        self.chain_key.key()
+    }
+
+    /// Returns the cipher key, MAC key and IV (concatenated) for message `index` on this chain.
+    pub fn message_key_bytes_at(&self, index: u32) -> Result<Vec<u8>> {
+        message_key_bytes_from(self.chain_key.clone(), index)
+    }
+}
+
+fn message_key_bytes_from(mut chain_key: ChainKey, index: u32) -> Result<Vec<u8>> {
+    if index < chain_key.index() {
+        return Err(SignalProtocolError::InvalidArgument(format!(
+            "message index {} precedes current chain index {}",
+            index,
+            chain_key.index()
+        )));
+    }
+    if (index - chain_key.index()) as usize > consts::MAX_FORWARD_JUMPS {
+        return Err(SignalProtocolError::InvalidArgument(format!(
+            "message index {} is too far ahead of current chain index {}",
+            index,
+            chain_key.index()
+        )));
+    }
+    while chain_key.index() < index {
+        chain_key = chain_key.next_chain_key()?;
+    }
+
+    let keys = chain_key.message_keys()?;
+    let mut result = Vec::with_capacity(32 + 32 + 16);
+    result.extend_from_slice(keys.cipher_key());
+    result.extend_from_slice(keys.mac_key());
+    result.extend_from_slice(keys.iv());
+    Ok(result)
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct UnacknowledgedPreKeyMessageItems {
     pre_key_id: Option<PreKeyId>,
@@ -66,10 +138,14 @@ impl SessionState {
         Ok(&self.session.alice_base_key)
     }
 
-    pub(crate) fn set_alice_base_key(&mut self, key: &[u8]) -> Result<()> {
-        // Should we check the length?
-        self.session.alice_base_key = key.to_vec();
-        Ok(())
+    pub(crate) fn creation_timestamp(&self) -> Result<u64> {
+        match self.session.creation_timestamp {
+            0 => Err(SignalProtocolError::InvalidState(
+                "creation_timestamp",
+                "session creation time was not recorded".into(),
+            )),
+            t => Ok(t),
+        }
     }
 
     pub fn session_version(&self) -> Result<u32> {
@@ -161,6 +237,23 @@ impl SessionState {
         Ok(results)
     }
 
+    #[cfg(feature = "forensics")]
+    pub(crate) fn all_receiver_chain_keys(&self) -> Result<Vec<(curve::PublicKey, ChainKey)>> {
+        let hkdf = kdf::HKDF::new(self.session_version()?)?;
+        let mut results = vec![];
+        for chain in self.session.receiver_chains.iter() {
+            let chain_key = chain
+                .chain_key
+                .as_ref()
+                .ok_or(SignalProtocolError::InvalidProtobufEncoding)?;
+            results.push((
+                curve::decode_point(&chain.sender_ratchet_key)?,
+                ChainKey::new(hkdf, &chain_key.key, chain_key.index)?,
+            ));
+        }
+        Ok(results)
+    }
+
     pub(crate) fn get_receiver_chain(
         &self,
         sender: &curve::PublicKey,
@@ -381,6 +474,37 @@ impl SessionState {
         ))
     }
 
+    pub(crate) fn key_confirmation(&self) -> Result<Vec<u8>> {
+        // Bob's initial sender chain is Alice's only receiver chain; once either side has used
+        // it, the other has moved on and there is nothing left to confirm.
+        let chain_key = match self.session.receiver_chains.as_slice() {
+            [] => self.get_sender_chain_key()?,
+            [chain] => self
+                .get_receiver_chain_key(&curve::decode_point(&chain.sender_ratchet_key)?)?
+                .ok_or(SignalProtocolError::InvalidProtobufEncoding)?,
+            _ => {
+                return Err(SignalProtocolError::InvalidState(
+                    "key_confirmation",
+                    "session has already ratcheted".to_owned(),
+                ))
+            }
+        };
+        if chain_key.index() != 0 {
+            return Err(SignalProtocolError::InvalidState(
+                "key_confirmation",
+                "initial chain has already been used".to_owned(),
+            ));
+        }
+
+        if self.session.key_confirmation.is_empty() {
+            return Err(SignalProtocolError::InvalidState(
+                "key_confirmation",
+                "session predates key confirmation".to_owned(),
+            ));
+        }
+        Ok(self.session.key_confirmation.clone())
+    }
+
     pub(crate) fn set_unacknowledged_pre_key_message(
         &mut self,
         pre_key_id: Option<PreKeyId>,
@@ -455,6 +579,28 @@ impl From<&SessionState> for SessionStructure {
     }
 }
 
+/// The outcome of [SessionRecord::validate_integrity].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum SessionRecordIntegrity {
+    Valid = 0,
+    /// The bytes are not a serialized session record at all.
+    Unparseable = 1,
+    /// A session state has a version this library cannot use.
+    InvalidVersion = 2,
+    /// A chain is missing its chain key, or holds message keys at or past the chain's index.
+    InconsistentChainIndices = 3,
+}
+
+fn chain_indices_are_consistent(chain: &session_structure::Chain) -> bool {
+    match &chain.chain_key {
+        None => false,
+        Some(chain_key) => chain
+            .message_keys
+            .iter()
+            .all(|message_key| message_key.index < chain_key.index),
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct SessionRecord {
     current_session: Option<SessionState>,
@@ -490,6 +636,64 @@ impl SessionRecord {
         })
     }
 
+    /// Checks whether `bytes` holds a session record that can be used safely.
+    ///
+    /// This lets callers detect storage corruption up front, rather than failing partway
+    /// through encrypting or decrypting with the record.
+    pub fn validate_integrity(bytes: &[u8]) -> SessionRecordIntegrity {
+        let record = match RecordStructure::decode(bytes) {
+            Ok(record) => record,
+            Err(_) => return SessionRecordIntegrity::Unparseable,
+        };
+
+        for session in record
+            .current_session
+            .iter()
+            .chain(record.previous_sessions.iter())
+        {
+            if session.session_version != CIPHERTEXT_MESSAGE_CURRENT_VERSION as u32 {
+                return SessionRecordIntegrity::InvalidVersion;
+            }
+            let chains_are_consistent = session
+                .sender_chain
+                .iter()
+                .chain(session.receiver_chains.iter())
+                .all(chain_indices_are_consistent);
+            if !chains_are_consistent {
+                return SessionRecordIntegrity::InconsistentChainIndices;
+            }
+        }
+
+        SessionRecordIntegrity::Valid
+    }
+
+    /// Reads the version of the current session in a serialized record without decoding the rest
+    /// of it, so a store can tell whether the record needs migrating.
+    ///
+    /// Returns 0 if the record has no current session.
+    pub fn storage_version(bytes: &[u8]) -> Result<u32> {
+        let record = RecordStructureVersion::decode(bytes)?;
+        Ok(match record.current_session {
+            None => 0,
+            // Matches SessionState::session_version.
+            Some(session) if session.session_version == 0 => 2,
+            Some(session) => session.session_version,
+        })
+    }
+
+    /// Whether the current session is older than [CIPHERTEXT_MESSAGE_CURRENT_VERSION].
+    ///
+    /// This is true exactly when [storage_version](Self::storage_version) of the serialized
+    /// record is nonzero and below the current version.
+    pub fn needs_upgrade(&self) -> Result<bool> {
+        match &self.current_session {
+            Some(session) => {
+                Ok(session.session_version()? < CIPHERTEXT_MESSAGE_CURRENT_VERSION as u32)
+            }
+            None => Ok(false),
+        }
+    }
+
     pub fn from_single_session_state(bytes: &[u8]) -> Result<Self> {
         let session = SessionState::new(SessionStructure::decode(bytes)?);
         Ok(Self {
@@ -607,6 +811,12 @@ impl SessionRecord {
         self.session_state()?.session_version()
     }
 
+    /// Returns when the current session state was established, in milliseconds since the Unix
+    /// epoch.
+    pub fn creation_timestamp(&self) -> Result<u64> {
+        self.session_state()?.creation_timestamp()
+    }
+
     pub fn local_identity_key_bytes(&self) -> Result<Vec<u8>> {
         self.session_state()?.local_identity_key_bytes()
     }
@@ -622,15 +832,261 @@ impl SessionRecord {
         }
     }
 
+    /// Returns true if the record has a current session state that is ready to encrypt, i.e.
+    /// one with a sender chain. Archived states are never used for encryption.
+    pub fn can_encrypt(&self) -> Result<bool> {
+        match &self.current_session {
+            Some(session) => session.has_sender_chain(),
+            None => Ok(false),
+        }
+    }
+
     pub fn alice_base_key(&self) -> Result<&[u8]> {
         self.session_state()?.alice_base_key()
     }
 
+    /// Returns true if `identity` is the remote identity of the current session or of any
+    /// archived session in this record.
+    pub fn identity_seen_before(&self, identity: &IdentityKey) -> Result<bool> {
+        for state in self
+            .current_session
+            .iter()
+            .chain(self.previous_sessions.iter())
+        {
+            if state.remote_identity_key()?.as_ref() == Some(identity) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns true if `message` set up the current session or one of the archived sessions in
+    /// this record, meaning it is a retransmission whose pre-key must not be consumed again.
+    pub fn pre_key_message_already_processed(&self, message: &PreKeySignalMessage) -> Result<bool> {
+        self.has_session_state(
+            message.message_version() as u32,
+            &message.base_key().serialize(),
+        )
+    }
+
     pub fn get_receiver_chain_key(&self, sender: &curve::PublicKey) -> Result<Option<ChainKey>> {
         self.session_state()?.get_receiver_chain_key(sender)
     }
 
+    /// Returns the cipher key, MAC key and IV (concatenated) for message `index` on the chain
+    /// belonging to `ratchet_key`, which may be our own sender ratchet key or one of the remote
+    /// party's. The record is not modified, so skipped-over keys are not saved.
+    pub fn message_key_bytes_at(
+        &self,
+        ratchet_key: &curve::PublicKey,
+        index: u32,
+    ) -> Result<Vec<u8>> {
+        let state = self.session_state()?;
+        let chain_key = if state.has_sender_chain()? && state.sender_ratchet_key()? == *ratchet_key
+        {
+            state.get_sender_chain_key()?
+        } else {
+            state.get_receiver_chain_key(ratchet_key)?.ok_or_else(|| {
+                SignalProtocolError::InvalidState(
+                    "message_key_bytes_at",
+                    "no chain for ratchet key".to_owned(),
+                )
+            })?
+        };
+        message_key_bytes_from(chain_key, index)
+    }
+
+    /// Returns every sender and receiver chain key in the current and archived session states.
+    ///
+    /// **The returned keys are enough to decrypt every not-yet-ratcheted message on their
+    /// chains**, including messages this device has not received yet, and once copied out they
+    /// no longer benefit from whatever protection the session store gives the record. This
+    /// exists only for authorized device-recovery tooling and is compiled in only with the
+    /// `forensics` feature, which must never be enabled in shipping clients.
+    #[cfg(feature = "forensics")]
+    pub fn export_all_chain_keys(&self) -> Result<Vec<ExportedChainKey>> {
+        let mut result = vec![];
+        let states = self
+            .current_session
+            .iter()
+            .map(|state| (0, state))
+            .chain((1..).zip(self.previous_sessions.iter()));
+        for (state_index, state) in states {
+            if state.has_sender_chain()? {
+                result.push(ExportedChainKey {
+                    chain_key: state.get_sender_chain_key()?,
+                    ratchet_key: state.sender_ratchet_key()?,
+                    state_index,
+                    is_sender: true,
+                });
+            }
+            for (ratchet_key, chain_key) in state.all_receiver_chain_keys()? {
+                result.push(ExportedChainKey {
+                    chain_key,
+                    ratchet_key,
+                    state_index,
+                    is_sender: false,
+                });
+            }
+        }
+        Ok(result)
+    }
+
     pub fn get_sender_chain_key_bytes(&self) -> Result<Vec<u8>> {
         self.session_state()?.get_sender_chain_key_bytes()
     }
+
+    pub fn get_root_key_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.session_state()?.root_key()?.key().to_vec())
+    }
+
+    /// Returns a 32-byte value that both parties to a freshly initialized session compute
+    /// identically, for exchanging as a key-confirmation MAC.
+    ///
+    /// It is derived with HKDF from the session's initial root key and is bound to the session's
+    /// Alice base key. It is only available until the initial chain is used: once Bob sends or
+    /// receives a message, or Alice receives one, this returns
+    /// [InvalidState](SignalProtocolError::InvalidState), as it does for sessions created before
+    /// the value was recorded.
+    pub fn compute_key_confirmation(&self) -> Result<Vec<u8>> {
+        self.session_state()?.key_confirmation()
+    }
+
+    /// Returns a 16-byte identifier for the current session, so that log events can be tied to
+    /// a session without logging anything about it.
+    ///
+    /// It is a truncated hash of the remote identity key and the session's Alice base key. It is
+    /// not secret, but it reveals neither key and nothing about message content. It does not
+    /// change as the ratchet advances or when the record is serialized and read back.
+    pub fn compute_session_id(&self) -> Result<Vec<u8>> {
+        let state = self.session_state()?;
+        let remote_identity = state.remote_identity_key_bytes()?.ok_or_else(|| {
+            SignalProtocolError::InvalidState(
+                "compute_session_id",
+                "session has no remote identity key".into(),
+            )
+        })?;
+
+        let mut hash = Sha256::new();
+        hash.update(b"Signal_Session_Id");
+        hash.update(&remote_identity);
+        hash.update(state.alice_base_key()?);
+        Ok(hash.finalize()[..16].to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(chain_index: u32, message_key_indices: &[u32]) -> session_structure::Chain {
+        session_structure::Chain {
+            chain_key: Some(session_structure::chain::ChainKey {
+                index: chain_index,
+                key: vec![0; 32],
+            }),
+            message_keys: message_key_indices
+                .iter()
+                .map(|&index| session_structure::chain::MessageKey {
+                    index,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn serialized_record(session: SessionStructure) -> Vec<u8> {
+        let record = RecordStructure {
+            current_session: Some(session),
+            previous_sessions: vec![],
+        };
+        let mut buf = vec![];
+        record.encode(&mut buf).expect("can encode");
+        buf
+    }
+
+    fn valid_session() -> SessionStructure {
+        SessionStructure {
+            session_version: CIPHERTEXT_MESSAGE_CURRENT_VERSION as u32,
+            sender_chain: Some(chain(4, &[])),
+            receiver_chains: vec![chain(7, &[2, 5])],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_integrity() -> Result<()> {
+        assert_eq!(
+            SessionRecord::validate_integrity(&SessionRecord::new_fresh().serialize()?),
+            SessionRecordIntegrity::Valid
+        );
+
+        let serialized = serialized_record(valid_session());
+        assert_eq!(
+            SessionRecord::validate_integrity(&serialized),
+            SessionRecordIntegrity::Valid
+        );
+
+        // The record starts with the current session's tag and length, then its version field.
+        let version_offset = serialized
+            .windows(2)
+            .position(|w| w == [0x08, CIPHERTEXT_MESSAGE_CURRENT_VERSION])
+            .expect("version is encoded");
+        let mut flipped = serialized.clone();
+        flipped[version_offset + 1] ^= 0x01;
+        assert_eq!(
+            SessionRecord::validate_integrity(&flipped),
+            SessionRecordIntegrity::InvalidVersion
+        );
+
+        assert_eq!(
+            SessionRecord::validate_integrity(&serialized[..serialized.len() - 1]),
+            SessionRecordIntegrity::Unparseable
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_storage_version() -> Result<()> {
+        let fresh = SessionRecord::new_fresh();
+        assert_eq!(SessionRecord::storage_version(&fresh.serialize()?)?, 0);
+        assert!(!fresh.needs_upgrade()?);
+
+        let current = serialized_record(valid_session());
+        assert_eq!(
+            SessionRecord::storage_version(&current)?,
+            CIPHERTEXT_MESSAGE_CURRENT_VERSION as u32
+        );
+        assert!(!SessionRecord::deserialize(&current)?.needs_upgrade()?);
+
+        for &(stored, reported) in &[(0, 2), (2, 2)] {
+            let serialized = serialized_record(SessionStructure {
+                session_version: stored,
+                ..valid_session()
+            });
+            assert_eq!(SessionRecord::storage_version(&serialized)?, reported);
+            assert!(SessionRecord::deserialize(&serialized)?.needs_upgrade()?);
+        }
+
+        assert!(SessionRecord::storage_version(&current[..current.len() - 1]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_integrity_chain_indices() {
+        let mut session = valid_session();
+        session.receiver_chains[0] = chain(5, &[2, 5]);
+        assert_eq!(
+            SessionRecord::validate_integrity(&serialized_record(session)),
+            SessionRecordIntegrity::InconsistentChainIndices
+        );
+
+        let mut session = valid_session();
+        session.sender_chain.as_mut().expect("present").chain_key = None;
+        assert_eq!(
+            SessionRecord::validate_integrity(&serialized_record(session)),
+            SessionRecordIntegrity::InconsistentChainIndices
+        );
+    }
 }
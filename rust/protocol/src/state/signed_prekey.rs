@@ -45,6 +45,13 @@ impl SignedPreKeyRecord {
         Ok(self.signed_pre_key.timestamp)
     }
 
+    /// Returns when this key is due to be replaced, `interval` after it was created.
+    ///
+    /// Saturates at `u64::MAX` rather than wrapping around to a time in the past.
+    pub fn next_rotation_time(&self, interval: u64) -> Result<u64> {
+        Ok(self.signed_pre_key.timestamp.saturating_add(interval))
+    }
+
     pub fn signature(&self) -> Result<Vec<u8>> {
         Ok(self.signed_pre_key.signature.clone())
     }
@@ -70,3 +77,21 @@ impl SignedPreKeyRecord {
         Ok(buf)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_next_rotation_time() -> Result<()> {
+        let key_pair = curve::KeyPair::generate(&mut OsRng);
+        let record = SignedPreKeyRecord::new(1, 1000, &key_pair, &[0; 64]);
+        assert_eq!(record.next_rotation_time(500)?, 1500);
+        assert_eq!(record.next_rotation_time(0)?, 1000);
+        assert_eq!(record.next_rotation_time(u64::MAX - 1000)?, u64::MAX);
+        assert_eq!(record.next_rotation_time(u64::MAX)?, u64::MAX);
+        Ok(())
+    }
+}
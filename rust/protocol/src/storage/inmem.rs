@@ -220,6 +220,17 @@ impl traits::SessionStore for InMemSessionStore {
         self.sessions.insert(address.clone(), record.clone());
         Ok(())
     }
+
+    async fn get_device_ids_for_name(&self, name: &str, _ctx: Context) -> Result<Vec<u32>> {
+        let mut device_ids: Vec<u32> = self
+            .sessions
+            .keys()
+            .filter(|address| address.name() == name)
+            .map(|address| address.device_id())
+            .collect();
+        device_ids.sort_unstable();
+        Ok(device_ids)
+    }
 }
 
 #[derive(Clone)]
@@ -385,6 +396,10 @@ impl traits::SessionStore for InMemSignalProtocolStore {
     ) -> Result<()> {
         self.session_store.store_session(address, record, ctx).await
     }
+
+    async fn get_device_ids_for_name(&self, name: &str, ctx: Context) -> Result<Vec<u32>> {
+        self.session_store.get_device_ids_for_name(name, ctx).await
+    }
 }
 
 #[async_trait(?Send)]
@@ -5,7 +5,7 @@
 
 use async_trait::async_trait;
 
-use crate::error::Result;
+use crate::error::{Result, SignalProtocolError};
 use crate::state::{PreKeyId, PreKeyRecord, SessionRecord, SignedPreKeyId, SignedPreKeyRecord};
 use crate::{IdentityKey, IdentityKeyPair, ProtocolAddress, SenderKeyName, SenderKeyRecord};
 
@@ -89,6 +89,18 @@ pub trait SessionStore {
         record: &SessionRecord,
         ctx: Context,
     ) -> Result<()>;
+
+    /// Returns the device ids of every stored session for `name`, including device 1.
+    ///
+    /// Stores that cannot enumerate their sessions can rely on the default, which fails, so
+    /// operations that need the list (such as archiving every session for a name) report an
+    /// error rather than silently skipping devices.
+    async fn get_device_ids_for_name(&self, _name: &str, _ctx: Context) -> Result<Vec<u32>> {
+        Err(SignalProtocolError::InvalidState(
+            "get_device_ids_for_name",
+            "this session store cannot list its sessions".to_owned(),
+        ))
+    }
 }
 
 #[async_trait(?Send)]
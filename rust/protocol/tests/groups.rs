@@ -191,6 +191,99 @@ fn group_basic_encrypt_decrypt() -> Result<(), SignalProtocolError> {
     })
 }
 
+#[test]
+fn group_two_senders() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let mut csprng = OsRng;
+
+        let group_id = "summer camp planning committee";
+        let alice_address = ProtocolAddress::new("+14159999111".to_owned(), 1);
+        let bob_address = ProtocolAddress::new("+14159999222".to_owned(), 1);
+        let alice_sender = SenderKeyName::new(group_id.to_owned(), alice_address)?;
+        let bob_sender = SenderKeyName::new(group_id.to_owned(), bob_address)?;
+
+        let mut alice_store = test_in_memory_protocol_store();
+        let mut bob_store = test_in_memory_protocol_store();
+
+        let alice_distribution_message = create_sender_key_distribution_message(
+            &alice_sender,
+            &mut alice_store,
+            &mut csprng,
+            None,
+        )
+        .await?;
+        let bob_distribution_message =
+            create_sender_key_distribution_message(&bob_sender, &mut bob_store, &mut csprng, None)
+                .await?;
+
+        process_sender_key_distribution_message(
+            &alice_sender,
+            &SenderKeyDistributionMessage::try_from(alice_distribution_message.serialized())?,
+            &mut bob_store,
+            None,
+        )
+        .await?;
+        process_sender_key_distribution_message(
+            &bob_sender,
+            &SenderKeyDistributionMessage::try_from(bob_distribution_message.serialized())?,
+            &mut alice_store,
+            None,
+        )
+        .await?;
+
+        let alice_ciphertext = group_encrypt(
+            &mut alice_store,
+            &alice_sender,
+            "space camp?".as_bytes(),
+            &mut csprng,
+            None,
+        )
+        .await?;
+        let bob_ciphertext1 = group_encrypt(
+            &mut bob_store,
+            &bob_sender,
+            "sure".as_bytes(),
+            &mut csprng,
+            None,
+        )
+        .await?;
+        let bob_ciphertext2 = group_encrypt(
+            &mut bob_store,
+            &bob_sender,
+            "bring snacks".as_bytes(),
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let bob_plaintext =
+            group_decrypt(&alice_ciphertext, &mut bob_store, &alice_sender, None).await?;
+        assert_eq!(String::from_utf8(bob_plaintext).unwrap(), "space camp?");
+
+        // Bob's second message arrives first, so the key for his first one is kept for later.
+        let alice_plaintext2 =
+            group_decrypt(&bob_ciphertext2, &mut alice_store, &bob_sender, None).await?;
+        assert_eq!(String::from_utf8(alice_plaintext2).unwrap(), "bring snacks");
+        let alice_plaintext1 =
+            group_decrypt(&bob_ciphertext1, &mut alice_store, &bob_sender, None).await?;
+        assert_eq!(String::from_utf8(alice_plaintext1).unwrap(), "sure");
+
+        assert!(matches!(
+            group_decrypt(&bob_ciphertext1, &mut alice_store, &bob_sender, None).await,
+            Err(SignalProtocolError::DuplicatedMessage(_, 0))
+        ));
+
+        // Messages aren't accepted under the other sender's key.
+        assert!(
+            group_decrypt(&alice_ciphertext, &mut alice_store, &bob_sender, None)
+                .await
+                .is_err()
+        );
+
+        Ok(())
+    })
+}
+
 #[test]
 fn group_large_messages() -> Result<(), SignalProtocolError> {
     block_on(async {
@@ -449,6 +542,374 @@ fn group_out_of_order() -> Result<(), SignalProtocolError> {
     })
 }
 
+#[test]
+fn group_decrypt_batch_out_of_order_with_duplicate() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let mut csprng = OsRng;
+
+        let sender_address = ProtocolAddress::new("+14159999111".to_owned(), 1);
+        let group_sender =
+            SenderKeyName::new("summer camp planning committee".to_owned(), sender_address)?;
+
+        let mut alice_store = test_in_memory_protocol_store();
+        let mut bob_store = test_in_memory_protocol_store();
+
+        let sent_distribution_message = create_sender_key_distribution_message(
+            &group_sender,
+            &mut alice_store,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let recv_distribution_message =
+            SenderKeyDistributionMessage::try_from(sent_distribution_message.serialized()).unwrap();
+
+        process_sender_key_distribution_message(
+            &group_sender,
+            &recv_distribution_message,
+            &mut bob_store,
+            None,
+        )
+        .await?;
+
+        let mut ciphertexts = Vec::with_capacity(10);
+        for i in 0..ciphertexts.capacity() {
+            ciphertexts.push(
+                group_encrypt(
+                    &mut alice_store,
+                    &group_sender,
+                    format!("nefarious plotting {:02}/10", i).as_bytes(),
+                    &mut csprng,
+                    None,
+                )
+                .await?,
+            );
+        }
+        ciphertexts.shuffle(&mut csprng);
+        let duplicate = ciphertexts[3].clone();
+        ciphertexts.push(duplicate);
+
+        let batch: Vec<&[u8]> = ciphertexts.iter().map(|c| &c[..]).collect();
+        let results = group_decrypt_batch(&batch, &mut bob_store, &group_sender, None).await?;
+        assert_eq!(results.len(), 11);
+
+        assert!(matches!(
+            results[10],
+            Err(SignalProtocolError::DuplicatedMessage(_, _))
+        ));
+
+        let mut plaintexts = results[..10]
+            .iter()
+            .map(|r| String::from_utf8(r.as_ref().expect("decrypts").clone()).unwrap())
+            .collect::<Vec<_>>();
+        plaintexts.sort();
+        for (i, plaintext) in plaintexts.iter().enumerate() {
+            assert_eq!(*plaintext, format!("nefarious plotting {:02}/10", i));
+        }
+
+        // The consumed keys were saved, so the whole batch is now duplicates.
+        let results = group_decrypt_batch(&batch, &mut bob_store, &group_sender, None).await?;
+        assert!(results
+            .iter()
+            .all(|r| matches!(r, Err(SignalProtocolError::DuplicatedMessage(_, _)))));
+
+        // Without a sender key record, the whole batch fails rather than each message.
+        let mut carol_store = test_in_memory_protocol_store();
+        assert!(matches!(
+            group_decrypt_batch(&batch, &mut carol_store, &group_sender, None).await,
+            Err(SignalProtocolError::InvalidSenderKeyId)
+        ));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn group_derive_message_key_at() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let mut csprng = OsRng;
+
+        let sender_address = ProtocolAddress::new("+14159999111".to_owned(), 1);
+        let group_sender =
+            SenderKeyName::new("summer camp planning committee".to_owned(), sender_address)?;
+
+        let mut alice_store = test_in_memory_protocol_store();
+        let mut bob_store = test_in_memory_protocol_store();
+
+        let sent_distribution_message = create_sender_key_distribution_message(
+            &group_sender,
+            &mut alice_store,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let recv_distribution_message =
+            SenderKeyDistributionMessage::try_from(sent_distribution_message.serialized()).unwrap();
+
+        process_sender_key_distribution_message(
+            &group_sender,
+            &recv_distribution_message,
+            &mut bob_store,
+            None,
+        )
+        .await?;
+
+        let alice_record = alice_store
+            .load_sender_key(&group_sender, None)
+            .await?
+            .expect("alice has a sender key");
+        let alice_key_2 = alice_record.derive_message_key_at(2)?;
+
+        let mut ciphertexts = Vec::with_capacity(3);
+        for i in 0..ciphertexts.capacity() {
+            ciphertexts.push(
+                group_encrypt(
+                    &mut alice_store,
+                    &group_sender,
+                    format!("message {}", i).as_bytes(),
+                    &mut csprng,
+                    None,
+                )
+                .await?,
+            );
+        }
+
+        let bob_record = bob_store
+            .load_sender_key(&group_sender, None)
+            .await?
+            .expect("bob has a sender key");
+        let bob_serialized = bob_record.serialize()?;
+
+        let bob_key_0 = bob_record.derive_message_key_at(0)?;
+        let bob_key_2 = bob_record.derive_message_key_at(2)?;
+        assert_eq!(bob_key_2.seed()?, alice_key_2.seed()?);
+        assert_eq!(bob_key_2.iteration()?, 2);
+
+        // Deriving must not advance the chain
+        assert_eq!(bob_record.serialize()?, bob_serialized);
+
+        let plaintext = group_decrypt(&ciphertexts[2], &mut bob_store, &group_sender, None).await?;
+        assert_eq!(String::from_utf8(plaintext).unwrap(), "message 2");
+
+        // Skipped keys are still available after decrypting out of order
+        let bob_record = bob_store
+            .load_sender_key(&group_sender, None)
+            .await?
+            .expect("bob has a sender key");
+        assert_eq!(
+            bob_record.derive_message_key_at(0)?.seed()?,
+            bob_key_0.seed()?
+        );
+        assert!(matches!(
+            bob_record.derive_message_key_at(2),
+            Err(SignalProtocolError::DuplicatedMessage(3, 2))
+        ));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn group_record_distribution_message_for_new_member() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let mut csprng = OsRng;
+
+        let sender_address = ProtocolAddress::new("+14159999111".to_owned(), 1);
+        let group_sender =
+            SenderKeyName::new("summer camp planning committee".to_owned(), sender_address)?;
+
+        let mut alice_store = test_in_memory_protocol_store();
+        let mut bob_store = test_in_memory_protocol_store();
+        let mut carol_store = test_in_memory_protocol_store();
+
+        let sent_distribution_message = create_sender_key_distribution_message(
+            &group_sender,
+            &mut alice_store,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        process_sender_key_distribution_message(
+            &group_sender,
+            &SenderKeyDistributionMessage::try_from(sent_distribution_message.serialized())?,
+            &mut bob_store,
+            None,
+        )
+        .await?;
+
+        let first_ciphertext = group_encrypt(
+            &mut alice_store,
+            &group_sender,
+            "before carol joined".as_bytes(),
+            &mut csprng,
+            None,
+        )
+        .await?;
+        let plaintext =
+            group_decrypt(&first_ciphertext, &mut bob_store, &group_sender, None).await?;
+        assert_eq!(String::from_utf8(plaintext).unwrap(), "before carol joined");
+
+        let alice_record = alice_store
+            .load_sender_key(&group_sender, None)
+            .await?
+            .expect("alice has a sender key");
+        let carol_distribution_message = alice_record.create_distribution_message()?;
+        assert_eq!(
+            carol_distribution_message.id()?,
+            sent_distribution_message.id()?
+        );
+        assert_eq!(carol_distribution_message.iteration()?, 1);
+
+        process_sender_key_distribution_message(
+            &group_sender,
+            &SenderKeyDistributionMessage::try_from(carol_distribution_message.serialized())?,
+            &mut carol_store,
+            None,
+        )
+        .await?;
+
+        // Carol cannot read what was sent before she joined
+        assert!(
+            group_decrypt(&first_ciphertext, &mut carol_store, &group_sender, None)
+                .await
+                .is_err()
+        );
+
+        let second_ciphertext = group_encrypt(
+            &mut alice_store,
+            &group_sender,
+            "after carol joined".as_bytes(),
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let plaintext =
+            group_decrypt(&second_ciphertext, &mut carol_store, &group_sender, None).await?;
+        assert_eq!(String::from_utf8(plaintext).unwrap(), "after carol joined");
+
+        let plaintext =
+            group_decrypt(&second_ciphertext, &mut bob_store, &group_sender, None).await?;
+        assert_eq!(String::from_utf8(plaintext).unwrap(), "after carol joined");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn group_messages_until_rotation() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let mut csprng = OsRng;
+
+        let sender_address = ProtocolAddress::new("+14159999111".to_owned(), 1);
+        let group_sender =
+            SenderKeyName::new("rotation schedule review".to_owned(), sender_address)?;
+
+        assert!(SenderKeyRecord::new_empty()
+            .messages_until_rotation()
+            .is_err());
+
+        let mut alice_store = test_in_memory_protocol_store();
+
+        create_sender_key_distribution_message(&group_sender, &mut alice_store, &mut csprng, None)
+            .await?;
+
+        let alice_record = alice_store
+            .load_sender_key(&group_sender, None)
+            .await?
+            .expect("alice has a sender key");
+        assert_eq!(alice_record.messages_until_rotation()?, i32::MAX as u32);
+
+        for i in 0..5 {
+            group_encrypt(
+                &mut alice_store,
+                &group_sender,
+                format!("message {}", i).as_bytes(),
+                &mut csprng,
+                None,
+            )
+            .await?;
+        }
+
+        let alice_record = alice_store
+            .load_sender_key(&group_sender, None)
+            .await?
+            .expect("alice has a sender key");
+        assert_eq!(alice_record.messages_until_rotation()?, i32::MAX as u32 - 5);
+
+        // Start a chain one message short of the limit.
+        let signing_key = KeyPair::generate(&mut csprng);
+        let mut alice_record = SenderKeyRecord::new_empty();
+        alice_record.set_sender_key_state(
+            1,
+            i32::MAX as u32 - 1,
+            &[0x42; 32],
+            signing_key.public_key,
+            Some(signing_key.private_key),
+        )?;
+        alice_store
+            .store_sender_key(&group_sender, &alice_record, None)
+            .await?;
+        assert_eq!(alice_record.messages_until_rotation()?, 1);
+
+        let mut bob_store = test_in_memory_protocol_store();
+        let mut bob_record = SenderKeyRecord::new_empty();
+        bob_record.set_sender_key_state(
+            1,
+            i32::MAX as u32 - 1,
+            &[0x42; 32],
+            signing_key.public_key,
+            None,
+        )?;
+        bob_store
+            .store_sender_key(&group_sender, &bob_record, None)
+            .await?;
+
+        let last_one = group_encrypt(
+            &mut alice_store,
+            &group_sender,
+            b"last one",
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let alice_record = alice_store
+            .load_sender_key(&group_sender, None)
+            .await?
+            .expect("alice has a sender key");
+        assert_eq!(alice_record.messages_until_rotation()?, 0);
+
+        // Sending is not blocked at the limit; receivers reject what lies beyond it.
+        let one_too_many = group_encrypt(
+            &mut alice_store,
+            &group_sender,
+            b"one too many",
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let alice_record = alice_store
+            .load_sender_key(&group_sender, None)
+            .await?
+            .expect("alice has a sender key");
+        assert_eq!(alice_record.messages_until_rotation()?, 0);
+
+        let plaintext = group_decrypt(&last_one, &mut bob_store, &group_sender, None).await?;
+        assert_eq!(plaintext, b"last one");
+        assert!(matches!(
+            group_decrypt(&one_too_many, &mut bob_store, &group_sender, None).await,
+            Err(SignalProtocolError::InvalidMessage(_))
+        ));
+
+        Ok(())
+    })
+}
+
 #[test]
 fn group_too_far_in_the_future() -> Result<(), SignalProtocolError> {
     block_on(async {
@@ -585,3 +1046,27 @@ fn group_message_key_limit() -> Result<(), SignalProtocolError> {
         Ok(())
     })
 }
+
+#[test]
+fn group_state_key_from_distribution_message() -> Result<(), SignalProtocolError> {
+    let mut csprng = OsRng;
+    let signing_key = KeyPair::generate(&mut csprng).public_key;
+
+    let distribution = SenderKeyDistributionMessage::new(1, 7, &[0x42; 32], signing_key)?;
+    assert_eq!(
+        hex::encode(distribution.derive_group_key()?),
+        "75922fd20215798a8a0c57d7943f13bb911b3bb0cae91d5249c06a7f7ee325c2"
+    );
+
+    // Every recipient of the same distribution message derives the same key
+    let received = SenderKeyDistributionMessage::try_from(distribution.serialized())?;
+    assert_eq!(
+        received.derive_group_key()?,
+        distribution.derive_group_key()?
+    );
+
+    let other = SenderKeyDistributionMessage::new(1, 7, &[0x43; 32], signing_key)?;
+    assert_ne!(other.derive_group_key()?, distribution.derive_group_key()?);
+
+    Ok(())
+}
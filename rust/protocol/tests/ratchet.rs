@@ -148,3 +148,60 @@ fn test_ratcheting_session_as_alice() -> Result<(), SignalProtocolError> {
 
     Ok(())
 }
+
+#[test]
+fn test_key_confirmation() -> Result<(), SignalProtocolError> {
+    let mut csprng = rand::rngs::OsRng;
+
+    let alice_identity = IdentityKeyPair::generate(&mut csprng);
+    let alice_base_key = KeyPair::generate(&mut csprng);
+    let bob_identity = IdentityKeyPair::generate(&mut csprng);
+    let bob_signed_pre_key = KeyPair::generate(&mut csprng);
+
+    let alice_parameters = AliceSignalProtocolParameters::new(
+        alice_identity,
+        alice_base_key,
+        *bob_identity.identity_key(),
+        bob_signed_pre_key.public_key,
+        None,
+        bob_signed_pre_key.public_key,
+    );
+    let bob_parameters = BobSignalProtocolParameters::new(
+        bob_identity,
+        bob_signed_pre_key,
+        None,
+        bob_signed_pre_key,
+        *alice_identity.identity_key(),
+        alice_base_key.public_key,
+    );
+
+    let alice_record = initialize_alice_session_record(&alice_parameters, &mut csprng)?;
+    let bob_record = initialize_bob_session_record(&bob_parameters)?;
+
+    let alice_confirmation = alice_record.compute_key_confirmation()?;
+    assert_eq!(alice_confirmation.len(), 32);
+    assert_eq!(alice_confirmation, bob_record.compute_key_confirmation()?);
+
+    // Bob hasn't ratcheted yet, so his root key is still the initial one.
+    let info = [
+        b"Signal Key Confirmation".as_ref(),
+        &alice_base_key.public_key.serialize(),
+    ]
+    .concat();
+    let expected = HKDF::new(3)?.derive_secrets(&bob_record.get_root_key_bytes()?, &info, 32)?;
+    assert_eq!(alice_confirmation, expected.into_vec());
+
+    // A session with a different Alice base key does not confirm.
+    let other_parameters = AliceSignalProtocolParameters::new(
+        alice_identity,
+        KeyPair::generate(&mut csprng),
+        *bob_identity.identity_key(),
+        bob_signed_pre_key.public_key,
+        None,
+        bob_signed_pre_key.public_key,
+    );
+    let other_record = initialize_alice_session_record(&other_parameters, &mut csprng)?;
+    assert_ne!(alice_confirmation, other_record.compute_key_confirmation()?);
+
+    Ok(())
+}
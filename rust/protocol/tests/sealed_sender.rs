@@ -105,6 +105,29 @@ fn test_sender_cert() -> Result<(), SignalProtocolError> {
         sender_cert.validate(&trust_root.public_key, expires + 1)?,
         false
     ); // expired
+    assert!(sender_cert.has_valid_uuid()?);
+
+    for bad_uuid in &[
+        "",
+        "not a uuid",
+        "9d0652a3dcc34d11975f74d61598733f",
+        "9d0652a3-dcc3-4d11-975f-74d61598733",
+        "9d0652a3-dcc3-4d11-975f-74d61598733fa",
+        "9d0652a3-dcc3-4d11-975f-74d6159873zz",
+        "9d0652a3+dcc3-4d11-975f-74d61598733f",
+    ] {
+        let bad_cert = SenderCertificate::new(
+            bad_uuid.to_string(),
+            None,
+            key.public_key,
+            device_id,
+            expires,
+            sender_cert.signer()?.clone(),
+            &server_key.private_key,
+            &mut rng,
+        )?;
+        assert!(!bad_cert.has_valid_uuid()?, "{}", bad_uuid);
+    }
 
     let mut sender_cert_data = sender_cert.serialized()?.to_vec();
     let sender_cert_bits = sender_cert_data.len() * 8;
@@ -134,6 +157,73 @@ fn test_sender_cert() -> Result<(), SignalProtocolError> {
     Ok(())
 }
 
+#[test]
+fn test_sender_cert_validate_chain() -> Result<(), SignalProtocolError> {
+    let mut rng = OsRng;
+    let trust_root = KeyPair::generate(&mut rng);
+    let server_key = KeyPair::generate(&mut rng);
+    let key = KeyPair::generate(&mut rng);
+
+    let expires = 1605722925;
+    let new_sender_cert = |server_cert: ServerCertificate,
+                           signer: &PrivateKey,
+                           rng: &mut OsRng|
+     -> Result<SenderCertificate, SignalProtocolError> {
+        SenderCertificate::new(
+            "9d0652a3-dcc3-4d11-975f-74d61598733f".to_string(),
+            None,
+            key.public_key,
+            42,
+            expires,
+            server_cert,
+            signer,
+            rng,
+        )
+    };
+
+    let server_cert =
+        ServerCertificate::new(1, server_key.public_key, &trust_root.private_key, &mut rng)?;
+    let sender_cert = new_sender_cert(server_cert.clone(), &server_key.private_key, &mut rng)?;
+
+    sender_cert.validate_chain(&trust_root.public_key, expires)?;
+
+    // Expired.
+    assert!(matches!(
+        sender_cert.validate_chain(&trust_root.public_key, expires + 1),
+        Err(SignalProtocolError::ExpiredCertificate(e)) if e == expires
+    ));
+
+    // Server certificate not signed by the trust root.
+    let other_root = KeyPair::generate(&mut rng);
+    assert!(matches!(
+        sender_cert.validate_chain(&other_root.public_key, expires),
+        Err(SignalProtocolError::SignatureValidationFailed)
+    ));
+
+    // Sender certificate not signed by the server key.
+    let other_key = KeyPair::generate(&mut rng);
+    let badly_signed_cert = new_sender_cert(server_cert, &other_key.private_key, &mut rng)?;
+    assert!(matches!(
+        badly_signed_cert.validate_chain(&trust_root.public_key, expires),
+        Err(SignalProtocolError::SignatureValidationFailed)
+    ));
+
+    // Revoked server certificate.
+    let revoked_server_cert = ServerCertificate::new(
+        0xDEADC357,
+        server_key.public_key,
+        &trust_root.private_key,
+        &mut rng,
+    )?;
+    let revoked_cert = new_sender_cert(revoked_server_cert, &server_key.private_key, &mut rng)?;
+    assert!(matches!(
+        revoked_cert.validate_chain(&trust_root.public_key, expires),
+        Err(SignalProtocolError::SignatureValidationFailed)
+    ));
+
+    Ok(())
+}
+
 #[test]
 fn test_sealed_sender() -> Result<(), SignalProtocolError> {
     block_on(async {
@@ -299,3 +389,203 @@ fn test_sealed_sender() -> Result<(), SignalProtocolError> {
         Ok(())
     })
 }
+
+#[test]
+fn test_sealed_sender_encrypt_from_usmc() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let mut rng = OsRng;
+
+        let alice_device_id = 23;
+        let bob_device_id = 42;
+
+        let alice_e164 = "+14151111111".to_owned();
+        let bob_e164 = "+14151114444".to_owned();
+
+        let alice_uuid = "9d0652a3-dcc3-4d11-975f-74d61598733f".to_string();
+        let bob_uuid = "796abedb-ca4e-4f18-8803-1fde5b921f9f".to_string();
+
+        let bob_uuid_address = ProtocolAddress::new(bob_uuid.clone(), bob_device_id);
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+        let mut bob_store = support::test_in_memory_protocol_store();
+
+        let alice_pubkey = *alice_store.get_identity_key_pair(None).await?.public_key();
+
+        let trust_root = KeyPair::generate(&mut rng);
+        let server_key = KeyPair::generate(&mut rng);
+
+        let server_cert =
+            ServerCertificate::new(1, server_key.public_key, &trust_root.private_key, &mut rng)?;
+
+        let expires = 1605722925;
+
+        let sender_cert = SenderCertificate::new(
+            alice_uuid.clone(),
+            Some(alice_e164.clone()),
+            alice_pubkey,
+            alice_device_id,
+            expires,
+            server_cert,
+            &server_key.private_key,
+            &mut rng,
+        )?;
+
+        // Alice has no session with Bob yet.
+        let unsent_usmc = UnidentifiedSenderMessageContent::new(
+            CiphertextMessageType::Whisper,
+            sender_cert.clone(),
+            vec![1, 2, 3],
+        )?;
+        assert!(matches!(
+            sealed_sender_encrypt_from_usmc(
+                &bob_uuid_address,
+                &unsent_usmc,
+                &mut alice_store.session_store,
+                &mut alice_store.identity_store,
+                None,
+                &mut rng,
+            )
+            .await,
+            Err(SignalProtocolError::SessionNotFound(_))
+        ));
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut rng).await?;
+
+        process_prekey_bundle(
+            &bob_uuid_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut rng,
+            None,
+        )
+        .await?;
+
+        let alice_message = encrypt(&mut alice_store, &bob_uuid_address, "hello bob").await?;
+        let alice_usmc = UnidentifiedSenderMessageContent::new(
+            alice_message.message_type(),
+            sender_cert.clone(),
+            alice_message.serialize().to_vec(),
+        )?;
+        let alice_ctext = sealed_sender_encrypt_from_usmc(
+            &bob_uuid_address,
+            &alice_usmc,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            None,
+            &mut rng,
+        )
+        .await?;
+
+        let bob_usmc =
+            sealed_sender_decrypt_to_usmc(&alice_ctext, &mut bob_store.identity_store, None)
+                .await?;
+        assert_eq!(bob_usmc.serialized()?, alice_usmc.serialized()?);
+        assert_eq!(bob_usmc.sender()?.sender_uuid()?, alice_uuid);
+
+        let bob_ptext = sealed_sender_decrypt(
+            &alice_ctext,
+            &trust_root.public_key,
+            expires - 1,
+            Some(bob_e164.clone()),
+            bob_uuid.clone(),
+            bob_device_id,
+            &mut bob_store.identity_store,
+            &mut bob_store.session_store,
+            &mut bob_store.pre_key_store,
+            &mut bob_store.signed_pre_key_store,
+            None,
+        )
+        .await?;
+
+        assert_eq!(bob_ptext.message, b"hello bob");
+        assert_eq!(bob_ptext.sender_uuid, alice_uuid);
+        assert_eq!(bob_ptext.device_id, alice_device_id);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_sealed_sender_decrypt_to_usmc() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let mut rng = OsRng;
+
+        let bob_device_id = 42;
+        let bob_uuid = "796abedb-ca4e-4f18-8803-1fde5b921f9f".to_string();
+        let bob_uuid_address = ProtocolAddress::new(bob_uuid, bob_device_id);
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+        let mut bob_store = support::test_in_memory_protocol_store();
+
+        let alice_pubkey = *alice_store.get_identity_key_pair(None).await?.public_key();
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut rng).await?;
+
+        process_prekey_bundle(
+            &bob_uuid_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut rng,
+            None,
+        )
+        .await?;
+
+        let trust_root = KeyPair::generate(&mut rng);
+        let server_key = KeyPair::generate(&mut rng);
+
+        let server_cert =
+            ServerCertificate::new(1, server_key.public_key, &trust_root.private_key, &mut rng)?;
+
+        let sender_cert = SenderCertificate::new(
+            "9d0652a3-dcc3-4d11-975f-74d61598733f".to_string(),
+            Some("+14151111111".to_owned()),
+            alice_pubkey,
+            23,
+            1605722925,
+            server_cert,
+            &server_key.private_key,
+            &mut rng,
+        )?;
+
+        let alice_ctext = sealed_sender_encrypt(
+            &bob_uuid_address,
+            &sender_cert,
+            &[1, 2, 3],
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            None,
+            &mut rng,
+        )
+        .await?;
+
+        // The certificate comes back without being validated against any trust root.
+        let bob_usmc =
+            sealed_sender_decrypt_to_usmc(&alice_ctext, &mut bob_store.identity_store, None)
+                .await?;
+        assert_eq!(bob_usmc.sender()?.serialized()?, sender_cert.serialized()?);
+        assert_eq!(bob_usmc.msg_type()?, CiphertextMessageType::PreKey);
+
+        let usm = UnidentifiedSenderMessage::deserialize(&alice_ctext)?;
+        let mut encrypted_static = usm.encrypted_static()?.to_vec();
+        encrypted_static[0] ^= 1;
+        let tampered = UnidentifiedSenderMessage::new(
+            usm.ephemeral_public()?,
+            encrypted_static,
+            usm.encrypted_message()?.to_vec(),
+        )?;
+
+        assert!(matches!(
+            sealed_sender_decrypt_to_usmc(
+                tampered.serialized()?,
+                &mut bob_store.identity_store,
+                None
+            )
+            .await,
+            Err(SignalProtocolError::InvalidSealedSenderMessage(_))
+        ));
+
+        Ok(())
+    })
+}
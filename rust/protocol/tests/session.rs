@@ -5,6 +5,7 @@
 
 mod support;
 
+use async_trait::async_trait;
 use futures::executor::block_on;
 use libsignal_protocol::*;
 use rand::rngs::OsRng;
@@ -279,18 +280,31 @@ fn test_bad_signed_pre_key_signature() -> Result<(), SignalProtocolError> {
                 *bob_store.get_identity_key_pair(None).await?.identity_key(),
             )?;
 
-            assert!(process_prekey_bundle(
-                &bob_address,
-                &mut alice_store.session_store,
-                &mut alice_store.identity_store,
-                &bob_pre_key_bundle,
-                &mut csprng,
-                None,
-            )
-            .await
-            .is_err());
+            assert!(matches!(
+                process_prekey_bundle(
+                    &bob_address,
+                    &mut alice_store.session_store,
+                    &mut alice_store.identity_store,
+                    &bob_pre_key_bundle,
+                    &mut csprng,
+                    None,
+                )
+                .await
+                .unwrap_err(),
+                SignalProtocolError::SignatureValidationFailed
+            ));
         }
 
+        // A rejected bundle must not leave anything behind in the stores.
+        assert!(alice_store
+            .load_session(&bob_address, None)
+            .await?
+            .is_none());
+        assert!(alice_store
+            .get_identity(&bob_address, None)
+            .await?
+            .is_none());
+
         // Finally check that the non-corrupted signature is accepted:
 
         let bob_pre_key_bundle = PreKeyBundle::new(
@@ -313,6 +327,12 @@ fn test_bad_signed_pre_key_signature() -> Result<(), SignalProtocolError> {
         )
         .await?;
 
+        let outgoing_message = encrypt(&mut alice_store, &bob_address, "hello").await?;
+        assert_eq!(
+            outgoing_message.message_type(),
+            CiphertextMessageType::PreKey
+        );
+
         Ok(())
     })
 }
@@ -459,19 +479,670 @@ fn bad_message_bundle() -> Result<(), SignalProtocolError> {
             .private_key()
             .calculate_signature(&bob_signed_pre_key_public, &mut csprng)?;
 
-        let pre_key_id = 31337;
-        let signed_pre_key_id = 22;
+        let pre_key_id = 31337;
+        let signed_pre_key_id = 22;
+
+        let bob_pre_key_bundle = PreKeyBundle::new(
+            bob_store.get_local_registration_id(None).await?,
+            1, // device id
+            Some((pre_key_id, bob_pre_key_pair.public_key)),
+            signed_pre_key_id, // signed pre key id
+            bob_signed_pre_key_pair.public_key,
+            bob_signed_pre_key_signature.to_vec(),
+            *bob_store.get_identity_key_pair(None).await?.identity_key(),
+        )?;
+
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        bob_store
+            .save_pre_key(
+                pre_key_id,
+                &PreKeyRecord::new(pre_key_id, &bob_pre_key_pair),
+                None,
+            )
+            .await?;
+        bob_store
+            .save_signed_pre_key(
+                signed_pre_key_id,
+                &SignedPreKeyRecord::new(
+                    signed_pre_key_id,
+                    /*timestamp*/ 42,
+                    &bob_signed_pre_key_pair,
+                    &bob_signed_pre_key_signature,
+                ),
+                None,
+            )
+            .await?;
+
+        assert!(alice_store
+            .load_session(&bob_address, None)
+            .await?
+            .is_some());
+        assert_eq!(
+            alice_store
+                .load_session(&bob_address, None)
+                .await?
+                .unwrap()
+                .session_version()?,
+            3
+        );
+
+        let original_message = "L'homme est condamné à être libre";
+
+        assert!(bob_store.get_pre_key(pre_key_id, None).await.is_ok());
+        let outgoing_message = encrypt(&mut alice_store, &bob_address, original_message).await?;
+
+        assert_eq!(
+            outgoing_message.message_type(),
+            CiphertextMessageType::PreKey
+        );
+
+        let outgoing_message = outgoing_message.serialize().to_vec();
+
+        let mut corrupted_message: Vec<u8> = outgoing_message.clone();
+        corrupted_message[outgoing_message.len() - 10] ^= 1;
+
+        let incoming_message = CiphertextMessage::PreKeySignalMessage(
+            PreKeySignalMessage::try_from(corrupted_message.as_slice())?,
+        );
+
+        assert!(decrypt(&mut bob_store, &alice_address, &incoming_message)
+            .await
+            .is_err());
+        assert!(bob_store.get_pre_key(pre_key_id, None).await.is_ok());
+
+        let incoming_message = CiphertextMessage::PreKeySignalMessage(
+            PreKeySignalMessage::try_from(outgoing_message.as_slice())?,
+        );
+
+        let ptext = decrypt(&mut bob_store, &alice_address, &incoming_message).await?;
+
+        assert_eq!(String::from_utf8(ptext).unwrap(), original_message);
+        assert!(matches!(
+            bob_store.get_pre_key(pre_key_id, None).await.unwrap_err(),
+            SignalProtocolError::InvalidPreKeyId
+        ));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn prekey_message_with_consumed_or_mismatched_prekey() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let mut csprng = OsRng;
+
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1);
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
+        let carol_address = ProtocolAddress::new("+14151111113".to_owned(), 1);
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+        let mut bob_store = support::test_in_memory_protocol_store();
+        let mut carol_store = support::test_in_memory_protocol_store();
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
+        let pre_key_id = bob_pre_key_bundle
+            .pre_key_id()?
+            .expect("has one-time prekey");
+
+        for store in [&mut alice_store, &mut carol_store].iter_mut() {
+            process_prekey_bundle(
+                &bob_address,
+                &mut store.session_store,
+                &mut store.identity_store,
+                &bob_pre_key_bundle,
+                &mut csprng,
+                None,
+            )
+            .await?;
+        }
+
+        let alice_message = encrypt(&mut alice_store, &bob_address, "from alice").await?;
+        assert_eq!(alice_message.message_type(), CiphertextMessageType::PreKey);
+
+        // A message with a ciphertext version other than the current one is rejected up front.
+        let mut serialized = alice_message.serialize().to_vec();
+        serialized[0] = (serialized[0] & 0xF0) | 2;
+        assert!(matches!(
+            PreKeySignalMessage::try_from(serialized.as_slice()).unwrap_err(),
+            SignalProtocolError::LegacyCiphertextVersion(2)
+        ));
+        serialized[0] = (serialized[0] & 0xF0) | 4;
+        assert!(matches!(
+            PreKeySignalMessage::try_from(serialized.as_slice()).unwrap_err(),
+            SignalProtocolError::UnrecognizedCiphertextVersion(4)
+        ));
+
+        let ptext = decrypt(&mut bob_store, &alice_address, &alice_message).await?;
+        assert_eq!(String::from_utf8(ptext).unwrap(), "from alice");
+        assert!(bob_store
+            .load_session(&alice_address, None)
+            .await?
+            .is_some());
+        assert!(matches!(
+            bob_store.get_pre_key(pre_key_id, None).await.unwrap_err(),
+            SignalProtocolError::InvalidPreKeyId
+        ));
+
+        // Carol built her session from the same bundle, but its one-time prekey is gone.
+        let carol_message = encrypt(&mut carol_store, &bob_address, "from carol").await?;
+        assert!(matches!(
+            decrypt(&mut bob_store, &carol_address, &carol_message)
+                .await
+                .unwrap_err(),
+            SignalProtocolError::InvalidPreKeyId
+        ));
+        assert!(bob_store
+            .load_session(&carol_address, None)
+            .await?
+            .is_none());
+
+        // Bob's persisted session with Alice works in both directions.
+        let bob_reply = encrypt(&mut bob_store, &alice_address, "from bob").await?;
+        assert_eq!(bob_reply.message_type(), CiphertextMessageType::Whisper);
+        let ptext = decrypt(&mut alice_store, &bob_address, &bob_reply).await?;
+        assert_eq!(String::from_utf8(ptext).unwrap(), "from bob");
+
+        Ok(())
+    })
+}
+
+#[test]
+fn optional_one_time_prekey() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1);
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+        let mut bob_store = support::test_in_memory_protocol_store();
+
+        let mut csprng = OsRng;
+        let bob_signed_pre_key_pair = KeyPair::generate(&mut csprng);
+
+        let bob_signed_pre_key_public = bob_signed_pre_key_pair.public_key.serialize();
+        let bob_signed_pre_key_signature = bob_store
+            .get_identity_key_pair(None)
+            .await?
+            .private_key()
+            .calculate_signature(&bob_signed_pre_key_public, &mut csprng)?;
+
+        let signed_pre_key_id = 22;
+
+        let bob_pre_key_bundle = PreKeyBundle::new(
+            bob_store.get_local_registration_id(None).await?,
+            1,                 // device id
+            None,              // no pre key
+            signed_pre_key_id, // signed pre key id
+            bob_signed_pre_key_pair.public_key,
+            bob_signed_pre_key_signature.to_vec(),
+            *bob_store.get_identity_key_pair(None).await?.identity_key(),
+        )?;
+
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        assert_eq!(
+            alice_store
+                .load_session(&bob_address, None)
+                .await?
+                .unwrap()
+                .session_version()?,
+            3
+        );
+
+        let original_message = "L'homme est condamné à être libre";
+
+        let outgoing_message = encrypt(&mut alice_store, &bob_address, original_message).await?;
+
+        assert_eq!(
+            outgoing_message.message_type(),
+            CiphertextMessageType::PreKey
+        );
+
+        let incoming_message = CiphertextMessage::PreKeySignalMessage(
+            PreKeySignalMessage::try_from(outgoing_message.serialize())?,
+        );
+
+        bob_store
+            .save_signed_pre_key(
+                signed_pre_key_id,
+                &SignedPreKeyRecord::new(
+                    signed_pre_key_id,
+                    /*timestamp*/ 42,
+                    &bob_signed_pre_key_pair,
+                    &bob_signed_pre_key_signature,
+                ),
+                None,
+            )
+            .await?;
+
+        let ptext = decrypt(&mut bob_store, &alice_address, &incoming_message).await?;
+
+        assert_eq!(String::from_utf8(ptext).unwrap(), original_message);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn identity_seen_before() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
+
+        let mut csprng = OsRng;
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+        let mut bob_store = support::test_in_memory_protocol_store();
+        let mut bob_new_store = support::test_in_memory_protocol_store();
+
+        let bob_identity = *bob_store.get_identity_key_pair(None).await?.identity_key();
+        let bob_new_identity = *bob_new_store
+            .get_identity_key_pair(None)
+            .await?
+            .identity_key();
+        let unrelated_identity = *IdentityKeyPair::generate(&mut csprng).identity_key();
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let session = alice_store.load_session(&bob_address, None).await?.unwrap();
+        assert!(session.identity_seen_before(&bob_identity)?);
+        assert!(!session.identity_seen_before(&bob_new_identity)?);
+
+        // Bob reinstalls with a new identity, which Alice accepts
+        alice_store
+            .save_identity(&bob_address, &bob_new_identity, None)
+            .await?;
+        let bob_new_pre_key_bundle = create_pre_key_bundle(&mut bob_new_store, &mut csprng).await?;
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_new_pre_key_bundle,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        // If Bob reverts to his original identity, it is recognized from the archived session
+        let session = alice_store.load_session(&bob_address, None).await?.unwrap();
+        assert!(session.identity_seen_before(&bob_new_identity)?);
+        assert!(session.identity_seen_before(&bob_identity)?);
+        assert!(!session.identity_seen_before(&unrelated_identity)?);
+
+        assert!(!SessionRecord::new_fresh().identity_seen_before(&bob_identity)?);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn replayed_prekey_message_already_processed() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1);
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
+
+        let mut csprng = OsRng;
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+        let mut bob_store = support::test_in_memory_protocol_store();
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let original_message = "Tu ne quittes pas le mot de passe";
+        let outgoing_message = encrypt(&mut alice_store, &bob_address, original_message).await?;
+        assert_eq!(
+            outgoing_message.message_type(),
+            CiphertextMessageType::PreKey
+        );
+        let prekey_message = PreKeySignalMessage::try_from(outgoing_message.serialize())?;
+
+        assert!(!SessionRecord::new_fresh().pre_key_message_already_processed(&prekey_message)?);
+
+        let incoming_message = CiphertextMessage::PreKeySignalMessage(prekey_message.clone());
+        let ptext = decrypt(&mut bob_store, &alice_address, &incoming_message).await?;
+        assert_eq!(String::from_utf8(ptext).unwrap(), original_message);
+
+        // The same message arriving again is recognized without touching the pre-key store
+        let bob_session = bob_store.load_session(&alice_address, None).await?.unwrap();
+        assert!(bob_session.pre_key_message_already_processed(&prekey_message)?);
+
+        // A message from a fresh session with Bob is not
+        let mut alice_new_store = support::test_in_memory_protocol_store();
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_new_store.session_store,
+            &mut alice_new_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut csprng,
+            None,
+        )
+        .await?;
+        let other_message = encrypt(&mut alice_new_store, &bob_address, original_message).await?;
+        let other_prekey_message = PreKeySignalMessage::try_from(other_message.serialize())?;
+        assert!(!bob_session.pre_key_message_already_processed(&other_prekey_message)?);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn plan_decrypt_matches_store_writes() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1);
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
+
+        let mut csprng = OsRng;
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+        let mut bob_store = support::test_in_memory_protocol_store();
+
+        let alice_identity = *alice_store
+            .get_identity_key_pair(None)
+            .await?
+            .identity_key();
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
+        let pre_key_id = bob_pre_key_bundle.pre_key_id()?.unwrap();
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let message1 = encrypt(&mut alice_store, &bob_address, "first").await?;
+        let message2 = encrypt(&mut alice_store, &bob_address, "second").await?;
+        let message1 = PreKeySignalMessage::try_from(message1.serialize())?;
+        let message2 = PreKeySignalMessage::try_from(message2.serialize())?;
+
+        let plan = plan_decrypt_prekey(
+            &message1,
+            bob_store.load_session(&alice_address, None).await?.as_ref(),
+            bob_store.get_identity(&alice_address, None).await?.as_ref(),
+        )?;
+        assert!(plan.creates_session());
+        assert_eq!(plan.consumed_pre_key_id(), Some(pre_key_id));
+        assert!(plan.changes_identity());
+
+        decrypt(
+            &mut bob_store,
+            &alice_address,
+            &CiphertextMessage::PreKeySignalMessage(message1),
+        )
+        .await?;
+        assert!(bob_store
+            .load_session(&alice_address, None)
+            .await?
+            .is_some());
+        assert!(bob_store.get_pre_key(pre_key_id, None).await.is_err());
+        assert_eq!(
+            bob_store.get_identity(&alice_address, None).await?,
+            Some(alice_identity)
+        );
+
+        // The second message was sent before Alice heard back, so it reuses the same session
+        let session_before = bob_store
+            .load_session(&alice_address, None)
+            .await?
+            .unwrap()
+            .serialize()?;
+        let plan = plan_decrypt_prekey(
+            &message2,
+            bob_store.load_session(&alice_address, None).await?.as_ref(),
+            bob_store.get_identity(&alice_address, None).await?.as_ref(),
+        )?;
+        assert!(!plan.creates_session());
+        assert_eq!(plan.consumed_pre_key_id(), None);
+        assert!(!plan.changes_identity());
+
+        decrypt(
+            &mut bob_store,
+            &alice_address,
+            &CiphertextMessage::PreKeySignalMessage(message2),
+        )
+        .await?;
+        let session_after = bob_store.load_session(&alice_address, None).await?.unwrap();
+        assert_eq!(
+            session_after.alice_base_key()?,
+            SessionRecord::deserialize(&session_before)?.alice_base_key()?
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn estimated_ciphertext_size_matches_encryption() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1);
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
+
+        let mut csprng = OsRng;
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+        let mut bob_store = support::test_in_memory_protocol_store();
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let mut last_prekey_message = None;
+        for &len in &[0, 15, 16, 17, 300] {
+            let ptext = "x".repeat(len);
+            let session = alice_store.load_session(&bob_address, None).await?.unwrap();
+            let estimate = estimate_ciphertext_size(len, &session)?;
+            let message = encrypt(&mut alice_store, &bob_address, &ptext).await?;
+            assert_eq!(message.message_type(), CiphertextMessageType::PreKey);
+            assert_eq!(estimate, message.serialize().len());
+            last_prekey_message = Some(message);
+        }
+
+        decrypt(
+            &mut bob_store,
+            &alice_address,
+            &last_prekey_message.unwrap(),
+        )
+        .await?;
+        let reply = encrypt(&mut bob_store, &alice_address, "ack").await?;
+        decrypt(&mut alice_store, &bob_address, &reply).await?;
+
+        for &len in &[0, 31, 32, 1000] {
+            let ptext = "x".repeat(len);
+            let session = alice_store.load_session(&bob_address, None).await?.unwrap();
+            let estimate = estimate_ciphertext_size(len, &session)?;
+            let message = encrypt(&mut alice_store, &bob_address, &ptext).await?;
+            assert_eq!(message.message_type(), CiphertextMessageType::Whisper);
+            assert_eq!(estimate, message.serialize().len());
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+fn max_plaintext_size_fits_budget() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1);
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
+
+        let mut csprng = OsRng;
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+        let mut bob_store = support::test_in_memory_protocol_store();
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let session = alice_store.load_session(&bob_address, None).await?.unwrap();
+        assert!(matches!(
+            max_plaintext_size(estimate_ciphertext_size(0, &session)? - 1, &session),
+            Err(SignalProtocolError::InvalidArgument(_))
+        ));
+
+        let mut last_message = None;
+        for &budget in &[200, 256, 1000] {
+            let session = alice_store.load_session(&bob_address, None).await?.unwrap();
+            let max = max_plaintext_size(budget, &session)?;
+            assert!(estimate_ciphertext_size(max + 1, &session)? > budget);
+
+            let message = encrypt(&mut alice_store, &bob_address, &"x".repeat(max)).await?;
+            assert_eq!(message.message_type(), CiphertextMessageType::PreKey);
+            assert!(message.serialize().len() <= budget);
+            last_message = Some(message);
+        }
+
+        decrypt(&mut bob_store, &alice_address, &last_message.unwrap()).await?;
+        let reply = encrypt(&mut bob_store, &alice_address, "ack").await?;
+        decrypt(&mut alice_store, &bob_address, &reply).await?;
+
+        for &budget in &[80, 100, 1000] {
+            let session = alice_store.load_session(&bob_address, None).await?.unwrap();
+            let max = max_plaintext_size(budget, &session)?;
+            assert!(estimate_ciphertext_size(max + 1, &session)? > budget);
+
+            let message = encrypt(&mut alice_store, &bob_address, &"x".repeat(max)).await?;
+            assert_eq!(message.message_type(), CiphertextMessageType::Whisper);
+            assert!(message.serialize().len() <= budget);
+        }
+
+        Ok(())
+    })
+}
+
+#[test]
+fn message_keys_agree_at_index() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1);
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
+
+        let mut csprng = OsRng;
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+        let mut bob_store = support::test_in_memory_protocol_store();
+
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
+        process_prekey_bundle(
+            &bob_address,
+            &mut alice_store.session_store,
+            &mut alice_store.identity_store,
+            &bob_pre_key_bundle,
+            &mut csprng,
+            None,
+        )
+        .await?;
+
+        let outgoing_message = encrypt(&mut alice_store, &bob_address, "hi").await?;
+        let prekey_message = PreKeySignalMessage::try_from(outgoing_message.serialize())?;
+        let alice_ratchet_key = *prekey_message.message().sender_ratchet_key();
+        decrypt(
+            &mut bob_store,
+            &alice_address,
+            &CiphertextMessage::PreKeySignalMessage(prekey_message),
+        )
+        .await?;
+
+        let alice_session = alice_store.load_session(&bob_address, None).await?.unwrap();
+        let bob_session = bob_store.load_session(&alice_address, None).await?.unwrap();
+
+        for &index in &[1, 5] {
+            let alice_keys = alice_session.message_key_bytes_at(&alice_ratchet_key, index)?;
+            let bob_keys = bob_session.message_key_bytes_at(&alice_ratchet_key, index)?;
+            assert_eq!(alice_keys.len(), 80);
+            assert_eq!(alice_keys, bob_keys);
+        }
+        assert_ne!(
+            alice_session.message_key_bytes_at(&alice_ratchet_key, 1)?,
+            alice_session.message_key_bytes_at(&alice_ratchet_key, 2)?
+        );
+
+        // Message 0 has already been consumed on both sides
+        assert!(alice_session
+            .message_key_bytes_at(&alice_ratchet_key, 0)
+            .is_err());
+        assert!(bob_session
+            .message_key_bytes_at(&alice_ratchet_key, 0)
+            .is_err());
+
+        // Both chains are at index 1, and jumps are capped like they are for decryption
+        assert!(bob_session
+            .message_key_bytes_at(&alice_ratchet_key, 1 + 25_000)
+            .is_ok());
+        assert!(bob_session
+            .message_key_bytes_at(&alice_ratchet_key, 1 + 25_001)
+            .is_err());
+        assert!(alice_session
+            .message_key_bytes_at(&alice_ratchet_key, u32::MAX)
+            .is_err());
+
+        Ok(())
+    })
+}
+
+#[test]
+#[cfg(feature = "forensics")]
+fn exported_chain_keys_derive_message_keys() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1);
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
+
+        let mut csprng = OsRng;
 
-        let bob_pre_key_bundle = PreKeyBundle::new(
-            bob_store.get_local_registration_id(None).await?,
-            1, // device id
-            Some((pre_key_id, bob_pre_key_pair.public_key)),
-            signed_pre_key_id, // signed pre key id
-            bob_signed_pre_key_pair.public_key,
-            bob_signed_pre_key_signature.to_vec(),
-            *bob_store.get_identity_key_pair(None).await?.identity_key(),
-        )?;
+        let mut alice_store = support::test_in_memory_protocol_store();
+        let mut bob_store = support::test_in_memory_protocol_store();
 
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
         process_prekey_bundle(
             &bob_address,
             &mut alice_store.session_store,
@@ -482,158 +1153,195 @@ fn bad_message_bundle() -> Result<(), SignalProtocolError> {
         )
         .await?;
 
-        bob_store
-            .save_pre_key(
-                pre_key_id,
-                &PreKeyRecord::new(pre_key_id, &bob_pre_key_pair),
-                None,
-            )
-            .await?;
-        bob_store
-            .save_signed_pre_key(
-                signed_pre_key_id,
-                &SignedPreKeyRecord::new(
-                    signed_pre_key_id,
-                    /*timestamp*/ 42,
-                    &bob_signed_pre_key_pair,
-                    &bob_signed_pre_key_signature,
-                ),
-                None,
-            )
-            .await?;
-
-        assert!(alice_store
-            .load_session(&bob_address, None)
-            .await?
-            .is_some());
-        assert_eq!(
-            alice_store
-                .load_session(&bob_address, None)
-                .await?
-                .unwrap()
-                .session_version()?,
-            3
-        );
+        let outgoing_message = encrypt(&mut alice_store, &bob_address, "hi").await?;
+        let prekey_message = PreKeySignalMessage::try_from(outgoing_message.serialize())?;
+        let alice_ratchet_key = *prekey_message.message().sender_ratchet_key();
+        // Alice drops this sender chain once she ratchets on Bob's reply, so snapshot it first.
+        let alice_session_before_reply =
+            alice_store.load_session(&bob_address, None).await?.unwrap();
+        decrypt(
+            &mut bob_store,
+            &alice_address,
+            &CiphertextMessage::PreKeySignalMessage(prekey_message),
+        )
+        .await?;
 
-        let original_message = "L'homme est condamné à être libre";
+        let reply = encrypt(&mut bob_store, &alice_address, "hello").await?;
+        let signal_message = SignalMessage::try_from(reply.serialize())?;
+        let bob_ratchet_key = *signal_message.sender_ratchet_key();
+        decrypt(
+            &mut alice_store,
+            &bob_address,
+            &CiphertextMessage::SignalMessage(signal_message),
+        )
+        .await?;
 
-        assert!(bob_store.get_pre_key(pre_key_id, None).await.is_ok());
-        let outgoing_message = encrypt(&mut alice_store, &bob_address, original_message).await?;
+        let alice_session = alice_store.load_session(&bob_address, None).await?.unwrap();
+        let mut bob_session = bob_store.load_session(&alice_address, None).await?.unwrap();
 
+        let exported = bob_session.export_all_chain_keys()?;
+        let bob_sender = exported
+            .iter()
+            .find(|k| k.is_sender())
+            .expect("sender chain");
+        assert_eq!(bob_sender.state_index(), 0);
+        assert_eq!(*bob_sender.ratchet_key(), bob_ratchet_key);
         assert_eq!(
-            outgoing_message.message_type(),
-            CiphertextMessageType::PreKey
+            bob_sender.message_key_bytes_at(3)?,
+            alice_session.message_key_bytes_at(&bob_ratchet_key, 3)?
         );
 
-        let outgoing_message = outgoing_message.serialize().to_vec();
+        let bob_receiver = exported
+            .iter()
+            .find(|k| !k.is_sender() && *k.ratchet_key() == alice_ratchet_key)
+            .expect("receiver chain");
+        assert_eq!(bob_receiver.state_index(), 0);
+        assert_eq!(
+            bob_receiver.message_key_bytes_at(2)?,
+            alice_session_before_reply.message_key_bytes_at(&alice_ratchet_key, 2)?
+        );
 
-        let mut corrupted_message: Vec<u8> = outgoing_message.clone();
-        corrupted_message[outgoing_message.len() - 10] ^= 1;
+        bob_session.archive_current_state()?;
+        let archived = bob_session.export_all_chain_keys()?;
+        assert_eq!(archived.len(), exported.len());
+        for (before, after) in exported.iter().zip(&archived) {
+            assert_eq!(after.state_index(), 1);
+            assert_eq!(after.key(), before.key());
+            assert_eq!(after.index(), before.index());
+        }
 
-        let incoming_message = CiphertextMessage::PreKeySignalMessage(
-            PreKeySignalMessage::try_from(corrupted_message.as_slice())?,
-        );
+        Ok(())
+    })
+}
 
-        assert!(decrypt(&mut bob_store, &alice_address, &incoming_message)
-            .await
-            .is_err());
-        assert!(bob_store.get_pre_key(pre_key_id, None).await.is_ok());
+#[test]
+fn session_creation_timestamp() -> Result<(), SignalProtocolError> {
+    let (alice_session, bob_session) = initialize_sessions_v3()?;
 
-        let incoming_message = CiphertextMessage::PreKeySignalMessage(
-            PreKeySignalMessage::try_from(outgoing_message.as_slice())?,
-        );
+    for session in &[alice_session, bob_session] {
+        assert_eq!(session.creation_timestamp()?, TEST_TIMESTAMP);
 
-        let ptext = decrypt(&mut bob_store, &alice_address, &incoming_message).await?;
+        let round_tripped = SessionRecord::deserialize(&session.serialize()?)?;
+        assert_eq!(round_tripped.creation_timestamp()?, TEST_TIMESTAMP);
+    }
 
-        assert_eq!(String::from_utf8(ptext).unwrap(), original_message);
-        assert!(matches!(
-            bob_store.get_pre_key(pre_key_id, None).await.unwrap_err(),
-            SignalProtocolError::InvalidPreKeyId
-        ));
+    assert!(SessionRecord::new_fresh().creation_timestamp().is_err());
 
-        Ok(())
-    })
+    Ok(())
 }
 
 #[test]
-fn optional_one_time_prekey() -> Result<(), SignalProtocolError> {
+fn session_creation_timestamp_from_prekey_exchange() -> Result<(), SignalProtocolError> {
     block_on(async {
+        let mut csprng = OsRng;
+
         let alice_address = ProtocolAddress::new("+14151111111".to_owned(), 1);
         let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
 
         let mut alice_store = support::test_in_memory_protocol_store();
         let mut bob_store = support::test_in_memory_protocol_store();
 
-        let mut csprng = OsRng;
-        let bob_signed_pre_key_pair = KeyPair::generate(&mut csprng);
-
-        let bob_signed_pre_key_public = bob_signed_pre_key_pair.public_key.serialize();
-        let bob_signed_pre_key_signature = bob_store
-            .get_identity_key_pair(None)
-            .await?
-            .private_key()
-            .calculate_signature(&bob_signed_pre_key_public, &mut csprng)?;
-
-        let signed_pre_key_id = 22;
-
-        let bob_pre_key_bundle = PreKeyBundle::new(
-            bob_store.get_local_registration_id(None).await?,
-            1,                 // device id
-            None,              // no pre key
-            signed_pre_key_id, // signed pre key id
-            bob_signed_pre_key_pair.public_key,
-            bob_signed_pre_key_signature.to_vec(),
-            *bob_store.get_identity_key_pair(None).await?.identity_key(),
-        )?;
+        let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
 
-        process_prekey_bundle(
+        process_prekey_bundle_with_timestamp(
             &bob_address,
             &mut alice_store.session_store,
             &mut alice_store.identity_store,
             &bob_pre_key_bundle,
+            TEST_TIMESTAMP,
             &mut csprng,
             None,
         )
         .await?;
 
-        assert_eq!(
-            alice_store
-                .load_session(&bob_address, None)
-                .await?
-                .unwrap()
-                .session_version()?,
-            3
-        );
+        let alice_message = encrypt(&mut alice_store, &bob_address, "hi bob").await?;
+        message_decrypt_with_timestamp(
+            &alice_message,
+            &alice_address,
+            &mut bob_store.session_store,
+            &mut bob_store.identity_store,
+            &mut bob_store.pre_key_store,
+            &mut bob_store.signed_pre_key_store,
+            TEST_TIMESTAMP + 1,
+            &mut csprng,
+            None,
+        )
+        .await?;
 
-        let original_message = "L'homme est condamné à être libre";
+        let alice_session = alice_store.load_session(&bob_address, None).await?.unwrap();
+        assert_eq!(alice_session.creation_timestamp()?, TEST_TIMESTAMP);
+        let bob_session = bob_store.load_session(&alice_address, None).await?.unwrap();
+        assert_eq!(bob_session.creation_timestamp()?, TEST_TIMESTAMP + 1);
 
-        let outgoing_message = encrypt(&mut alice_store, &bob_address, original_message).await?;
+        Ok(())
+    })
+}
 
-        assert_eq!(
-            outgoing_message.message_type(),
-            CiphertextMessageType::PreKey
-        );
+#[test]
+fn session_can_encrypt() -> Result<(), SignalProtocolError> {
+    assert!(!SessionRecord::new_fresh().can_encrypt()?);
 
-        let incoming_message = CiphertextMessage::PreKeySignalMessage(
-            PreKeySignalMessage::try_from(outgoing_message.serialize())?,
-        );
+    let (mut alice_session, bob_session) = initialize_sessions_v3()?;
+    assert!(alice_session.can_encrypt()?);
+    assert!(bob_session.can_encrypt()?);
 
-        bob_store
-            .save_signed_pre_key(
-                signed_pre_key_id,
-                &SignedPreKeyRecord::new(
-                    signed_pre_key_id,
-                    /*timestamp*/ 42,
-                    &bob_signed_pre_key_pair,
-                    &bob_signed_pre_key_signature,
-                ),
-                None,
-            )
+    alice_session.archive_current_state()?;
+    assert!(!alice_session.has_current_session_state());
+    assert!(!alice_session.can_encrypt()?);
+
+    let round_tripped = SessionRecord::deserialize(&alice_session.serialize()?)?;
+    assert!(!round_tripped.can_encrypt()?);
+
+    Ok(())
+}
+
+#[test]
+fn session_id_is_stable() -> Result<(), SignalProtocolError> {
+    let (alice_session, bob_session) = initialize_sessions_v3()?;
+
+    let alice_id = alice_session.compute_session_id()?;
+    assert_eq!(alice_id.len(), 16);
+
+    let round_tripped = SessionRecord::deserialize(&alice_session.serialize()?)?;
+    assert_eq!(round_tripped.compute_session_id()?, alice_id);
+
+    let (other_alice_session, _) = initialize_sessions_v3()?;
+    assert_ne!(other_alice_session.compute_session_id()?, alice_id);
+    assert_ne!(bob_session.compute_session_id()?, alice_id);
+
+    assert!(SessionRecord::new_fresh().compute_session_id().is_err());
+
+    Ok(())
+}
+
+#[test]
+fn encrypt_uses_and_updates_stored_session() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let bob_address = ProtocolAddress::new("+14151111112".to_owned(), 1);
+        let mut alice_store = support::test_in_memory_protocol_store();
+
+        assert!(matches!(
+            encrypt(&mut alice_store, &bob_address, "no session yet").await,
+            Err(SignalProtocolError::SessionNotFound(_))
+        ));
+
+        let (alice_session, _bob_session) = initialize_sessions_v3()?;
+        let initial_chain_key = alice_session.get_sender_chain_key_bytes()?;
+        alice_store
+            .store_session(&bob_address, &alice_session, None)
             .await?;
 
-        let ptext = decrypt(&mut bob_store, &alice_address, &incoming_message).await?;
+        let ctext = encrypt(&mut alice_store, &bob_address, "hi there").await?;
+        assert_eq!(ctext.message_type(), CiphertextMessageType::Whisper);
 
-        assert_eq!(String::from_utf8(ptext).unwrap(), original_message);
+        let stored_session = alice_store
+            .load_session(&bob_address, None)
+            .await?
+            .expect("session persisted");
+        assert_ne!(
+            stored_session.get_sender_chain_key_bytes()?,
+            initial_chain_key
+        );
 
         Ok(())
     })
@@ -1708,3 +2416,118 @@ fn simultaneous_initiate_lost_message_repeated_messages() -> Result<(), SignalPr
         Ok(())
     })
 }
+
+#[test]
+fn device_ids_for_name() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let mut csprng = OsRng;
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+
+        for &device_id in &[2, 1] {
+            let bob_address = ProtocolAddress::new("+14151111112".to_owned(), device_id);
+            let mut bob_store = support::test_in_memory_protocol_store();
+            let bob_pre_key_bundle = create_pre_key_bundle(&mut bob_store, &mut csprng).await?;
+            process_prekey_bundle(
+                &bob_address,
+                &mut alice_store.session_store,
+                &mut alice_store.identity_store,
+                &bob_pre_key_bundle,
+                &mut csprng,
+                None,
+            )
+            .await?;
+        }
+
+        assert_eq!(
+            alice_store
+                .get_device_ids_for_name("+14151111112", None)
+                .await?,
+            vec![1, 2]
+        );
+        assert!(alice_store
+            .get_device_ids_for_name("+14151111113", None)
+            .await?
+            .is_empty());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn archive_all_sessions_for_name_archives_each_device() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let mut csprng = OsRng;
+
+        let mut alice_store = support::test_in_memory_protocol_store();
+
+        let bob_addresses = [
+            ProtocolAddress::new("+14151111112".to_owned(), 1),
+            ProtocolAddress::new("+14151111112".to_owned(), 2),
+        ];
+        let carol_address = ProtocolAddress::new("+14151111113".to_owned(), 1);
+
+        for address in bob_addresses.iter().chain(std::iter::once(&carol_address)) {
+            let mut remote_store = support::test_in_memory_protocol_store();
+            let pre_key_bundle = create_pre_key_bundle(&mut remote_store, &mut csprng).await?;
+            process_prekey_bundle(
+                address,
+                &mut alice_store.session_store,
+                &mut alice_store.identity_store,
+                &pre_key_bundle,
+                &mut csprng,
+                None,
+            )
+            .await?;
+        }
+
+        archive_all_sessions_for_name("+14151111112", &mut alice_store.session_store, None).await?;
+
+        for address in &bob_addresses {
+            let session = alice_store.load_session(address, None).await?.unwrap();
+            assert!(!session.has_current_session_state());
+        }
+        let session = alice_store
+            .load_session(&carol_address, None)
+            .await?
+            .unwrap();
+        assert!(session.has_current_session_state());
+
+        Ok(())
+    })
+}
+
+/// A session store that relies on the default `get_device_ids_for_name`.
+struct UnlistableSessionStore(InMemSessionStore);
+
+#[async_trait(?Send)]
+impl SessionStore for UnlistableSessionStore {
+    async fn load_session(
+        &self,
+        address: &ProtocolAddress,
+        ctx: Context,
+    ) -> Result<Option<SessionRecord>, SignalProtocolError> {
+        self.0.load_session(address, ctx).await
+    }
+
+    async fn store_session(
+        &mut self,
+        address: &ProtocolAddress,
+        record: &SessionRecord,
+        ctx: Context,
+    ) -> Result<(), SignalProtocolError> {
+        self.0.store_session(address, record, ctx).await
+    }
+}
+
+#[test]
+fn archive_all_sessions_for_name_requires_device_listing() -> Result<(), SignalProtocolError> {
+    block_on(async {
+        let mut store = UnlistableSessionStore(InMemSessionStore::new());
+        assert!(matches!(
+            archive_all_sessions_for_name("+14151111112", &mut store, None).await,
+            Err(SignalProtocolError::InvalidState("get_device_ids_for_name", _))
+        ));
+        Ok(())
+    })
+}
@@ -6,6 +6,10 @@
 use libsignal_protocol::*;
 use rand::{rngs::OsRng, CryptoRng, Rng};
 
+/// The time, in milliseconds since the Unix epoch, at which test sessions are created.
+#[allow(dead_code)]
+pub const TEST_TIMESTAMP: u64 = 1605722925000;
+
 pub fn test_in_memory_protocol_store() -> InMemSignalProtocolStore {
     let mut csprng = OsRng;
     let identity_key = IdentityKeyPair::generate(&mut csprng);
@@ -125,7 +129,8 @@ pub fn initialize_sessions_v3() -> Result<(SessionRecord, SessionRecord), Signal
         bob_ephemeral_key.public_key,
     );
 
-    let alice_session = initialize_alice_session_record(&alice_params, &mut csprng)?;
+    let alice_session =
+        initialize_alice_session_record_with_timestamp(&alice_params, TEST_TIMESTAMP, &mut csprng)?;
 
     let bob_params = BobSignalProtocolParameters::new(
         bob_identity,
@@ -136,7 +141,7 @@ pub fn initialize_sessions_v3() -> Result<(SessionRecord, SessionRecord), Signal
         alice_base_key.public_key,
     );
 
-    let bob_session = initialize_bob_session_record(&bob_params)?;
+    let bob_session = initialize_bob_session_record_with_timestamp(&bob_params, TEST_TIMESTAMP)?;
 
     Ok((alice_session, bob_session))
 }
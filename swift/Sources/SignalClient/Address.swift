@@ -12,6 +12,13 @@ public class ProtocolAddress: ClonableHandleOwner {
         super.init(owned: handle!)
     }
 
+    /// Parses the canonical `name.deviceId` form produced by `canonicalString`.
+    public init(canonicalString: String) throws {
+        var handle: OpaquePointer?
+        try checkError(signal_address_from_string(&handle, canonicalString))
+        super.init(owned: handle!)
+    }
+
     internal override init(borrowing handle: OpaquePointer?) {
         super.init(borrowing: handle)
     }
@@ -43,6 +50,14 @@ public class ProtocolAddress: ClonableHandleOwner {
             }
         }
     }
+
+    public var canonicalString: String {
+        return failOnError {
+            try invokeFnReturningString {
+                signal_address_to_string($0, nativeHandle)
+            }
+        }
+    }
 }
 
 extension ProtocolAddress: Hashable {
@@ -0,0 +1,46 @@
+//
+// Copyright 2021 Signal Messenger, LLC
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+
+public func aes256CbcEncrypt<KeyBytes, IvBytes, MessageBytes>(key: KeyBytes, iv: IvBytes, plaintext: MessageBytes) throws -> [UInt8]
+    where KeyBytes: ContiguousBytes, IvBytes: ContiguousBytes, MessageBytes: ContiguousBytes {
+    return try key.withUnsafeBytes { keyBytes in
+        try iv.withUnsafeBytes { ivBytes in
+            try plaintext.withUnsafeBytes { messageBytes in
+                try invokeFnReturningArray {
+                    signal_aes256_cbc_encrypt($0,
+                                              $1,
+                                              keyBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                              keyBytes.count,
+                                              ivBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                              ivBytes.count,
+                                              messageBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                              messageBytes.count)
+                }
+            }
+        }
+    }
+}
+
+public func aes256CbcDecrypt<KeyBytes, IvBytes, MessageBytes>(key: KeyBytes, iv: IvBytes, ciphertext: MessageBytes) throws -> [UInt8]
+    where KeyBytes: ContiguousBytes, IvBytes: ContiguousBytes, MessageBytes: ContiguousBytes {
+    return try key.withUnsafeBytes { keyBytes in
+        try iv.withUnsafeBytes { ivBytes in
+            try ciphertext.withUnsafeBytes { messageBytes in
+                try invokeFnReturningArray {
+                    signal_aes256_cbc_decrypt($0,
+                                              $1,
+                                              keyBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                              keyBytes.count,
+                                              ivBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                              ivBytes.count,
+                                              messageBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                              messageBytes.count)
+                }
+            }
+        }
+    }
+}
@@ -19,6 +19,18 @@ public class Aes256GcmSiv: ClonableHandleOwner {
         return signal_aes256_gcm_siv_destroy(handle)
     }
 
+    public func rekey<Bytes: ContiguousBytes>(_ bytes: Bytes) throws {
+        try bytes.withUnsafeBytes {
+            try checkError(signal_aes256_gcm_siv_rekey(nativeHandle, $0.baseAddress?.assumingMemoryBound(to: UInt8.self), $0.count))
+        }
+    }
+
+    public static var tagLength: Int {
+        var result: UInt32 = 0
+        failOnError(signal_aes256_gcm_siv_tag_length(&result))
+        return Int(result)
+    }
+
     public func encrypt<MessageBytes, NonceBytes, AssociatedDataBytes>(
       _ message: MessageBytes,
       _ nonce: NonceBytes,
@@ -46,6 +58,35 @@ public class Aes256GcmSiv: ClonableHandleOwner {
         }
     }
 
+    /// Encrypts `message` in place and returns the tag, avoiding a copy of the message.
+    public func encryptInPlace<NonceBytes, AssociatedDataBytes>(
+      _ message: inout [UInt8],
+      _ nonce: NonceBytes,
+      _ associated_data: AssociatedDataBytes) throws -> [UInt8]
+      where NonceBytes: ContiguousBytes,
+            AssociatedDataBytes: ContiguousBytes {
+
+        var tag = [UInt8](repeating: 0, count: Aes256GcmSiv.tagLength)
+        try message.withUnsafeMutableBytes { messageBytes in
+            try tag.withUnsafeMutableBytes { tagBytes in
+                try nonce.withUnsafeBytes { nonceBytes in
+                    try associated_data.withUnsafeBytes { adBytes in
+                        try checkError(signal_aes256_gcm_siv_encrypt_in_place(nativeHandle,
+                                                                              messageBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                                                              messageBytes.count,
+                                                                              tagBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                                                              tagBytes.count,
+                                                                              nonceBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                                                              nonceBytes.count,
+                                                                              adBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                                                              adBytes.count))
+                    }
+                }
+            }
+        }
+        return tag
+    }
+
     public func decrypt<MessageBytes, NonceBytes, AssociatedDataBytes> (
       _ message: MessageBytes,
       _ nonce: NonceBytes,
@@ -0,0 +1,13 @@
+//
+// Copyright 2021 Signal Messenger, LLC
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+
+/// Returns the size an attachment of `plaintextLength` bytes is padded to before upload.
+public func attachmentPaddedSize(plaintextLength: UInt64) throws -> UInt64 {
+    return try invokeFnReturningInteger {
+        signal_attachment_padded_size($0, plaintextLength)
+    }
+}
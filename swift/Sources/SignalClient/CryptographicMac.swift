@@ -0,0 +1,37 @@
+//
+// Copyright 2021 Signal Messenger, LLC
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+
+public class CryptographicMac: ClonableHandleOwner {
+    public init<KeyBytes: ContiguousBytes>(key: KeyBytes) {
+        let handle: OpaquePointer? = key.withUnsafeBytes { keyBytes in
+            var result: OpaquePointer?
+            failOnError(signal_cryptographic_mac_new(&result, keyBytes.baseAddress?.assumingMemoryBound(to: UInt8.self), keyBytes.count))
+            return result
+        }
+        super.init(owned: handle!)
+    }
+
+    internal override class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_cryptographic_mac_destroy(handle)
+    }
+
+    internal override class func cloneNativeHandle(_ newHandle: inout OpaquePointer?, currentHandle: OpaquePointer?) -> SignalFfiErrorRef? {
+        return signal_cryptographic_mac_clone(&newHandle, currentHandle)
+    }
+
+    public func update<InputBytes: ContiguousBytes>(_ input: InputBytes) throws {
+        try input.withUnsafeBytes { inputBytes in
+            try checkError(signal_cryptographic_mac_update(nativeHandle, inputBytes.baseAddress?.assumingMemoryBound(to: UInt8.self), inputBytes.count))
+        }
+    }
+
+    public func finalize() throws -> [UInt8] {
+        return try invokeFnReturningArray {
+            signal_cryptographic_mac_finalize($0, $1, nativeHandle)
+        }
+    }
+}
@@ -91,6 +91,10 @@ public class InMemorySignalProtocolStore: IdentityKeyStore, PreKeyStore, SignedP
         sessionMap[address] = record
     }
 
+    public func deviceIds(forName name: String, context: StoreContext) throws -> [UInt32] {
+        return sessionMap.keys.filter { $0.name == name }.map { $0.deviceId }.sorted()
+    }
+
     public func storeSenderKey(name: SenderKeyName, record: SenderKeyRecord, context: StoreContext) throws {
         senderKeyMap[name] = record
     }
@@ -35,6 +35,17 @@ public protocol SignedPreKeyStore: AnyObject {
 public protocol SessionStore: AnyObject {
     func loadSession(for address: ProtocolAddress, context: StoreContext) throws -> SessionRecord?
     func storeSession(_ record: SessionRecord, for address: ProtocolAddress, context: StoreContext) throws
+    /// Returns the device IDs of every stored session for `name`, including device 1.
+    ///
+    /// Only needed for `signalDeviceIds(forName:sessionStore:context:)` and
+    /// `signalArchiveAllSessions(forName:sessionStore:context:)`; the default throws.
+    func deviceIds(forName name: String, context: StoreContext) throws -> [UInt32]
+}
+
+extension SessionStore {
+    public func deviceIds(forName name: String, context: StoreContext) throws -> [UInt32] {
+        throw SignalError.invalidState("this session store cannot list its sessions")
+    }
 }
 
 public protocol SenderKeyStore: AnyObject {
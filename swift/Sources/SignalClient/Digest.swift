@@ -0,0 +1,39 @@
+//
+// Copyright 2021 Signal Messenger, LLC
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+
+public func sha256<InputBytes: ContiguousBytes>(_ input: InputBytes) -> [UInt8] {
+    return input.withUnsafeBytes { inputBytes in
+        failOnError {
+            try invokeFnReturningArray {
+                signal_sha256_digest($0, $1, inputBytes.baseAddress?.assumingMemoryBound(to: UInt8.self), inputBytes.count)
+            }
+        }
+    }
+}
+
+public func sha512<InputBytes: ContiguousBytes>(_ input: InputBytes) -> [UInt8] {
+    return input.withUnsafeBytes { inputBytes in
+        failOnError {
+            try invokeFnReturningArray {
+                signal_sha512_digest($0, $1, inputBytes.baseAddress?.assumingMemoryBound(to: UInt8.self), inputBytes.count)
+            }
+        }
+    }
+}
+
+/// Compares two byte strings without revealing where they differ; safe to use on MACs and other secrets.
+public func constantTimeEqual<A: ContiguousBytes, B: ContiguousBytes>(_ a: A, _ b: B) -> Bool {
+    return a.withUnsafeBytes { aBytes in
+        b.withUnsafeBytes { bBytes in
+            var result = false
+            failOnError(signal_constant_time_equal(&result,
+                                                   aBytes.baseAddress?.assumingMemoryBound(to: UInt8.self), aBytes.count,
+                                                   bBytes.baseAddress?.assumingMemoryBound(to: UInt8.self), bBytes.count))
+            return result
+        }
+    }
+}
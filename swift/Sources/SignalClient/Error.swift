@@ -27,6 +27,7 @@ public enum SignalError: Error {
     case fingerprintVersionMismatch(String)
     case fingerprintParsingError(String)
     case sealedSenderSelfSend(String)
+    case expiredCertificate(String)
     case untrustedIdentity(String)
     case invalidKeyIdentifier(String)
     case sessionNotFound(String)
@@ -37,6 +38,15 @@ public enum SignalError: Error {
 
 internal typealias SignalFfiErrorRef = OpaquePointer
 
+/// Returns an English description of a numeric `SignalErrorCode`, suitable for logging.
+public func describeSignalErrorCode(_ code: UInt32) -> String {
+    return failOnError {
+        try invokeFnReturningString {
+            signal_error_describe_code($0, code)
+        }
+    }
+}
+
 internal func checkError(_ error: SignalFfiErrorRef?) throws {
     guard let error = error else { return }
 
@@ -78,6 +88,8 @@ internal func checkError(_ error: SignalFfiErrorRef?) throws {
         throw SignalError.fingerprintParsingError(errStr)
     case SignalErrorCode_SealedSenderSelfSend:
         throw SignalError.sealedSenderSelfSend(errStr)
+    case SignalErrorCode_ExpiredCertificate:
+        throw SignalError.expiredCertificate(errStr)
     case SignalErrorCode_InvalidKey:
         throw SignalError.invalidKey(errStr)
     case SignalErrorCode_InvalidSignature:
@@ -20,6 +20,13 @@ public struct ScannableFingerprint {
         self.encoding = encoding
     }
 
+    /// Whether fingerprints of the two versions can be compared at all.
+    public static func versionsCompatible(_ ours: UInt32, _ theirs: UInt32) -> Bool {
+        var result = false
+        failOnError(signal_fingerprint_versions_compatible(&result, ours, theirs))
+        return result
+    }
+
     public func compare(against other: ScannableFingerprint) throws -> Bool {
         var result: Bool = false
         try checkError(signal_fingerprint_compare(&result, encoding, encoding.count,
@@ -31,10 +38,13 @@ public struct ScannableFingerprint {
 public struct Fingerprint {
     public let scannable: ScannableFingerprint
     public let displayable: DisplayableFingerprint
+    /// The exact bytes to encode into the safety-number QR code, version header included.
+    public let qrCodePayload: [UInt8]
 
-    internal init(displayable: DisplayableFingerprint, scannable: ScannableFingerprint) {
+    internal init(displayable: DisplayableFingerprint, scannable: ScannableFingerprint, qrCodePayload: [UInt8]) {
         self.displayable = displayable
         self.scannable = scannable
+        self.qrCodePayload = qrCodePayload
     }
 }
 
@@ -71,8 +81,12 @@ public struct NumericFingerprintGenerator {
             signal_fingerprint_scannable_encoding($0, $1, obj)
         }
         let scannable = ScannableFingerprint(encoding: scannableBits)
+
+        let qrCodePayload = try invokeFnReturningArray {
+            signal_fingerprint_get_qr_code_payload($0, $1, obj)
+        }
         try checkError(signal_fingerprint_destroy(obj))
 
-        return Fingerprint(displayable: displayable, scannable: scannable)
+        return Fingerprint(displayable: displayable, scannable: scannable, qrCodePayload: qrCodePayload)
     }
 }
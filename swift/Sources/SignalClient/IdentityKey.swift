@@ -19,6 +19,36 @@ public struct IdentityKey: Equatable {
     public func serialize() -> [UInt8] {
         return publicKey.serialize()
     }
+
+    /// A 16-byte digest of the key for detecting identity changes. Not a fingerprint.
+    public var changeHash: [UInt8] {
+        return failOnError {
+            try invokeFnReturningArray {
+                signal_identitykey_compute_change_hash($0, $1, publicKey.nativeHandle)
+            }
+        }
+    }
+
+    /// The serialized key as eleven space-separated groups of six lowercase hex digits.
+    public var displayString: String {
+        return failOnError {
+            try invokeFnReturningString {
+                signal_identitykey_to_display_string($0, publicKey.nativeHandle)
+            }
+        }
+    }
+
+    public func verifyAlternateIdentity<Bytes: ContiguousBytes>(_ other: IdentityKey, signature: Bytes) throws -> Bool {
+        var result = false
+        try signature.withUnsafeBytes {
+            try checkError(signal_identitykey_verify_alternate_identity_signature(&result,
+                                                                                  publicKey.nativeHandle,
+                                                                                  other.publicKey.nativeHandle,
+                                                                                  $0.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                                                                  $0.count))
+        }
+        return result
+    }
 }
 
 public struct IdentityKeyPair {
@@ -26,9 +56,15 @@ public struct IdentityKeyPair {
     public let privateKey: PrivateKey
 
     public static func generate() -> IdentityKeyPair {
-        let privateKey = PrivateKey.generate()
-        let publicKey = privateKey.publicKey
-        return IdentityKeyPair(publicKey: publicKey, privateKey: privateKey)
+        var keyPair: OpaquePointer?
+        failOnError(signal_key_pair_generate(&keyPair))
+        defer { failOnError(signal_key_pair_destroy(keyPair)) }
+
+        var pubkeyPtr: OpaquePointer?
+        var privkeyPtr: OpaquePointer?
+        failOnError(signal_key_pair_get_public_key(&pubkeyPtr, keyPair))
+        failOnError(signal_key_pair_get_private_key(&privkeyPtr, keyPair))
+        return IdentityKeyPair(publicKey: PublicKey(owned: pubkeyPtr!), privateKey: PrivateKey(owned: privkeyPtr!))
     }
 
     public init<Bytes: ContiguousBytes>(bytes: Bytes) throws {
@@ -55,6 +91,14 @@ public struct IdentityKeyPair {
         }
     }
 
+    public func signAlternateIdentity(_ other: IdentityKey) -> [UInt8] {
+        return failOnError {
+            try invokeFnReturningArray {
+                signal_identitykeypair_sign_alternate_identity($0, $1, publicKey.nativeHandle, privateKey.nativeHandle, other.publicKey.nativeHandle)
+            }
+        }
+    }
+
     public var identityKey: IdentityKey {
         return IdentityKey(publicKey: publicKey)
     }
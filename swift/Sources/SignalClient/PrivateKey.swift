@@ -51,6 +51,21 @@ public class PrivateKey: ClonableHandleOwner {
         }
     }
 
+    /// Signs the canonical encoding of `fields`: the number of fields as a big-endian UInt32,
+    /// then each field preceded by its length as a big-endian UInt32.
+    public func generateCanonicalSignature(fields: [[UInt8]]) -> [UInt8] {
+        var serialized: [UInt8] = []
+        for field in fields {
+            withUnsafeBytes(of: UInt32(field.count).bigEndian) { serialized.append(contentsOf: $0) }
+            serialized.append(contentsOf: field)
+        }
+        return failOnError {
+            try invokeFnReturningArray {
+                signal_privatekey_sign_canonical($0, $1, nativeHandle, serialized, serialized.count)
+            }
+        }
+    }
+
     public func keyAgreement(with other: PublicKey) -> [UInt8] {
         return failOnError {
             try invokeFnReturningArray {
@@ -59,6 +74,12 @@ public class PrivateKey: ClonableHandleOwner {
         }
     }
 
+    public func validatedKeyAgreement(with other: PublicKey) throws -> [UInt8] {
+        return try invokeFnReturningArray {
+            signal_privatekey_agree_validated($0, $1, nativeHandle, other.nativeHandle)
+        }
+    }
+
     public var publicKey: PublicKey {
         return failOnError {
             try invokeFnReturningPublicKey {
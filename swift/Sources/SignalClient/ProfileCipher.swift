@@ -0,0 +1,30 @@
+//
+// Copyright 2021 Signal Messenger, LLC
+// SPDX-License-Identifier: AGPL-3.0-only
+//
+
+import Foundation
+
+/// Encrypts a profile name under a 32-byte profile key, padding it to a fixed size bucket.
+public func encryptProfileName<KeyBytes: ContiguousBytes>(_ name: String, profileKey: KeyBytes) throws -> [UInt8] {
+    return try profileKey.withUnsafeBytes { keyBytes in
+        try invokeFnReturningArray {
+            signal_profile_cipher_encrypt_name($0, $1, name,
+                                               keyBytes.baseAddress?.assumingMemoryBound(to: UInt8.self), keyBytes.count)
+        }
+    }
+}
+
+/// Decrypts a profile name produced by `encryptProfileName`, removing the padding.
+public func decryptProfileName<CiphertextBytes, KeyBytes>(_ ciphertext: CiphertextBytes, profileKey: KeyBytes) throws -> String
+where CiphertextBytes: ContiguousBytes, KeyBytes: ContiguousBytes {
+    return try ciphertext.withUnsafeBytes { ciphertextBytes in
+        try profileKey.withUnsafeBytes { keyBytes in
+            try invokeFnReturningString {
+                signal_profile_cipher_decrypt_name($0,
+                                                   ciphertextBytes.baseAddress?.assumingMemoryBound(to: UInt8.self), ciphertextBytes.count,
+                                                   keyBytes.baseAddress?.assumingMemoryBound(to: UInt8.self), keyBytes.count)
+            }
+        }
+    }
+}
@@ -39,6 +39,17 @@ SignalFfiError *signal_decrypt_pre_key_message(const unsigned char **result,
                                                FfiSignedPreKeyStoreStruct *signed_prekey_store,
                                                void *ctx)
 
+SignalFfiError *signal_decrypt_pre_key_message_with_session_version(const unsigned char **result,
+                                                                    size_t *result_len,
+                                                                    uint32_t *session_version,
+                                                                    const PreKeySignalMessage *message,
+                                                                    const ProtocolAddress *protocol_address,
+                                                                    FfiSessionStoreStruct *session_store,
+                                                                    FfiIdentityKeyStoreStruct *identity_key_store,
+                                                                    FfiPreKeyStoreStruct *prekey_store,
+                                                                    FfiSignedPreKeyStoreStruct *signed_prekey_store,
+                                                                    void *ctx)
+
  */
 
 public func signalEncrypt<Bytes: ContiguousBytes>(message: Bytes,
@@ -97,6 +108,137 @@ public func signalDecryptPreKey(message: PreKeySignalMessage,
     }
 }
 
+public struct PreKeyDecryptionResult {
+    public var plaintext: [UInt8]
+    /// The version of the session the message was decrypted with, which may have just been created.
+    public var sessionVersion: UInt32
+}
+
+public func signalDecryptPreKeyWithSessionVersion(message: PreKeySignalMessage,
+                                                  from: ProtocolAddress,
+                                                  sessionStore: SessionStore,
+                                                  identityStore: IdentityKeyStore,
+                                                  preKeyStore: PreKeyStore,
+                                                  signedPreKeyStore: SignedPreKeyStore,
+                                                  context: StoreContext) throws -> PreKeyDecryptionResult {
+    var sessionVersion: UInt32 = 0
+    let plaintext = try context.withOpaquePointer { context in
+        try withSessionStore(sessionStore) { ffiSessionStore in
+            try withIdentityKeyStore(identityStore) { ffiIdentityStore in
+                try withPreKeyStore(preKeyStore) { ffiPreKeyStore in
+                    try withSignedPreKeyStore(signedPreKeyStore) { ffiSignedPreKeyStore in
+                        try invokeFnReturningArray {
+                            signal_decrypt_pre_key_message_with_session_version($0, $1, &sessionVersion, message.nativeHandle, from.nativeHandle, ffiSessionStore, ffiIdentityStore, ffiPreKeyStore, ffiSignedPreKeyStore, context)
+                        }
+                    }
+                }
+            }
+        }
+    }
+    return PreKeyDecryptionResult(plaintext: plaintext, sessionVersion: sessionVersion)
+}
+
+/// Returns whether `message` failing to decrypt with the numeric `SignalErrorCode` `code`
+/// means the session with its sender should be archived and the sender asked to retry.
+public func shouldResetSession<Bytes: ContiguousBytes>(afterDecryptionError code: UInt32,
+                                                       messageType: CiphertextMessage.MessageType,
+                                                       message: Bytes) throws -> Bool {
+    return try message.withUnsafeBytes {
+        var result = false
+        try checkError(signal_session_cipher_should_reset_session(&result, code, messageType.rawValue, $0.baseAddress?.assumingMemoryBound(to: UInt8.self), $0.count))
+        return result
+    }
+}
+
+/// The store writes `signalDecryptPreKey` makes when it succeeds, beyond saving the session.
+public struct DecryptPlan {
+    public var createsSession: Bool
+    public var consumedPreKeyId: UInt32?
+    public var changesIdentity: Bool
+}
+
+public func signalPlanDecryptPreKey(message: PreKeySignalMessage,
+                                    from: ProtocolAddress,
+                                    sessionStore: SessionStore,
+                                    identityStore: IdentityKeyStore,
+                                    context: StoreContext) throws -> DecryptPlan {
+    let session = try sessionStore.loadSession(for: from, context: context)
+    let storedIdentity = try identityStore.identity(for: from, context: context)
+
+    var plan: OpaquePointer?
+    try checkError(signal_session_cipher_plan_decrypt(&plan, message.nativeHandle, session?.nativeHandle, storedIdentity?.publicKey.nativeHandle))
+    defer { failOnError(signal_decrypt_plan_destroy(plan)) }
+
+    var createsSession = false
+    try checkError(signal_decrypt_plan_creates_session(&createsSession, plan))
+    let consumedPreKeyId = try invokeFnReturningInteger {
+        signal_decrypt_plan_get_consumed_pre_key_id($0, plan)
+    }
+    var changesIdentity = false
+    try checkError(signal_decrypt_plan_changes_identity(&changesIdentity, plan))
+
+    return DecryptPlan(createsSession: createsSession,
+                       consumedPreKeyId: consumedPreKeyId == 0xFFFFFFFF ? nil : consumedPreKeyId,
+                       changesIdentity: changesIdentity)
+}
+
+/// Returns the serialized size of the message `signalEncrypt` would produce next for a
+/// plaintext of `plaintextLength` bytes. Later messages may be a few bytes larger.
+public func signalEstimateCiphertextSize(plaintextLength: Int,
+                                         for address: ProtocolAddress,
+                                         sessionStore: SessionStore,
+                                         context: StoreContext) throws -> Int {
+    guard let session = try sessionStore.loadSession(for: address, context: context) else {
+        throw SignalError.sessionNotFound("\(address)")
+    }
+    let size = try invokeFnReturningInteger {
+        signal_session_cipher_estimate_ciphertext_size($0, UInt32(plaintextLength), session.nativeHandle)
+    }
+    return Int(size)
+}
+
+/// Returns the largest plaintext length for which `signalEstimateCiphertextSize` is within
+/// `ciphertextBudget` bytes.
+public func signalMaxPlaintextSize(ciphertextBudget: Int,
+                                   for address: ProtocolAddress,
+                                   sessionStore: SessionStore,
+                                   context: StoreContext) throws -> Int {
+    guard let session = try sessionStore.loadSession(for: address, context: context) else {
+        throw SignalError.sessionNotFound("\(address)")
+    }
+    let size = try invokeFnReturningInteger {
+        signal_session_cipher_max_plaintext_size($0, UInt32(ciphertextBudget), session.nativeHandle)
+    }
+    return Int(size)
+}
+
+/// Returns the device IDs for `name` that have sessions in `sessionStore`.
+public func signalDeviceIds(forName name: String,
+                            sessionStore: SessionStore,
+                            context: StoreContext) throws -> [UInt32] {
+    return try context.withOpaquePointer { context in
+        try withSessionStore(sessionStore) { ffiSessionStore in
+            var deviceIds: UnsafePointer<UInt32>?
+            var deviceIdsLen = 0
+            try checkError(signal_session_store_get_device_ids_for_name(&deviceIds, &deviceIdsLen, name, ffiSessionStore, context))
+            defer { signal_free_device_id_list(deviceIds, deviceIdsLen) }
+            return Array(UnsafeBufferPointer(start: deviceIds, count: deviceIdsLen))
+        }
+    }
+}
+
+/// Archives the current state of every session stored for `name` in `sessionStore`, as when that
+/// user's identity key changes.
+public func signalArchiveAllSessions(forName name: String,
+                                     sessionStore: SessionStore,
+                                     context: StoreContext) throws {
+    try context.withOpaquePointer { context in
+        try withSessionStore(sessionStore) { ffiSessionStore in
+            try checkError(signal_session_store_archive_all_sessions_for_name(name, ffiSessionStore, context))
+        }
+    }
+}
+
 public func processPreKeyBundle(_ bundle: PreKeyBundle,
                                 for address: ProtocolAddress,
                                 sessionStore: SessionStore,
@@ -141,6 +283,40 @@ public func groupDecrypt<Bytes: ContiguousBytes>(groupId: SenderKeyName,
     }
 }
 
+/// Decrypts `messages` in order, as if by repeated calls to `groupDecrypt`.
+///
+/// Returns the plaintexts in the same order, with `nil` for each message that could not be
+/// decrypted (including duplicates).
+/// Errors from the store, or a missing sender key, are thrown for the whole batch.
+public func groupDecryptBatch(groupId: SenderKeyName,
+                              messages: [[UInt8]],
+                              store: SenderKeyStore,
+                              context: StoreContext) throws -> [[UInt8]?] {
+    var serialized: [UInt8] = []
+    for message in messages {
+        withUnsafeBytes(of: UInt32(message.count).bigEndian) { serialized.append(contentsOf: $0) }
+        serialized.append(contentsOf: message)
+    }
+    let encoded = try context.withOpaquePointer { context in
+        try withSenderKeyStore(store) { ffiStore in
+            try invokeFnReturningArray {
+                signal_group_decrypt_batch($0, $1, groupId.nativeHandle, serialized, serialized.count, ffiStore, context)
+            }
+        }
+    }
+
+    var results: [[UInt8]?] = []
+    var offset = 0
+    while offset < encoded.count {
+        let success = encoded[offset] != 0
+        let length = Int(encoded[offset + 1 ..< offset + 5].reduce(0) { ($0 << 8) | UInt32($1) })
+        let plaintext = Array(encoded[offset + 5 ..< offset + 5 + length])
+        results.append(success ? plaintext : nil)
+        offset += 5 + length
+    }
+    return results
+}
+
 public func processSenderKeyDistributionMessage(sender: SenderKeyName,
                                                 message: SenderKeyDistributionMessage,
                                                 store: SenderKeyStore,
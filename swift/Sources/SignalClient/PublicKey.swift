@@ -58,6 +58,13 @@ public class PublicKey: ClonableHandleOwner {
         return result
     }
 
+    /// True if this key is a low-order point, which would yield an all-zero shared secret.
+    public var isWeak: Bool {
+        var result = false
+        failOnError(signal_publickey_is_weak(&result, nativeHandle))
+        return result
+    }
+
     public func compare(_ other: PublicKey) -> Int32 {
         var result: Int32 = 0
         failOnError(signal_publickey_compare(&result, nativeHandle, other.nativeHandle))
@@ -67,7 +74,9 @@ public class PublicKey: ClonableHandleOwner {
 
 extension PublicKey: Equatable {
     public static func == (lhs: PublicKey, rhs: PublicKey) -> Bool {
-        return lhs.compare(rhs) == 0
+        var result = false
+        failOnError(signal_publickey_equals(&result, lhs.nativeHandle, rhs.nativeHandle))
+        return result
     }
 }
 
@@ -73,6 +73,12 @@ public class ServerCertificate: ClonableHandleOwner {
             }
         }
     }
+
+    public func verify(trustRoot: PublicKey) throws -> Bool {
+        var result: Bool = false
+        try checkError(signal_server_certificate_verify(&result, nativeHandle, trustRoot.nativeHandle))
+        return result
+    }
 }
 
 public class SenderCertificate: ClonableHandleOwner {
@@ -123,6 +129,24 @@ public class SenderCertificate: ClonableHandleOwner {
         }
     }
 
+    public func isForDevice(_ deviceId: UInt32) -> Bool {
+        var result = false
+        failOnError(signal_sender_certificate_is_for_device(&result, nativeHandle, deviceId))
+        return result
+    }
+
+    public var hasValidUuid: Bool {
+        var result = false
+        failOnError(signal_sender_certificate_has_valid_uuid(&result, nativeHandle))
+        return result
+    }
+
+    public func signerKeyIdEquals(_ keyId: UInt32) -> Bool {
+        var result = false
+        failOnError(signal_sender_certificate_signer_key_id_equals(&result, nativeHandle, keyId))
+        return result
+    }
+
     public func serialize() -> [UInt8] {
         return failOnError {
             try invokeFnReturningArray {
@@ -186,6 +210,13 @@ public class SenderCertificate: ClonableHandleOwner {
         try checkError(signal_sender_certificate_validate(&result, nativeHandle, trustRoot.nativeHandle, time))
         return result
     }
+
+    /// Like `validate(trustRoot:time:)`, but throws `SignalError.expiredCertificate` or
+    /// `SignalError.invalidSignature` instead of returning `false`.
+    public func validateChain(trustRoot: PublicKey, time: UInt64) throws {
+        var result: Bool = false
+        try checkError(signal_sender_certificate_validate_chain(&result, nativeHandle, trustRoot.nativeHandle, time))
+    }
 }
 
 public func sealedSenderEncrypt<Bytes: ContiguousBytes>(message: Bytes,
@@ -211,6 +242,90 @@ public func sealedSenderEncrypt<Bytes: ContiguousBytes>(message: Bytes,
     }
 }
 
+/// Fails with `SignalError.sessionNotFound` if `sessionStore` has no current session for `address`.
+public func sealedSenderEncrypt(_ content: UnidentifiedSenderMessageContent,
+                                for address: ProtocolAddress,
+                                sessionStore: SessionStore,
+                                identityStore: IdentityKeyStore,
+                                context: StoreContext) throws -> [UInt8] {
+    return try context.withOpaquePointer { context in
+        try withSessionStore(sessionStore) { ffiSessionStore in
+            try withIdentityKeyStore(identityStore) { ffiIdentityStore in
+                try invokeFnReturningArray {
+                    signal_sealed_session_cipher_encrypt_from_usmc($0, $1,
+                                                                   address.nativeHandle, content.nativeHandle,
+                                                                   ffiSessionStore, ffiIdentityStore, context)
+                }
+            }
+        }
+    }
+}
+
+public class UnidentifiedSenderMessage: ClonableHandleOwner {
+    public init<Bytes: ContiguousBytes>(bytes: Bytes) throws {
+        let handle: OpaquePointer? = try bytes.withUnsafeBytes {
+            var result: OpaquePointer?
+            try checkError(signal_unidentified_sender_message_deserialize(&result, $0.baseAddress?.assumingMemoryBound(to: UInt8.self), $0.count))
+            return result
+        }
+        super.init(owned: handle!)
+    }
+
+    public init<StaticBytes, MessageBytes>(ephemeralPublic: PublicKey,
+                                           encryptedStatic: StaticBytes,
+                                           encryptedMessage: MessageBytes) throws
+    where StaticBytes: ContiguousBytes, MessageBytes: ContiguousBytes {
+        var result: OpaquePointer?
+        try encryptedStatic.withUnsafeBytes { staticBytes in
+            try encryptedMessage.withUnsafeBytes { messageBytes in
+                try checkError(signal_unidentified_sender_message_new(&result,
+                                                                      ephemeralPublic.nativeHandle,
+                                                                      staticBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                                                      staticBytes.count,
+                                                                      messageBytes.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                                                      messageBytes.count))
+            }
+        }
+        super.init(owned: result!)
+    }
+
+    internal override class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
+        return signal_unidentified_sender_message_destroy(handle)
+    }
+
+    public func serialize() -> [UInt8] {
+        return failOnError {
+            try invokeFnReturningArray {
+                signal_unidentified_sender_message_get_serialized($0, $1, nativeHandle)
+            }
+        }
+    }
+
+    public var ephemeralPublic: PublicKey {
+        return failOnError {
+            try invokeFnReturningPublicKey {
+                signal_unidentified_sender_message_get_ephemeral_public($0, nativeHandle)
+            }
+        }
+    }
+
+    public var encryptedStatic: [UInt8] {
+        return failOnError {
+            try invokeFnReturningArray {
+                signal_unidentified_sender_message_get_encrypted_static($0, $1, nativeHandle)
+            }
+        }
+    }
+
+    public var encryptedMessage: [UInt8] {
+        return failOnError {
+            try invokeFnReturningArray {
+                signal_unidentified_sender_message_get_encrypted_message($0, $1, nativeHandle)
+            }
+        }
+    }
+}
+
 public class UnidentifiedSenderMessageContent: ClonableHandleOwner {
     public init<Bytes: ContiguousBytes>(message: Bytes,
                                         identityStore: IdentityKeyStore,
@@ -251,6 +366,13 @@ public class UnidentifiedSenderMessageContent: ClonableHandleOwner {
         return .init(rawValue: rawType)
     }
 
+    /// Returns a short name for `type`, for logging.
+    public static func messageTypeName(_ type: CiphertextMessage.MessageType) throws -> String {
+        return try invokeFnReturningString {
+            signal_unidentified_sender_message_content_get_msg_type_name($0, type.rawValue)
+        }
+    }
+
     public var contents: [UInt8] {
         return failOnError {
             try invokeFnReturningArray {
@@ -22,6 +22,13 @@ public class SenderKeyName: ClonableHandleOwner {
         try self.init(groupName: groupName, senderName: sender.name, deviceId: sender.deviceId)
     }
 
+    /// Parses a key produced by `storageKey`.
+    public init(storageKey: String) throws {
+        var handle: OpaquePointer?
+        try checkError(signal_sender_key_name_from_storage_key(&handle, storageKey))
+        super.init(owned: handle!)
+    }
+
     internal override init(owned handle: OpaquePointer) {
         super.init(owned: handle)
     }
@@ -53,6 +60,15 @@ public class SenderKeyName: ClonableHandleOwner {
             }
         }
     }
+
+    /// The canonical `group:sender:device` key for sender-key storage.
+    public var storageKey: String {
+        return failOnError {
+            try invokeFnReturningString {
+                signal_sender_key_name_to_storage_key($0, nativeHandle)
+            }
+        }
+    }
 }
 
 extension SenderKeyName: Hashable {
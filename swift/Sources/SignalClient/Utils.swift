@@ -324,11 +324,30 @@ internal func withSessionStore<Result>(_ store: SessionStore, _ body: (UnsafePoi
         }
     }
 
+    func ffiShimGetDeviceIdsForName(store_ctx: UnsafeMutableRawPointer?,
+                                    device_idsp: UnsafeMutablePointer<UnsafeMutablePointer<UInt32>?>?,
+                                    device_ids_len: UnsafeMutablePointer<Int>?,
+                                    name: UnsafePointer<CChar>?,
+                                    ctx: UnsafeMutableRawPointer?) -> Int32 {
+        let storeContext = store_ctx!.assumingMemoryBound(to: ErrorHandlingContext<SessionStore>.self)
+        return storeContext.pointee.catchCallbackErrors { store in
+            let context = ctx!.assumingMemoryBound(to: StoreContext.self).pointee
+            let deviceIds = try store.deviceIds(forName: String(cString: name!), context: context)
+            // Rust takes ownership of the list and releases it with free().
+            let buffer = malloc(max(deviceIds.count, 1) * MemoryLayout<UInt32>.stride)!.assumingMemoryBound(to: UInt32.self)
+            buffer.initialize(from: deviceIds, count: deviceIds.count)
+            device_idsp!.pointee = buffer
+            device_ids_len!.pointee = deviceIds.count
+            return 0
+        }
+    }
+
     return try rethrowCallbackErrors(store) {
         var ffiStore = SignalSessionStore(
             ctx: $0,
             load_session: ffiShimLoadSession,
-            store_session: ffiShimStoreSession)
+            store_session: ffiShimStoreSession,
+            get_device_ids_for_name: ffiShimGetDeviceIdsForName)
         return try body(&ffiStore)
     }
 }
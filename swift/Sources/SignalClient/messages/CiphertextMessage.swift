@@ -3,6 +3,8 @@
 // SPDX-License-Identifier: AGPL-3.0-only
 //
 
+import Foundation
+
 public class CiphertextMessage {
     private var handle: OpaquePointer?
 
@@ -28,6 +30,14 @@ public class CiphertextMessage {
         public static var senderKeyDistribution: Self {
             return Self(SignalCiphertextMessageType_SenderKeyDistribution)
         }
+
+        public func matches<Bytes: ContiguousBytes>(body: Bytes) -> Bool {
+            return body.withUnsafeBytes {
+                var result = false
+                failOnError(signal_ciphertext_message_type_matches_body(&result, rawValue, $0.baseAddress?.assumingMemoryBound(to: UInt8.self), $0.count))
+                return result
+            }
+        }
     }
 
     deinit {
@@ -46,6 +56,12 @@ public class CiphertextMessage {
         }
     }
 
+    public func innerCiphertext() throws -> [UInt8] {
+        return try invokeFnReturningArray {
+            signal_ciphertext_message_get_inner_ciphertext($0, $1, handle)
+        }
+    }
+
     public var messageType: MessageType {
         let rawValue = failOnError {
             try invokeFnReturningInteger {
@@ -25,6 +25,10 @@ public class SenderKeyDistributionMessage {
         }
     }
 
+    public init(record: SenderKeyRecord) throws {
+        try checkError(signal_sender_key_record_create_distribution_message(&handle, record.nativeHandle))
+    }
+
     public init<Bytes: ContiguousBytes>(keyId: UInt32,
                                         iteration: UInt32,
                                         chainKey: Bytes,
@@ -45,6 +49,12 @@ public class SenderKeyDistributionMessage {
         try checkError(signal_sender_key_distribution_message_deserialize(&handle, bytes, bytes.count))
     }
 
+    public static var chainKeyLength: Int {
+        var result: UInt32 = 0
+        failOnError(signal_sender_key_distribution_message_chain_key_length(&result))
+        return Int(result)
+    }
+
     public var signatureKey: PublicKey {
         return failOnError {
             try invokeFnReturningPublicKey {
@@ -84,4 +94,10 @@ public class SenderKeyDistributionMessage {
             }
         }
     }
+
+    public func deriveGroupKey() throws -> [UInt8] {
+        return try invokeFnReturningArray {
+            signal_sender_key_distribution_message_derive_group_key($0, $1, handle)
+        }
+    }
 }
@@ -73,4 +73,10 @@ public class SenderKeyMessage {
         try checkError(signal_sender_key_message_verify_signature(&result, handle, key.nativeHandle))
         return result
     }
+
+    public func verifySignature(against distribution: SenderKeyDistributionMessage) throws -> Bool {
+        var result: Bool = false
+        try checkError(signal_group_cipher_verify_against_distribution(&result, handle, distribution.nativeHandle))
+        return result
+    }
 }
@@ -60,6 +60,14 @@ public class SignalMessage {
         }
     }
 
+    public var senderRatchetKeyBytes: [UInt8] {
+        return failOnError {
+            try invokeFnReturningArray {
+                signal_message_get_sender_ratchet_key_bytes($0, $1, handle)
+            }
+        }
+    }
+
     public var body: [UInt8] {
         return failOnError {
             try invokeFnReturningArray {
@@ -50,20 +50,34 @@ public class PreKeyBundle {
                                         identity identityKey: IdentityKey) throws {
         handle = try signedPrekeySignature.withUnsafeBytes {
             var result: OpaquePointer?
-            try checkError(signal_pre_key_bundle_new(&result,
-                                                     registrationId,
-                                                     deviceId,
-                                                     ~0,
-                                                     nil,
-                                                     signedPrekeyId,
-                                                     signedPrekey.nativeHandle,
-                                                     $0.baseAddress?.assumingMemoryBound(to: UInt8.self),
-                                                     $0.count,
-                                                     identityKey.publicKey.nativeHandle))
+            try checkError(signal_pre_key_bundle_new_without_one_time_pre_key(&result,
+                                                                              registrationId,
+                                                                              deviceId,
+                                                                              signedPrekeyId,
+                                                                              signedPrekey.nativeHandle,
+                                                                              $0.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                                                              $0.count,
+                                                                              identityKey.publicKey.nativeHandle))
             return result
         }
     }
 
+    public init<Bytes: ContiguousBytes>(linkingPayload: Bytes) throws {
+        handle = try linkingPayload.withUnsafeBytes {
+            var result: OpaquePointer?
+            try checkError(signal_pre_key_bundle_from_linking_payload(&result,
+                                                                      $0.baseAddress?.assumingMemoryBound(to: UInt8.self),
+                                                                      $0.count))
+            return result
+        }
+    }
+
+    public func linkingPayload() throws -> [UInt8] {
+        return try invokeFnReturningArray {
+            signal_pre_key_bundle_to_linking_payload($0, $1, handle)
+        }
+    }
+
     public var registrationId: UInt32 {
         return failOnError {
             try invokeFnReturningInteger {
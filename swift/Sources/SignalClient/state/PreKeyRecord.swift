@@ -39,6 +39,22 @@ public class PreKeyRecord: ClonableHandleOwner {
         try self.init(id: id, publicKey: privateKey.publicKey, privateKey: privateKey)
     }
 
+    /// Returns the ids of all `records` that share their public key with another record, in order.
+    public static func findDuplicateKeys(in records: [PreKeyRecord]) throws -> [UInt32] {
+        var serialized: [UInt8] = []
+        for record in records {
+            let bytes = record.serialize()
+            withUnsafeBytes(of: UInt32(bytes.count).bigEndian) { serialized.append(contentsOf: $0) }
+            serialized.append(contentsOf: bytes)
+        }
+        let ids = try invokeFnReturningArray {
+            signal_pre_key_record_find_duplicate_keys($0, $1, serialized, serialized.count)
+        }
+        return stride(from: 0, to: ids.count, by: 4).map { i in
+            ids[i..<i + 4].reduce(0) { ($0 << 8) | UInt32($1) }
+        }
+    }
+
     public func serialize() -> [UInt8] {
         return failOnError {
             try invokeFnReturningArray {
@@ -44,4 +44,10 @@ public class SenderKeyRecord: ClonableHandleOwner {
             }
         }
     }
+
+    public func messagesUntilRotation() throws -> UInt32 {
+        return try invokeFnReturningInteger {
+            signal_sender_key_record_messages_until_rotation($0, nativeHandle)
+        }
+    }
 }
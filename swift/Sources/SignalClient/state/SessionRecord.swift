@@ -6,6 +6,40 @@
 import Foundation
 
 public class SessionRecord: ClonableHandleOwner {
+    public struct Integrity: RawRepresentable, Hashable {
+        public var rawValue: UInt8
+        public init(rawValue: UInt8) {
+            self.rawValue = rawValue
+        }
+
+        internal init(_ knownValue: SignalSessionRecordIntegrity) {
+            self.init(rawValue: UInt8(knownValue.rawValue))
+        }
+
+        public static var valid: Self {
+            return Self(SignalSessionRecordIntegrity_Valid)
+        }
+        public static var unparseable: Self {
+            return Self(SignalSessionRecordIntegrity_Unparseable)
+        }
+        public static var invalidVersion: Self {
+            return Self(SignalSessionRecordIntegrity_InvalidVersion)
+        }
+        public static var inconsistentChainIndices: Self {
+            return Self(SignalSessionRecordIntegrity_InconsistentChainIndices)
+        }
+    }
+
+    /// Checks a serialized record for corruption without deserializing it for use.
+    public static func validateIntegrity<Bytes: ContiguousBytes>(bytes: Bytes) -> Integrity {
+        let rawValue: UInt8 = bytes.withUnsafeBytes {
+            var result: UInt8 = 0
+            failOnError(signal_session_record_validate_integrity(&result, $0.baseAddress?.assumingMemoryBound(to: UInt8.self), $0.count))
+            return result
+        }
+        return Integrity(rawValue: rawValue)
+    }
+
     internal override class func destroyNativeHandle(_ handle: OpaquePointer) -> SignalFfiErrorRef? {
         return signal_session_record_destroy(handle)
     }
@@ -27,6 +61,57 @@ public class SessionRecord: ClonableHandleOwner {
         super.init(borrowing: handle)
     }
 
+    internal override init(owned handle: OpaquePointer) {
+        super.init(owned: handle)
+    }
+
+    /// Sets up Alice's side of a session directly from Bob's keys, without a pre-key bundle.
+    ///
+    /// Pass `theirOneTimePreKey` if Bob is expected to use the matching one-time pre-key.
+    ///
+    /// This exists so tests can build a matching pair of sessions; use `processPreKeyBundle` to
+    /// start a real session.
+    public static func initializeAliceSession(identityKeyPair: IdentityKeyPair,
+                                              baseKey: PrivateKey,
+                                              theirIdentity: IdentityKey,
+                                              theirSignedPreKey: PublicKey,
+                                              theirOneTimePreKey: PublicKey?,
+                                              theirRatchetKey: PublicKey) throws -> SessionRecord {
+        var handle: OpaquePointer?
+        try checkError(signal_session_record_initialize_alice_session(&handle,
+                                                                      identityKeyPair.privateKey.nativeHandle,
+                                                                      identityKeyPair.publicKey.nativeHandle,
+                                                                      baseKey.nativeHandle,
+                                                                      baseKey.publicKey.nativeHandle,
+                                                                      theirIdentity.publicKey.nativeHandle,
+                                                                      theirSignedPreKey.nativeHandle,
+                                                                      theirOneTimePreKey?.nativeHandle,
+                                                                      theirRatchetKey.nativeHandle))
+        return SessionRecord(owned: handle!)
+    }
+
+    /// Sets up Bob's side of a session to match `initializeAliceSession`.
+    public static func initializeBobSession(identityKeyPair: IdentityKeyPair,
+                                            signedPreKey: PrivateKey,
+                                            oneTimePreKey: PrivateKey?,
+                                            ephemeralKey: PrivateKey,
+                                            theirIdentity: IdentityKey,
+                                            theirBaseKey: PublicKey) throws -> SessionRecord {
+        var handle: OpaquePointer?
+        try checkError(signal_session_record_initialize_bob_session(&handle,
+                                                                    identityKeyPair.privateKey.nativeHandle,
+                                                                    identityKeyPair.publicKey.nativeHandle,
+                                                                    signedPreKey.nativeHandle,
+                                                                    signedPreKey.publicKey.nativeHandle,
+                                                                    oneTimePreKey?.nativeHandle,
+                                                                    oneTimePreKey?.publicKey.nativeHandle,
+                                                                    ephemeralKey.nativeHandle,
+                                                                    ephemeralKey.publicKey.nativeHandle,
+                                                                    theirIdentity.publicKey.nativeHandle,
+                                                                    theirBaseKey.nativeHandle))
+        return SessionRecord(owned: handle!)
+    }
+
     public func serialize() -> [UInt8] {
         return failOnError {
             try invokeFnReturningArray {
@@ -41,6 +126,13 @@ public class SessionRecord: ClonableHandleOwner {
         return result
     }
 
+    /// Whether the current session state is ready to encrypt messages.
+    public var canEncrypt: Bool {
+        var result = false
+        failOnError(signal_session_record_can_encrypt(&result, nativeHandle))
+        return result
+    }
+
     public func archiveCurrentState() {
         failOnError(signal_session_record_archive_current_state(nativeHandle))
     }
@@ -50,4 +142,69 @@ public class SessionRecord: ClonableHandleOwner {
             signal_session_record_get_remote_registration_id($0, nativeHandle)
         }
     }
+
+    public struct RegistrationIds {
+        public var local: UInt32
+        public var remote: UInt32
+    }
+
+    public func registrationIds() throws -> RegistrationIds {
+        let ids = try invokeFnReturningArray {
+            signal_session_record_get_registration_ids($0, $1, nativeHandle)
+        }
+        let local = ids[0..<4].reduce(0) { ($0 << 8) | UInt32($1) }
+        let remote = ids[4..<8].reduce(0) { ($0 << 8) | UInt32($1) }
+        return RegistrationIds(local: local, remote: remote)
+    }
+
+    /// A value both sides of a freshly initialized session compute identically, for use as a
+    /// key-confirmation MAC. Throws once the session's initial chain has been used.
+    public func computeKeyConfirmation() throws -> [UInt8] {
+        return try invokeFnReturningArray {
+            signal_session_record_compute_key_confirmation($0, $1, nativeHandle)
+        }
+    }
+
+    /// A 16-byte identifier for the current session, for correlating log events. It is not
+    /// secret, but reveals nothing about the session's keys or messages.
+    public func computeSessionId() throws -> [UInt8] {
+        return try invokeFnReturningArray {
+            signal_session_record_compute_session_id($0, $1, nativeHandle)
+        }
+    }
+
+    public func creationTimestamp() throws -> UInt64 {
+        return try invokeFnReturningInteger {
+            signal_session_record_get_creation_timestamp($0, nativeHandle)
+        }
+    }
+
+    public func identitySeenBefore(_ identity: IdentityKey) throws -> Bool {
+        var result = false
+        try checkError(signal_session_record_identity_seen_before(&result, nativeHandle, identity.publicKey.nativeHandle))
+        return result
+    }
+
+    public func preKeyMessageAlreadyProcessed(_ message: PreKeySignalMessage) throws -> Bool {
+        var result = false
+        try checkError(signal_session_record_pre_key_message_already_processed(&result, nativeHandle, message.nativeHandle))
+        return result
+    }
+
+    /// True if the current session is older than the version this library creates.
+    public func needsUpgrade() throws -> Bool {
+        var result = false
+        try checkError(signal_session_record_needs_upgrade(&result, nativeHandle))
+        return result
+    }
+
+    /// Reads the version of the current session in a serialized record without fully
+    /// deserializing it. Returns 0 if the record has no current session.
+    public static func storageVersion<Bytes: ContiguousBytes>(bytes: Bytes) throws -> UInt32 {
+        return try bytes.withUnsafeBytes { bytes in
+            try invokeFnReturningInteger {
+                signal_session_record_storage_version($0, bytes.baseAddress?.assumingMemoryBound(to: UInt8.self), bytes.count)
+            }
+        }
+    }
 }
@@ -42,6 +42,19 @@ public class SignedPreKeyRecord: ClonableHandleOwner {
         super.init(borrowing: handle)
     }
 
+    /// Returns the earliest time at which one of `records` becomes older than `maxAge`.
+    public static func earliestExpiry(of records: [SignedPreKeyRecord], maxAge: UInt64) throws -> UInt64 {
+        var serialized: [UInt8] = []
+        for record in records {
+            let bytes = record.serialize()
+            withUnsafeBytes(of: UInt32(bytes.count).bigEndian) { serialized.append(contentsOf: $0) }
+            serialized.append(contentsOf: bytes)
+        }
+        return try invokeFnReturningInteger {
+            signal_signed_pre_key_record_earliest_expiry($0, serialized, serialized.count, maxAge)
+        }
+    }
+
     public func serialize() -> [UInt8] {
         return failOnError {
             try invokeFnReturningArray {
@@ -89,4 +102,23 @@ public class SignedPreKeyRecord: ClonableHandleOwner {
             }
         }
     }
+
+    public func verifyOwnSignature(identityKey: PublicKey) throws -> Bool {
+        var result = false
+        try checkError(signal_signed_pre_key_record_verify_own_signature(&result, nativeHandle, identityKey.nativeHandle))
+        return result
+    }
+
+    /// Returns when this key is due to be replaced, `interval` after it was created.
+    public func nextRotationTime(interval: UInt64) throws -> UInt64 {
+        return try invokeFnReturningInteger {
+            signal_signed_pre_key_record_next_rotation_time($0, nativeHandle, interval)
+        }
+    }
+
+    public func verifyKeyPair() throws -> Bool {
+        var result = false
+        try checkError(signal_signed_pre_key_record_verify_key_pair(&result, nativeHandle))
+        return result
+    }
 }
@@ -24,6 +24,16 @@ typedef enum {
   SignalCiphertextMessageType_SenderKeyDistribution = 5,
 } SignalCiphertextMessageType;
 
+/**
+ * ts: export const enum SessionRecordIntegrity { Valid = 0, Unparseable = 1, InvalidVersion = 2, InconsistentChainIndices = 3 }
+ */
+typedef enum {
+  SignalSessionRecordIntegrity_Valid = 0,
+  SignalSessionRecordIntegrity_Unparseable = 1,
+  SignalSessionRecordIntegrity_InvalidVersion = 2,
+  SignalSessionRecordIntegrity_InconsistentChainIndices = 3,
+} SignalSessionRecordIntegrity;
+
 typedef enum {
   SignalDirection_Sending = 0,
   SignalDirection_Receiving = 1,
@@ -53,6 +63,7 @@ typedef enum {
   SignalErrorCode_UnrecognizedMessageVersion = 23,
   SignalErrorCode_InvalidMessage = 30,
   SignalErrorCode_SealedSenderSelfSend = 31,
+  SignalErrorCode_ExpiredCertificate = 32,
   SignalErrorCode_InvalidKey = 40,
   SignalErrorCode_InvalidSignature = 41,
   SignalErrorCode_FingerprintIdentifierMismatch = 50,
@@ -69,8 +80,14 @@ typedef struct SignalAes256GcmSiv SignalAes256GcmSiv;
 
 typedef struct SignalCiphertextMessage SignalCiphertextMessage;
 
+typedef struct SignalCryptographicMac SignalCryptographicMac;
+
+typedef struct SignalDecryptPlan SignalDecryptPlan;
+
 typedef struct SignalFingerprint SignalFingerprint;
 
+typedef struct SignalKeyPair SignalKeyPair;
+
 typedef struct SignalPreKeyBundle SignalPreKeyBundle;
 
 typedef struct SignalPreKeyRecord SignalPreKeyRecord;
@@ -103,16 +120,32 @@ typedef struct SignalMessage SignalMessage;
 
 typedef struct SignalSignedPreKeyRecord SignalSignedPreKeyRecord;
 
+typedef struct SignalUnidentifiedSenderMessage SignalUnidentifiedSenderMessage;
+
 typedef struct SignalUnidentifiedSenderMessageContent SignalUnidentifiedSenderMessageContent;
 
 typedef int (*SignalLoadSession)(void *store_ctx, SignalSessionRecord **recordp, const SignalProtocolAddress *address, void *ctx);
 
 typedef int (*SignalStoreSession)(void *store_ctx, const SignalProtocolAddress *address, const SignalSessionRecord *record, void *ctx);
 
+/**
+ * The list written to `device_idsp` must be allocated with `malloc`; Rust takes ownership of it
+ * and releases it with `free`.
+ */
+typedef int (*SignalGetDeviceIdsForName)(void *store_ctx, uint32_t **device_idsp, size_t *device_ids_len, const char *name, void *ctx);
+
 typedef struct {
   void *ctx;
   SignalLoadSession load_session;
   SignalStoreSession store_session;
+  /**
+   * May be NULL for stores that cannot list their sessions, in which case listing or archiving
+   * every session for a name fails with an invalid state error.
+   *
+   * This field was added after `store_session`, so callers compiled against an older header
+   * must be rebuilt.
+   */
+  SignalGetDeviceIdsForName get_device_ids_for_name;
 } SignalSessionStore;
 
 typedef int (*SignalGetIdentityKeyPair)(void *store_ctx, SignalPrivateKey **keyp, void *ctx);
@@ -185,6 +218,8 @@ void signal_free_string(const char *buf);
 
 void signal_free_buffer(const unsigned char *buf, size_t buf_len);
 
+void signal_free_device_id_list(const uint32_t *buf, size_t buf_len);
+
 SignalFfiError *signal_error_get_message(const SignalFfiError *err, const char **out);
 
 uint32_t signal_error_get_type(const SignalFfiError *err);
@@ -196,6 +231,16 @@ SignalFfiError *signal_identitykeypair_deserialize(SignalPrivateKey **private_ke
                                                    const unsigned char *input,
                                                    size_t input_len);
 
+SignalFfiError *signal_session_store_get_device_ids_for_name(const uint32_t **out,
+                                                            size_t *out_len,
+                                                            const char *name,
+                                                            const SignalSessionStore *session_store,
+                                                            void *ctx);
+
+SignalFfiError *signal_session_store_archive_all_sessions_for_name(const char *name,
+                                                                   const SignalSessionStore *session_store,
+                                                                   void *ctx);
+
 SignalFfiError *signal_process_prekey_bundle(SignalPreKeyBundle *bundle,
                                              const SignalProtocolAddress *protocol_address,
                                              const SignalSessionStore *session_store,
@@ -228,6 +273,21 @@ SignalFfiError *signal_decrypt_pre_key_message(const unsigned char **result,
                                                const SignalSignedPreKeyStore *signed_prekey_store,
                                                void *ctx);
 
+/**
+ * Like signal_decrypt_pre_key_message, but also reports the version of the session the message
+ * was decrypted with, as stored in the session store afterwards.
+ */
+SignalFfiError *signal_decrypt_pre_key_message_with_session_version(const unsigned char **result,
+                                                                    size_t *result_len,
+                                                                    uint32_t *session_version,
+                                                                    const SignalPreKeySignalMessage *message,
+                                                                    const SignalProtocolAddress *protocol_address,
+                                                                    const SignalSessionStore *session_store,
+                                                                    const SignalIdentityKeyStore *identity_key_store,
+                                                                    const SignalPreKeyStore *prekey_store,
+                                                                    const SignalSignedPreKeyStore *signed_prekey_store,
+                                                                    void *ctx);
+
 SignalFfiError *signal_create_sender_key_distribution_message(SignalSenderKeyDistributionMessage **obj,
                                                               const SignalSenderKeyName *sender_key_name,
                                                               const SignalSenderKeyStore *store,
@@ -254,6 +314,14 @@ SignalFfiError *signal_group_decrypt_message(const unsigned char **out,
                                              const SignalSenderKeyStore *store,
                                              void *ctx);
 
+SignalFfiError *signal_group_decrypt_batch(const unsigned char **out,
+                                           size_t *out_len,
+                                           const SignalSenderKeyName *sender_key_name,
+                                           const unsigned char *messages,
+                                           size_t messages_len,
+                                           const SignalSenderKeyStore *store,
+                                           void *ctx);
+
 SignalFfiError *signal_sealed_session_cipher_encrypt(const unsigned char **out,
                                                      size_t *out_len,
                                                      const SignalProtocolAddress *destination,
@@ -264,6 +332,14 @@ SignalFfiError *signal_sealed_session_cipher_encrypt(const unsigned char **out,
                                                      const SignalIdentityKeyStore *identity_key_store,
                                                      void *ctx);
 
+SignalFfiError *signal_sealed_session_cipher_encrypt_from_usmc(const unsigned char **out,
+                                                               size_t *out_len,
+                                                               const SignalProtocolAddress *destination,
+                                                               const SignalUnidentifiedSenderMessageContent *content,
+                                                               const SignalSessionStore *session_store,
+                                                               const SignalIdentityKeyStore *identity_key_store,
+                                                               void *ctx);
+
 SignalFfiError *signal_sealed_session_cipher_decrypt_to_usmc(SignalUnidentifiedSenderMessageContent **out,
                                                              const unsigned char *ctext,
                                                              size_t ctext_len,
@@ -294,10 +370,21 @@ SignalFfiError *signal_aes256_gcm_siv_destroy(SignalAes256GcmSiv *p);
 
 SignalFfiError *signal_ciphertext_message_destroy(SignalCiphertextMessage *p);
 
+SignalFfiError *signal_cryptographic_mac_destroy(SignalCryptographicMac *p);
+
+SignalFfiError *signal_cryptographic_mac_clone(SignalCryptographicMac **new_obj,
+                                               const SignalCryptographicMac *obj);
+
+SignalFfiError *signal_decrypt_plan_destroy(SignalDecryptPlan *p);
+
 SignalFfiError *signal_fingerprint_destroy(SignalFingerprint *p);
 
 SignalFfiError *signal_fingerprint_clone(SignalFingerprint **new_obj, const SignalFingerprint *obj);
 
+SignalFfiError *signal_key_pair_destroy(SignalKeyPair *p);
+
+SignalFfiError *signal_key_pair_clone(SignalKeyPair **new_obj, const SignalKeyPair *obj);
+
 SignalFfiError *signal_pre_key_bundle_destroy(SignalPreKeyBundle *p);
 
 SignalFfiError *signal_pre_key_bundle_clone(SignalPreKeyBundle **new_obj,
@@ -370,6 +457,8 @@ SignalFfiError *signal_signed_pre_key_record_destroy(SignalSignedPreKeyRecord *p
 SignalFfiError *signal_signed_pre_key_record_clone(SignalSignedPreKeyRecord **new_obj,
                                                    const SignalSignedPreKeyRecord *obj);
 
+SignalFfiError *signal_unidentified_sender_message_destroy(SignalUnidentifiedSenderMessage *p);
+
 SignalFfiError *signal_unidentified_sender_message_content_destroy(SignalUnidentifiedSenderMessageContent *p);
 
 SignalFfiError *signal_hkdf_derive(unsigned char *output,
@@ -382,6 +471,34 @@ SignalFfiError *signal_hkdf_derive(unsigned char *output,
                                    const unsigned char *salt,
                                    size_t salt_len);
 
+SignalFfiError *signal_sha256_digest(const unsigned char **out,
+                                     size_t *out_len,
+                                     const unsigned char *input,
+                                     size_t input_len);
+
+SignalFfiError *signal_sha512_digest(const unsigned char **out,
+                                     size_t *out_len,
+                                     const unsigned char *input,
+                                     size_t input_len);
+
+SignalFfiError *signal_constant_time_equal(bool *out,
+                                          const unsigned char *a,
+                                          size_t a_len,
+                                          const unsigned char *b,
+                                          size_t b_len);
+
+SignalFfiError *signal_cryptographic_mac_new(SignalCryptographicMac **out,
+                                             const unsigned char *key,
+                                             size_t key_len);
+
+SignalFfiError *signal_cryptographic_mac_update(SignalCryptographicMac *mac,
+                                                const unsigned char *input,
+                                                size_t input_len);
+
+SignalFfiError *signal_cryptographic_mac_finalize(const unsigned char **out,
+                                                  size_t *out_len,
+                                                  SignalCryptographicMac *mac);
+
 SignalFfiError *signal_address_new(SignalProtocolAddress **out,
                                    const char *name,
                                    uint32_t device_id);
@@ -402,10 +519,18 @@ SignalFfiError *signal_address_get_device_id(uint32_t *out, const SignalProtocol
 
 SignalFfiError *signal_address_get_name(const char **out, const SignalProtocolAddress *obj);
 
+SignalFfiError *signal_address_from_string(SignalProtocolAddress **out, const char *s);
+
+SignalFfiError *signal_address_to_string(const char **out, const SignalProtocolAddress *addr);
+
 SignalFfiError *signal_publickey_compare(int32_t *out,
                                          const SignalPublicKey *key1,
                                          const SignalPublicKey *key2);
 
+SignalFfiError *signal_publickey_equals(bool *out,
+                                        const SignalPublicKey *key1,
+                                        const SignalPublicKey *key2);
+
 SignalFfiError *signal_publickey_verify(bool *out,
                                         const SignalPublicKey *key,
                                         const unsigned char *message,
@@ -413,6 +538,10 @@ SignalFfiError *signal_publickey_verify(bool *out,
                                         const unsigned char *signature,
                                         size_t signature_len);
 
+SignalFfiError *signal_publickey_is_valid(bool *out, const unsigned char *data, size_t data_len);
+
+SignalFfiError *signal_publickey_is_weak(bool *out, const SignalPublicKey *key);
+
 SignalFfiError *signal_privatekey_deserialize(SignalPrivateKey **p,
                                               const unsigned char *data,
                                               size_t data_len);
@@ -423,6 +552,17 @@ SignalFfiError *signal_privatekey_serialize(const unsigned char **out,
 
 SignalFfiError *signal_privatekey_generate(SignalPrivateKey **out);
 
+SignalFfiError *signal_key_pair_generate(SignalKeyPair **out);
+
+SignalFfiError *signal_key_pair_get_public_key(SignalPublicKey **out, const SignalKeyPair *key_pair);
+
+SignalFfiError *signal_key_pair_get_private_key(SignalPrivateKey **out,
+                                                const SignalKeyPair *key_pair);
+
+SignalFfiError *signal_key_pair_serialize_as_identity_key_pair(const unsigned char **out,
+                                                               size_t *out_len,
+                                                               const SignalKeyPair *key_pair);
+
 SignalFfiError *signal_privatekey_get_public_key(SignalPublicKey **out, const SignalPrivateKey *k);
 
 SignalFfiError *signal_privatekey_sign(const unsigned char **out,
@@ -431,16 +571,46 @@ SignalFfiError *signal_privatekey_sign(const unsigned char **out,
                                        const unsigned char *message,
                                        size_t message_len);
 
+SignalFfiError *signal_privatekey_sign_canonical(const unsigned char **out,
+                                                 size_t *out_len,
+                                                 const SignalPrivateKey *key,
+                                                 const unsigned char *fields,
+                                                 size_t fields_len);
+
 SignalFfiError *signal_privatekey_agree(const unsigned char **out,
                                         size_t *out_len,
                                         const SignalPrivateKey *private_key,
                                         const SignalPublicKey *public_key);
 
+SignalFfiError *signal_privatekey_agree_validated(const unsigned char **out,
+                                                  size_t *out_len,
+                                                  const SignalPrivateKey *private_key,
+                                                  const SignalPublicKey *public_key);
+
 SignalFfiError *signal_identitykeypair_serialize(const unsigned char **out,
                                                  size_t *out_len,
                                                  const SignalPublicKey *public_key,
                                                  const SignalPrivateKey *private_key);
 
+SignalFfiError *signal_identitykeypair_sign_alternate_identity(const unsigned char **out,
+                                                               size_t *out_len,
+                                                               const SignalPublicKey *public_key,
+                                                               const SignalPrivateKey *private_key,
+                                                               const SignalPublicKey *other_identity);
+
+SignalFfiError *signal_identitykey_verify_alternate_identity_signature(bool *out,
+                                                                       const SignalPublicKey *identity_key,
+                                                                       const SignalPublicKey *other_identity,
+                                                                       const unsigned char *signature,
+                                                                       size_t signature_len);
+
+SignalFfiError *signal_identitykey_to_display_string(const char **out,
+                                                     const SignalPublicKey *identity_key);
+
+SignalFfiError *signal_identitykey_compute_change_hash(const unsigned char **out,
+                                                       size_t *out_len,
+                                                       const SignalPublicKey *identity_key);
+
 SignalFfiError *signal_fingerprint_new(SignalFingerprint **out,
                                        uint32_t iterations,
                                        uint32_t version,
@@ -463,10 +633,29 @@ SignalFfiError *signal_fingerprint_compare(bool *out,
                                            const unsigned char *fprint2,
                                            size_t fprint2_len);
 
+SignalFfiError *signal_fingerprint_get_truncated_display_string(const char **out,
+                                                               const SignalFingerprint *fingerprint,
+                                                               uint32_t digits);
+
+SignalFfiError *signal_fingerprint_verify_qr_payload(bool *out,
+                                                     const SignalFingerprint *fingerprint,
+                                                     const unsigned char *scanned,
+                                                     size_t scanned_len);
+
+SignalFfiError *signal_fingerprint_get_qr_code_payload(const unsigned char **out,
+                                                       size_t *out_len,
+                                                       const SignalFingerprint *fingerprint);
+
+SignalFfiError *signal_fingerprint_versions_compatible(bool *out, uint32_t ours, uint32_t theirs);
+
 SignalFfiError *signal_message_deserialize(SignalMessage **p,
                                            const unsigned char *data,
                                            size_t data_len);
 
+SignalFfiError *signal_message_get_sender_ratchet_key_bytes(const unsigned char **out,
+                                                            size_t *out_len,
+                                                            const SignalMessage *obj);
+
 SignalFfiError *signal_message_get_body(const unsigned char **out,
                                         size_t *out_len,
                                         const SignalMessage *obj);
@@ -519,6 +708,9 @@ SignalFfiError *signal_pre_key_signal_message_get_identity_key(SignalPublicKey *
 SignalFfiError *signal_pre_key_signal_message_get_signal_message(SignalMessage **out,
                                                                  const SignalPreKeySignalMessage *m);
 
+SignalFfiError *signal_pre_key_signal_message_used_one_time_pre_key(bool *out,
+                                                                    const SignalPreKeySignalMessage *m);
+
 SignalFfiError *signal_pre_key_signal_message_deserialize(SignalPreKeySignalMessage **p,
                                                           const unsigned char *data,
                                                           size_t data_len);
@@ -586,6 +778,10 @@ SignalFfiError *signal_sender_key_distribution_message_get_id(uint32_t *out,
 SignalFfiError *signal_sender_key_distribution_message_get_iteration(uint32_t *out,
                                                                      const SignalSenderKeyDistributionMessage *obj);
 
+SignalFfiError *signal_sender_key_distribution_message_derive_group_key(const unsigned char **out,
+                                                                        size_t *out_len,
+                                                                        const SignalSenderKeyDistributionMessage *obj);
+
 SignalFfiError *signal_sender_key_distribution_message_new(SignalSenderKeyDistributionMessage **out,
                                                            uint32_t key_id,
                                                            uint32_t iteration,
@@ -593,9 +789,15 @@ SignalFfiError *signal_sender_key_distribution_message_new(SignalSenderKeyDistri
                                                            size_t chainkey_len,
                                                            const SignalPublicKey *pk);
 
+SignalFfiError *signal_sender_key_distribution_message_chain_key_length(uint32_t *out);
+
 SignalFfiError *signal_sender_key_distribution_message_get_signature_key(SignalPublicKey **out,
                                                                          const SignalSenderKeyDistributionMessage *m);
 
+SignalFfiError *signal_group_cipher_verify_against_distribution(bool *out,
+                                                                const SignalSenderKeyMessage *message,
+                                                                const SignalSenderKeyDistributionMessage *distribution);
+
 SignalFfiError *signal_pre_key_bundle_new(SignalPreKeyBundle **out,
                                           uint32_t registration_id,
                                           uint32_t device_id,
@@ -607,6 +809,19 @@ SignalFfiError *signal_pre_key_bundle_new(SignalPreKeyBundle **out,
                                           size_t signed_prekey_signature_len,
                                           const SignalPublicKey *identity_key);
 
+SignalFfiError *signal_pre_key_bundle_new_without_one_time_pre_key(SignalPreKeyBundle **out,
+                                                                   uint32_t registration_id,
+                                                                   uint32_t device_id,
+                                                                   uint32_t signed_prekey_id,
+                                                                   const SignalPublicKey *signed_prekey,
+                                                                   const unsigned char *signed_prekey_signature,
+                                                                   size_t signed_prekey_signature_len,
+                                                                   const SignalPublicKey *identity_key);
+
+SignalFfiError *signal_session_record_pre_key_message_already_processed(bool *out,
+                                                                        const SignalSessionRecord *s,
+                                                                        const SignalPreKeySignalMessage *message);
+
 SignalFfiError *signal_pre_key_bundle_get_identity_key(SignalPublicKey **out,
                                                        const SignalPreKeyBundle *p);
 
@@ -630,6 +845,14 @@ SignalFfiError *signal_pre_key_bundle_get_pre_key_public(SignalPublicKey **out,
 SignalFfiError *signal_pre_key_bundle_get_signed_pre_key_public(SignalPublicKey **out,
                                                                 const SignalPreKeyBundle *obj);
 
+SignalFfiError *signal_pre_key_bundle_to_linking_payload(const unsigned char **out,
+                                                         size_t *out_len,
+                                                         const SignalPreKeyBundle *obj);
+
+SignalFfiError *signal_pre_key_bundle_from_linking_payload(SignalPreKeyBundle **out,
+                                                           const unsigned char *payload,
+                                                           size_t payload_len);
+
 SignalFfiError *signal_signed_pre_key_record_deserialize(SignalSignedPreKeyRecord **p,
                                                          const unsigned char *data,
                                                          size_t data_len);
@@ -662,6 +885,68 @@ SignalFfiError *signal_signed_pre_key_record_new(SignalSignedPreKeyRecord **out,
                                                  const unsigned char *signature,
                                                  size_t signature_len);
 
+SignalFfiError *signal_signed_pre_key_record_verify_own_signature(bool *out,
+                                                                  const SignalSignedPreKeyRecord *record,
+                                                                  const SignalPublicKey *identity_key);
+
+SignalFfiError *signal_signed_pre_key_record_verify_key_pair(bool *out,
+                                                             const SignalSignedPreKeyRecord *record);
+
+SignalFfiError *signal_signed_pre_key_record_next_rotation_time(uint64_t *out,
+                                                                const SignalSignedPreKeyRecord *record,
+                                                                uint64_t interval);
+
+SignalFfiError *signal_signed_pre_key_record_earliest_expiry(uint64_t *out,
+                                                             const unsigned char *serialized_records,
+                                                             size_t serialized_records_len,
+                                                             uint64_t max_age);
+
+SignalFfiError *signal_session_record_validate_integrity(uint8_t *out,
+                                                         const unsigned char *serialized,
+                                                         size_t serialized_len);
+
+SignalFfiError *signal_session_cipher_plan_decrypt(SignalDecryptPlan **out,
+                                                   const SignalPreKeySignalMessage *message,
+                                                   const SignalSessionRecord *session_record,
+                                                   const SignalPublicKey *stored_identity);
+
+SignalFfiError *signal_session_cipher_estimate_ciphertext_size(uint32_t *out,
+                                                               uint32_t plaintext_length,
+                                                               const SignalSessionRecord *session_record);
+
+SignalFfiError *signal_session_cipher_max_plaintext_size(uint32_t *out,
+                                                         uint32_t ciphertext_budget,
+                                                         const SignalSessionRecord *session_record);
+
+SignalFfiError *signal_decrypt_plan_creates_session(bool *out, const SignalDecryptPlan *obj);
+
+SignalFfiError *signal_decrypt_plan_get_consumed_pre_key_id(uint32_t *out,
+                                                            const SignalDecryptPlan *obj);
+
+SignalFfiError *signal_decrypt_plan_changes_identity(bool *out, const SignalDecryptPlan *obj);
+
+SignalFfiError *signal_session_record_initialize_alice_session(SignalSessionRecord **out,
+                                                               const SignalPrivateKey *identity_key_private,
+                                                               const SignalPublicKey *identity_key_public,
+                                                               const SignalPrivateKey *base_private,
+                                                               const SignalPublicKey *base_public,
+                                                               const SignalPublicKey *their_identity_key,
+                                                               const SignalPublicKey *their_signed_prekey,
+                                                               const SignalPublicKey *their_one_time_prekey,
+                                                               const SignalPublicKey *their_ratchet_key);
+
+SignalFfiError *signal_session_record_initialize_bob_session(SignalSessionRecord **out,
+                                                             const SignalPrivateKey *identity_key_private,
+                                                             const SignalPublicKey *identity_key_public,
+                                                             const SignalPrivateKey *signed_prekey_private,
+                                                             const SignalPublicKey *signed_prekey_public,
+                                                             const SignalPrivateKey *prekey_private,
+                                                             const SignalPublicKey *prekey_public,
+                                                             const SignalPrivateKey *eph_private,
+                                                             const SignalPublicKey *eph_public,
+                                                             const SignalPublicKey *their_identity_key,
+                                                             const SignalPublicKey *their_base_key);
+
 SignalFfiError *signal_pre_key_record_deserialize(SignalPreKeyRecord **p,
                                                   const unsigned char *data,
                                                   size_t data_len);
@@ -678,6 +963,11 @@ SignalFfiError *signal_pre_key_record_get_public_key(SignalPublicKey **out,
 SignalFfiError *signal_pre_key_record_get_private_key(SignalPrivateKey **out,
                                                       const SignalPreKeyRecord *obj);
 
+SignalFfiError *signal_pre_key_record_find_duplicate_keys(const unsigned char **out,
+                                                          size_t *out_len,
+                                                          const unsigned char *serialized_records,
+                                                          size_t serialized_records_len);
+
 SignalFfiError *signal_pre_key_record_new(SignalPreKeyRecord **out,
                                           uint32_t id,
                                           const SignalPublicKey *pub_key,
@@ -697,6 +987,11 @@ SignalFfiError *signal_sender_key_name_new(SignalSenderKeyName **out,
 SignalFfiError *signal_sender_key_name_get_sender_device_id(uint32_t *out,
                                                             const SignalSenderKeyName *skn);
 
+SignalFfiError *signal_sender_key_name_to_storage_key(const char **out,
+                                                      const SignalSenderKeyName *skn);
+
+SignalFfiError *signal_sender_key_name_from_storage_key(SignalSenderKeyName **out, const char *key);
+
 SignalFfiError *signal_sender_key_record_deserialize(SignalSenderKeyRecord **p,
                                                      const unsigned char *data,
                                                      size_t data_len);
@@ -707,6 +1002,12 @@ SignalFfiError *signal_sender_key_record_serialize(const unsigned char **out,
 
 SignalFfiError *signal_sender_key_record_new_fresh(SignalSenderKeyRecord **out);
 
+SignalFfiError *signal_sender_key_record_create_distribution_message(SignalSenderKeyDistributionMessage **out,
+                                                                     const SignalSenderKeyRecord *record);
+
+SignalFfiError *signal_sender_key_record_messages_until_rotation(uint32_t *out,
+                                                                 const SignalSenderKeyRecord *record);
+
 SignalFfiError *signal_server_certificate_deserialize(SignalServerCertificate **p,
                                                       const unsigned char *data,
                                                       size_t data_len);
@@ -734,6 +1035,10 @@ SignalFfiError *signal_server_certificate_new(SignalServerCertificate **out,
                                               const SignalPublicKey *server_key,
                                               const SignalPrivateKey *trust_root);
 
+SignalFfiError *signal_server_certificate_verify(bool *out,
+                                                 const SignalServerCertificate *cert,
+                                                 const SignalPublicKey *trust_root);
+
 SignalFfiError *signal_sender_certificate_deserialize(SignalSenderCertificate **p,
                                                       const unsigned char *data,
                                                       size_t data_len);
@@ -770,6 +1075,22 @@ SignalFfiError *signal_sender_certificate_validate(bool *out,
                                                    const SignalPublicKey *key,
                                                    uint64_t time);
 
+SignalFfiError *signal_sender_certificate_validate_chain(bool *out,
+                                                         const SignalSenderCertificate *cert,
+                                                         const SignalPublicKey *trust_root,
+                                                         uint64_t time);
+
+SignalFfiError *signal_sender_certificate_is_for_device(bool *out,
+                                                        const SignalSenderCertificate *cert,
+                                                        uint32_t device_id);
+
+SignalFfiError *signal_sender_certificate_has_valid_uuid(bool *out,
+                                                        const SignalSenderCertificate *cert);
+
+SignalFfiError *signal_sender_certificate_signer_key_id_equals(bool *out,
+                                                               const SignalSenderCertificate *cert,
+                                                               uint32_t key_id);
+
 SignalFfiError *signal_sender_certificate_get_server_certificate(SignalServerCertificate **out,
                                                                  const SignalSenderCertificate *cert);
 
@@ -800,16 +1121,64 @@ SignalFfiError *signal_unidentified_sender_message_content_get_sender_cert(Signa
 SignalFfiError *signal_unidentified_sender_message_content_get_msg_type(uint8_t *out,
                                                                         const SignalUnidentifiedSenderMessageContent *m);
 
+SignalFfiError *signal_unidentified_sender_message_content_get_msg_type_name(const char **out,
+                                                                             uint8_t msg_type);
+
+SignalFfiError *signal_unidentified_sender_message_deserialize(SignalUnidentifiedSenderMessage **p,
+                                                               const unsigned char *data,
+                                                               size_t data_len);
+
+SignalFfiError *signal_unidentified_sender_message_get_serialized(const unsigned char **out,
+                                                                  size_t *out_len,
+                                                                  const SignalUnidentifiedSenderMessage *obj);
+
+SignalFfiError *signal_unidentified_sender_message_get_encrypted_message(const unsigned char **out,
+                                                                         size_t *out_len,
+                                                                         const SignalUnidentifiedSenderMessage *obj);
+
+SignalFfiError *signal_unidentified_sender_message_get_encrypted_static(const unsigned char **out,
+                                                                        size_t *out_len,
+                                                                        const SignalUnidentifiedSenderMessage *obj);
+
+SignalFfiError *signal_unidentified_sender_message_get_ephemeral_public(SignalPublicKey **out,
+                                                                        const SignalUnidentifiedSenderMessage *obj);
+
+SignalFfiError *signal_unidentified_sender_message_new(SignalUnidentifiedSenderMessage **out,
+                                                       const SignalPublicKey *public_key,
+                                                       const unsigned char *encrypted_static,
+                                                       size_t encrypted_static_len,
+                                                       const unsigned char *encrypted_message,
+                                                       size_t encrypted_message_len);
+
 SignalFfiError *signal_ciphertext_message_type(uint8_t *out, const SignalCiphertextMessage *msg);
 
 SignalFfiError *signal_ciphertext_message_serialize(const unsigned char **out,
                                                     size_t *out_len,
                                                     const SignalCiphertextMessage *obj);
 
+SignalFfiError *signal_ciphertext_message_get_inner_ciphertext(const unsigned char **out,
+                                                               size_t *out_len,
+                                                               const SignalCiphertextMessage *obj);
+
+SignalFfiError *signal_ciphertext_message_type_matches_body(bool *out,
+                                                            uint8_t message_type,
+                                                            const unsigned char *body,
+                                                            size_t body_len);
+
+SignalFfiError *signal_error_describe_code(const char **out, uint32_t code);
+
+SignalFfiError *signal_session_cipher_should_reset_session(bool *out,
+                                                           uint32_t error_code,
+                                                           uint8_t message_type,
+                                                           const unsigned char *message,
+                                                           size_t message_len);
+
 SignalFfiError *signal_session_record_archive_current_state(SignalSessionRecord *session_record);
 
 SignalFfiError *signal_session_record_has_current_state(bool *out, const SignalSessionRecord *obj);
 
+SignalFfiError *signal_session_record_can_encrypt(bool *out, const SignalSessionRecord *obj);
+
 SignalFfiError *signal_session_record_deserialize(SignalSessionRecord **p,
                                                   const unsigned char *data,
                                                   size_t data_len);
@@ -824,6 +1193,31 @@ SignalFfiError *signal_session_record_get_local_registration_id(uint32_t *out,
 SignalFfiError *signal_session_record_get_remote_registration_id(uint32_t *out,
                                                                  const SignalSessionRecord *obj);
 
+SignalFfiError *signal_session_record_get_registration_ids(const unsigned char **out,
+                                                           size_t *out_len,
+                                                           const SignalSessionRecord *s);
+
+SignalFfiError *signal_session_record_compute_key_confirmation(const unsigned char **out,
+                                                               size_t *out_len,
+                                                               const SignalSessionRecord *obj);
+
+SignalFfiError *signal_session_record_compute_session_id(const unsigned char **out,
+                                                         size_t *out_len,
+                                                         const SignalSessionRecord *obj);
+
+SignalFfiError *signal_session_record_get_creation_timestamp(uint64_t *out,
+                                                             const SignalSessionRecord *obj);
+
+SignalFfiError *signal_session_record_needs_upgrade(bool *out, const SignalSessionRecord *obj);
+
+SignalFfiError *signal_session_record_storage_version(uint32_t *out,
+                                                      const unsigned char *data,
+                                                      size_t data_len);
+
+SignalFfiError *signal_session_record_identity_seen_before(bool *out,
+                                                           const SignalSessionRecord *s,
+                                                           const SignalPublicKey *identity_key);
+
 SignalFfiError *signal_aes256_gcm_siv_new(SignalAes256GcmSiv **out,
                                           const unsigned char *key,
                                           size_t key_len);
@@ -838,6 +1232,16 @@ SignalFfiError *signal_aes256_gcm_siv_encrypt(const unsigned char **out,
                                               const unsigned char *associated_data,
                                               size_t associated_data_len);
 
+SignalFfiError *signal_aes256_gcm_siv_encrypt_in_place(const SignalAes256GcmSiv *aes_gcm_siv,
+                                                       unsigned char *buffer,
+                                                       size_t buffer_len,
+                                                       unsigned char *tag_out,
+                                                       size_t tag_out_len,
+                                                       const unsigned char *nonce,
+                                                       size_t nonce_len,
+                                                       const unsigned char *associated_data,
+                                                       size_t associated_data_len);
+
 SignalFfiError *signal_aes256_gcm_siv_decrypt(const unsigned char **out,
                                               size_t *out_len,
                                               const SignalAes256GcmSiv *aes_gcm_siv,
@@ -848,4 +1252,42 @@ SignalFfiError *signal_aes256_gcm_siv_decrypt(const unsigned char **out,
                                               const unsigned char *associated_data,
                                               size_t associated_data_len);
 
+SignalFfiError *signal_aes256_gcm_siv_rekey(SignalAes256GcmSiv *aes_gcm_siv,
+                                            const unsigned char *key,
+                                            size_t key_len);
+
+SignalFfiError *signal_aes256_gcm_siv_tag_length(uint32_t *out);
+
+SignalFfiError *signal_aes256_cbc_encrypt(const unsigned char **out,
+                                          size_t *out_len,
+                                          const unsigned char *key,
+                                          size_t key_len,
+                                          const unsigned char *iv,
+                                          size_t iv_len,
+                                          const unsigned char *ptext,
+                                          size_t ptext_len);
+
+SignalFfiError *signal_aes256_cbc_decrypt(const unsigned char **out,
+                                          size_t *out_len,
+                                          const unsigned char *key,
+                                          size_t key_len,
+                                          const unsigned char *iv,
+                                          size_t iv_len,
+                                          const unsigned char *ctext,
+                                          size_t ctext_len);
+
+SignalFfiError *signal_attachment_padded_size(uint64_t *out, uint64_t plaintext_length);
+
+SignalFfiError *signal_profile_cipher_encrypt_name(const unsigned char **out,
+                                                   size_t *out_len,
+                                                   const char *name,
+                                                   const unsigned char *profile_key,
+                                                   size_t profile_key_len);
+
+SignalFfiError *signal_profile_cipher_decrypt_name(const char **out,
+                                                   const unsigned char *ciphertext,
+                                                   size_t ciphertext_len,
+                                                   const unsigned char *profile_key,
+                                                   size_t profile_key_len);
+
 #endif /* SIGNAL_FFI_H_ */
@@ -68,14 +68,72 @@ class PublicAPITests: TestCaseBase {
         let recovered = try! gcm_siv.decrypt(ctext, nonce, ad)
         XCTAssertEqual(recovered, ptext)
 
+        var buffer = ptext
+        let tag = try! gcm_siv.encryptInPlace(&buffer, nonce, ad)
+        XCTAssertEqual(buffer + tag, expected_ctext)
+
         XCTAssertThrowsError(try gcm_siv.decrypt(ptext, nonce, ad))
         XCTAssertThrowsError(try gcm_siv.decrypt(ctext, ad, nonce))
     }
 
+    func testAesCbc() {
+        // NIST SP 800-38A F.2.5, followed by one block of PKCS7 padding.
+        let key: [UInt8] = [0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d, 0x77, 0x81,
+                            0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3, 0x09, 0x14, 0xdf, 0xf4]
+        let iv: [UInt8] = Array(0...15)
+        let ptext: [UInt8] = [0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a,
+                              0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac, 0x45, 0xaf, 0x8e, 0x51,
+                              0x30, 0xc8, 0x1c, 0x46, 0xa3, 0x5c, 0xe4, 0x11, 0xe5, 0xfb, 0xc1, 0x19, 0x1a, 0x0a, 0x52, 0xef,
+                              0xf6, 0x9f, 0x24, 0x45, 0xdf, 0x4f, 0x9b, 0x17, 0xad, 0x2b, 0x41, 0x7b, 0xe6, 0x6c, 0x37, 0x10]
+        let expected_ctext: [UInt8] = [0xf5, 0x8c, 0x4c, 0x04, 0xd6, 0xe5, 0xf1, 0xba, 0x77, 0x9e, 0xab, 0xfb, 0x5f, 0x7b, 0xfb, 0xd6,
+                                       0x9c, 0xfc, 0x4e, 0x96, 0x7e, 0xdb, 0x80, 0x8d, 0x67, 0x9f, 0x77, 0x7b, 0xc6, 0x70, 0x2c, 0x7d,
+                                       0x39, 0xf2, 0x33, 0x69, 0xa9, 0xd9, 0xba, 0xcf, 0xa5, 0x30, 0xe2, 0x63, 0x04, 0x23, 0x14, 0x61,
+                                       0xb2, 0xeb, 0x05, 0xe2, 0xc3, 0x9b, 0xe9, 0xfc, 0xda, 0x6c, 0x19, 0x07, 0x8c, 0x6a, 0x9d, 0x1b,
+                                       0x3f, 0x46, 0x17, 0x96, 0xd6, 0xb0, 0xd6, 0xb2, 0xe0, 0xc2, 0xa7, 0x2b, 0x4d, 0x80, 0xe6, 0x44]
+
+        let ctext = try! aes256CbcEncrypt(key: key, iv: iv, plaintext: ptext)
+        XCTAssertEqual(ctext, expected_ctext)
+        XCTAssertEqual(try! aes256CbcDecrypt(key: key, iv: iv, ciphertext: ctext), ptext)
+
+        for length in 0...33 {
+            let message = [UInt8](repeating: UInt8(length), count: length)
+            let encrypted = try! aes256CbcEncrypt(key: key, iv: iv, plaintext: message)
+            XCTAssertEqual(try! aes256CbcDecrypt(key: key, iv: iv, ciphertext: encrypted), message)
+        }
+
+        XCTAssertThrowsError(try aes256CbcDecrypt(key: key, iv: iv, ciphertext: ctext.dropLast())) {
+            guard case SignalError.invalidArgument(_) = $0 else {
+                XCTFail("wrong error thrown: \($0)")
+                return
+            }
+        }
+        XCTAssertThrowsError(try aes256CbcDecrypt(key: key, iv: iv, ciphertext: ctext.dropLast(16))) {
+            guard case SignalError.invalidCiphertext(_) = $0 else {
+                XCTFail("wrong error thrown: \($0)")
+                return
+            }
+        }
+    }
+
+    func testConstantTimeEqual() {
+        let mac: [UInt8] = [1, 2, 3, 4, 5, 6, 7, 8]
+
+        XCTAssertTrue(constantTimeEqual(mac, [1, 2, 3, 4, 5, 6, 7, 8]))
+        XCTAssertFalse(constantTimeEqual(mac, [1, 2, 3, 4, 5, 6, 7, 9]))
+        XCTAssertFalse(constantTimeEqual(mac, mac.dropLast()))
+        XCTAssertTrue(constantTimeEqual([UInt8](), [UInt8]()))
+    }
+
     func testAddress() {
         let addr = try! ProtocolAddress(name: "addr1", deviceId: 5)
         XCTAssertEqual(addr.name, "addr1")
         XCTAssertEqual(addr.deviceId, 5)
+        XCTAssertEqual(addr.canonicalString, "addr1.5")
+
+        let dotted = try! ProtocolAddress(canonicalString: "a.b.c.7")
+        XCTAssertEqual(dotted.name, "a.b.c")
+        XCTAssertEqual(dotted.deviceId, 7)
+        XCTAssertThrowsError(try ProtocolAddress(canonicalString: "addr1"))
     }
 
     func testPkOperations() {
@@ -95,6 +153,8 @@ class PublicAPITests: TestCaseBase {
         let pk_reloaded = sk_reloaded.publicKey
 
         XCTAssertEqual(pk, pk_reloaded)
+        XCTAssertEqual(try! PublicKey(pk_bytes), try! PublicKey(pk_bytes))
+        XCTAssertNotEqual(pk, PrivateKey.generate().publicKey)
 
         XCTAssertEqual(pk.serialize(), pk_reloaded.serialize())
 
@@ -161,6 +221,8 @@ class PublicAPITests: TestCaseBase {
 
         XCTAssertEqual(aliceFingerprint.scannable.encoding, ALICE_SCANNABLE_FINGERPRINT_V1)
         XCTAssertEqual(bobFingerprint.scannable.encoding, BOB_SCANNABLE_FINGERPRINT_V1)
+        XCTAssertEqual(aliceFingerprint.qrCodePayload, ALICE_SCANNABLE_FINGERPRINT_V1)
+        XCTAssertEqual(bobFingerprint.qrCodePayload, BOB_SCANNABLE_FINGERPRINT_V1)
 
         // testVectorsVersion2
 
@@ -181,6 +243,11 @@ class PublicAPITests: TestCaseBase {
 
         XCTAssertEqual(aliceFingerprint2.scannable.encoding, ALICE_SCANNABLE_FINGERPRINT_V2)
         XCTAssertEqual(bobFingerprint2.scannable.encoding, BOB_SCANNABLE_FINGERPRINT_V2)
+        XCTAssertEqual(aliceFingerprint2.qrCodePayload, ALICE_SCANNABLE_FINGERPRINT_V2)
+        XCTAssertEqual(bobFingerprint2.qrCodePayload, BOB_SCANNABLE_FINGERPRINT_V2)
+
+        XCTAssertTrue(ScannableFingerprint.versionsCompatible(1, 1))
+        XCTAssertFalse(ScannableFingerprint.versionsCompatible(1, 2))
 
         // testMismatchingFingerprints
 
@@ -315,6 +382,59 @@ class PublicAPITests: TestCaseBase {
         testRoundTrip(signedPreKeyRecord, serialize: { $0.serialize() }, deserialize: { try .init(bytes: $0) })
     }
 
+    func testSignalMessageSenderRatchetKey() {
+        // The same message and key are checked in Java's SignalMessageTest.
+        let ratchetKey: [UInt8] = [0x05] + Array(1...32)
+        let messageBytes: [UInt8] = [0x33, 0x0a, 0x21] + ratchetKey +
+            [0x10, 0x07, 0x18, 0x03, 0x22, 0x04, 0xde, 0xad, 0xbe, 0xef] +
+            [UInt8](repeating: 0, count: 8)
+
+        let message = try! SignalMessage(bytes: messageBytes)
+        XCTAssertEqual(message.senderRatchetKeyBytes, ratchetKey)
+        XCTAssertEqual(message.senderRatchetKey.serialize(), ratchetKey)
+        XCTAssertEqual(message.counter, 7)
+    }
+
+    func testDescribeErrorCode() {
+        XCTAssertEqual(describeSignalErrorCode(5), "invalid argument")
+        XCTAssertEqual(describeSignalErrorCode(41), "invalid signature")
+        XCTAssertEqual(describeSignalErrorCode(60), "untrusted identity")
+        XCTAssertEqual(describeSignalErrorCode(90), "duplicated message")
+        XCTAssertEqual(describeSignalErrorCode(9999), "unknown error")
+    }
+
+    func testShouldResetSession() throws {
+        let ratchetKey: [UInt8] = [0x05] + Array(1...32)
+        let whisperBytes: [UInt8] = [0x33, 0x0a, 0x21] + ratchetKey +
+            [0x10, 0x07, 0x18, 0x03, 0x22, 0x04, 0xde, 0xad, 0xbe, 0xef] +
+            [UInt8](repeating: 0, count: 8)
+        let preKeyBytes = try PreKeySignalMessage(version: 3,
+                                                  registrationId: 9,
+                                                  preKeyId: 23,
+                                                  signedPreKeyId: 802,
+                                                  baseKey: PrivateKey.generate().publicKey,
+                                                  identityKey: PrivateKey.generate().publicKey,
+                                                  message: try SignalMessage(bytes: whisperBytes)).serialize()
+
+        // InvalidMessage, SessionNotFound, InvalidKeyIdentifier
+        XCTAssertTrue(try shouldResetSession(afterDecryptionError: 30, messageType: .whisper, message: whisperBytes))
+        XCTAssertTrue(try shouldResetSession(afterDecryptionError: 80, messageType: .whisper, message: whisperBytes))
+        XCTAssertTrue(try shouldResetSession(afterDecryptionError: 70, messageType: .preKey, message: preKeyBytes))
+        // DuplicatedMessage, UntrustedIdentity, LegacyCiphertextVersion, CallbackError
+        XCTAssertFalse(try shouldResetSession(afterDecryptionError: 90, messageType: .whisper, message: whisperBytes))
+        XCTAssertFalse(try shouldResetSession(afterDecryptionError: 60, messageType: .preKey, message: preKeyBytes))
+        XCTAssertFalse(try shouldResetSession(afterDecryptionError: 21, messageType: .whisper, message: whisperBytes))
+        XCTAssertFalse(try shouldResetSession(afterDecryptionError: 100, messageType: .whisper, message: whisperBytes))
+        // Unknown codes, group messages, and bodies of the wrong type never reset the session.
+        XCTAssertFalse(try shouldResetSession(afterDecryptionError: 9999, messageType: .whisper, message: whisperBytes))
+        XCTAssertFalse(try shouldResetSession(afterDecryptionError: 30, messageType: .senderKey, message: [UInt8]()))
+        XCTAssertFalse(try shouldResetSession(afterDecryptionError: 30, messageType: .preKey, message: whisperBytes))
+
+        XCTAssertThrowsError(try shouldResetSession(afterDecryptionError: 30,
+                                                    messageType: .init(rawValue: 0xFF),
+                                                    message: whisperBytes))
+    }
+
     static var allTests: [(String, (PublicAPITests) -> () throws -> Void)] {
         return [
             ("testAddreses", testAddress),
@@ -323,9 +443,14 @@ class PublicAPITests: TestCaseBase {
             ("testHkdfSimple", testHkdfSimple),
             ("testHkdfUsingRFCExample", testHkdfUsingRFCExample),
             ("testAesGcmSiv", testAesGcmSiv),
+            ("testAesCbc", testAesCbc),
+            ("testConstantTimeEqual", testConstantTimeEqual),
             ("testGroupCipher", testGroupCipher),
             ("testSenderCertifications", testSenderCertificates),
             ("testSerializationRoundTrip", testSerializationRoundTrip),
+            ("testSignalMessageSenderRatchetKey", testSignalMessageSenderRatchetKey),
+            ("testDescribeErrorCode", testDescribeErrorCode),
+            ("testShouldResetSession", testShouldResetSession),
         ]
     }
 }
@@ -79,15 +79,22 @@ class SessionTests: TestCaseBase {
 
         let ctext_b = try! PreKeySignalMessage(bytes: ctext_a.serialize())
 
-        let ptext_b = try! signalDecryptPreKey(message: ctext_b,
-                                               from: alice_address,
-                                               sessionStore: bob_store,
-                                               identityStore: bob_store,
-                                               preKeyStore: bob_store,
-                                               signedPreKeyStore: bob_store,
-                                               context: NullContext())
-
-        XCTAssertEqual(ptext_a, ptext_b)
+        let result_b = try! signalDecryptPreKeyWithSessionVersion(message: ctext_b,
+                                                                  from: alice_address,
+                                                                  sessionStore: bob_store,
+                                                                  identityStore: bob_store,
+                                                                  preKeyStore: bob_store,
+                                                                  signedPreKeyStore: bob_store,
+                                                                  context: NullContext())
+
+        XCTAssertEqual(ptext_a, result_b.plaintext)
+        XCTAssertEqual(result_b.sessionVersion, 3)
+
+        let bob_session = try! bob_store.loadSession(for: alice_address, context: NullContext())!
+        let bob_ids = try! bob_session.registrationIds()
+        XCTAssertEqual(bob_ids.local, try! bob_store.localRegistrationId(context: NullContext()))
+        XCTAssertEqual(bob_ids.remote, try! alice_store.localRegistrationId(context: NullContext()))
+        XCTAssertEqual(bob_ids.remote, try! bob_session.remoteRegistrationId())
 
         // Bob replies
         let ptext2_b: [UInt8] = [23]
@@ -210,12 +217,107 @@ class SessionTests: TestCaseBase {
         XCTAssertFalse(session.hasCurrentState)
     }
 
+    func testDeviceIdsForName() throws {
+        let alice_store = InMemorySignalProtocolStore()
+
+        for deviceId: UInt32 in [2, 1] {
+            let bob_address = try ProtocolAddress(name: "+14151111112", deviceId: deviceId)
+            initializeSessions(alice_store: alice_store, bob_store: InMemorySignalProtocolStore(), bob_address: bob_address)
+        }
+
+        XCTAssertEqual(try signalDeviceIds(forName: "+14151111112", sessionStore: alice_store, context: NullContext()), [1, 2])
+        XCTAssertEqual(try signalDeviceIds(forName: "+14151111113", sessionStore: alice_store, context: NullContext()), [])
+    }
+
+    func testDeviceIdsForNameDefault() {
+        let store = UnlistableSessionStore()
+        XCTAssertThrowsError(try signalDeviceIds(forName: "+14151111112", sessionStore: store, context: NullContext())) {
+            guard case SignalError.invalidState(_) = $0 else {
+                XCTFail("wrong error thrown: \($0)")
+                return
+            }
+        }
+        XCTAssertThrowsError(try signalArchiveAllSessions(forName: "+14151111112", sessionStore: store, context: NullContext()))
+    }
+
+    func testArchiveAllSessionsForName() throws {
+        let alice_store = InMemorySignalProtocolStore()
+        let bob_addresses = [try ProtocolAddress(name: "+14151111112", deviceId: 1),
+                             try ProtocolAddress(name: "+14151111112", deviceId: 2)]
+        let carol_address = try ProtocolAddress(name: "+14151111113", deviceId: 1)
+
+        for address in bob_addresses + [carol_address] {
+            initializeSessions(alice_store: alice_store, bob_store: InMemorySignalProtocolStore(), bob_address: address)
+        }
+
+        try signalArchiveAllSessions(forName: "+14151111112", sessionStore: alice_store, context: NullContext())
+
+        for address in bob_addresses {
+            XCTAssertEqual(try alice_store.loadSession(for: address, context: NullContext())?.hasCurrentState, false)
+        }
+        XCTAssertEqual(try alice_store.loadSession(for: carol_address, context: NullContext())?.hasCurrentState, true)
+    }
+
+    func testInitializeSessionsDirectly() throws {
+        let alice_address = try ProtocolAddress(name: "+14151111111", deviceId: 1)
+        let bob_address = try ProtocolAddress(name: "+14151111112", deviceId: 1)
+
+        let alice_identity = IdentityKeyPair.generate()
+        let bob_identity = IdentityKeyPair.generate()
+        let alice_base_key = PrivateKey.generate()
+        let bob_signed_pre_key = PrivateKey.generate()
+        let bob_one_time_pre_key = PrivateKey.generate()
+        let bob_ephemeral_key = PrivateKey.generate()
+
+        for use_one_time_pre_key in [false, true] {
+            let alice_store = InMemorySignalProtocolStore(identity: alice_identity, deviceId: 1)
+            let bob_store = InMemorySignalProtocolStore(identity: bob_identity, deviceId: 1)
+
+            let alice_session = try SessionRecord.initializeAliceSession(
+                identityKeyPair: alice_identity,
+                baseKey: alice_base_key,
+                theirIdentity: bob_identity.identityKey,
+                theirSignedPreKey: bob_signed_pre_key.publicKey,
+                theirOneTimePreKey: use_one_time_pre_key ? bob_one_time_pre_key.publicKey : nil,
+                theirRatchetKey: bob_ephemeral_key.publicKey)
+            let bob_session = try SessionRecord.initializeBobSession(
+                identityKeyPair: bob_identity,
+                signedPreKey: bob_signed_pre_key,
+                oneTimePreKey: use_one_time_pre_key ? bob_one_time_pre_key : nil,
+                ephemeralKey: bob_ephemeral_key,
+                theirIdentity: alice_identity.identityKey,
+                theirBaseKey: alice_base_key.publicKey)
+
+            try alice_store.storeSession(alice_session, for: bob_address, context: NullContext())
+            try bob_store.storeSession(bob_session, for: alice_address, context: NullContext())
+
+            let ptext: [UInt8] = [8, 6, 7, 5, 3, 0, 9]
+            let ctext = try signalEncrypt(message: ptext,
+                                          for: bob_address,
+                                          sessionStore: alice_store,
+                                          identityStore: alice_store,
+                                          context: NullContext())
+            XCTAssertEqual(ctext.messageType, .whisper)
+
+            let decrypted = try signalDecrypt(message: try SignalMessage(bytes: ctext.serialize()),
+                                              from: alice_address,
+                                              sessionStore: bob_store,
+                                              identityStore: bob_store,
+                                              context: NullContext())
+            XCTAssertEqual(decrypted, ptext)
+        }
+    }
+
     static var allTests: [(String, (SessionTests) -> () throws -> Void)] {
         return [
             ("testSessionCipher", testSessionCipher),
             ("testSessionCipherWithBadStore", testSessionCipherWithBadStore),
             ("testSealedSenderSession", testSealedSenderSession),
             ("testArchiveSession", testArchiveSession),
+            ("testDeviceIdsForName", testDeviceIdsForName),
+            ("testDeviceIdsForNameDefault", testDeviceIdsForNameDefault),
+            ("testArchiveAllSessionsForName", testArchiveAllSessionsForName),
+            ("testInitializeSessionsDirectly", testInitializeSessionsDirectly),
         ]
     }
 }
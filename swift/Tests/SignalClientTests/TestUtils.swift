@@ -13,3 +13,16 @@ class BadStore: InMemorySignalProtocolStore {
         throw Error.badness
     }
 }
+
+/// A session store that relies on the default `deviceIds(forName:context:)`.
+class UnlistableSessionStore: SessionStore {
+    private var sessions: [ProtocolAddress: SessionRecord] = [:]
+
+    func loadSession(for address: ProtocolAddress, context: StoreContext) throws -> SessionRecord? {
+        return sessions[address]
+    }
+
+    func storeSession(_ record: SessionRecord, for address: ProtocolAddress, context: StoreContext) throws {
+        sessions[address] = record
+    }
+}